@@ -0,0 +1,137 @@
+//! Benchmarks of parsing and serialization over realistic control files.
+//!
+//! Run using `cargo bench`, a subset can be selected by passing a filter, e.g.
+//! `cargo bench -- serialize/typed`.
+
+use std::collections::BTreeMap;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use rfc822_like::document::{CompactDocument, Document};
+use rfc822_like::{Deserializer, Serializer};
+use serde::{Deserialize, Serialize};
+
+/// Number of copies of the `Packages` fixture concatenated to get a realistically sized index.
+const PACKAGES_COPIES: usize = 64;
+
+#[derive(serde_derive::Deserialize, serde_derive::Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct Package {
+    package: String,
+    source: Option<String>,
+    version: String,
+    #[serde(rename = "Installed-Size")]
+    installed_size: Option<String>,
+    maintainer: String,
+    architecture: String,
+    #[serde(rename = "Multi-Arch")]
+    multi_arch: Option<String>,
+    #[serde(rename = "Pre-Depends")]
+    pre_depends: Option<String>,
+    depends: Option<String>,
+    recommends: Option<String>,
+    suggests: Option<String>,
+    breaks: Option<String>,
+    description: String,
+    homepage: Option<String>,
+    tag: Option<String>,
+    section: Option<String>,
+    priority: Option<String>,
+    filename: String,
+    size: String,
+    #[serde(rename = "MD5sum")]
+    md5sum: String,
+    #[serde(rename = "SHA256")]
+    sha256: String,
+}
+
+// Only deserialization is measured
+#[allow(dead_code)]
+#[derive(serde_derive::Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Location {
+    package: String,
+    version: String,
+    filename: String,
+}
+
+fn packages() -> String {
+    let packages = include_str!("fixtures/Packages");
+    let mut input = String::with_capacity((packages.len() + 1) * PACKAGES_COPIES);
+    for i in 0..PACKAGES_COPIES {
+        if i > 0 {
+            input.push('\n');
+        }
+        input.push_str(packages);
+    }
+    input
+}
+
+fn fixtures() -> Vec<(&'static str, String)> {
+    vec![
+        ("Packages", packages()),
+        ("control", include_str!("fixtures/control").to_owned()),
+    ]
+}
+
+fn serialize<T: Serialize>(value: &T, wrap: bool) -> String {
+    let mut output = String::new();
+    value.serialize(Serializer::new(&mut output).wrap_long_lines(wrap)).unwrap();
+    output
+}
+
+fn parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    for (name, input) in fixtures() {
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_with_input(BenchmarkId::new("map", name), &input, |b, input| {
+            b.iter(|| rfc822_like::from_str::<Vec<BTreeMap<String, String>>>(input).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("document", name), &input, |b, input| {
+            b.iter(|| Document::parse(input).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("compact", name), &input, |b, input| {
+            b.iter(|| CompactDocument::parse(input).unwrap())
+        });
+    }
+    let input = packages();
+    group.throughput(Throughput::Bytes(input.len() as u64));
+    group.bench_with_input(BenchmarkId::new("typed", "Packages"), &input, |b, input| {
+        b.iter(|| rfc822_like::from_str::<Vec<Package>>(input).unwrap())
+    });
+    group.bench_with_input(BenchmarkId::new("typed-selected", "Packages"), &input, |b, input| {
+        b.iter(|| {
+            let deserializer = Deserializer::new(input.as_bytes()).select_fields(&["Package", "Version", "Filename"]);
+            Vec::<Location>::deserialize(deserializer).unwrap()
+        })
+    });
+    group.finish();
+}
+
+fn serialize_all(c: &mut Criterion) {
+    let mut group = c.benchmark_group("serialize");
+    for (name, input) in fixtures() {
+        let document = Document::parse(&input).unwrap();
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        for &wrap in &[false, true] {
+            let id = if wrap { "document-wrapped" } else { "document" };
+            group.bench_with_input(BenchmarkId::new(id, name), &document, |b, document| {
+                b.iter(|| serialize(document, wrap))
+            });
+        }
+        group.bench_with_input(BenchmarkId::new("document-display", name), &document, |b, document| {
+            b.iter(|| document.to_string())
+        });
+    }
+    let input = packages();
+    let packages = rfc822_like::from_str::<Vec<Package>>(&input).unwrap();
+    group.throughput(Throughput::Bytes(input.len() as u64));
+    for &wrap in &[false, true] {
+        let id = if wrap { "typed-wrapped" } else { "typed" };
+        group.bench_with_input(BenchmarkId::new(id, "Packages"), &packages, |b, packages| {
+            b.iter(|| serialize(packages, wrap))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, parse, serialize_all);
+criterion_main!(benches);
@@ -0,0 +1,6 @@
+// Declares custom cfgs so that newer compilers don't warn about them. Unlike the `[lints]`
+// table of the manifest this is understood by old versions of cargo too.
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rustc-check-cfg=cfg(rfc822_like_test_reversible_map_vec_serialization)");
+}
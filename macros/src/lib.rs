@@ -0,0 +1,99 @@
+//! Procedural macros for the `rfc822-like` crate.
+//!
+//! Use them through the `rfc822_like` crate with the `macros` feature enabled instead of
+//! depending on this crate directly.
+
+#![deny(missing_docs)]
+
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, parse_quote, Attribute, Data, DeriveInput, Fields, Lit, Meta, NestedMeta};
+
+/// Adds `#[serde(rename = "...")]` converting `snake_case` field names to `Train-Case`.
+///
+/// For example `installed_size` becomes `Installed-Size` which is the style of Debian fields.
+/// Fields that already have `#[serde(rename = "...")]` are left intact, which is useful for
+/// irregular names like `MD5sum`.
+/// The attribute has to be placed above `#[derive(Serialize, Deserialize)]`.
+#[proc_macro_attribute]
+pub fn rename_train_case(attr: TokenStream, item: TokenStream) -> TokenStream {
+    if !attr.is_empty() {
+        return syn::Error::new(proc_macro2::Span::call_site(), "rename_train_case doesn't accept arguments").to_compile_error().into();
+    }
+
+    let mut input = parse_macro_input!(item as DeriveInput);
+    match &mut input.data {
+        Data::Struct(data) => match &mut data.fields {
+            Fields::Named(fields) => {
+                for field in &mut fields.named {
+                    let ident = field.ident.as_ref().expect("named field without ident");
+                    add_rename(&mut field.attrs, &ident.to_string());
+                }
+            },
+            _ => return syn::Error::new_spanned(&input.ident, "rename_train_case requires named fields").to_compile_error().into(),
+        },
+        _ => return syn::Error::new_spanned(&input.ident, "rename_train_case only supports structs").to_compile_error().into(),
+    }
+    quote!(#input).into()
+}
+
+fn add_rename(attrs: &mut Vec<Attribute>, ident: &str) {
+    if !has_rename(attrs) {
+        let name = train_case(ident);
+        attrs.push(parse_quote!(#[serde(rename = #name)]));
+    }
+}
+
+/// Returns `true` if any of the `serde` attributes contains `rename`.
+fn has_rename(attrs: &[Attribute]) -> bool {
+    attrs.iter()
+        .filter(|attr| attr.path.is_ident("serde"))
+        .filter_map(|attr| attr.parse_meta().ok())
+        .any(|meta| match meta {
+            Meta::List(list) => list.nested.iter().any(|nested| match nested {
+                NestedMeta::Meta(Meta::NameValue(name_value)) => name_value.path.is_ident("rename") && match name_value.lit { Lit::Str(_) => true, _ => false },
+                NestedMeta::Meta(Meta::List(list)) => list.path.is_ident("rename"),
+                _ => false,
+            }),
+            _ => false,
+        })
+}
+
+/// Converts `snake_case` identifier to `Train-Case`.
+fn train_case(ident: &str) -> String {
+    let ident = if ident.starts_with("r#") { &ident[2..] } else { ident };
+    let mut result = String::with_capacity(ident.len() + 4);
+    let mut word_start = true;
+    for c in ident.chars() {
+        if c == '_' {
+            word_start = true;
+            continue;
+        }
+        if word_start {
+            if !result.is_empty() {
+                result.push('-');
+            }
+            result.extend(c.to_uppercase());
+            word_start = false;
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::train_case;
+
+    #[test]
+    fn conversion() {
+        assert_eq!(train_case("installed_size"), "Installed-Size");
+        assert_eq!(train_case("package"), "Package");
+        assert_eq!(train_case("r#type"), "Type");
+        assert_eq!(train_case("vcs_git"), "Vcs-Git");
+        assert_eq!(train_case("sha256"), "Sha256");
+    }
+}
@@ -0,0 +1,249 @@
+//! Command line tool converting RFC822-like files to and from JSON and YAML.
+//!
+//! Run `rfc822-like help` to see the usage.
+//! Files are read from standard input if no file or `-` is given and the output is written to
+//! standard output so that the tool can be used in pipelines.
+
+use std::io::{self, Read, Write};
+use std::process::exit;
+use rfc822_like::document::Document;
+
+const USAGE: &str = "Usage: rfc822-like COMMAND [FILE]
+
+Commands:
+    to-json [--compact] [FILE]  convert RFC822-like input to a JSON array of objects
+    to-yaml [FILE]              convert RFC822-like input to a YAML sequence of mappings
+    from-json [FILE]            convert a JSON array of objects to RFC822-like output
+    from-yaml [FILE]            convert a YAML sequence of mappings to RFC822-like output
+    fmt [FILE]                  re-render all fields in the canonical form
+    check [FILE...]             check that the files are syntactically valid
+    lint [FILE...]              print formatting warnings such as trailing whitespace
+    help                        print this message
+
+FILE defaults to standard input, `-` can be used to specify it explicitly.
+";
+
+#[derive(Debug, thiserror::Error)]
+enum Error {
+    #[error("failed to read {0}")]
+    Read(String, #[source] io::Error),
+    #[error("failed to write the output")]
+    Write(#[source] io::Error),
+    #[error("failed to parse {0}")]
+    Parse(String, #[source] rfc822_like::document::Error),
+    #[error("failed to convert {0}")]
+    Json(String, #[source] serde_json::Error),
+    #[error("failed to convert {0}")]
+    Yaml(String, #[source] serde_yaml::Error),
+    #[error("{0}")]
+    Usage(String),
+    #[error("{0} file(s) are invalid")]
+    Invalid(usize),
+}
+
+/// Input file or standard input.
+struct Input {
+    path: Option<String>,
+}
+
+impl Input {
+    fn new(path: Option<String>) -> Self {
+        Input { path: path.filter(|path| path != "-"), }
+    }
+
+    fn name(&self) -> String {
+        match &self.path {
+            Some(path) => path.clone(),
+            None => "standard input".to_owned(),
+        }
+    }
+
+    fn read(&self) -> Result<String, Error> {
+        let mut contents = String::new();
+        match &self.path {
+            Some(path) => std::fs::File::open(path).and_then(|mut file| file.read_to_string(&mut contents)),
+            None => io::stdin().read_to_string(&mut contents),
+        }.map_err(|error| Error::Read(self.name(), error))?;
+        Ok(contents)
+    }
+
+    fn parse(&self) -> Result<Document, Error> {
+        self.read()?.parse().map_err(|error| Error::Parse(self.name(), error))
+    }
+}
+
+enum Command {
+    ToJson { compact: bool, },
+    ToYaml,
+    FromJson,
+    FromYaml,
+    Fmt,
+}
+
+impl Command {
+    fn run(&self, input: &Input) -> Result<String, Error> {
+        match self {
+            Command::ToJson { compact, } => {
+                let document = input.parse()?;
+                let mut output = if *compact {
+                    serde_json::to_string(&document)
+                } else {
+                    serde_json::to_string_pretty(&document)
+                }.map_err(|error| Error::Json(input.name(), error))?;
+                output.push('\n');
+                Ok(output)
+            },
+            Command::ToYaml => serde_yaml::to_string(&input.parse()?).map_err(|error| Error::Yaml(input.name(), error)),
+            Command::FromJson => {
+                let document = serde_json::from_str::<Document>(&input.read()?).map_err(|error| Error::Json(input.name(), error))?;
+                Ok(document.to_string())
+            },
+            Command::FromYaml => {
+                let document = serde_yaml::from_str::<Document>(&input.read()?).map_err(|error| Error::Yaml(input.name(), error))?;
+                Ok(document.to_string())
+            },
+            Command::Fmt => {
+                let mut document = input.parse()?;
+                for paragraph in document.paragraphs_mut() {
+                    paragraph.discard_formatting();
+                }
+                Ok(document.to_string())
+            },
+        }
+    }
+}
+
+/// Checks all files reporting each failure to stderr.
+fn check(inputs: Vec<Input>) -> Result<(), Error> {
+    let inputs = if inputs.is_empty() { vec![Input::new(None)] } else { inputs };
+    let mut invalid = 0;
+    for input in &inputs {
+        if let Err(error) = input.parse() {
+            report(&error);
+            invalid += 1;
+        }
+    }
+    if invalid > 0 {
+        return Err(Error::Invalid(invalid));
+    }
+    Ok(())
+}
+
+/// Lints all files printing the problems to stdout.
+///
+/// Only files that failed to parse are considered invalid.
+fn lint(inputs: Vec<Input>) -> Result<(), Error> {
+    let inputs = if inputs.is_empty() { vec![Input::new(None)] } else { inputs };
+    let linter = rfc822_like::lint::Linter::new();
+    let mut invalid = 0;
+    let mut output = String::new();
+    for input in &inputs {
+        let contents = match input.read() {
+            Ok(contents) => contents,
+            Err(error) => {
+                report(&error);
+                invalid += 1;
+                continue;
+            },
+        };
+        let lints = linter.lint_str(&contents);
+        if lints.has_errors() {
+            invalid += 1;
+        }
+        for problem in &lints {
+            output.push_str(&format!("{}: {}\n", input.name(), problem));
+        }
+    }
+    io::stdout().write_all(output.as_bytes()).map_err(Error::Write)?;
+    if invalid > 0 {
+        return Err(Error::Invalid(invalid));
+    }
+    Ok(())
+}
+
+fn single_input(mut args: Vec<String>) -> Result<Input, Error> {
+    if args.len() > 1 {
+        return Err(Error::Usage("too many arguments".to_owned()));
+    }
+    Ok(Input::new(args.pop()))
+}
+
+fn run(mut args: Vec<String>) -> Result<(), Error> {
+    if args.is_empty() {
+        return Err(Error::Usage("missing command".to_owned()));
+    }
+    let command = args.remove(0);
+    let command = match &*command {
+        "to-json" => {
+            let len = args.len();
+            args.retain(|arg| arg != "--compact");
+            Command::ToJson { compact: args.len() != len, }
+        },
+        "to-yaml" => Command::ToYaml,
+        "from-json" => Command::FromJson,
+        "from-yaml" => Command::FromYaml,
+        "fmt" => Command::Fmt,
+        "check" => return check(args.into_iter().map(|arg| Input::new(Some(arg))).collect()),
+        "lint" => return lint(args.into_iter().map(|arg| Input::new(Some(arg))).collect()),
+        "help" | "--help" | "-h" => {
+            print!("{}", USAGE);
+            return Ok(());
+        },
+        _ => return Err(Error::Usage(format!("unknown command '{}'", command))),
+    };
+    let output = command.run(&single_input(args)?)?;
+    io::stdout().write_all(output.as_bytes()).map_err(Error::Write)
+}
+
+/// Prints the error including its sources.
+fn report(error: &Error) {
+    let mut message = format!("error: {}", error);
+    let mut source = std::error::Error::source(error);
+    while let Some(error) = source {
+        message.push_str(&format!(": {}", error));
+        source = error.source();
+    }
+    eprintln!("{}", message);
+}
+
+fn main() {
+    match run(std::env::args().skip(1).collect()) {
+        Ok(()) => (),
+        Err(Error::Usage(message)) => {
+            eprintln!("error: {}\n\n{}", message, USAGE);
+            exit(2);
+        },
+        // Individual problems were already reported.
+        Err(Error::Invalid(_)) => exit(1),
+        Err(error) => {
+            report(&error);
+            exit(1);
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use super::{Command, Input};
+
+    fn run(command: Command, contents: &str) -> String {
+        let mut file = std::env::temp_dir();
+        file.push(format!("rfc822-like-cli-test-{}-{}", std::process::id(), contents.len()));
+        std::fs::File::create(&file).unwrap().write_all(contents.as_bytes()).unwrap();
+        let result = command.run(&Input::new(Some(file.to_str().unwrap().to_owned())));
+        std::fs::remove_file(&file).unwrap();
+        result.unwrap()
+    }
+
+    #[test]
+    fn conversions() {
+        let input = "Package: foo\nDescription: short\n long\n\nPackage: bar\n";
+        let json = run(Command::ToJson { compact: true, }, input);
+        assert_eq!(json, "[{\"Package\":\"foo\",\"Description\":\"short\\nlong\"},{\"Package\":\"bar\"}]\n");
+        assert_eq!(run(Command::FromJson, &json), input);
+        let yaml = run(Command::ToYaml, input);
+        assert_eq!(run(Command::FromYaml, &yaml), input);
+        assert_eq!(run(Command::Fmt, "Package:foo\nDescription:   short\n\tlong\n"), "Package: foo\nDescription: short\n long\n");
+    }
+}
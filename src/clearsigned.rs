@@ -0,0 +1,247 @@
+//! Reading the payload of OpenPGP clearsigned messages such as `InRelease` files.
+//!
+//! A clearsigned message wraps the text between the `-----BEGIN PGP SIGNED MESSAGE-----` line
+//! followed by armor headers and the `-----BEGIN PGP SIGNATURE-----` block.
+//! Lines of the text starting with `-` are escaped by prepending `- `.
+//! The [`payload`] function and the `ClearsignedReader` detect the framing and return only the
+//! unescaped text so that it can be deserialized.
+//! Input that isn't clearsigned is returned unchanged.
+//!
+//! **The signature is not verified**, verify it using an OpenPGP implementation before trusting
+//! the payload.
+//! The newline preceding the signature is kept in the payload since it terminates the last
+//! field.
+//!
+//! # Example
+//!
+//! ```
+//! use std::collections::HashMap;
+//!
+//! let input = "-----BEGIN PGP SIGNED MESSAGE-----
+//! Hash: SHA512
+//!
+//! Origin: Debian
+//! Suite: stable
+//! -----BEGIN PGP SIGNATURE-----
+//!
+//! iQIzBAEBCgAdFiEE
+//! -----END PGP SIGNATURE-----
+//! ";
+//!
+//! let payload = rfc822_like::clearsigned::payload(input).unwrap();
+//! assert_eq!(payload, "Origin: Debian\nSuite: stable\n");
+//!
+//! let release = rfc822_like::from_str::<HashMap<String, String>>(&payload).unwrap();
+//! assert_eq!(release["Suite"], "stable");
+//! ```
+
+use std::borrow::Cow;
+#[cfg(feature = "io")]
+use std::io;
+use crate::split::InclusiveLines;
+
+const BEGIN_MESSAGE: &[u8] = b"-----BEGIN PGP SIGNED MESSAGE-----";
+const BEGIN_SIGNATURE: &[u8] = b"-----BEGIN PGP SIGNATURE-----";
+
+#[derive(Debug, thiserror::Error)]
+enum ErrorInner {
+    #[error("the armor headers of the clearsigned message are not terminated by an empty line")]
+    UnterminatedHeaders,
+    #[error("the clearsigned message doesn't contain a signature")]
+    MissingSignature,
+}
+
+/// Error returned when the input starts as a clearsigned message but the framing is incomplete.
+#[derive(Debug, thiserror::Error)]
+#[error(transparent)]
+pub struct Error(#[from] ErrorInner);
+
+/// Position in the framing of the message.
+#[derive(Debug, Copy, Clone)]
+enum State {
+    Start,
+    Headers,
+    Payload,
+    // The input is not clearsigned
+    Plain,
+    // The signature was reached, the rest of the input is ignored
+    Done,
+}
+
+impl State {
+    /// Returns the state after `line` and the offset of the part of the line in the payload.
+    fn next(self, line: &[u8]) -> (State, Option<usize>) {
+        let trimmed = trim_end(line);
+        match self {
+            State::Start if trimmed == BEGIN_MESSAGE => (State::Headers, None),
+            State::Start | State::Plain => (State::Plain, Some(0)),
+            State::Headers if trimmed.is_empty() => (State::Payload, None),
+            State::Headers => (State::Headers, None),
+            State::Payload if trimmed == BEGIN_SIGNATURE => (State::Done, None),
+            State::Payload if line.starts_with(b"- ") => (State::Payload, Some(2)),
+            State::Payload => (State::Payload, Some(0)),
+            State::Done => (State::Done, None),
+        }
+    }
+
+    /// Checks that the input didn't end in the middle of the message.
+    fn finish(self) -> Result<(), Error> {
+        match self {
+            State::Headers => Err(ErrorInner::UnterminatedHeaders.into()),
+            State::Payload => Err(ErrorInner::MissingSignature.into()),
+            State::Start | State::Plain | State::Done => Ok(()),
+        }
+    }
+}
+
+/// Trailing whitespace of the framing lines is ignored.
+fn trim_end(line: &[u8]) -> &[u8] {
+    let len = line.iter().rposition(|byte| !byte.is_ascii_whitespace()).map_or(0, |pos| pos + 1);
+    &line[..len]
+}
+
+/// Returns the signed text if `input` is clearsigned or `input` itself otherwise.
+pub fn payload(input: &str) -> Result<Cow<'_, str>, Error> {
+    let mut state = State::Start;
+    let mut payload = String::new();
+    for line in InclusiveLines(input) {
+        let (next, start) = state.next(line.as_bytes());
+        if let State::Plain = next {
+            return Ok(Cow::Borrowed(input));
+        }
+        if let Some(start) = start {
+            payload.push_str(&line[start..]);
+        }
+        state = next;
+        if let State::Done = state {
+            break;
+        }
+    }
+    state.finish()?;
+    Ok(Cow::Owned(payload))
+}
+
+/// Reader returning the signed text if the underlying reader is clearsigned.
+///
+/// Input that isn't clearsigned is passed through unchanged.
+/// The reader stops at the signature, the rest of the underlying reader is not read.
+/// Incomplete framing is reported as an error of kind `InvalidData` wrapping [`Error`].
+///
+/// This type requires the `io` feature.
+#[cfg(feature = "io")]
+#[derive(Debug)]
+pub struct ClearsignedReader<R> {
+    reader: R,
+    state: State,
+    // The current line of the payload, only `line[pos..]` wasn't consumed yet
+    line: Vec<u8>,
+    pos: usize,
+}
+
+#[cfg(feature = "io")]
+impl<R: io::BufRead> ClearsignedReader<R> {
+    /// Creates the reader reading from `reader`.
+    pub fn new(reader: R) -> Self {
+        ClearsignedReader {
+            reader,
+            state: State::Start,
+            line: Vec::new(),
+            pos: 0,
+        }
+    }
+
+    /// Returns the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+#[cfg(feature = "io")]
+impl<R: io::BufRead> io::BufRead for ClearsignedReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        while self.pos == self.line.len() {
+            match self.state {
+                // Avoids copying input that isn't clearsigned
+                State::Plain => return self.reader.fill_buf(),
+                State::Done => return Ok(&[]),
+                State::Start | State::Headers | State::Payload => (),
+            }
+            self.line.clear();
+            self.pos = 0;
+            if self.reader.read_until(b'\n', &mut self.line)? == 0 {
+                self.state.finish().map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+                self.state = State::Done;
+                return Ok(&[]);
+            }
+            let (state, start) = self.state.next(&self.line);
+            self.state = state;
+            self.pos = start.unwrap_or(self.line.len());
+        }
+        Ok(&self.line[self.pos..])
+    }
+
+    fn consume(&mut self, amount: usize) {
+        if self.pos == self.line.len() {
+            if let State::Plain = self.state {
+                self.reader.consume(amount);
+                return;
+            }
+        }
+        self.pos = (self.pos + amount).min(self.line.len());
+    }
+}
+
+#[cfg(feature = "io")]
+impl<R: io::BufRead> io::Read for ClearsignedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        use io::BufRead;
+
+        let available = self.fill_buf()?;
+        let amount = available.len().min(buf.len());
+        buf[..amount].copy_from_slice(&available[..amount]);
+        self.consume(amount);
+        Ok(amount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+    use super::payload;
+
+    const IN_RELEASE: &str = "-----BEGIN PGP SIGNED MESSAGE-----\r\nHash: SHA256\n\nOrigin: Debian\nDescription: not\n - the signature\n\n-----BEGIN PGP SIGNATURE-----\n\niQIzBAEBCAAdFiEE\n=abcd\n-----END PGP SIGNATURE-----\n";
+    const PAYLOAD: &str = "Origin: Debian\nDescription: not\n - the signature\n\n";
+
+    #[test]
+    fn strip() {
+        assert_eq!(payload(IN_RELEASE).unwrap(), PAYLOAD);
+        match payload("Origin: Debian\n").unwrap() {
+            Cow::Borrowed(input) => assert_eq!(input, "Origin: Debian\n"),
+            Cow::Owned(_) => panic!("plain input was copied"),
+        }
+        assert_eq!(payload("").unwrap(), "");
+        let escaped = "-----BEGIN PGP SIGNED MESSAGE-----\n\n- -----BEGIN PGP SIGNATURE-----\n-- not escaped\n-----BEGIN PGP SIGNATURE-----\n";
+        assert_eq!(payload(escaped).unwrap(), "-----BEGIN PGP SIGNATURE-----\n-- not escaped\n");
+        assert_eq!(payload("-----BEGIN PGP SIGNED MESSAGE-----\nHash: SHA256\n").unwrap_err().to_string(), "the armor headers of the clearsigned message are not terminated by an empty line");
+        assert_eq!(payload("-----BEGIN PGP SIGNED MESSAGE-----\n\nOrigin: Debian\n").unwrap_err().to_string(), "the clearsigned message doesn't contain a signature");
+    }
+
+    #[cfg(feature = "io")]
+    #[test]
+    fn reader() {
+        use std::io::Read;
+        use super::ClearsignedReader;
+
+        let mut output = String::new();
+        ClearsignedReader::new(IN_RELEASE.as_bytes()).read_to_string(&mut output).unwrap();
+        assert_eq!(output, PAYLOAD);
+        output.clear();
+        ClearsignedReader::new("Origin: Debian\n\nSuite: stable".as_bytes()).read_to_string(&mut output).unwrap();
+        assert_eq!(output, "Origin: Debian\n\nSuite: stable");
+
+        let records = crate::from_reader::<Vec<std::collections::HashMap<String, String>>, _>(ClearsignedReader::new(IN_RELEASE.as_bytes())).unwrap();
+        assert_eq!(records[0]["Origin"], "Debian");
+        assert_eq!(records[0]["Description"], "not\n- the signature");
+        assert!(crate::from_reader::<Vec<std::collections::HashMap<String, String>>, _>(ClearsignedReader::new("-----BEGIN PGP SIGNED MESSAGE-----\n\nOrigin: Debian\n".as_bytes())).is_err());
+    }
+}
@@ -2,6 +2,7 @@
 
 use std::fmt;
 use std::io;
+use crate::report::Location;
 
 #[derive(Debug, thiserror::Error)]
 pub(crate) enum ErrorInner {
@@ -9,10 +10,107 @@ pub(crate) enum ErrorInner {
     Custom(String),
     #[error("Line {0} doesn't contain a colon")]
     MissingColon(usize),
+    #[error("Line {line} contains invalid field name {key:?}")]
+    InvalidKey { line: usize, key: String, },
     #[error("I/O error")]
     IoError(#[from] io::Error),
+    #[error("Line {line} contains invalid UTF-8 at byte offset {offset} (bytes: {context})")]
+    InvalidUtf8 { line: usize, offset: usize, context: ByteContext, },
+    #[error("{location}: {message}")]
+    InvalidField { location: Location, message: String, },
     #[error("The deserialized type is ambiguous and must be explicitly specified. (RFC822 is NOT self-describing.)")]
     AmbiguousType,
+    #[error("Line {0} starts a record following the last element of the tuple")]
+    TrailingRecord(usize),
+}
+
+/// Hex dump of bytes surrounding invalid data.
+///
+/// The offending byte is enclosed in square brackets.
+#[derive(Debug)]
+pub(crate) struct ByteContext {
+    bytes: Vec<u8>,
+    pos: usize,
+}
+
+impl ByteContext {
+    const RADIUS: usize = 8;
+
+    pub(crate) fn new(bytes: &[u8], pos: usize) -> Self {
+        let begin = pos.saturating_sub(Self::RADIUS);
+        let end = (pos + Self::RADIUS + 1).min(bytes.len());
+        ByteContext {
+            bytes: bytes[begin..end].to_owned(),
+            pos: pos - begin,
+        }
+    }
+}
+
+impl fmt::Display for ByteContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, byte) in self.bytes.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            if i == self.pos {
+                write!(f, "[{:02x}]", byte)?;
+            } else {
+                write!(f, "{:02x}", byte)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Error {
+    /// Attaches the location to errors raised by visitors, which don't know where they are.
+    ///
+    /// Errors that already carry a location and empty locations are left intact.
+    pub(crate) fn with_location<F: FnOnce() -> Location>(self, location: F) -> Self {
+        match self.0 {
+            ErrorInner::Custom(message) => {
+                let location = location();
+                if location.is_empty() {
+                    ErrorInner::Custom(message).into()
+                } else {
+                    ErrorInner::InvalidField { location, message, }.into()
+                }
+            },
+            inner => Error(inner),
+        }
+    }
+
+    /// Reports invalid UTF-8 in `bytes` at the line containing it.
+    pub(crate) fn invalid_utf8(bytes: &[u8], error: std::str::Utf8Error) -> Self {
+        let pos = error.valid_up_to();
+        let line_start = bytes[..pos].iter().rposition(|byte| *byte == b'\n').map_or(0, |newline| newline + 1);
+        let line_end = bytes[pos..].iter().position(|byte| *byte == b'\n').map_or(bytes.len(), |newline| pos + newline + 1);
+        ErrorInner::InvalidUtf8 {
+            line: bytes[..line_start].iter().filter(|byte| **byte == b'\n').count() + 1,
+            offset: pos,
+            context: ByteContext::new(&bytes[line_start..line_end], pos - line_start),
+        }.into()
+    }
+
+    /// Returns the number (one-based) of the line at which the error was detected if known.
+    pub fn line(&self) -> Option<usize> {
+        match &self.0 {
+            ErrorInner::MissingColon(line) => Some(*line),
+            ErrorInner::InvalidKey { line, .. } => Some(*line),
+            ErrorInner::InvalidUtf8 { line, .. } => Some(*line),
+            ErrorInner::InvalidField { location, .. } => location.line(),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of the field the error relates to if known.
+    pub fn field(&self) -> Option<&str> {
+        match &self.0 {
+            ErrorInner::InvalidKey { key, .. } => Some(key),
+            ErrorInner::InvalidField { location, .. } => location.field(),
+            _ => None,
+        }
+    }
 }
 
 impl serde::de::Error for Error {
@@ -32,6 +130,7 @@ impl serde::de::Error for Error {
 pub struct Error(#[from] ErrorInner);
 
 /// Error returned when opening a file and subsequent deserialization fail.
+#[cfg(feature = "fs")]
 #[derive(Debug, thiserror::Error)]
 pub enum ReadFileError {
     /// Variant returned when a file couldn't be opened.
@@ -1,17 +1,27 @@
 //! # Deserialization of RFC822-like format
 //!
 //! This module contains types and methods used for deserialization of RFC822-like format.
-//! The current implementation is very basic.
-//! It lacks borrowing the contents of the string, etc.
+//! Values can be borrowed from input in memory, see [`Deserializer::from_str`].
 //! It is mainly meant for quick look at `apt` metadata, however if you have a use case that needs
 //! more than that I will be happy to accept a PR.
 
-use serde::de::{Visitor, MapAccess, SeqAccess, DeserializeSeed, IntoDeserializer};
+use serde::de::{Visitor, MapAccess, SeqAccess, DeserializeSeed, DeserializeOwned, IntoDeserializer, Unexpected};
+use std::borrow::Cow;
 use std::io;
+use std::ops::Range;
+use std::marker::PhantomData;
 use error::ErrorInner;
+use crate::key_mapping::KeyMapping;
+use crate::report::Location;
 pub use error::Error;
+pub use options::Options;
+pub use raw_value::RawValue;
+pub use read::{Read, SliceRead};
 
 pub mod error;
+mod options;
+mod read;
+pub(crate) mod raw_value;
 
 /// Deserializes a single record or multiple records separated by empty lines.
 ///
@@ -24,9 +34,12 @@ pub mod error;
 /// * struct
 /// * sequence of maps with str-deserializable keys
 /// * sequence of structs
+/// * tuple of the above where each map or struct consumes one record and a sequence consumes all
+///   remaining records, e.g. `(Source, Vec<Binary>)` for `debian/control` files, records left
+///   after the last element are an error
 ///
-/// Further, values of maps and types of fields of structs must be either deserializable from `str`
-/// or sequence of `str`.
+/// Further, values of maps and types of fields of structs must be either deserializable from `str`,
+/// numbers or sequence of `str`.
 ///
 /// # Example
 /// 
@@ -87,20 +100,434 @@ pub mod error;
 /// let deserialized = Package::deserialize(Deserializer::new(&mut reader)).unwrap();
 /// assert_eq!(deserialized, expected);
 /// ```
-pub struct Deserializer<R: io::BufRead> {
+pub struct Deserializer<R> {
     state: DeserializerState<R>,
 }
 
-impl<R: io::BufRead> Deserializer<R> {
+impl<R> Deserializer<R> {
     /// Creates a `Deserializer` from buffered reader.
     pub fn new(reader: R) -> Self {
         Deserializer {
             state: DeserializerState::new(reader),
         }
     }
+
+    /// Creates a `Deserializer` configured using `options`.
+    pub fn with_options(reader: R, options: Options) -> Self {
+        let mut deserializer = Deserializer::new(reader)
+            .human_readable(options.human_readable)
+            .strict_keys(options.strict_keys);
+        if let Some(mapping) = options.key_mapping {
+            deserializer = deserializer.key_mapping(mapping);
+        }
+        if let Some(max) = options.max_records {
+            deserializer = deserializer.max_records(max);
+        }
+        if let Some(names) = options.select_fields {
+            deserializer = deserializer.select_fields(names);
+        }
+        for (name, value) in options.default_fields {
+            deserializer = deserializer.default_field(name, value);
+        }
+        if let Some(values) = options.bool_values {
+            deserializer = deserializer.bool_values(values);
+        }
+        deserializer
+    }
+
+    /// Returns the settings configured using the builder methods.
+    ///
+    /// `max_records` is the number of records that can still be read.
+    /// If the same default field was set multiple times the last value is returned.
+    pub fn to_options(&self) -> Options {
+        let state = &self.state;
+        Options {
+            key_mapping: state.key_mapping.clone(),
+            human_readable: state.values.human_readable,
+            strict_keys: state.strict_keys,
+            max_records: state.remaining_records,
+            select_fields: state.selected_fields.clone(),
+            default_fields: state.defaults.iter().cloned().collect(),
+            bool_values: if state.values.bool_values == DEFAULT_BOOL_VALUES {
+                None
+            } else {
+                Some(state.values.bool_values.iter().map(|(yes, no)| (yes.clone().into_owned(), no.clone().into_owned())).collect())
+            },
+        }
+    }
+
+    /// Converts field names to names of struct fields using the `mapping`, e.g. `Installed-Size`
+    /// to `installed_size`.
+    ///
+    /// Keys of maps are not converted.
+    /// Field names equal to the name of a struct field, e.g. set by `#[serde(rename)]`, are
+    /// kept as-is so the mapping can be used with renamed structs too.
+    pub fn key_mapping(mut self, mapping: KeyMapping) -> Self {
+        self.state.key_mapping = Some(mapping);
+        self
+    }
+
+    /// Sets the value reported by `is_human_readable()`, `true` by default.
+    ///
+    /// Types such as dates or UUIDs expect their textual representation only if this is `true`,
+    /// see [`Serializer::human_readable`](crate::Serializer::human_readable).
+    pub fn human_readable(mut self, human_readable: bool) -> Self {
+        self.state.values.human_readable = human_readable;
+        self
+    }
+
+    /// Rejects field names not conforming to the deb822 syntax, `false` by default.
+    ///
+    /// Field names must consist only of printable ASCII characters other than space and colon
+    /// and must not start with `#` or `-`.
+    /// Without this anything preceding the first colon is accepted as the name, including spaces
+    /// and control characters.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use rfc822_like::de::Deserializer;
+    /// use serde::Deserialize;
+    ///
+    /// let input = "Package: foo\nBad Name: bar\n";
+    /// let error = HashMap::<String, String>::deserialize(Deserializer::new(input.as_bytes()).strict_keys(true)).unwrap_err();
+    /// assert_eq!(error.to_string(), "Line 2 contains invalid field name \"Bad Name\"");
+    /// ```
+    pub fn strict_keys(mut self, strict: bool) -> Self {
+        self.state.strict_keys = strict;
+        self
+    }
+
+    /// Stops after reading `max` records when deserializing sequences or iterating records.
+    ///
+    /// Nothing past the end of the last record is read from the reader, so previews of huge
+    /// indexes only pay for the records they show.
+    /// Single records (structs and maps) are not affected.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use rfc822_like::de::Deserializer;
+    /// use serde::Deserialize;
+    ///
+    /// let input = "Package: foo\n\nPackage: bar\n\nthis is not even valid\n";
+    /// let records = Vec::<HashMap<String, String>>::deserialize(Deserializer::new(input.as_bytes()).max_records(2)).unwrap();
+    /// assert_eq!(records.len(), 2);
+    /// ```
+    pub fn max_records(mut self, max: usize) -> Self {
+        self.state.remaining_records = Some(max);
+        self
+    }
+
+    /// Only deserializes fields with the given names, other fields are skipped.
+    ///
+    /// The names are those in the input, before applying [`key_mapping`](Self::key_mapping),
+    /// and are matched case-insensitively.
+    /// Skipped fields are never unfolded nor passed to the visitor, which makes reading a few
+    /// fields of each record of a large index much faster.
+    /// Structs with required fields that weren't selected fail to deserialize.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use rfc822_like::de::Deserializer;
+    /// use serde::Deserialize;
+    ///
+    /// let input = "Package: foo\nVersion: 1.0\nDescription: The Foo\n It's very foo.\n";
+    /// let deserializer = Deserializer::new(input.as_bytes()).select_fields(&["package", "version"]);
+    /// let record = HashMap::<String, String>::deserialize(deserializer).unwrap();
+    /// assert_eq!(record.len(), 2);
+    /// assert_eq!(record["Version"], "1.0");
+    /// ```
+    pub fn select_fields<I: IntoIterator>(mut self, names: I) -> Self where I::Item: AsRef<str> {
+        self.state.selected_fields = Some(names.into_iter().map(|name| name.as_ref().to_owned()).collect());
+        self
+    }
+
+    /// Supplies `value` for the field `name` in records that don't contain it.
+    ///
+    /// This is useful for archive-wide conventions such as `Priority: optional` so that the
+    /// struct fields don't have to be `Option`s.
+    /// The default is deserialized as if it was written at the end of the record, after
+    /// [`key_mapping`](Self::key_mapping) and [`select_fields`](Self::select_fields) are
+    /// applied.
+    /// Names are matched case-insensitively, the value is unfolded.
+    /// Empty records (e.g. an empty input) don't get any defaults.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rfc822_like::de::Deserializer;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(serde_derive::Deserialize)]
+    /// #[serde(rename_all = "PascalCase")]
+    /// struct Package {
+    ///     package: String,
+    ///     priority: String,
+    /// }
+    ///
+    /// let input = "Package: foo\n\nPackage: bar\nPriority: important\n";
+    /// let deserializer = Deserializer::new(input.as_bytes()).default_field("Priority", "optional");
+    /// let packages = Vec::<Package>::deserialize(deserializer).unwrap();
+    /// assert_eq!(packages[0].priority, "optional");
+    /// assert_eq!(packages[1].priority, "important");
+    /// ```
+    pub fn default_field<N: Into<String>, V: Into<String>>(mut self, name: N, value: V) -> Self {
+        self.state.defaults.push((name.into(), value.into()));
+        self.state.present.push(false);
+        self
+    }
+
+    /// Calls `validator` with the name, raw value and location of each field named `name`.
+    ///
+    /// The validator can reject the value by returning an error message, which fails
+    /// deserialization, or normalize it by returning a replacement.
+    /// `Ok(None)` keeps the value as it is.
+    /// The raw value is trimmed but not unfolded and the replacement is unfolded the same way.
+    /// Names are matched case-insensitively before applying [`key_mapping`](Self::key_mapping).
+    /// Multiple validators of the same field are called in the order they were added, each
+    /// getting the value returned by the previous one.
+    /// Fields skipped by [`select_fields`](Self::select_fields) and values supplied by
+    /// [`default_field`](Self::default_field) aren't validated.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rfc822_like::de::Deserializer;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Debug, serde_derive::Deserialize)]
+    /// #[serde(rename_all = "PascalCase")]
+    /// struct Package {
+    ///     package: String,
+    ///     architecture: String,
+    /// }
+    ///
+    /// fn deserializer(input: &str) -> Deserializer<&[u8]> {
+    ///     Deserializer::new(input.as_bytes())
+    ///         .validate_field("Package", |_, value, _| {
+    ///             if value.is_empty() {
+    ///                 Err("package name is empty".to_owned())
+    ///             } else {
+    ///                 Ok(None)
+    ///             }
+    ///         })
+    ///         .validate_field("Architecture", |_, value, _| Ok(Some(value.to_ascii_lowercase())))
+    /// }
+    ///
+    /// let packages = Vec::<Package>::deserialize(deserializer("Package: foo\nArchitecture: AMD64\n")).unwrap();
+    /// assert_eq!(packages[0].architecture, "amd64");
+    ///
+    /// let error = Vec::<Package>::deserialize(deserializer("Package: foo\nArchitecture: all\n\nPackage:\nArchitecture: all\n")).unwrap_err();
+    /// assert_eq!(error.to_string(), "line 4, paragraph 2, field Package: package name is empty");
+    /// ```
+    pub fn validate_field<N, F>(mut self, name: N, validator: F) -> Self where N: Into<String>, F: FnMut(&str, &str, &Location) -> Result<Option<String>, String> + Send + 'static {
+        self.state.validators.push((name.into(), Box::new(validator)));
+        self
+    }
+
+    /// Sets the pairs of values accepted as `true` and `false` by `bool` fields.
+    ///
+    /// By default `yes`/`no`, `true`/`false` and `1`/`0` are accepted.
+    /// The values are compared case-insensitively.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rfc822_like::de::Deserializer;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(serde_derive::Deserialize)]
+    /// #[serde(rename_all = "PascalCase")]
+    /// struct Package {
+    ///     #[serde(default)]
+    ///     essential: bool,
+    /// }
+    ///
+    /// let package = Package::deserialize(Deserializer::new("Essential: yes\n".as_bytes())).unwrap();
+    /// assert!(package.essential);
+    ///
+    /// let deserializer = Deserializer::new("Essential: true\n".as_bytes()).bool_values(vec![("yes", "no")]);
+    /// assert!(Package::deserialize(deserializer).is_err());
+    /// ```
+    pub fn bool_values<I, T, F>(mut self, values: I) -> Self where I: IntoIterator<Item=(T, F)>, T: Into<Cow<'static, str>>, F: Into<Cow<'static, str>> {
+        self.state.values.bool_values = values.into_iter().map(|(yes, no)| (yes.into(), no.into())).collect();
+        self
+    }
+
 }
 
-impl<'de, R: io::BufRead> serde::Deserializer<'de> for Deserializer<R> {
+impl<'de> Deserializer<SliceRead<'de>> {
+    /// Creates a `Deserializer` reading the `input` in memory.
+    ///
+    /// Unlike other readers this allows deserializing fields as `&str` or `Cow<str>` borrowing
+    /// from the input, which is much cheaper for large indexes.
+    /// Only values that don't need to be unfolded can be borrowed, i.e. single-line values and
+    /// values not modified by validators; `Cow<str>` handles the others by allocating.
+    /// Field names are borrowed unless they are converted using a key mapping.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::borrow::Cow;
+    /// use rfc822_like::de::Deserializer;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(serde_derive::Deserialize)]
+    /// #[serde(rename_all = "PascalCase")]
+    /// struct Package<'a> {
+    ///     package: &'a str,
+    ///     #[serde(borrow)]
+    ///     description: Cow<'a, str>,
+    /// }
+    ///
+    /// let input = "Package: foo\nDescription: The Foo\n It's very foo.\n\nPackage: bar\nDescription: The Bar\n";
+    /// let packages = Vec::<Package>::deserialize(Deserializer::from_str(input)).unwrap();
+    /// assert_eq!(packages[1].package, "bar");
+    /// assert_eq!(packages[0].description, "The Foo\nIt's very foo.");
+    /// ```
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(input: &'de str) -> Self {
+        Deserializer::new(SliceRead::new(input))
+    }
+}
+
+impl<R: io::BufRead> Deserializer<R> {
+    /// Deserializes the next record into `place` returning `false` if there are no more records.
+    ///
+    /// Internal buffers are reused between calls and `place` is filled using
+    /// `Deserialize::deserialize_in_place` so strings and vectors keep their allocations.
+    /// Derived implementations only reuse allocations if the `deserialize_in_place` feature of
+    /// `serde_derive` is enabled, otherwise this is equivalent to assigning a new value.
+    ///
+    /// `place` is left in an unspecified state if an error is returned.
+    pub fn next_in_place<T: DeserializeOwned>(&mut self, place: &mut T) -> Result<bool, Error> {
+        if !self.state.take_record() || !self.state.skip_empty_lines()? {
+            return Ok(false);
+        }
+        T::deserialize_in_place(SingleRecordDeserializer::new(&mut self.state), place)?;
+        Ok(true)
+    }
+
+    /// Returns an iterator deserializing records one at a time.
+    ///
+    /// Unlike deserializing `Vec<T>` this only keeps a single record in memory so it's suitable
+    /// for scanning huge files such as `Packages` indexes.
+    /// The iterator stops after returning an error.
+    /// Records are only read when requested so adapters like `take` stop reading the input as
+    /// soon as they have enough records.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rfc822_like::de::Deserializer;
+    ///
+    /// #[derive(serde_derive::Deserialize)]
+    /// #[serde(rename_all = "PascalCase")]
+    /// struct Record {
+    ///     package: String,
+    /// }
+    ///
+    /// let input = "Package: foo\n\nPackage: bar\n";
+    /// let names = Deserializer::new(input.as_bytes())
+    ///     .into_records::<Record>()
+    ///     .map(|record| record.map(|record| record.package))
+    ///     .collect::<Result<Vec<_>, _>>()
+    ///     .unwrap();
+    /// assert_eq!(names, ["foo", "bar"]);
+    /// ```
+    pub fn into_records<T: DeserializeOwned>(self) -> Records<R, T> {
+        Records {
+            deserializer: self,
+            failed: false,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Feeds each record to `f` together with the accumulated value, returning the final value.
+    ///
+    /// Only one record is in memory at a time so this computes statistics over whole archives in
+    /// a single pass.
+    /// Combined with [`select_fields`](Self::select_fields) only the fields needed by the
+    /// computation are unfolded and stored.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::collections::BTreeMap;
+    /// use rfc822_like::de::Deserializer;
+    ///
+    /// #[derive(serde_derive::Deserialize)]
+    /// #[serde(rename_all = "PascalCase")]
+    /// struct Package {
+    ///     section: String,
+    ///     #[serde(rename = "Installed-Size")]
+    ///     installed_size: String,
+    /// }
+    ///
+    /// let input = "Package: foo\nSection: net\nInstalled-Size: 10\nDescription: The Foo\n\nPackage: bar\nSection: net\nInstalled-Size: 32\n\nPackage: baz\nSection: doc\nInstalled-Size: 5\n";
+    /// let sizes = Deserializer::new(input.as_bytes())
+    ///     .select_fields(&["Section", "Installed-Size"])
+    ///     .fold_records(BTreeMap::new(), |mut sizes, package: Package| {
+    ///         *sizes.entry(package.section).or_insert(0) += package.installed_size.parse::<u64>().unwrap();
+    ///         sizes
+    ///     })
+    ///     .unwrap();
+    /// assert_eq!(sizes["net"], 42);
+    /// assert_eq!(sizes["doc"], 5);
+    /// ```
+    pub fn fold_records<T, A, F>(self, init: A, mut f: F) -> Result<A, Error> where T: DeserializeOwned, F: FnMut(A, T) -> A {
+        self.into_records().try_fold(init, |accumulator, record| record.map(|record| f(accumulator, record)))
+    }
+}
+
+/// Iterator over records of the input, see [`Deserializer::into_records`].
+pub struct Records<R, T> {
+    deserializer: Deserializer<R>,
+    failed: bool,
+    _phantom: PhantomData<fn() -> T>,
+}
+
+impl<R: io::BufRead, T: DeserializeOwned> Records<R, T> {
+    /// Deserializes the next record into `place`, see [`Deserializer::next_in_place`].
+    pub fn next_in_place(&mut self, place: &mut T) -> Result<bool, Error> {
+        if self.failed {
+            return Ok(false);
+        }
+        let result = self.deserializer.next_in_place(place);
+        self.failed = result.is_err();
+        result
+    }
+}
+
+impl<R: io::BufRead, T: DeserializeOwned> Iterator for Records<R, T> {
+    type Item = Result<T, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let state = &mut self.deserializer.state;
+        if !state.take_record() {
+            return None;
+        }
+        let result = state.skip_empty_lines().and_then(|has_record| if has_record {
+            T::deserialize(SingleRecordDeserializer::new(state)).map(Some)
+        } else {
+            Ok(None)
+        });
+        self.failed = result.is_err();
+        result.transpose()
+    }
+}
+
+impl<R: io::BufRead, T: DeserializeOwned> std::iter::FusedIterator for Records<R, T> {}
+
+impl<'de, R: Read<'de>> serde::Deserializer<'de> for Deserializer<R> {
     type Error = Error;
 
     fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
@@ -112,27 +539,105 @@ impl<'de, R: io::BufRead> serde::Deserializer<'de> for Deserializer<R> {
     }
 
     fn deserialize_map<V: Visitor<'de>>(mut self, visitor: V) -> Result<V::Value, Self::Error> {
-        visitor.visit_map(&mut self.state)
+        self.state.visit_record(None, visitor)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(mut self, _name: &'static str, fields: &'static [&'static str], visitor: V) -> Result<V::Value, Self::Error> {
+        self.state.visit_record(Some(fields), visitor)
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(mut self, len: usize, visitor: V) -> Result<V::Value, Self::Error> {
+        let value = visitor.visit_seq(Tuple { state: &mut self.state, remaining: len, })?;
+        // Records are only left if none of the elements is a sequence
+        if self.state.take_record() && self.state.skip_empty_lines()? {
+            return Err(ErrorInner::TrailingRecord(self.state.line).into());
+        }
+        Ok(value)
     }
 
-    fn deserialize_struct<V: Visitor<'de>>(mut self, _name: &'static str, _fields: &'static [&'static str], visitor: V) -> Result<V::Value, Self::Error> {
-        visitor.visit_map(&mut self.state)
+    fn deserialize_tuple_struct<V: Visitor<'de>>(self, _name: &'static str, len: usize, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.state.values.human_readable
     }
 
     serde::forward_to_deserialize_any! {
         bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
-        bytes byte_buf option unit unit_struct newtype_struct tuple
+        bytes byte_buf option unit unit_struct newtype_struct
+        enum identifier ignored_any
+    }
+}
+
+/// Elements of a tuple, each consuming one record unless it's a sequence.
+struct Tuple<'a, R> {
+    state: &'a mut DeserializerState<R>,
+    remaining: usize,
+}
+
+impl<'a, 'de, R: Read<'de>> SeqAccess<'de> for Tuple<'a, R> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error> where T: DeserializeSeed<'de> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        seed.deserialize(TupleElementDeserializer(self.state)).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+/// Deserializes a single record or, if a sequence is requested, all remaining records.
+///
+/// This allows deserializing tuples such as `(Source, Vec<Binary>)`.
+struct TupleElementDeserializer<'a, R>(&'a mut DeserializerState<R>);
+
+impl<'a, 'de, R: Read<'de>> serde::Deserializer<'de> for TupleElementDeserializer<'a, R> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+        Err(ErrorInner::AmbiguousType.into())
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_seq(Seq(self.0))
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.0.visit_record(None, visitor)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(self, _name: &'static str, fields: &'static [&'static str], visitor: V) -> Result<V::Value, Self::Error> {
+        self.0.visit_record(Some(fields), visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.0.values.human_readable
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct tuple
         tuple_struct enum identifier ignored_any
     }
 }
 
-struct Seq<'a, R: io::BufRead>(&'a mut DeserializerState<R>);
+struct Seq<'a, R>(&'a mut DeserializerState<R>);
 
-impl<'a, 'de, R: io::BufRead> SeqAccess<'de> for Seq<'a, R> {
+impl<'a, 'de, R: Read<'de>> SeqAccess<'de> for Seq<'a, R> {
     type Error = Error;
 
     fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error> where T: DeserializeSeed<'de> {
-        if self.0.eof {
+        if self.0.eof || !self.0.take_record() {
             return Ok(None);
         }
 
@@ -144,11 +649,11 @@ impl<'a, 'de, R: io::BufRead> SeqAccess<'de> for Seq<'a, R> {
     }
 }
 
-struct SingleRecordDeserializer<'a, R: io::BufRead> {
+struct SingleRecordDeserializer<'a, R> {
     state: &'a mut DeserializerState<R>,
 }
 
-impl<'a, R: io::BufRead> SingleRecordDeserializer<'a, R> {
+impl<'a, R> SingleRecordDeserializer<'a, R> {
     fn new(state: &'a mut DeserializerState<R>) -> Self {
         SingleRecordDeserializer {
             state,
@@ -156,52 +661,178 @@ impl<'a, R: io::BufRead> SingleRecordDeserializer<'a, R> {
     }
 }
 
-struct DeserializerState<R: io::BufRead> {
+/// Callback registered by `Deserializer::validate_field`.
+type Validator = Box<dyn FnMut(&str, &str, &Location) -> Result<Option<String>, String> + Send>;
+
+/// Pairs of values of `bool` fields meaning `true` and `false`.
+pub(crate) type BoolValues = [(Cow<'static, str>, Cow<'static, str>)];
+
+/// Values of `bool` fields accepted unless `Deserializer::bool_values` is used.
+const DEFAULT_BOOL_VALUES: &BoolValues = &[
+    (Cow::Borrowed("yes"), Cow::Borrowed("no")),
+    (Cow::Borrowed("true"), Cow::Borrowed("false")),
+    (Cow::Borrowed("1"), Cow::Borrowed("0")),
+];
+
+/// Settings affecting deserialization of field values.
+///
+/// These are shared with the deserializer of parsed documents.
+#[derive(Debug, Clone)]
+pub(crate) struct ValueSettings {
+    pub(crate) human_readable: bool,
+    pub(crate) bool_values: Vec<(Cow<'static, str>, Cow<'static, str>)>,
+}
+
+impl ValueSettings {
+    pub(crate) fn from_options(options: &Options) -> Self {
+        let bool_values = match &options.bool_values {
+            Some(values) => values.iter().map(|(yes, no)| (yes.clone().into(), no.clone().into())).collect(),
+            None => DEFAULT_BOOL_VALUES.to_vec(),
+        };
+        ValueSettings {
+            human_readable: options.human_readable,
+            bool_values,
+        }
+    }
+}
+
+impl Default for ValueSettings {
+    fn default() -> Self {
+        ValueSettings {
+            human_readable: true,
+            bool_values: DEFAULT_BOOL_VALUES.to_vec(),
+        }
+    }
+}
+
+struct DeserializerState<R> {
     reader: R,
+    // Lines read so far, only `buf[start..]` wasn't consumed yet
     buf: String,
+    start: usize,
+    // Position of the colon of the current field in `buf`
+    colon: usize,
+    // Reused for unfolding multi-line values
+    scratch: String,
+    // Scratch buffer for validating UTF-8 of each line before appending it to `buf`
+    bytes: Vec<u8>,
     line: usize,
+    offset: usize,
     eof: bool,
     empty: bool,
+    // Names of fields to deserialize, all fields if `None`
+    selected_fields: Option<Vec<String>>,
+    // Values of fields missing in records, see `Deserializer::default_field`
+    defaults: Vec<(String, String)>,
+    // Whether each default field is present in the current record
+    present: Vec<bool>,
+    // Index of the next default to consider once the record ended, `None` while reading fields
+    next_default: Option<usize>,
+    record_has_fields: bool,
+    // Index of the current record, only used for reporting locations
+    paragraph: usize,
+    // Line and paragraph of the first field of the current record, reported with errors of the
+    // whole record such as missing fields
+    record_location: Location,
+    validators: Vec<(String, Validator)>,
+    // Number of records left before the limit set by `max_records` is reached
+    remaining_records: Option<usize>,
+    key_mapping: Option<KeyMapping>,
+    // Fields of the struct being deserialized, the mapping only applies to structs, not maps
+    struct_fields: Option<&'static [&'static str]>,
+    strict_keys: bool,
+    values: ValueSettings,
 }
 
-impl<R: io::BufRead> DeserializerState<R> {
+impl<R> DeserializerState<R> {
     fn new(reader: R) -> Self {
         DeserializerState {
             reader,
             buf: String::new(),
+            start: 0,
+            colon: 0,
+            scratch: String::new(),
+            bytes: Vec::new(),
             line: 0,
+            offset: 0,
             eof: false,
             empty: true,
+            selected_fields: None,
+            defaults: Vec::new(),
+            present: Vec::new(),
+            next_default: None,
+            record_has_fields: false,
+            paragraph: 0,
+            record_location: Location::new(),
+            validators: Vec::new(),
+            remaining_records: None,
+            key_mapping: None,
+            struct_fields: None,
+            strict_keys: false,
+            values: ValueSettings::default(),
+        }
+    }
+}
+
+impl<'de, R: Read<'de>> DeserializerState<R> {
+    /// Appends next line to `buf` and returns its length.
+    ///
+    /// Unlike `BufRead::read_line` this reports the position of invalid UTF-8.
+    fn read_line(&mut self) -> Result<usize, Error> {
+        self.bytes.clear();
+        let amount = self.reader.next_line(&mut self.bytes).map_err(ErrorInner::from)?;
+        match std::str::from_utf8(&self.bytes) {
+            Ok(line) => self.buf.push_str(line),
+            Err(error) => {
+                let pos = error.valid_up_to();
+                return Err(ErrorInner::InvalidUtf8 {
+                    line: self.line + 1,
+                    offset: self.offset + pos,
+                    context: error::ByteContext::new(&self.bytes, pos),
+                }.into());
+            },
+        }
+        if amount > 0 {
+            self.line += 1;
         }
+        self.offset += amount;
+        Ok(amount)
     }
 
-    fn get_key(&mut self) -> Result<Option<&str>, Error> {
-        if self.buf.is_empty() {
-            match self.reader.read_line(&mut self.buf).map_err(ErrorInner::from)? {
+    /// Reads the line of the next field and records the position of its colon.
+    ///
+    /// Returns `false` if the record ended.
+    fn get_key(&mut self) -> Result<bool, Error> {
+        if self.start == self.buf.len() {
+            match self.read_line()? {
                 0 => {
                     self.eof = true;
-                    return Ok(None)
+                    return Ok(false)
                 },
                 // just \n
                 1 => {
-                    self.buf.clear();
+                    self.clear_buf(self.buf.len());
                     self.empty = true;
-                    self.line += 1;
-                    return Ok(None);
+                    return Ok(false);
                 },
-                _ => self.line += 1,
+                _ => (),
             }
         }
-        if self.buf == "\n" {
-            self.buf.clear();
+        if &self.buf[self.start..] == "\n" {
+            self.clear_buf(self.buf.len());
             self.empty = true;
-            return Ok(None);
+            return Ok(false);
         }
 
-        match self.buf.find(':') {
+        match self.buf[self.start..].find(':') {
             Some(pos) => {
+                let key = &self.buf[self.start..(self.start + pos)];
+                if self.strict_keys && !is_valid_key(key) {
+                    return Err(ErrorInner::InvalidKey { line: self.line, key: key.to_owned(), }.into());
+                }
                 self.empty = false;
-                Ok(Some(&self.buf[..pos]))
+                self.colon = self.start + pos;
+                Ok(true)
             },
             None => {
                 Err(ErrorInner::MissingColon(self.line).into())
@@ -209,74 +840,305 @@ impl<R: io::BufRead> DeserializerState<R> {
         }
     }
 
-    fn get_value(&mut self) -> Result<(&str, usize), Error> {
+    /// Reads the continuation lines and returns the range of the value in `buf`.
+    ///
+    /// Must be called right after `get_key` returned `true`.
+    fn get_value(&mut self) -> Result<(usize, usize), Error> {
         let mut pos = self.buf.len();
         loop {
-            let amount = self.reader.read_line(&mut self.buf).map_err(ErrorInner::from)?;
-            if amount == 0 || !(self.buf[pos..].starts_with(' ') || self.buf[pos..].starts_with('\t')) {
-                break;
+            let amount = self.read_line()?;
+            match self.buf.as_bytes().get(pos) {
+                Some(b' ') | Some(b'\t') => pos += amount,
+                _ => break,
             }
-            pos += amount;
         }
-        let begin = self.buf.find(':').expect("The caller didn't handle the error") + 1;
-        Ok((self.buf[begin..pos].trim(), pos))
+        Ok((self.colon + 1, pos))
+    }
+
+    /// Prepares the defaults for the next record.
+    fn end_record(&mut self) {
+        self.paragraph += 1;
+        self.next_default = None;
+        self.record_has_fields = false;
+        for present in &mut self.present {
+            *present = false;
+        }
+    }
+
+    fn is_selected(&self, key: &str) -> bool {
+        match &self.selected_fields {
+            Some(names) => names.iter().any(|name| name.eq_ignore_ascii_case(key)),
+            None => true,
+        }
+    }
+
+    /// Counts the next record against the limit, returns `false` if the limit was reached.
+    fn take_record(&mut self) -> bool {
+        match &mut self.remaining_records {
+            Some(0) => false,
+            Some(remaining) => {
+                *remaining -= 1;
+                true
+            },
+            None => true,
+        }
+    }
+
+    /// Skips empty lines preceding the next record, returns `false` if there's no record left.
+    fn skip_empty_lines(&mut self) -> Result<bool, Error> {
+        loop {
+            if self.start == self.buf.len() && self.read_line()? == 0 {
+                self.eof = true;
+                return Ok(false);
+            }
+            if &self.buf[self.start..] != "\n" {
+                return Ok(true);
+            }
+            self.clear_buf(self.buf.len());
+        }
+    }
+
+    /// Passes the current record to the visitor, adding the location to errors it raises.
+    fn visit_record<V: Visitor<'de>>(&mut self, struct_fields: Option<&'static [&'static str]>, visitor: V) -> Result<V::Value, Error> {
+        self.struct_fields = struct_fields;
+        visitor.visit_map(&mut *self).map_err(|error| error.with_location(|| self.record_location.clone()))
+    }
+
+    /// Returns the location of the field named `key`, defaults have no line.
+    fn field_location(&self, line: Option<usize>, key: &str) -> Location {
+        let location = Location::new().with_paragraph(self.paragraph).with_field(key);
+        match line {
+            Some(line) => location.with_line(line),
+            None => location,
+        }
+    }
+
+    /// Returns the borrowed input at the range of `buf` if the reader supports borrowing.
+    fn borrow(&self, range: Range<usize>) -> Option<&'de str> {
+        // `buf` holds the last bytes read from the input
+        let base = self.offset - self.buf.len();
+        self.reader.slice((base + range.start)..(base + range.end))
     }
 
+    /// Marks everything up to `pos` as consumed.
+    ///
+    /// The consumed part is only removed once it's at least as long as the rest so that each byte
+    /// is moved at most once on average, keeping the cost linear in the size of the input.
     fn clear_buf(&mut self, pos: usize) {
-        self.buf.replace_range(0..pos, "");
+        self.start = pos;
+        if self.start == self.buf.len() {
+            self.buf.clear();
+            self.start = 0;
+        } else if self.start >= self.buf.len() - self.start {
+            self.buf.drain(..self.start);
+            self.start = 0;
+        }
     }
 }
 
-impl<'a, 'de, R: io::BufRead> MapAccess<'de> for &'a mut DeserializerState<R> {
+impl<'de, R: Read<'de>> MapAccess<'de> for &mut DeserializerState<R> {
     type Error = Error;
 
     fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error> where K: DeserializeSeed<'de> {
-        self
-            .get_key()?
-            .map(move |key| seed.deserialize(KeyDeserializer(key)))
-            .transpose()
+        if self.next_default.is_none() {
+            loop {
+                if !self.get_key()? {
+                    if !self.record_has_fields {
+                        return Ok(None);
+                    }
+                    if self.defaults.is_empty() {
+                        self.end_record();
+                        return Ok(None);
+                    }
+                    self.next_default = Some(0);
+                    break;
+                }
+                if !self.record_has_fields {
+                    self.record_location = Location::new().with_line(self.line).with_paragraph(self.paragraph);
+                    self.record_has_fields = true;
+                }
+                let key = &self.buf[self.start..self.colon];
+                if let Some(index) = self.defaults.iter().position(|(name, _)| name.eq_ignore_ascii_case(key)) {
+                    self.present[index] = true;
+                }
+                if self.is_selected(key) {
+                    break;
+                }
+                let (_, pos) = self.get_value()?;
+                self.clear_buf(pos);
+            }
+        }
+        let (key, borrowed, line) = match self.next_default {
+            Some(first) => match (first..self.defaults.len()).find(|index| !self.present[*index] && self.is_selected(&self.defaults[*index].0)) {
+                Some(index) => {
+                    self.next_default = Some(index + 1);
+                    (self.defaults[index].0.as_str(), None, None)
+                },
+                None => {
+                    self.end_record();
+                    return Ok(None);
+                },
+            },
+            None => (&self.buf[self.start..self.colon], self.borrow(self.start..self.colon), Some(self.line)),
+        };
+        let result = match &self.key_mapping {
+            // Names of renamed fields already match
+            Some(mapping) if self.struct_fields.map_or(false, |fields| !fields.contains(&key)) => seed.deserialize(KeyDeserializer(&mapping.to_ident(key), None, self.values.human_readable)),
+            _ => seed.deserialize(KeyDeserializer(key, borrowed, self.values.human_readable)),
+        };
+        result.map(Some).map_err(|error| error.with_location(|| self.field_location(line, key)))
     }
 
     fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error> where V: DeserializeSeed<'de> {
-        let (value, pos) = self
-            .get_value()?;
-        let result = seed.deserialize(ValueDeserializer(value));
+        if let Some(next) = self.next_default {
+            let (key, value) = &self.defaults[next - 1];
+            return seed.deserialize(ValueDeserializer { value, settings: &self.values, scratch: None, })
+                .map_err(|error| error.with_location(|| self.field_location(None, key)));
+        }
+        // The line of the key, `get_value` reads past the field
+        let line = self.line;
+        let (begin, pos) = self.get_value()?;
+        let raw = &self.buf[begin..pos];
+        let value_start = begin + raw.len() - raw.trim_start().len();
+        let value = raw.trim();
+        let mut validated = None;
+        if !self.validators.is_empty() {
+            let key = &self.buf[self.start..self.colon];
+            let location = Location::new().with_line(line).with_paragraph(self.paragraph).with_field(key);
+            for (name, validator) in &mut self.validators {
+                if name.eq_ignore_ascii_case(key) {
+                    let current = validated.as_deref().unwrap_or(value);
+                    match validator(key, current, &location) {
+                        Ok(None) => (),
+                        Ok(Some(replacement)) => validated = Some(replacement),
+                        Err(message) => return Err(ErrorInner::InvalidField { location, message }.into()),
+                    }
+                }
+            }
+        }
+        let borrowed = match validated {
+            Some(_) => None,
+            None => self.borrow(value_start..(value_start + value.len())),
+        };
+        let result = match borrowed {
+            Some(value) => seed.deserialize(BorrowedValueDeserializer { value, settings: &self.values, scratch: Some(&mut self.scratch), }),
+            None => seed.deserialize(ValueDeserializer { value: validated.as_deref().unwrap_or(value), settings: &self.values, scratch: Some(&mut self.scratch), }),
+        };
+        let result = result.map_err(|error| error.with_location(|| self.field_location(Some(line), &self.buf[self.start..self.colon])));
         self.clear_buf(pos);
         result
     }
 }
 
-impl<'a, 'de, R: io::BufRead> serde::Deserializer<'de> for SingleRecordDeserializer<'a, R> {
+impl<'a, 'de, R: Read<'de>> serde::Deserializer<'de> for SingleRecordDeserializer<'a, R> {
     type Error = Error;
 
-    fn deserialize_any<V>(mut self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
-        visitor.visit_map(&mut self.state)
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        self.state.visit_record(None, visitor)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(self, _name: &'static str, fields: &'static [&'static str], visitor: V) -> Result<V::Value, Self::Error> {
+        self.state.visit_record(Some(fields), visitor)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.state.values.human_readable
     }
 
     serde::forward_to_deserialize_any! {
         bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
         bytes byte_buf option unit unit_struct newtype_struct seq tuple
-        tuple_struct map struct enum identifier ignored_any
+        tuple_struct map enum identifier ignored_any
     }
 }
 
-struct KeyDeserializer<'a>(&'a str);
+/// Implements deserialization of numbers by parsing the value, which is written using `Display`.
+///
+/// The value is `self.0` unless the field holding it is given before a semicolon.
+macro_rules! deserialize_numbers {
+    ($field:tt; $($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                match self.$field.parse() {
+                    Ok(number) => visitor.$visit(number),
+                    Err(_) => Err(serde::de::Error::invalid_value(Unexpected::Str(self.$field), &visitor)),
+                }
+            }
+        )*
+    };
+    ($($method:ident => $visit:ident,)*) => {
+        deserialize_numbers! { 0; $($method => $visit,)* }
+    };
+}
+
+/// Deserializer of field names and list items, the second item is the same string borrowed
+/// from the input.
+struct KeyDeserializer<'a, 'de>(&'a str, Option<&'de str>, bool);
 
-impl<'a, 'de> serde::Deserializer<'de> for KeyDeserializer<'a> {
+impl<'a, 'de> serde::Deserializer<'de> for KeyDeserializer<'a, 'de> {
     type Error = Error;
 
     fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
-        visitor.visit_str(self.0)
+        match self.1 {
+            Some(key) => visitor.visit_borrowed_str(key),
+            None => visitor.visit_str(self.0),
+        }
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.2
+    }
+
+    deserialize_numbers! {
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
     }
 
     serde::forward_to_deserialize_any! {
-        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bool char str string
         bytes byte_buf option unit unit_struct newtype_struct seq tuple
         tuple_struct map struct enum identifier ignored_any
     }
 }
 
-struct ValueDeserializer<'a>(&'a str);
+/// Checks the deb822 syntax of field names.
+fn is_valid_key(key: &str) -> bool {
+    !key.is_empty()
+        && !key.starts_with('#')
+        && !key.starts_with('-')
+        && key.bytes().all(|byte| byte > b' ' && byte < 0x7f && byte != b':')
+}
+
+/// Deserializer of field values.
+pub(crate) struct ValueDeserializer<'a> {
+    pub(crate) value: &'a str,
+    pub(crate) settings: &'a ValueSettings,
+    // Buffer reused for unfolding multi-line values borrowed by the visitor
+    pub(crate) scratch: Option<&'a mut String>,
+}
+
+/// Appends the value with continuation lines joined to `output`.
+fn unfold_value(value: &str, output: &mut String) {
+    let mut iter = value.split('\n');
+    output.push_str(iter.next().expect("split didn't return any item"));
+
+    for line in iter {
+        output.push('\n');
+        if line != " ." {
+            output.push_str(line.trim_start());
+        }
+    }
+}
 
 impl<'a, 'de> serde::Deserializer<'de> for ValueDeserializer<'a> {
     type Error = Error;
@@ -286,33 +1148,47 @@ impl<'a, 'de> serde::Deserializer<'de> for ValueDeserializer<'a> {
     }
 
     fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
-        if self.0.contains("\n ") {
-            let mut string = String::with_capacity(self.0.len());
-            let mut iter = self.0.split('\n');
-            string.push_str(iter.next().expect("split didn't return any item"));
-
-            for line in iter {
-                string.push('\n');
-                if line != " ." {
-                    string.push_str(line.trim_start());
-                }
-            }
+        if !self.value.contains("\n ") {
+            return visitor.visit_str(self.value);
+        }
+        match self.scratch {
+            // Visitors that don't need an owned string (e.g. parsing numbers or dates) don't
+            // allocate at all.
+            Some(scratch) => {
+                scratch.clear();
+                unfold_value(self.value, scratch);
+                visitor.visit_str(scratch)
+            },
+            None => self.deserialize_string(visitor),
+        }
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        if !self.value.contains("\n ") {
+            return visitor.visit_str(self.value);
+        }
+        // The visitor wants to own the string so it's built directly without going through
+        // the scratch buffer.
+        let mut string = String::with_capacity(self.value.len());
+        unfold_value(self.value, &mut string);
+        visitor.visit_string(string)
+    }
 
-            visitor.visit_string(string)
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        if name == raw_value::TOKEN {
+            visitor.visit_newtype_struct(self.value.into_deserializer())
         } else {
-            visitor.visit_str(self.0)
+            self.deserialize_str(visitor)
         }
     }
 
-    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
-        // Possible optimization: we could instead have a visitor that points to appropriate
-        // position inside the buffer and removes the beginning if called here, or turns it into a
-        // slice if called above.
-        self.deserialize_str(visitor)
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        // Ignored values don't need to be unfolded
+        visitor.visit_unit()
     }
 
     fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
-        visitor.visit_seq(StrSeq(self.0.split(',')))
+        visitor.visit_seq(StrSeq(self.value.split(','), self.settings.human_readable))
     }
 
     fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
@@ -328,17 +1204,154 @@ impl<'a, 'de> serde::Deserializer<'de> for ValueDeserializer<'a> {
     where
         V: Visitor<'de>,
     {
-        visitor.visit_enum(self.0.into_deserializer())
+        visitor.visit_enum(self.value.into_deserializer())
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.settings.human_readable
+    }
+
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        for (yes, no) in &self.settings.bool_values {
+            if self.value.eq_ignore_ascii_case(yes) {
+                return visitor.visit_bool(true);
+            }
+            if self.value.eq_ignore_ascii_case(no) {
+                return visitor.visit_bool(false);
+            }
+        }
+        Err(serde::de::Error::invalid_value(Unexpected::Str(self.value), &visitor))
+    }
+
+    deserialize_numbers! {
+        value;
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
     }
 
     serde::forward_to_deserialize_any! {
-        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char
-        bytes byte_buf unit unit_struct newtype_struct tuple
-        tuple_struct map struct identifier ignored_any
+        char
+        bytes byte_buf unit unit_struct tuple
+        tuple_struct map struct identifier
     }
 }
 
-struct StrDeserializer<'a>(&'a str);
+/// Deserializer of a value borrowed from the input, see [`ValueDeserializer`].
+///
+/// Values that need unfolding can not be borrowed and are passed the same way as by
+/// `ValueDeserializer`.
+struct BorrowedValueDeserializer<'a, 'de> {
+    value: &'de str,
+    settings: &'a ValueSettings,
+    scratch: Option<&'a mut String>,
+}
+
+impl<'a, 'de> BorrowedValueDeserializer<'a, 'de> {
+    fn unborrowed(self) -> ValueDeserializer<'a> where 'de: 'a {
+        ValueDeserializer {
+            value: self.value,
+            settings: self.settings,
+            scratch: self.scratch,
+        }
+    }
+}
+
+macro_rules! forward_to_value_deserializer {
+    ($($method:ident($($arg:ident: $arg_type:ty),*);)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, $($arg: $arg_type,)* visitor: V) -> Result<V::Value, Self::Error> {
+                self.unborrowed().$method($($arg,)* visitor)
+            }
+        )*
+    }
+}
+
+impl<'a, 'de> serde::Deserializer<'de> for BorrowedValueDeserializer<'a, 'de> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        if self.value.contains("\n ") {
+            self.unborrowed().deserialize_str(visitor)
+        } else {
+            visitor.visit_borrowed_str(self.value)
+        }
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        if self.value.contains("\n ") {
+            self.unborrowed().deserialize_string(visitor)
+        } else {
+            visitor.visit_borrowed_str(self.value)
+        }
+    }
+
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        if name == raw_value::TOKEN {
+            self.unborrowed().deserialize_newtype_struct(name, visitor)
+        } else {
+            self.deserialize_str(visitor)
+        }
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        visitor.visit_seq(BorrowedStrSeq(self.value.split(','), self.settings.human_readable))
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        visitor.visit_some(self)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.settings.human_readable
+    }
+
+    forward_to_value_deserializer! {
+        deserialize_bool();
+        deserialize_i8();
+        deserialize_i16();
+        deserialize_i32();
+        deserialize_i64();
+        deserialize_i128();
+        deserialize_u8();
+        deserialize_u16();
+        deserialize_u32();
+        deserialize_u64();
+        deserialize_u128();
+        deserialize_f32();
+        deserialize_f64();
+        deserialize_char();
+        deserialize_bytes();
+        deserialize_byte_buf();
+        deserialize_unit();
+        deserialize_unit_struct(name: &'static str);
+        deserialize_tuple(len: usize);
+        deserialize_tuple_struct(name: &'static str, len: usize);
+        deserialize_map();
+        deserialize_struct(name: &'static str, fields: &'static [&'static str]);
+        deserialize_enum(name: &'static str, variants: &'static [&'static str]);
+        deserialize_ignored_any();
+    }
+}
+
+struct StrDeserializer<'a>(&'a str, bool);
 
 impl<'a, 'de> serde::Deserializer<'de> for StrDeserializer<'a> {
     type Error = Error;
@@ -347,28 +1360,62 @@ impl<'a, 'de> serde::Deserializer<'de> for StrDeserializer<'a> {
         visitor.visit_str(self.0)
     }
 
+    fn is_human_readable(&self) -> bool {
+        self.1
+    }
+
+    deserialize_numbers! {
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+    }
+
     serde::forward_to_deserialize_any! {
-        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bool char str string
         bytes byte_buf option unit unit_struct newtype_struct seq tuple
         tuple_struct map struct enum identifier ignored_any
     }
 }
 
-struct StrSeq<'a>(std::str::Split<'a, char>);
+struct StrSeq<'a>(std::str::Split<'a, char>, bool);
 
 impl<'a, 'de> SeqAccess<'de> for StrSeq<'a> {
     type Error = Error;
 
     fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error> where T: DeserializeSeed<'de> {
-        self.0.next().map(|item| seed.deserialize(StrDeserializer(item.trim()))).transpose()
+        self.0.next().map(|item| seed.deserialize(StrDeserializer(item.trim(), self.1))).transpose()
     }
 
     // fn size_hint(&self) -> Option<usize> { ... } not specialized for split
 }
 
+/// Items of a list borrowed from the input.
+struct BorrowedStrSeq<'de>(std::str::Split<'de, char>, bool);
+
+impl<'de> SeqAccess<'de> for BorrowedStrSeq<'de> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error> where T: DeserializeSeed<'de> {
+        self.0.next().map(|item| {
+            let item = item.trim();
+            seed.deserialize(KeyDeserializer(item, Some(item), self.1))
+        }).transpose()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use serde::Deserialize;
+    use std::collections::HashMap;
 
     #[test]
     fn test_single() {
@@ -634,6 +1681,314 @@ mod tests {
         }
     }
 
+    #[test]
+    fn many_fields() {
+        let mut input = String::new();
+        for i in 0..1000 {
+            input.push_str(&format!("Field-{}: {}\n continued\n", i, i));
+        }
+        input.push_str("\nField-0: last\n");
+        let records = Vec::<HashMap<String, String>>::deserialize(super::Deserializer::new(input.as_bytes())).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].len(), 1000);
+        assert_eq!(records[0]["Field-999"], "999\ncontinued");
+        assert_eq!(records[1]["Field-0"], "last");
+    }
+
+    #[test]
+    fn records() {
+        #[derive(Debug, Default, serde_derive::Deserialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct Record {
+            package: String,
+        }
+
+        let input = "Package: foo\n\nPackage: bar\n\n";
+        let records = super::Deserializer::new(input.as_bytes()).into_records::<HashMap<String, String>>().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1]["Package"], "bar");
+
+        let mut records = super::Deserializer::new(input.as_bytes()).into_records::<Record>();
+        let mut record = Record::default();
+        assert!(records.next_in_place(&mut record).unwrap());
+        assert_eq!(record.package, "foo");
+        assert!(records.next_in_place(&mut record).unwrap());
+        assert_eq!(record.package, "bar");
+        assert!(!records.next_in_place(&mut record).unwrap());
+
+        let mut records = super::Deserializer::new("Package: foo\n\nfoo\n\nPackage: bar\n".as_bytes()).into_records::<Record>();
+        assert!(records.next().unwrap().is_ok());
+        assert!(records.next().unwrap().is_err());
+        assert!(records.next().is_none());
+    }
+
+    #[test]
+    fn fold_records() {
+        let input = "Package: foo\nSize: 1\n\nPackage: bar\nSize: 2\n\n\nPackage: baz\nSize: 3\n";
+        let total = super::Deserializer::new(input.as_bytes())
+            .max_records(2)
+            .fold_records(0, |total, record: HashMap<String, String>| total + record["Size"].parse::<u32>().unwrap())
+            .unwrap();
+        assert_eq!(total, 3);
+
+        let lengths = super::Deserializer::new(input.as_bytes())
+            .select_fields(&["Package"])
+            .fold_records(Vec::new(), |mut lengths, record: HashMap<String, String>| {
+                lengths.push(record.len());
+                lengths
+            })
+            .unwrap();
+        assert_eq!(lengths, [1, 1, 1]);
+
+        let invalid = "Package: foo\n\nno colon\n";
+        assert!(super::Deserializer::new(invalid.as_bytes()).fold_records(0, |count, _: HashMap<String, String>| count + 1).is_err());
+    }
+
+    #[test]
+    fn max_records() {
+        let input = "Package: foo\n\nPackage: bar\n\nPackage: baz\n";
+        let mut reader = input.as_bytes();
+        let records = Vec::<HashMap<String, String>>::deserialize(super::Deserializer::new(&mut reader).max_records(1)).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(reader, b"Package: bar\n\nPackage: baz\n");
+
+        let records = super::Deserializer::new(input.as_bytes()).max_records(2).into_records::<HashMap<String, String>>();
+        assert_eq!(records.count(), 2);
+
+        let mut reader = input.as_bytes();
+        let records = super::Deserializer::new(&mut reader).into_records::<HashMap<String, String>>().take(1);
+        assert_eq!(records.count(), 1);
+        assert_eq!(reader, b"Package: bar\n\nPackage: baz\n");
+    }
+
+    #[test]
+    fn select_fields() {
+        #[derive(serde_derive::Deserialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct Record {
+            package: String,
+            filename: Option<String>,
+        }
+
+        let input = "Package: foo\nDescription: The Foo\n It's very foo.\nFilename: foo.deb\n\nPackage: bar\nVersion: 1.0\n";
+        let deserializer = super::Deserializer::new(input.as_bytes()).select_fields(vec!["PACKAGE".to_owned(), "Filename".to_owned()]);
+        let records = Vec::<HashMap<String, String>>::deserialize(deserializer).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].keys().map(AsRef::as_ref).collect::<std::collections::BTreeSet<&str>>(), ["Filename", "Package"].iter().copied().collect());
+        assert_eq!(records[1].len(), 1);
+
+        let deserializer = super::Deserializer::new(input.as_bytes()).select_fields(&["Package"]);
+        let records = Vec::<Record>::deserialize(deserializer).unwrap();
+        assert_eq!(records[0].package, "foo");
+        assert!(records[0].filename.is_none());
+    }
+
+    #[test]
+    fn default_field() {
+        let input = "Package: foo\n\nPackage: bar\nESSENTIAL: yes\n";
+        let deserializer = super::Deserializer::new(input.as_bytes())
+            .default_field("Essential", "no")
+            .default_field("Priority", "optional");
+        let records = Vec::<HashMap<String, String>>::deserialize(deserializer).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0]["Essential"], "no");
+        assert_eq!(records[0]["Priority"], "optional");
+        assert_eq!(records[1]["ESSENTIAL"], "yes");
+        assert!(!records[1].contains_key("Essential"));
+        assert_eq!(records[1]["Priority"], "optional");
+
+        let records = super::Deserializer::new(input.as_bytes())
+            .default_field("Priority", "optional")
+            .select_fields(&["Package"])
+            .into_records::<HashMap<String, String>>()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].len(), 1);
+
+        let deserializer = super::Deserializer::new("".as_bytes()).default_field("Priority", "optional");
+        assert!(HashMap::<String, String>::deserialize(deserializer).unwrap().is_empty());
+    }
+
+    #[test]
+    fn strict_keys() {
+        assert!(super::is_valid_key("Package"));
+        assert!(super::is_valid_key("X-Foo_bar.2"));
+        assert!(!super::is_valid_key(""));
+        assert!(!super::is_valid_key("#Package"));
+        assert!(!super::is_valid_key("-Package"));
+        assert!(!super::is_valid_key("Pack\tage"));
+        assert!(!super::is_valid_key("Packagé"));
+
+        let parse = |input: &str, strict| HashMap::<String, String>::deserialize(super::Deserializer::new(input.as_bytes()).strict_keys(strict));
+        assert_eq!(parse("Package: foo\nX-\u{1}: bar\n", false).unwrap().len(), 2);
+        assert_eq!(parse("Package: foo\nX-\u{1}: bar\n", true).unwrap_err().to_string(), "Line 2 contains invalid field name \"X-\\u{1}\"");
+        assert_eq!(parse("-Package: foo\n", true).unwrap_err().to_string(), "Line 1 contains invalid field name \"-Package\"");
+        assert_eq!(parse("Package: foo\n", true).unwrap()["Package"], "foo");
+    }
+
+    #[test]
+    fn validate_field() {
+        let input = "Package: foo\nVersion:  1.0 \n\nPackage: bar\nversion: 2.0\n";
+        let seen = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen_clone = seen.clone();
+        let deserializer = super::Deserializer::new(input.as_bytes())
+            .validate_field("VERSION", move |name, value, location| {
+                seen_clone.lock().unwrap().push((name.to_owned(), value.to_owned(), location.line(), location.paragraph()));
+                Ok(Some(format!("{}-1", value)))
+            })
+            .validate_field("Version", |_, value, _| Ok(Some(value.to_uppercase())));
+        let records = Vec::<HashMap<String, String>>::deserialize(deserializer).unwrap();
+        assert_eq!(records[0]["Version"], "1.0-1");
+        assert_eq!(records[1]["version"], "2.0-1");
+        assert_eq!(records[0]["Package"], "foo");
+        let expected = vec![
+            ("Version".to_owned(), "1.0".to_owned(), Some(2), Some(0)),
+            ("version".to_owned(), "2.0".to_owned(), Some(5), Some(1)),
+        ];
+        assert_eq!(*seen.lock().unwrap(), expected);
+
+        let deserializer = super::Deserializer::new(input.as_bytes())
+            .validate_field("Package", |_, value, _| if value == "bar" { Err("bar is banned".to_owned()) } else { Ok(None) });
+        let error = Vec::<HashMap<String, String>>::deserialize(deserializer).unwrap_err();
+        assert_eq!(error.to_string(), "line 4, paragraph 2, field Package: bar is banned");
+    }
+
+    #[test]
+    fn unfold_borrowed() {
+        struct LineCount(usize);
+
+        impl<'de> Deserialize<'de> for LineCount {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                struct Visitor;
+
+                impl<'de> serde::de::Visitor<'de> for Visitor {
+                    type Value = LineCount;
+
+                    fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                        f.write_str("a string")
+                    }
+
+                    fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<Self::Value, E> {
+                        assert!(!value.contains("\n "));
+                        Ok(LineCount(value.lines().count()))
+                    }
+
+                    fn visit_string<E: serde::de::Error>(self, _: String) -> Result<Self::Value, E> {
+                        panic!("the value was unnecessarily allocated");
+                    }
+                }
+
+                deserializer.deserialize_str(Visitor)
+            }
+        }
+
+        let input = "A: first\n second\n .\n third\nB: x\n y\n";
+        let map = HashMap::<String, LineCount>::deserialize(super::Deserializer::new(input.as_bytes())).unwrap();
+        assert_eq!(map["A"].0, 4);
+        assert_eq!(map["B"].0, 2);
+        let map = HashMap::<String, String>::deserialize(super::Deserializer::new(input.as_bytes())).unwrap();
+        assert_eq!(map["A"], "first\nsecond\n\nthird");
+    }
+
+    #[test]
+    fn key_mapping() {
+        #[derive(Debug, serde_derive::Deserialize)]
+        struct Record {
+            installed_size: String,
+            md5sum: Option<String>,
+        }
+
+        let input = "Installed-Size: 42\nMD5sum: 00\n\nInstalled-Size: 1\n";
+        let deserializer = super::Deserializer::new(input.as_bytes()).key_mapping(crate::key_mapping::KeyMapping::debian());
+        let records = Vec::<Record>::deserialize(deserializer).unwrap();
+        assert_eq!(records[0].md5sum.as_deref(), Some("00"));
+        assert_eq!(records[1].installed_size, "1");
+
+        let deserializer = super::Deserializer::new(input.as_bytes()).key_mapping(crate::key_mapping::KeyMapping::debian());
+        let map = HashMap::<String, String>::deserialize(deserializer).unwrap();
+        assert!(map.contains_key("Installed-Size"));
+
+        #[derive(Debug, serde_derive::Deserialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct Renamed {
+            #[serde(rename = "Installed-Size")]
+            installed_size: String,
+            #[serde(rename = "MD5sum")]
+            md5sum: String,
+            description: Option<String>,
+            homepage: Option<String>,
+        }
+
+        let input = "Installed-Size: 42\nMD5sum: 00\nDescription: foo\nhomepage: https://example.org\n";
+        let deserializer = super::Deserializer::new(input.as_bytes()).key_mapping(crate::key_mapping::KeyMapping::debian());
+        let record = Renamed::deserialize(deserializer).unwrap();
+        assert_eq!(record.installed_size, "42");
+        assert_eq!(record.md5sum, "00");
+        assert_eq!(record.description.as_deref(), Some("foo"));
+        assert!(record.homepage.is_none());
+    }
+
+    #[test]
+    fn human_readable() {
+        struct Probe(bool);
+
+        impl<'de> Deserialize<'de> for Probe {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let human_readable = deserializer.is_human_readable();
+                String::deserialize(deserializer).map(|_| Probe(human_readable))
+            }
+        }
+
+        #[derive(serde_derive::Deserialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct Record {
+            value: Probe,
+            list: Vec<Probe>,
+        }
+
+        let input = "Value: foo\nList: a, b\n";
+        let record = Record::deserialize(super::Deserializer::new(input.as_bytes())).unwrap();
+        assert!(record.value.0 && record.list.iter().all(|probe| probe.0));
+        let records = Vec::<Record>::deserialize(super::Deserializer::new(input.as_bytes()).human_readable(false)).unwrap();
+        assert!(!records[0].value.0 && records[0].list.iter().all(|probe| !probe.0));
+    }
+
+    #[test]
+    fn test_first_and_rest() {
+        #[derive(Debug, serde_derive::Deserialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct Source {
+            source: String,
+        }
+
+        #[derive(Debug, serde_derive::Deserialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct Binary {
+            package: String,
+        }
+
+        let mut input = b"Source: foo\n\nPackage: foo\n\nPackage: foo-doc\n" as &[u8];
+        let (source, binaries) = <(Source, Vec<Binary>)>::deserialize(super::Deserializer::new(&mut input)).unwrap();
+        assert_eq!(source.source, "foo");
+        assert_eq!(binaries.iter().map(|binary| binary.package.as_str()).collect::<Vec<_>>(), ["foo", "foo-doc"]);
+
+        let mut input = b"Source: foo\n" as &[u8];
+        let (_, binaries) = <(Source, Vec<Binary>)>::deserialize(super::Deserializer::new(&mut input)).unwrap();
+        assert!(binaries.is_empty());
+
+        let mut input = b"Source: foo\n\nPackage: foo\n" as &[u8];
+        let (_, binary) = <(Source, Binary)>::deserialize(super::Deserializer::new(&mut input)).unwrap();
+        assert_eq!(binary.package, "foo");
+
+        let mut input = b"Source: foo\n\nPackage: foo\n\n\nPackage: bar\n" as &[u8];
+        let error = <(Source, Binary)>::deserialize(super::Deserializer::new(&mut input)).unwrap_err();
+        assert_eq!(error.to_string(), "Line 6 starts a record following the last element of the tuple");
+
+        let mut input = b"Package: foo\n\nPackage: bar\n" as &[u8];
+        assert!(<(Source, Vec<Binary>)>::deserialize(super::Deserializer::new(&mut input)).is_err());
+    }
+
     #[test]
     fn test_option_none() {
         #[derive(serde_derive::Deserialize)]
@@ -666,6 +2021,23 @@ mod tests {
         assert_eq!(package.description, Some("The Internet of Money".to_owned()));
     }
 
+    #[test]
+    fn test_invalid_utf8() {
+        #[derive(Debug, serde_derive::Deserialize)]
+        #[serde(rename_all = "PascalCase")]
+        #[allow(dead_code)]
+        struct Record {
+            name: String,
+            description: String,
+        }
+
+        let input = b"Name: bitcoin\nDescription: Magic\n Internet \xff Money\n";
+        let error = crate::from_bytes::<Record>(input).unwrap_err();
+        assert_eq!(error.to_string(), "Line 3 contains invalid UTF-8 at byte offset 43 (bytes: 6e 74 65 72 6e 65 74 20 [ff] 20 4d 6f 6e 65 79 0a)");
+        let error = crate::from_bytes::<Record>(b"Name: \xc3").unwrap_err();
+        assert_eq!(error.to_string(), "Line 1 contains invalid UTF-8 at byte offset 6 (bytes: 4e 61 6d 65 3a 20 [c3])");
+    }
+
     #[test]
     fn test_deserialize_unit_variant() {
         #[derive(serde_derive::Deserialize, PartialEq, Eq, Debug)]
@@ -685,4 +2057,102 @@ mod tests {
         let baz = Baz::deserialize(deserializer).unwrap();
         assert_eq!(baz.foo, Foo::Bar);
     }
+
+    #[test]
+    fn borrowed() {
+        use std::borrow::Cow;
+        use std::collections::BTreeMap;
+
+        #[derive(serde_derive::Deserialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct Package<'a> {
+            package: &'a str,
+            #[serde(borrow)]
+            description: Cow<'a, str>,
+            #[serde(borrow)]
+            depends: Vec<&'a str>,
+            version: Option<&'a str>,
+        }
+
+        let input = "Package: foo\nDescription: The Foo\n It's very foo.\nDepends: bar, baz\n\nPackage:  qux \nDescription: The Qux\nDepends: foo\nVersion: 1.0\n";
+        let packages = crate::from_str::<Vec<Package>>(input).unwrap();
+        assert_eq!(packages[0].package, "foo");
+        assert_eq!(packages[0].description, "The Foo\nIt's very foo.");
+        match &packages[0].description {
+            Cow::Owned(_) => (),
+            Cow::Borrowed(_) => panic!("multi-line value was borrowed"),
+        }
+        assert_eq!(packages[0].depends, ["bar", "baz"]);
+        assert!(packages[0].version.is_none());
+        assert_eq!(packages[1].package, "qux");
+        match &packages[1].description {
+            Cow::Owned(_) => panic!("single-line value was copied"),
+            Cow::Borrowed(description) => assert_eq!(*description, "The Qux"),
+        }
+        assert_eq!(packages[1].version, Some("1.0"));
+
+        let record = crate::from_str::<BTreeMap<&str, &str>>("Package: foo\nVersion: 1.0\n").unwrap();
+        assert_eq!(record["Version"], "1.0");
+        assert!(crate::from_str::<BTreeMap<&str, &str>>("Description: The Foo\n It's very foo.\n").is_err());
+
+        let deserializer = super::Deserializer::from_str("Package: foo\n").default_field("Version", "1.0");
+        assert!(BTreeMap::<&str, &str>::deserialize(deserializer).is_err());
+        let deserializer = super::Deserializer::from_str("Package: foo\n").default_field("Version", "1.0");
+        assert_eq!(BTreeMap::<String, Cow<str>>::deserialize(deserializer).unwrap()["Version"], "1.0");
+    }
+
+    #[test]
+    fn error_locations() {
+        #[derive(Debug, serde_derive::Deserialize)]
+        #[serde(rename_all = "PascalCase", deny_unknown_fields)]
+        struct Package {
+            package: String,
+            #[serde(rename = "Installed-Size")]
+            installed_size: Option<u64>,
+        }
+
+        let package = crate::from_str::<Package>("Package: foo\nInstalled-Size: 1\n").unwrap();
+        assert_eq!((package.package.as_str(), package.installed_size), ("foo", Some(1)));
+
+        let input = "Package: foo\nInstalled-Size: 1\n\nPackage: bar\nInstalled-Size:\n 1\n\nPackage: baz\nInstalled-Size: big\n";
+        let error = crate::from_str::<Vec<Package>>(input).unwrap_err();
+        assert_eq!(error.to_string(), "line 9, paragraph 3, field Installed-Size: invalid value: string \"big\", expected u64");
+        assert_eq!(error.line(), Some(9));
+        assert_eq!(error.field(), Some("Installed-Size"));
+
+        let error = crate::from_str::<Vec<Package>>("Package: foo\n\nInstalled-Size: 1\n").unwrap_err();
+        assert_eq!(error.to_string(), "line 3, paragraph 2: missing field `Package`");
+        let error = crate::from_str::<Package>("Package: foo\nVersion: 1.0\n").unwrap_err();
+        assert_eq!(error.to_string(), "line 2, paragraph 1, field Version: unknown field `Version`, expected `Package` or `Installed-Size`");
+
+        let deserializer = super::Deserializer::new(&b"Package: foo\n"[..]).default_field("Installed-Size", "-");
+        let error = Package::deserialize(deserializer).unwrap_err();
+        assert_eq!(error.to_string(), "paragraph 1, field Installed-Size: invalid value: string \"-\", expected u64");
+        assert_eq!(error.line(), None);
+    }
+
+    #[test]
+    fn bool_values() {
+        #[derive(Debug, serde_derive::Deserialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct Release {
+            not_automatic: bool,
+            but_automatic_upgrades: Option<bool>,
+        }
+
+        let release = crate::from_str::<Release>("NotAutomatic: Yes\nButAutomaticUpgrades: 0\n").unwrap();
+        assert!(release.not_automatic);
+        assert_eq!(release.but_automatic_upgrades, Some(false));
+        assert!(crate::from_str::<Release>("NotAutomatic: false\n").unwrap().but_automatic_upgrades.is_none());
+        let error = crate::from_str::<Release>("NotAutomatic: maybe\n").unwrap_err();
+        assert_eq!(error.to_string(), "line 1, paragraph 1, field NotAutomatic: invalid value: string \"maybe\", expected a boolean");
+
+        let deserializer = super::Deserializer::new(&b"NotAutomatic: on\nButAutomaticUpgrades: yes\n"[..]).bool_values(vec![("on", "off")]);
+        assert!(Release::deserialize(deserializer).is_err());
+        let deserializer = super::Deserializer::from_str("NotAutomatic: ON\n").bool_values(vec![("on".to_owned(), "off".to_owned())]);
+        assert!(Release::deserialize(deserializer).unwrap().not_automatic);
+
+        let document = crate::document::Document::parse("NotAutomatic: true\n").unwrap();
+        assert!(document.paragraphs()[0].deserialize_into::<Release>().unwrap().not_automatic);
+    }
 }
@@ -0,0 +1,82 @@
+//! Deserialization settings that can be stored in configuration files.
+
+use std::collections::BTreeMap;
+use crate::key_mapping::KeyMapping;
+
+/// Settings of the [`Deserializer`](super::Deserializer).
+///
+/// Each field corresponds to a builder method of the deserializer and has the same default.
+/// Validators set by [`validate_field`](super::Deserializer::validate_field) are code and thus
+/// not part of the options.
+/// With the `config` feature the options implement `Serialize` and `Deserialize` so parsing
+/// profiles can be loaded from configuration files of your application, missing fields keep the
+/// default values.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::HashMap;
+/// use rfc822_like::de::{Deserializer, Options};
+/// use serde::Deserialize;
+///
+/// let mut options = Options::default();
+/// options.strict_keys = true;
+/// options.default_fields.insert("Priority".to_owned(), "optional".to_owned());
+///
+/// let deserializer = Deserializer::with_options("Package: foo\n".as_bytes(), options);
+/// let record = HashMap::<String, String>::deserialize(deserializer).unwrap();
+/// assert_eq!(record["Priority"], "optional");
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "config", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "config", serde(default, rename_all = "kebab-case"))]
+pub struct Options {
+    /// See [`Deserializer::key_mapping`](super::Deserializer::key_mapping).
+    pub key_mapping: Option<KeyMapping>,
+    /// See [`Deserializer::human_readable`](super::Deserializer::human_readable).
+    pub human_readable: bool,
+    /// See [`Deserializer::strict_keys`](super::Deserializer::strict_keys).
+    pub strict_keys: bool,
+    /// See [`Deserializer::max_records`](super::Deserializer::max_records).
+    pub max_records: Option<usize>,
+    /// See [`Deserializer::select_fields`](super::Deserializer::select_fields).
+    pub select_fields: Option<Vec<String>>,
+    /// Values of missing fields, see
+    /// [`Deserializer::default_field`](super::Deserializer::default_field).
+    pub default_fields: BTreeMap<String, String>,
+    /// Values of `bool` fields, the default ones if `None`, see
+    /// [`Deserializer::bool_values`](super::Deserializer::bool_values).
+    pub bool_values: Option<Vec<(String, String)>>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            key_mapping: None,
+            human_readable: true,
+            strict_keys: false,
+            max_records: None,
+            select_fields: None,
+            default_fields: BTreeMap::new(),
+            bool_values: None,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "config", feature = "serde_json"))]
+mod tests {
+    use super::Options;
+
+    #[test]
+    fn load_profile() {
+        let options = serde_json::from_str::<Options>(r#"{"strict-keys": true, "select-fields": ["Package"], "default-fields": {"Priority": "optional"}}"#).unwrap();
+        assert!(options.strict_keys);
+        assert!(options.human_readable);
+        assert_eq!(options.default_fields["Priority"], "optional");
+        let json = serde_json::to_string(&options).unwrap();
+        assert_eq!(serde_json::from_str::<Options>(&json).unwrap(), options);
+
+        let deserializer = crate::Deserializer::new(&b""[..]).strict_keys(true).select_fields(&["Package"]).default_field("Priority", "optional");
+        assert_eq!(deserializer.to_options(), options);
+    }
+}
@@ -0,0 +1,148 @@
+//! Field values kept in their folded form.
+
+use std::borrow::Cow;
+use std::fmt;
+use serde::de::{Deserialize, Deserializer, Visitor};
+use crate::ser::Preformatted;
+
+/// Name of the newtype struct recognized by the value deserializer.
+pub(crate) const TOKEN: &str = "$rfc822_like::RawValue";
+
+/// Value of a field exactly as written, unfolded only on demand.
+///
+/// Unfolding long multi-line values such as `Description` requires copying them.
+/// Tools that read only a few fields of each record can use `RawValue` for the rest and only pay
+/// for unfolding the values they actually look at.
+///
+/// When deserialized from text the value contains the continuation lines including their
+/// indentation and the ` .` markers of empty lines.
+/// Other deserializers (e.g. deserializing from [`Paragraph`](crate::document::Paragraph)) only
+/// provide the unfolded value so that is stored instead.
+///
+/// Values deserialized from text are serialized back verbatim as
+/// [`Preformatted`](crate::ser::Preformatted), so unmodified fields are copied through
+/// untouched; other serializers receive the raw text then.
+/// Unfolded values are serialized as ordinary strings.
+///
+/// # Example
+///
+/// ```
+/// use rfc822_like::de::RawValue;
+///
+/// #[derive(serde_derive::Deserialize)]
+/// #[serde(rename_all = "PascalCase")]
+/// struct Package {
+///     package: String,
+///     description: RawValue,
+/// }
+///
+/// let input = "Package: foo\nDescription: The Foo\n It's very foo.\n .\n Really.\n";
+/// let package = rfc822_like::from_str::<Package>(input).unwrap();
+/// assert_eq!(package.description.as_raw(), "The Foo\n It's very foo.\n .\n Really.");
+/// assert_eq!(package.description.unfold(), "The Foo\nIt's very foo.\n\nReally.");
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Default)]
+pub struct RawValue {
+    value: String,
+    // `true` if the value came from text and wasn't unfolded
+    folded: bool,
+}
+
+impl RawValue {
+    /// Returns the value as written, without the leading and trailing whitespace.
+    pub fn as_raw(&self) -> &str {
+        &self.value
+    }
+
+    /// Returns the logical value, the same one `String` would be deserialized as.
+    ///
+    /// Single-line values are borrowed.
+    pub fn unfold(&self) -> Cow<'_, str> {
+        if self.value.contains("\n ") {
+            let mut value = String::with_capacity(self.value.len());
+            super::unfold_value(&self.value, &mut value);
+            Cow::Owned(value)
+        } else {
+            Cow::Borrowed(&self.value)
+        }
+    }
+
+    /// Returns the raw value.
+    pub fn into_raw(self) -> String {
+        self.value
+    }
+}
+
+impl fmt::Display for RawValue {
+    /// Writes the unfolded value.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.unfold())
+    }
+}
+
+struct RawValueVisitor;
+
+impl<'de> Visitor<'de> for RawValueVisitor {
+    type Value = RawValue;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a field value")
+    }
+
+    fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        self.visit_string(value.to_owned())
+    }
+
+    fn visit_string<E: serde::de::Error>(self, value: String) -> Result<Self::Value, E> {
+        Ok(RawValue { value, folded: false, })
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        Ok(RawValue { value, folded: true, })
+    }
+}
+
+impl<'de> Deserialize<'de> for RawValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_newtype_struct(TOKEN, RawValueVisitor)
+    }
+}
+
+impl serde::Serialize for RawValue {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.folded {
+            serde::Serialize::serialize(&Preformatted(&self.value), serializer)
+        } else {
+            serializer.serialize_str(&self.value)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RawValue;
+
+    #[derive(serde_derive::Deserialize, serde_derive::Serialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct Record {
+        a: RawValue,
+        b: RawValue,
+    }
+
+    #[test]
+    fn raw_value() {
+        let input = "A: first\n  second\n .\n third\nB: single\n";
+        let record = crate::from_str::<Record>(input).unwrap();
+        assert_eq!(record.a.as_raw(), "first\n  second\n .\n third");
+        assert_eq!(record.a.unfold(), "first\nsecond\n\nthird");
+        assert_eq!(record.b.unfold(), "single");
+        assert_eq!(crate::to_string(&record).unwrap(), "A: first\n  second\n .\n third\nB: single\n");
+
+        let paragraph = crate::document::Document::parse(input).unwrap().paragraphs()[0].clone();
+        let record = paragraph.deserialize_into::<Record>().unwrap();
+        assert_eq!(record.a.as_raw(), "first\nsecond\n\nthird");
+        assert_eq!(record.a.unfold(), "first\nsecond\n\nthird");
+        assert_eq!(crate::to_string(&record).unwrap(), "A: first\n second\n .\n third\nB: single\n");
+    }
+}
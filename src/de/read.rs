@@ -0,0 +1,68 @@
+//! Sources of input of the deserializer.
+
+use std::io;
+use std::ops::Range;
+
+mod private {
+    pub trait Sealed {}
+
+    impl<R: std::io::BufRead> Sealed for R {}
+    impl<'de> Sealed for super::SliceRead<'de> {}
+}
+
+/// Input the [`Deserializer`](super::Deserializer) reads lines from.
+///
+/// This is implemented for all buffered readers and for [`SliceRead`] which allows values to
+/// borrow from the input.
+/// The trait is sealed, it can not be implemented outside of this crate.
+pub trait Read<'de>: private::Sealed {
+    /// Appends the next line including the newline to `buf` and returns its length.
+    fn next_line(&mut self, buf: &mut Vec<u8>) -> io::Result<usize>;
+
+    /// Returns the part of the input at the byte `range` if it can outlive the deserializer.
+    fn slice(&self, range: Range<usize>) -> Option<&'de str>;
+}
+
+impl<'de, R: io::BufRead> Read<'de> for R {
+    fn next_line(&mut self, buf: &mut Vec<u8>) -> io::Result<usize> {
+        self.read_until(b'\n', buf)
+    }
+
+    fn slice(&self, _range: Range<usize>) -> Option<&'de str> {
+        None
+    }
+}
+
+/// Input in memory that values can borrow from.
+///
+/// Construct the deserializer using [`Deserializer::from_str`](super::Deserializer::from_str)
+/// to use it.
+#[derive(Debug, Clone)]
+pub struct SliceRead<'de> {
+    input: &'de str,
+    pos: usize,
+}
+
+impl<'de> SliceRead<'de> {
+    /// Creates the input reading `input` from the beginning.
+    pub fn new(input: &'de str) -> Self {
+        SliceRead {
+            input,
+            pos: 0,
+        }
+    }
+}
+
+impl<'de> Read<'de> for SliceRead<'de> {
+    fn next_line(&mut self, buf: &mut Vec<u8>) -> io::Result<usize> {
+        let rest = &self.input[self.pos..];
+        let amount = rest.find('\n').map_or(rest.len(), |pos| pos + 1);
+        buf.extend_from_slice(&rest.as_bytes()[..amount]);
+        self.pos += amount;
+        Ok(amount)
+    }
+
+    fn slice(&self, range: Range<usize>) -> Option<&'de str> {
+        self.input.get(range)
+    }
+}
@@ -0,0 +1,115 @@
+//! The `Binary` field listing binary packages built from a source package.
+//!
+//! `.dsc` files and `Sources` indices separate the names by commas while `.changes` files
+//! separate them by spaces.
+//! The [`Binary`] type accepts both and remembers which one was used.
+
+use std::convert::Infallible;
+use std::fmt;
+use std::str::FromStr;
+
+/// Separator used between package names.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum ListStyle {
+    /// `foo, bar` - used in `.dsc` and `Sources`.
+    Comma,
+    /// `foo bar` - used in `.changes`.
+    Space,
+}
+
+impl Default for ListStyle {
+    fn default() -> Self {
+        ListStyle::Comma
+    }
+}
+
+/// List of binary package names.
+///
+/// Comparisons ignore the style.
+#[derive(Debug, Clone, Default)]
+pub struct Binary {
+    /// Names of the packages.
+    pub packages: Vec<String>,
+    /// Style used when displaying the list.
+    pub style: ListStyle,
+}
+
+impl Binary {
+    /// Creates the list with given style.
+    pub fn new(packages: Vec<String>, style: ListStyle) -> Self {
+        Binary { packages, style, }
+    }
+
+    /// Returns the same list displayed in different style.
+    pub fn with_style(self, style: ListStyle) -> Self {
+        Binary { style, ..self }
+    }
+
+    /// Returns `true` if the package is in the list.
+    pub fn contains(&self, package: &str) -> bool {
+        self.packages.iter().any(|name| name == package)
+    }
+}
+
+impl PartialEq for Binary {
+    fn eq(&self, other: &Self) -> bool {
+        self.packages == other.packages
+    }
+}
+
+impl Eq for Binary {}
+
+impl std::hash::Hash for Binary {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.packages.hash(state);
+    }
+}
+
+impl FromStr for Binary {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let style = if s.contains(',') { ListStyle::Comma } else { ListStyle::Space };
+        let packages = s.split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|name| !name.is_empty())
+            .map(ToOwned::to_owned)
+            .collect();
+        Ok(Binary { packages, style, })
+    }
+}
+
+impl fmt::Display for Binary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let separator = match self.style {
+            ListStyle::Comma => ", ",
+            ListStyle::Space => " ",
+        };
+        for (i, package) in self.packages.iter().enumerate() {
+            if i > 0 {
+                f.write_str(separator)?;
+            }
+            f.write_str(package)?;
+        }
+        Ok(())
+    }
+}
+
+serde_via_str!(Binary, "a list of binary package names");
+
+#[cfg(test)]
+mod tests {
+    use super::{Binary, ListStyle};
+
+    #[test]
+    fn styles() {
+        let dsc = "foo, libfoo1,\n libfoo-dev".parse::<Binary>().unwrap();
+        assert_eq!(dsc.style, ListStyle::Comma);
+        assert_eq!(dsc.to_string(), "foo, libfoo1, libfoo-dev");
+        let changes = "foo libfoo1 libfoo-dev".parse::<Binary>().unwrap();
+        assert_eq!(changes.style, ListStyle::Space);
+        assert_eq!(dsc, changes);
+        assert!(changes.contains("libfoo1"));
+        assert_eq!(changes.with_style(ListStyle::Comma).to_string(), "foo, libfoo1, libfoo-dev");
+        assert_eq!("foo".parse::<Binary>().unwrap().packages, ["foo"]);
+    }
+}
@@ -0,0 +1,187 @@
+//! `.buildinfo` files recording the environment a package was built in.
+//!
+//! This module requires the `chrono` feature.
+//!
+//! # Example
+//!
+//! ```
+//! use rfc822_like::debian::buildinfo::Buildinfo;
+//!
+//! let input = "Format: 1.0\nSource: hello\nBinary: hello\nArchitecture: amd64\nVersion: 2.10-3\nInstalled-Build-Depends:\n debhelper (= 13.11.4),\n libc6 (= 2.36-9)\nEnvironment:\n DEB_BUILD_OPTIONS=\"parallel=4\"\n LANG=\"C.UTF-8\"\n";
+//! let buildinfo = rfc822_like::from_str::<Buildinfo>(input).unwrap();
+//! assert_eq!(buildinfo.installed_build_depends.0.len(), 2);
+//! assert_eq!(buildinfo.environment.get("LANG"), Some("C.UTF-8"));
+//! assert_eq!(rfc822_like::to_string(&buildinfo).unwrap(), input);
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
+use super::{Binary, Relations, Source, Version};
+use super::checksums::{FileEntry, FileList, Md5, Sha1, Sha256};
+use super::date::Date;
+
+#[derive(Debug, Clone, thiserror::Error)]
+enum ErrorInner {
+    #[error("missing '=' in '{0}'")]
+    MissingEquals(String),
+    #[error("value of '{0}' is not quoted")]
+    Unquoted(String),
+}
+
+/// Error returned when parsing the `Environment` field fails.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error(transparent)]
+pub struct ParseError(#[from] ErrorInner);
+
+/// Contents of the `.buildinfo` file.
+///
+/// The signature has to be stripped before parsing.
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Buildinfo {
+    /// Version of the format, currently `1.0`.
+    pub format: String,
+    /// Source package, optionally followed by its version.
+    pub source: Source,
+    /// Binary packages that were built.
+    pub binary: Option<Binary>,
+    /// Space-separated list of architectures of the built files, including `source` and `all`.
+    pub architecture: String,
+    /// Version of the source package.
+    pub version: Version,
+    /// Changelog entry of binary-only uploads.
+    #[serde(rename = "Binary-Only-Changes")]
+    pub binary_only_changes: Option<String>,
+    /// MD5 digests of the built files.
+    #[serde(rename = "Checksums-Md5")]
+    pub checksums_md5: Option<FileList<FileEntry<Md5>>>,
+    /// SHA1 digests of the built files.
+    #[serde(rename = "Checksums-Sha1")]
+    pub checksums_sha1: Option<FileList<FileEntry<Sha1>>>,
+    /// SHA256 digests of the built files.
+    #[serde(rename = "Checksums-Sha256")]
+    pub checksums_sha256: Option<FileList<FileEntry<Sha256>>>,
+    /// Name of the distribution the build ran on.
+    #[serde(rename = "Build-Origin")]
+    pub build_origin: Option<String>,
+    /// Architecture of the build machine.
+    #[serde(rename = "Build-Architecture")]
+    pub build_architecture: Option<String>,
+    /// Time the build finished.
+    #[serde(rename = "Build-Date")]
+    pub build_date: Option<Date>,
+    /// Version of the kernel the build ran on.
+    #[serde(rename = "Build-Kernel-Version")]
+    pub build_kernel_version: Option<String>,
+    /// Absolute path of the directory the package was built in.
+    #[serde(rename = "Build-Path")]
+    pub build_path: Option<String>,
+    /// Reasons why the build environment may be tainted.
+    #[serde(rename = "Build-Tainted-By")]
+    pub build_tainted_by: Option<String>,
+    /// Exact versions of all packages installed during the build.
+    ///
+    /// Serialized with each relation on its own line.
+    #[serde(rename = "Installed-Build-Depends", with = "one_per_line", default)]
+    pub installed_build_depends: Relations,
+    /// Environment variables affecting the build.
+    #[serde(default)]
+    pub environment: Environment,
+}
+
+mod one_per_line {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use crate::debian::Relations;
+
+    pub fn serialize<S: Serializer>(relations: &Relations, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&format_args!("{:#}", relations))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Relations, D::Error> {
+        Relations::deserialize(deserializer)
+    }
+}
+
+/// Environment variables in the form `NAME="value"`, one per line.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct Environment(pub Vec<(String, String)>);
+
+impl Environment {
+    /// Returns the value of the variable.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+    }
+}
+
+impl FromStr for Environment {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut variables = Vec::new();
+        for line in s.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            let pos = line.find('=').ok_or_else(|| ErrorInner::MissingEquals(line.to_owned()))?;
+            let value = &line[(pos + 1)..];
+            if value.len() < 2 || !value.starts_with('"') || !value.ends_with('"') {
+                return Err(ErrorInner::Unquoted(line[..pos].to_owned()).into());
+            }
+            let value = value[1..(value.len() - 1)].replace("\\\"", "\"").replace("\\\\", "\\");
+            variables.push((line[..pos].to_owned(), value));
+        }
+        Ok(Environment(variables))
+    }
+}
+
+impl fmt::Display for Environment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, value) in &self.0 {
+            write!(f, "\n{}=\"{}\"", name, value.replace('\\', "\\\\").replace('"', "\\\""))?;
+        }
+        Ok(())
+    }
+}
+
+serde_via_str!(Environment, "environment variables, one per line");
+
+#[cfg(test)]
+mod tests {
+    use super::{Buildinfo, Environment};
+
+    #[test]
+    fn parse() {
+        let input = "\
+Format: 1.0
+Source: hello
+Binary: hello
+Architecture: amd64
+Version: 2.10-3
+Checksums-Sha256:
+ 4a9e8c5e5d0e1e3f2b9a4a5c6d7e8f9a0b1c2d3e4f5a6b7c8d9e0f1a2b3c4d5e 53072 hello_2.10-3_amd64.deb
+Build-Origin: Debian
+Build-Architecture: amd64
+Build-Date: Sun, 08 Jan 2023 18:31:50 +0000
+Build-Path: /build/reproducible-path/hello-2.10
+Installed-Build-Depends:
+ autoconf (= 2.71-3),
+ debhelper (= 13.11.4),
+ libc6 (= 2.36-9)
+Environment:
+ DEB_BUILD_OPTIONS=\"parallel=4\"
+ SOURCE_DATE_EPOCH=\"1673201510\"
+";
+        let buildinfo = crate::from_str::<Buildinfo>(input).unwrap();
+        assert_eq!(buildinfo.installed_build_depends.0[1].0[0].name, "debhelper");
+        assert_eq!(buildinfo.environment.get("SOURCE_DATE_EPOCH"), Some("1673201510"));
+        assert_eq!(buildinfo.checksums_sha256.as_ref().unwrap().0[0].size, 53072);
+        assert_eq!(crate::to_string(&buildinfo).unwrap(), input);
+    }
+
+    #[test]
+    fn environment() {
+        let environment = "\nFOO=\"a \\\"b\\\" \\\\\"".parse::<Environment>().unwrap();
+        assert_eq!(environment.get("FOO"), Some("a \"b\" \\"));
+        assert_eq!(environment.to_string(), "\nFOO=\"a \\\"b\\\" \\\\\"");
+        assert_eq!("FOO".parse::<Environment>().unwrap_err().to_string(), "missing '=' in 'FOO'");
+        assert_eq!("FOO=bar".parse::<Environment>().unwrap_err().to_string(), "value of 'FOO' is not quoted");
+    }
+}
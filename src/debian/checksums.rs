@@ -0,0 +1,392 @@
+//! Checksum lists such as `Files`, `Checksums-Sha256` or `SHA256`.
+//!
+//! These fields are used in `.dsc`, `.changes` and `Release` files.
+//! The value starts with an empty line followed by one file per line:
+//!
+//! ```text
+//! Checksums-Sha256:
+//!  0b5ae9ba4a5e7d1f3e3a3f5a5f5d5e1b0b5ae9ba4a5e7d1f3e3a3f5a5f5d5e1b 1234 foo_1.0.orig.tar.gz
+//! ```
+//!
+//! # Example
+//!
+//! ```
+//! use rfc822_like::debian::checksums::{FileList, FileEntry, Md5};
+//!
+//! #[derive(serde_derive::Deserialize, serde_derive::Serialize)]
+//! #[serde(rename_all = "PascalCase")]
+//! struct Dsc {
+//!     files: FileList<FileEntry<Md5>>,
+//! }
+//!
+//! let input = "Files:\n d41d8cd98f00b204e9800998ecf8427e 0 foo_1.0.tar.xz\n";
+//! let dsc = rfc822_like::from_str::<Dsc>(input).unwrap();
+//! assert_eq!(dsc.files.0[0].path, "foo_1.0.tar.xz");
+//! assert_eq!(dsc.files.0[0].size, 0);
+//! assert_eq!(rfc822_like::to_string(&dsc).unwrap(), input);
+//! ```
+
+use std::fmt;
+use std::io;
+use std::str::FromStr;
+use crate::digest::{digest_reader, Digest};
+
+#[derive(Debug, Clone, thiserror::Error)]
+enum ErrorInner {
+    #[error("invalid length of {kind} digest: expected {expected} hex characters, got {actual}")]
+    DigestLength { kind: &'static str, expected: usize, actual: usize, },
+    #[error("invalid character '{0}' in digest")]
+    DigestChar(char),
+    #[error("missing {0} in '{1}'")]
+    MissingColumn(&'static str, String),
+    #[error("invalid file size in '{0}'")]
+    InvalidSize(String),
+    #[error("too many columns in '{0}'")]
+    TooManyColumns(String),
+}
+
+/// Error returned when parsing a digest or a list of files fails.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error(transparent)]
+pub struct ParseError(#[from] ErrorInner);
+
+/// A digest (hash) value of a particular kind.
+pub trait Checksum: FromStr<Err=ParseError> + fmt::Display + AsRef<[u8]> {
+    /// Name of the algorithm as used in field names, e.g. `SHA256`.
+    const NAME: &'static str;
+
+    /// Creates the checksum from bytes.
+    ///
+    /// Returns `None` if the length doesn't match.
+    fn from_bytes(bytes: &[u8]) -> Option<Self>;
+}
+
+fn parse_hex(s: &str, out: &mut [u8], kind: &'static str) -> Result<(), ParseError> {
+    if s.len() != out.len() * 2 {
+        return Err(ErrorInner::DigestLength { kind, expected: out.len() * 2, actual: s.len(), }.into());
+    }
+    let digit = |c: u8| (c as char).to_digit(16).map(|digit| digit as u8).ok_or(ErrorInner::DigestChar(c as char));
+    for (byte, pair) in out.iter_mut().zip(s.as_bytes().chunks(2)) {
+        *byte = digit(pair[0])? << 4 | digit(pair[1])?;
+    }
+    Ok(())
+}
+
+macro_rules! checksum_type {
+    ($(#[$attr:meta])* $name:ident, $len:expr, $field_name:expr) => {
+        $(#[$attr])*
+        #[derive(Copy, Clone)]
+        pub struct $name(pub [u8; $len]);
+
+        impl Checksum for $name {
+            const NAME: &'static str = $field_name;
+
+            fn from_bytes(bytes: &[u8]) -> Option<Self> {
+                if bytes.len() != $len {
+                    return None;
+                }
+                let mut array = [0; $len];
+                array.copy_from_slice(bytes);
+                Some($name(array))
+            }
+        }
+
+        impl AsRef<[u8]> for $name {
+            fn as_ref(&self) -> &[u8] {
+                &self.0
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = ParseError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let mut array = [0; $len];
+                parse_hex(s, &mut array, $field_name)?;
+                Ok($name(array))
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                for byte in &self.0[..] {
+                    write!(f, "{:02x}", byte)?;
+                }
+                Ok(())
+            }
+        }
+
+        impl fmt::Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}({})", stringify!($name), self)
+            }
+        }
+
+        impl PartialEq for $name {
+            fn eq(&self, other: &Self) -> bool {
+                self.0[..] == other.0[..]
+            }
+        }
+
+        impl Eq for $name {}
+
+        impl std::hash::Hash for $name {
+            fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+                self.0[..].hash(state);
+            }
+        }
+
+        serde_via_str!($name, concat!("hex-encoded ", $field_name, " digest"));
+    }
+}
+
+checksum_type!(
+    /// MD5 digest used in `Files` and `MD5Sum` fields.
+    Md5, 16, "MD5"
+);
+checksum_type!(
+    /// SHA1 digest used in `Checksums-Sha1` and `SHA1` fields.
+    Sha1, 20, "SHA1"
+);
+checksum_type!(
+    /// SHA256 digest used in `Checksums-Sha256` and `SHA256` fields.
+    Sha256, 32, "SHA256"
+);
+checksum_type!(
+    /// SHA512 digest used in `Checksums-Sha512` and `SHA512` fields.
+    Sha512, 64, "SHA512"
+);
+
+/// A single line of a file list: digest, size and path.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct FileEntry<C> {
+    /// The digest of the file.
+    pub digest: C,
+    /// The size of the file in bytes.
+    pub size: u64,
+    /// The path (or name) of the file.
+    pub path: String,
+}
+
+impl<C> FileEntry<C> {
+    /// Computes the entry of `data` stored at `path` using `digest`.
+    pub fn compute<D: Digest<Output=C>>(path: String, data: &[u8], mut digest: D) -> Self {
+        digest.update(data);
+        FileEntry {
+            digest: digest.finalize(),
+            size: data.len() as u64,
+            path,
+        }
+    }
+
+    /// Returns `true` if the size and digest of the content of `reader` match the entry.
+    pub fn verify<D: Digest<Output=C>, R: io::Read>(&self, digest: D, reader: R) -> io::Result<bool> where C: PartialEq {
+        let (size, actual) = digest_reader(digest, reader)?;
+        Ok(size == self.size && actual == self.digest)
+    }
+}
+
+/// A line of the `Files` field in `.changes` files which also contains section and priority.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct ChangesFileEntry {
+    /// The MD5 digest of the file.
+    pub digest: Md5,
+    /// The size of the file in bytes.
+    pub size: u64,
+    /// The section of the package.
+    pub section: String,
+    /// The priority of the package.
+    pub priority: String,
+    /// The name of the file.
+    pub path: String,
+}
+
+fn next_column<'a, I: Iterator<Item=&'a str>>(columns: &mut I, name: &'static str, line: &str) -> Result<&'a str, ParseError> {
+    columns.next().ok_or_else(|| ErrorInner::MissingColumn(name, line.to_owned()).into())
+}
+
+fn parse_size(size: &str, line: &str) -> Result<u64, ParseError> {
+    size.parse().map_err(|_| ErrorInner::InvalidSize(line.to_owned()).into())
+}
+
+fn check_end<'a, I: Iterator<Item=&'a str>>(mut columns: I, line: &str) -> Result<(), ParseError> {
+    match columns.next() {
+        Some(_) => Err(ErrorInner::TooManyColumns(line.to_owned()).into()),
+        None => Ok(()),
+    }
+}
+
+impl<C: Checksum> FromStr for FileEntry<C> {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut columns = s.split_whitespace();
+        let digest = next_column(&mut columns, "digest", s)?.parse()?;
+        let size = parse_size(next_column(&mut columns, "size", s)?, s)?;
+        let path = next_column(&mut columns, "path", s)?.to_owned();
+        check_end(columns, s)?;
+        Ok(FileEntry { digest, size, path, })
+    }
+}
+
+impl<C: Checksum> fmt::Display for FileEntry<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.digest, self.size, self.path)
+    }
+}
+
+impl FromStr for ChangesFileEntry {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut columns = s.split_whitespace();
+        let digest = next_column(&mut columns, "digest", s)?.parse()?;
+        let size = parse_size(next_column(&mut columns, "size", s)?, s)?;
+        let section = next_column(&mut columns, "section", s)?.to_owned();
+        let priority = next_column(&mut columns, "priority", s)?.to_owned();
+        let path = next_column(&mut columns, "path", s)?.to_owned();
+        check_end(columns, s)?;
+        Ok(ChangesFileEntry { digest, size, section, priority, path, })
+    }
+}
+
+impl fmt::Display for ChangesFileEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {} {} {}", self.digest, self.size, self.section, self.priority, self.path)
+    }
+}
+
+/// List of files, one per line.
+///
+/// The entry type is usually [`FileEntry`] or [`ChangesFileEntry`] but any type implementing
+/// `FromStr` and `Display` can be used for other line-based lists.
+/// Empty lines are ignored when parsing and the list is displayed starting with a newline so that
+/// each entry is on its own line when serialized.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct FileList<E>(pub Vec<E>);
+
+impl<E> Default for FileList<E> {
+    fn default() -> Self {
+        FileList(Vec::new())
+    }
+}
+
+impl<C> FileList<FileEntry<C>> {
+    /// Returns the entry with given path.
+    pub fn get(&self, path: &str) -> Option<&FileEntry<C>> {
+        self.0.iter().find(|entry| entry.path == path)
+    }
+}
+
+impl<E: FromStr> FromStr for FileList<E> {
+    type Err = E::Err;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(str::parse)
+            .collect::<Result<_, _>>()
+            .map(FileList)
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for FileList<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.0 {
+            write!(f, "\n{}", entry)?;
+        }
+        Ok(())
+    }
+}
+
+impl<E: fmt::Display> serde::Serialize for FileList<E> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "rustcrypto")]
+macro_rules! impl_digest {
+    ($hasher:ty, $checksum:ident) => {
+        /// This implementation requires the `rustcrypto` feature.
+        impl Digest for $hasher {
+            type Output = $checksum;
+
+            fn update(&mut self, data: &[u8]) {
+                sha2::Digest::update(self, data);
+            }
+
+            fn finalize(self) -> Self::Output {
+                $checksum::from_bytes(&sha2::Digest::finalize(self)).expect(concat!("invalid ", stringify!($checksum), " length"))
+            }
+        }
+    }
+}
+
+#[cfg(feature = "rustcrypto")]
+impl_digest!(md5::Md5, Md5);
+#[cfg(feature = "rustcrypto")]
+impl_digest!(sha1::Sha1, Sha1);
+#[cfg(feature = "rustcrypto")]
+impl_digest!(sha2::Sha256, Sha256);
+#[cfg(feature = "rustcrypto")]
+impl_digest!(sha2::Sha512, Sha512);
+
+impl<'de, E: FromStr> serde::Deserialize<'de> for FileList<E> where E::Err: fmt::Display {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct StrVisitor<E>(std::marker::PhantomData<E>);
+
+        impl<'de, E: FromStr> serde::de::Visitor<'de> for StrVisitor<E> where E::Err: fmt::Display {
+            type Value = FileList<E>;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a list of files, one per line")
+            }
+
+            fn visit_str<Err: serde::de::Error>(self, v: &str) -> Result<Self::Value, Err> {
+                v.parse().map_err(Err::custom)
+            }
+        }
+
+        deserializer.deserialize_str(StrVisitor(std::marker::PhantomData))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChangesFileEntry, FileEntry, FileList, Md5, Sha256};
+
+    #[test]
+    fn digest() {
+        let md5 = "d41d8cd98f00b204e9800998ecf8427e".parse::<Md5>().unwrap();
+        assert_eq!(md5.0[0], 0xd4);
+        assert_eq!(md5.to_string(), "d41d8cd98f00b204e9800998ecf8427e");
+        assert_eq!("d41d".parse::<Md5>().unwrap_err().to_string(), "invalid length of MD5 digest: expected 32 hex characters, got 4");
+        assert_eq!("x41d8cd98f00b204e9800998ecf8427e".parse::<Md5>().unwrap_err().to_string(), "invalid character 'x' in digest");
+    }
+
+    #[test]
+    fn serde() {
+        #[derive(serde_derive::Deserialize, serde_derive::Serialize)]
+        struct Changes {
+            #[serde(rename = "Checksums-Sha256")]
+            checksums_sha256: FileList<FileEntry<Sha256>>,
+            #[serde(rename = "Files")]
+            files: FileList<ChangesFileEntry>,
+        }
+
+        let input = "Checksums-Sha256:\n e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855 0 foo_1.0_all.deb\n 01ba4719c80b6fe911b091a7c05124b64eeece964e09c058ef8f9805daca546b 42 foo_1.0.dsc\nFiles:\n d41d8cd98f00b204e9800998ecf8427e 0 utils optional foo_1.0_all.deb\n";
+        let changes = crate::from_str::<Changes>(input).unwrap();
+        assert_eq!(changes.checksums_sha256.0.len(), 2);
+        assert_eq!(changes.checksums_sha256.0[1].size, 42);
+        assert_eq!(changes.files.0[0].section, "utils");
+        assert_eq!(crate::to_string(&changes).unwrap(), input);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!("d41d8cd98f00b204e9800998ecf8427e foo".parse::<FileList<FileEntry<Md5>>>().unwrap_err().to_string(), "invalid file size in 'd41d8cd98f00b204e9800998ecf8427e foo'");
+        assert_eq!("d41d8cd98f00b204e9800998ecf8427e 0".parse::<FileList<FileEntry<Md5>>>().unwrap_err().to_string(), "missing path in 'd41d8cd98f00b204e9800998ecf8427e 0'");
+        assert_eq!("d41d8cd98f00b204e9800998ecf8427e 0 a b".parse::<FileList<FileEntry<Md5>>>().unwrap_err().to_string(), "too many columns in 'd41d8cd98f00b204e9800998ecf8427e 0 a b'");
+    }
+}
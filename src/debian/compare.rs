@@ -0,0 +1,195 @@
+//! Comparison of `Packages` or `Sources` indexes by package identity.
+//!
+//! Unlike comparing documents field by field this understands that a package is identified by
+//! its name and architecture and reports how versions changed.
+//!
+//! # Example
+//!
+//! ```
+//! use rfc822_like::debian::compare::compare;
+//! use rfc822_like::debian::sources::Record;
+//!
+//! let old = rfc822_like::from_str::<Vec<Record>>("Package: foo\nBinary: foo\nVersion: 1.0\nMaintainer: Foo <foo@example.org>\nArchitecture: any\nFormat: 3.0 (native)\nDirectory: pool/main/f/foo\n").unwrap();
+//! let new = rfc822_like::from_str::<Vec<Record>>("Package: foo\nBinary: foo\nVersion: 1.1\nMaintainer: Foo <foo@example.org>\nArchitecture: any\nFormat: 3.0 (native)\nDirectory: pool/main/f/foo\n").unwrap();
+//! let diff = compare(&old, &new);
+//! assert_eq!(diff.upgraded[0].to_string(), "foo:source 1.0 -> 1.1");
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+use super::{packages, sources, Version};
+
+/// Record of an index identified by package name and architecture.
+pub trait Identity {
+    /// Name of the package.
+    fn name(&self) -> &str;
+
+    /// Architecture of the package, `source` for source packages.
+    fn architecture(&self) -> &str;
+
+    /// Version of the package.
+    fn version(&self) -> &Version;
+}
+
+impl Identity for packages::Record {
+    fn name(&self) -> &str {
+        &self.package
+    }
+
+    fn architecture(&self) -> &str {
+        &self.architecture
+    }
+
+    fn version(&self) -> &Version {
+        &self.version
+    }
+}
+
+impl Identity for sources::Record {
+    fn name(&self) -> &str {
+        &self.package
+    }
+
+    fn architecture(&self) -> &str {
+        "source"
+    }
+
+    fn version(&self) -> &Version {
+        &self.version
+    }
+}
+
+/// Package present in both indexes with different versions.
+#[derive(Debug)]
+pub struct VersionChange<'a, R> {
+    /// Record from the old index.
+    pub old: &'a R,
+    /// Record from the new index.
+    pub new: &'a R,
+}
+
+impl<'a, R> Clone for VersionChange<'a, R> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, R> Copy for VersionChange<'a, R> {}
+
+/// Displays the change as `name:arch old -> new`.
+impl<'a, R: Identity> fmt::Display for VersionChange<'a, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{} {} -> {}", self.new.name(), self.new.architecture(), self.old.version(), self.new.version())
+    }
+}
+
+/// Differences between two indexes.
+///
+/// All lists are sorted by name and architecture.
+#[derive(Debug)]
+pub struct IndexDiff<'a, R> {
+    /// Packages only present in the new index.
+    pub added: Vec<&'a R>,
+    /// Packages only present in the old index.
+    pub removed: Vec<&'a R>,
+    /// Packages with higher version in the new index.
+    pub upgraded: Vec<VersionChange<'a, R>>,
+    /// Packages with lower version in the new index.
+    pub downgraded: Vec<VersionChange<'a, R>>,
+}
+
+impl<'a, R> IndexDiff<'a, R> {
+    /// Returns `true` if the indexes contain the same versions of the same packages.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.upgraded.is_empty() && self.downgraded.is_empty()
+    }
+}
+
+fn latest<R: Identity>(records: &[R]) -> BTreeMap<(&str, &str), &R> {
+    let mut map = BTreeMap::new();
+    for record in records {
+        let latest = map.entry((record.name(), record.architecture())).or_insert(record);
+        if record.version() > latest.version() {
+            *latest = record;
+        }
+    }
+    map
+}
+
+/// Compares two indexes.
+///
+/// If an index contains multiple versions of the same package only the highest one is considered.
+pub fn compare<'a, R: Identity>(old: &'a [R], new: &'a [R]) -> IndexDiff<'a, R> {
+    let old = latest(old);
+    let mut new = latest(new);
+    let mut diff = IndexDiff {
+        added: Vec::new(),
+        removed: Vec::new(),
+        upgraded: Vec::new(),
+        downgraded: Vec::new(),
+    };
+
+    for (key, old) in old {
+        match new.remove(&key) {
+            Some(new) if new.version() > old.version() => diff.upgraded.push(VersionChange { old, new, }),
+            Some(new) if new.version() < old.version() => diff.downgraded.push(VersionChange { old, new, }),
+            Some(_) => (),
+            None => diff.removed.push(old),
+        }
+    }
+    diff.added.extend(new.into_iter().map(|(_, record)| record));
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compare, Identity};
+    use crate::debian::Version;
+
+    struct Package(&'static str, &'static str, Version);
+
+    impl Identity for Package {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn architecture(&self) -> &str {
+            self.1
+        }
+
+        fn version(&self) -> &Version {
+            &self.2
+        }
+    }
+
+    fn package(name: &'static str, architecture: &'static str, version: &str) -> Package {
+        Package(name, architecture, version.parse().unwrap())
+    }
+
+    #[test]
+    fn diff() {
+        let old = [
+            package("foo", "amd64", "1.0"),
+            package("foo", "arm64", "1.0"),
+            package("bar", "all", "2.0"),
+            package("baz", "amd64", "1:0.1"),
+            package("old", "amd64", "1.0"),
+            package("same", "amd64", "1.0"),
+        ];
+        let new = [
+            package("foo", "amd64", "1.0"),
+            package("foo", "amd64", "1.1"),
+            package("bar", "all", "2.0~rc1"),
+            package("baz", "amd64", "1:0.2"),
+            package("new", "amd64", "1.0"),
+            package("same", "amd64", "1.0"),
+        ];
+        let diff = compare(&old, &new);
+        assert_eq!(diff.added.iter().map(|record| record.0).collect::<Vec<_>>(), ["new"]);
+        assert_eq!(diff.removed.iter().map(|record| (record.0, record.1)).collect::<Vec<_>>(), [("foo", "arm64"), ("old", "amd64")]);
+        assert_eq!(diff.upgraded.iter().map(ToString::to_string).collect::<Vec<_>>(), ["baz:amd64 1:0.1 -> 1:0.2", "foo:amd64 1.0 -> 1.1"]);
+        assert_eq!(diff.downgraded.iter().map(ToString::to_string).collect::<Vec<_>>(), ["bar:all 2.0 -> 2.0~rc1"]);
+        assert!(!diff.is_empty());
+        assert!(compare(&old, &old).is_empty());
+    }
+}
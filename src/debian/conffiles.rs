@@ -0,0 +1,130 @@
+//! The `Conffiles` field of dpkg status records.
+//!
+//! Each line contains the path of a configuration file, the MD5 digest of the version shipped by
+//! the package and optional flags:
+//!
+//! ```text
+//! Conffiles:
+//!  /etc/foo.conf 0b5ae9ba4a5e7d1f3e3a3f5a5f5d5e1b
+//!  /etc/foo/old.conf 6cd0ffea3884a4e79330338dcc2987d6 obsolete
+//! ```
+//!
+//! # Example
+//!
+//! ```
+//! use rfc822_like::debian::conffiles::Conffiles;
+//!
+//! let conffiles = "\n/etc/foo.conf newconffile remove-on-upgrade".parse::<Conffiles>().unwrap();
+//! assert!(conffiles.0[0].digest.is_none());
+//! assert!(conffiles.0[0].remove_on_upgrade);
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+use super::checksums::{self, FileList, Md5};
+
+/// Placeholder used instead of the digest for conffiles that weren't installed yet.
+const NEW_CONFFILE: &str = "newconffile";
+
+#[derive(Debug, Clone, thiserror::Error)]
+enum ErrorInner {
+    #[error("missing digest in '{0}'")]
+    MissingDigest(String),
+    #[error("invalid digest in '{0}'")]
+    InvalidDigest(String, #[source] checksums::ParseError),
+    #[error("unknown flag '{0}'")]
+    UnknownFlag(String),
+}
+
+/// Error returned when parsing the `Conffiles` field fails.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error(transparent)]
+pub struct ParseError(#[from] ErrorInner);
+
+/// A single line of `Conffiles`.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Conffile {
+    /// Absolute path of the file.
+    pub path: String,
+    /// MD5 digest of the file, `None` if dpkg didn't install the file yet (`newconffile`).
+    pub digest: Option<Md5>,
+    /// The file is not shipped by the package anymore.
+    pub obsolete: bool,
+    /// The file will be removed on the next upgrade.
+    pub remove_on_upgrade: bool,
+}
+
+/// List of conffiles, one per line.
+pub type Conffiles = FileList<Conffile>;
+
+impl FromStr for Conffile {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut columns = s.split_whitespace();
+        let path = columns.next().unwrap_or("").to_owned();
+        let digest = match columns.next() {
+            Some(NEW_CONFFILE) => None,
+            Some(digest) => Some(digest.parse().map_err(|error| ErrorInner::InvalidDigest(s.to_owned(), error))?),
+            None => return Err(ErrorInner::MissingDigest(s.to_owned()).into()),
+        };
+        let mut conffile = Conffile { path, digest, obsolete: false, remove_on_upgrade: false, };
+        for flag in columns {
+            match flag {
+                "obsolete" => conffile.obsolete = true,
+                "remove-on-upgrade" => conffile.remove_on_upgrade = true,
+                _ => return Err(ErrorInner::UnknownFlag(flag.to_owned()).into()),
+            }
+        }
+        Ok(conffile)
+    }
+}
+
+impl fmt::Display for Conffile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ", self.path)?;
+        match &self.digest {
+            Some(digest) => write!(f, "{}", digest)?,
+            None => f.write_str(NEW_CONFFILE)?,
+        }
+        if self.obsolete {
+            f.write_str(" obsolete")?;
+        }
+        if self.remove_on_upgrade {
+            f.write_str(" remove-on-upgrade")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Conffiles;
+
+    #[test]
+    fn serde() {
+        #[derive(serde_derive::Deserialize, serde_derive::Serialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct Status {
+            package: String,
+            conffiles: Conffiles,
+        }
+
+        let input = "Package: foo\nConffiles:\n /etc/foo.conf 0b5ae9ba4a5e7d1f3e3a3f5a5f5d5e1b\n /etc/foo/old.conf 6cd0ffea3884a4e79330338dcc2987d6 obsolete\n /etc/foo/new.conf newconffile\n";
+        let status = crate::from_str::<Status>(input).unwrap();
+        let conffiles = &status.conffiles.0;
+        assert_eq!(conffiles.len(), 3);
+        assert_eq!(conffiles[0].path, "/etc/foo.conf");
+        assert!(!conffiles[0].obsolete);
+        assert!(conffiles[1].obsolete);
+        assert!(conffiles[2].digest.is_none());
+        assert_eq!(crate::to_string(&status).unwrap(), input);
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!("/etc/foo".parse::<Conffiles>().unwrap_err().to_string(), "missing digest in '/etc/foo'");
+        assert_eq!("/etc/foo abc".parse::<Conffiles>().unwrap_err().to_string(), "invalid digest in '/etc/foo abc'");
+        assert_eq!("/etc/foo newconffile gone".parse::<Conffiles>().unwrap_err().to_string(), "unknown flag 'gone'");
+    }
+}
@@ -0,0 +1,191 @@
+//! The `Maintainer`, `Uploaders` and similar fields.
+
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, thiserror::Error)]
+enum ErrorInner {
+    #[error("missing e-mail address in '{0}'")]
+    MissingEmail(String),
+    #[error("empty name in '{0}'")]
+    EmptyName(String),
+    #[error("unterminated quoted name in '{0}'")]
+    UnterminatedQuote(String),
+    #[error("unterminated e-mail address in '{0}'")]
+    UnterminatedEmail(String),
+    #[error("unexpected text after e-mail address in '{0}'")]
+    TrailingText(String),
+}
+
+/// Error returned when parsing a contact fails.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error(transparent)]
+pub struct ParseError(#[from] ErrorInner);
+
+/// Name and e-mail address in the form `Name <email@example.org>`.
+///
+/// The name may be quoted (`"Doe, John" <john@example.org>`) which is required if it contains a
+/// comma since the contacts are separated by commas in lists.
+/// The name is stored unquoted and it's quoted automatically when displayed if needed.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Contact {
+    /// The name of the person or team.
+    pub name: String,
+    /// The e-mail address.
+    pub email: String,
+}
+
+impl Contact {
+    fn needs_quoting(&self) -> bool {
+        self.name.contains(&[',', '"', '<', '>', '\\'] as &[char])
+    }
+}
+
+/// Parses the quoted string returning it unescaped and the rest of the input.
+fn parse_quoted(s: &str) -> Option<(String, &str)> {
+    let mut result = String::new();
+    let mut chars = s.char_indices();
+    while let Some((pos, c)) = chars.next() {
+        match c {
+            '"' => return Some((result, &s[(pos + 1)..])),
+            '\\' => result.push(chars.next()?.1),
+            c => result.push(c),
+        }
+    }
+    None
+}
+
+impl FromStr for Contact {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let input = s.trim();
+        let (name, rest) = if input.starts_with('"') {
+            parse_quoted(&input[1..]).ok_or_else(|| ErrorInner::UnterminatedQuote(input.to_owned()))?
+        } else {
+            let end = input.find('<').unwrap_or(input.len());
+            (input[..end].trim().to_owned(), &input[end..])
+        };
+        let rest = rest.trim();
+        if !rest.starts_with('<') {
+            return Err(ErrorInner::MissingEmail(input.to_owned()).into());
+        }
+        let end = rest.find('>').ok_or_else(|| ErrorInner::UnterminatedEmail(input.to_owned()))?;
+        if !rest[(end + 1)..].trim().is_empty() {
+            return Err(ErrorInner::TrailingText(input.to_owned()).into());
+        }
+        let email = rest[1..end].trim();
+        if email.is_empty() {
+            return Err(ErrorInner::MissingEmail(input.to_owned()).into());
+        }
+        if name.is_empty() {
+            return Err(ErrorInner::EmptyName(input.to_owned()).into());
+        }
+        Ok(Contact { name, email: email.to_owned(), })
+    }
+}
+
+impl fmt::Display for Contact {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.needs_quoting() {
+            f.write_str("\"")?;
+            for c in self.name.chars() {
+                if c == '"' || c == '\\' {
+                    f.write_str("\\")?;
+                }
+                write!(f, "{}", c)?;
+            }
+            f.write_str("\"")?;
+        } else {
+            f.write_str(&self.name)?;
+        }
+        write!(f, " <{}>", self.email)
+    }
+}
+
+/// Comma-separated list of contacts, e.g. the value of `Uploaders`.
+///
+/// Commas inside quoted names and e-mail addresses don't separate contacts.
+/// Empty items are skipped.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct Uploaders(pub Vec<Contact>);
+
+impl FromStr for Uploaders {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut contacts = Vec::new();
+        let mut start = 0;
+        let mut in_quotes = false;
+        let mut in_email = false;
+        let mut escaped = false;
+        for (pos, c) in s.char_indices() {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' if in_quotes => escaped = true,
+                '"' if !in_email => in_quotes = !in_quotes,
+                '<' if !in_quotes => in_email = true,
+                '>' if !in_quotes => in_email = false,
+                ',' if !in_quotes && !in_email => {
+                    if !s[start..pos].trim().is_empty() {
+                        contacts.push(s[start..pos].parse()?);
+                    }
+                    start = pos + 1;
+                },
+                _ => (),
+            }
+        }
+        if !s[start..].trim().is_empty() {
+            contacts.push(s[start..].parse()?);
+        }
+        Ok(Uploaders(contacts))
+    }
+}
+
+impl fmt::Display for Uploaders {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, contact) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{}", contact)?;
+        }
+        Ok(())
+    }
+}
+
+serde_via_str!(Contact, "a name followed by e-mail address in angle brackets");
+serde_via_str!(Uploaders, "a comma-separated list of contacts");
+
+#[cfg(test)]
+mod tests {
+    use super::{Contact, Uploaders};
+
+    #[test]
+    fn contact() {
+        let contact = "John Doe <john@example.org>".parse::<Contact>().unwrap();
+        assert_eq!(contact.name, "John Doe");
+        assert_eq!(contact.email, "john@example.org");
+        assert_eq!(contact.to_string(), "John Doe <john@example.org>");
+
+        let contact = r#""Doe, \"JD\" John" <john@example.org>"#.parse::<Contact>().unwrap();
+        assert_eq!(contact.name, "Doe, \"JD\" John");
+        assert_eq!(contact.to_string(), r#""Doe, \"JD\" John" <john@example.org>"#);
+
+        assert_eq!("John".parse::<Contact>().unwrap_err().to_string(), "missing e-mail address in 'John'");
+        assert_eq!("<john@example.org>".parse::<Contact>().unwrap_err().to_string(), "empty name in '<john@example.org>'");
+        assert_eq!("\"John <john@example.org>".parse::<Contact>().unwrap_err().to_string(), "unterminated quoted name in '\"John <john@example.org>'");
+        assert_eq!("John <john@example.org".parse::<Contact>().unwrap_err().to_string(), "unterminated e-mail address in 'John <john@example.org'");
+        assert_eq!("John <john@example.org> x".parse::<Contact>().unwrap_err().to_string(), "unexpected text after e-mail address in 'John <john@example.org> x'");
+    }
+
+    #[test]
+    fn uploaders() {
+        let input = "\"Doe, John\" <john@example.org>,\nJane Roe <\"jane,roe\"@example.org>,";
+        let uploaders = input.parse::<Uploaders>().unwrap();
+        assert_eq!(uploaders.0.len(), 2);
+        assert_eq!(uploaders.0[0].name, "Doe, John");
+        assert_eq!(uploaders.0[1].email, "\"jane,roe\"@example.org");
+        assert_eq!(uploaders.to_string(), "\"Doe, John\" <john@example.org>, Jane Roe <\"jane,roe\"@example.org>");
+    }
+}
@@ -0,0 +1,260 @@
+//! The `debian/control` file of source packages.
+//!
+//! The file consists of a paragraph describing the source package followed by one paragraph per
+//! binary package.
+//!
+//! # Example
+//!
+//! ```
+//! use rfc822_like::debian::control::Control;
+//!
+//! let input = "Source: hello\nMaintainer: Santiago Vila <sanvila@debian.org>\n\nPackage: hello\nArchitecture: any\nDescription: example package\n";
+//! let control = input.parse::<Control>().unwrap();
+//! assert_eq!(control.source.source, "hello");
+//! assert_eq!(control.binaries[0].package, "hello");
+//! assert_eq!(rfc822_like::to_string(&control).unwrap(), input);
+//! ```
+
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
+use serde::ser::SerializeSeq;
+use crate::document::{self, Document};
+use super::{Contact, MultiArch, Priority, Provides, Relations, Section, Uploaders, YesNo};
+use super::profiles::RestrictionFormula;
+use super::vcs::VcsLocation;
+
+#[derive(Debug, thiserror::Error)]
+enum ErrorInner {
+    #[error("failed to parse the file")]
+    Syntax(#[source] document::error::Error),
+    #[error("the file is empty")]
+    Empty,
+    #[error("the first paragraph is not a source paragraph (missing the Source field)")]
+    NotSource,
+    #[error("paragraph {0} is not a binary paragraph (missing the Package field)")]
+    NotBinary(usize),
+    #[error("the file doesn't contain any binary paragraphs")]
+    NoBinaries,
+    #[error("invalid paragraph {0}")]
+    InvalidParagraph(usize, #[source] crate::de::Error),
+}
+
+/// Error returned when the control file is invalid.
+#[derive(Debug, thiserror::Error)]
+#[error(transparent)]
+pub struct ParseError(#[from] ErrorInner);
+
+/// The first paragraph of the control file.
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SourceParagraph {
+    /// Name of the source package.
+    pub source: String,
+    /// Default section of binary packages.
+    pub section: Option<Section>,
+    /// Default priority of binary packages.
+    pub priority: Option<Priority>,
+    /// Maintainer of the package.
+    pub maintainer: Option<Contact>,
+    /// Co-maintainers of the package.
+    pub uploaders: Option<Uploaders>,
+    /// Packages required to build the package.
+    #[serde(rename = "Build-Depends")]
+    pub build_depends: Option<Relations>,
+    /// Packages required to build architecture-independent packages.
+    #[serde(rename = "Build-Depends-Indep")]
+    pub build_depends_indep: Option<Relations>,
+    /// Packages required to build architecture-dependent packages.
+    #[serde(rename = "Build-Depends-Arch")]
+    pub build_depends_arch: Option<Relations>,
+    /// Packages that must not be installed when building the package.
+    #[serde(rename = "Build-Conflicts")]
+    pub build_conflicts: Option<Relations>,
+    /// Packages that must not be installed when building architecture-independent packages.
+    #[serde(rename = "Build-Conflicts-Indep")]
+    pub build_conflicts_indep: Option<Relations>,
+    /// Packages that must not be installed when building architecture-dependent packages.
+    #[serde(rename = "Build-Conflicts-Arch")]
+    pub build_conflicts_arch: Option<Relations>,
+    /// Version of the Debian policy the package complies with.
+    #[serde(rename = "Standards-Version")]
+    pub standards_version: Option<String>,
+    /// URL of the upstream project.
+    pub homepage: Option<String>,
+    /// URL of the web interface of the packaging repository.
+    #[serde(rename = "Vcs-Browser")]
+    pub vcs_browser: Option<String>,
+    /// Git repository.
+    #[serde(rename = "Vcs-Git")]
+    pub vcs_git: Option<VcsLocation>,
+    /// Whether building the package requires root, usually `no`.
+    #[serde(rename = "Rules-Requires-Root")]
+    pub rules_requires_root: Option<String>,
+    /// Comma-separated list of test suites, e.g. `autopkgtest`.
+    pub testsuite: Option<String>,
+}
+
+/// Paragraph describing a binary package.
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct BinaryParagraph {
+    /// Name of the binary package.
+    pub package: String,
+    /// Architectures the package is built for, e.g. `any` or `all`.
+    pub architecture: String,
+    /// Section overriding the one of the source package.
+    pub section: Option<Section>,
+    /// Priority overriding the one of the source package.
+    pub priority: Option<Priority>,
+    /// Type of the package, e.g. `udeb`.
+    #[serde(rename = "Package-Type")]
+    pub package_type: Option<String>,
+    /// How the package behaves when multiple architectures are installed.
+    #[serde(rename = "Multi-Arch")]
+    pub multi_arch: Option<MultiArch>,
+    /// Whether the package is essential.
+    pub essential: Option<YesNo>,
+    /// Build profiles the package is built with.
+    #[serde(rename = "Build-Profiles")]
+    pub build_profiles: Option<RestrictionFormula>,
+    /// Packages required to be fully installed before this package is unpacked.
+    #[serde(rename = "Pre-Depends")]
+    pub pre_depends: Option<Relations>,
+    /// Packages required to be configured before this package is configured.
+    pub depends: Option<Relations>,
+    /// Packages installed together with this package in all usual installations.
+    pub recommends: Option<Relations>,
+    /// Packages that may be useful together with this package.
+    pub suggests: Option<Relations>,
+    /// Packages enhanced by this package.
+    pub enhances: Option<Relations>,
+    /// Packages broken by this package.
+    pub breaks: Option<Relations>,
+    /// Packages that can't be installed together with this package.
+    pub conflicts: Option<Relations>,
+    /// Packages replaced by this package.
+    pub replaces: Option<Relations>,
+    /// Virtual packages provided by this package.
+    pub provides: Option<Provides>,
+    /// URL of the upstream project.
+    pub homepage: Option<String>,
+    /// Synopsis followed by the long description.
+    pub description: Option<String>,
+}
+
+/// Contents of the `debian/control` file.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Control {
+    /// The source paragraph.
+    pub source: SourceParagraph,
+    /// The binary paragraphs in their original order.
+    pub binaries: Vec<BinaryParagraph>,
+}
+
+impl Control {
+    /// Converts the parsed document validating the order of paragraphs.
+    ///
+    /// The first paragraph must contain the `Source` field, all other paragraphs must contain the
+    /// `Package` field and there has to be at least one of them.
+    pub fn from_document(document: &Document) -> Result<Self, ParseError> {
+        let (source, binaries) = document.paragraphs().split_first().ok_or(ErrorInner::Empty)?;
+        if source.get("Source").is_none() {
+            return Err(ErrorInner::NotSource.into());
+        }
+        if binaries.is_empty() {
+            return Err(ErrorInner::NoBinaries.into());
+        }
+        let source = source.deserialize_into().map_err(|error| ErrorInner::InvalidParagraph(1, error))?;
+        let binaries = binaries.iter()
+            .enumerate()
+            .map(|(i, paragraph)| {
+                if paragraph.get("Package").is_none() {
+                    return Err(ErrorInner::NotBinary(i + 2).into());
+                }
+                paragraph.deserialize_into().map_err(|error| ErrorInner::InvalidParagraph(i + 2, error).into())
+            })
+            .collect::<Result<_, ParseError>>()?;
+
+        Ok(Control { source, binaries, })
+    }
+
+    /// Returns the binary paragraph of the package.
+    pub fn binary(&self, package: &str) -> Option<&BinaryParagraph> {
+        self.binaries.iter().find(|binary| binary.package == package)
+    }
+}
+
+impl FromStr for Control {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let document = Document::parse(s).map_err(ErrorInner::Syntax)?;
+        Control::from_document(&document)
+    }
+}
+
+impl Serialize for Control {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.binaries.len() + 1))?;
+        seq.serialize_element(&self.source)?;
+        for binary in &self.binaries {
+            seq.serialize_element(binary)?;
+        }
+        seq.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Control {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let document = Document::deserialize(deserializer)?;
+        Control::from_document(&document).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Control;
+
+    #[test]
+    fn parse() {
+        let input = "\
+Source: hello
+Section: devel
+Priority: optional
+Maintainer: Santiago Vila <sanvila@debian.org>
+Build-Depends: debhelper-compat (= 13), help2man <!nodoc>
+Standards-Version: 4.6.2
+Homepage: https://www.gnu.org/software/hello/
+Rules-Requires-Root: no
+
+Package: hello
+Architecture: any
+Depends: ${shlibs:Depends}, ${misc:Depends}
+Description: example package based on GNU hello
+ The GNU hello program produces a familiar, friendly greeting.
+
+Package: hello-doc
+Architecture: all
+Multi-Arch: foreign
+Build-Profiles: <!nodoc>
+Description: documentation of hello
+";
+        let control = input.parse::<Control>().unwrap();
+        assert_eq!(control.binaries.len(), 2);
+        assert!(control.binary("hello-doc").unwrap().build_profiles.is_some());
+        assert!(control.binary("hello-dbg").is_none());
+        assert_eq!(crate::to_string(&control).unwrap(), input);
+        assert_eq!(crate::from_str::<Control>(input).unwrap(), control);
+    }
+
+    #[test]
+    fn invalid() {
+        let err = |input: &str| input.parse::<Control>().unwrap_err().to_string();
+
+        assert_eq!(err(""), "the file is empty");
+        assert_eq!(err("Package: hello\n\nSource: hello\n"), "the first paragraph is not a source paragraph (missing the Source field)");
+        assert_eq!(err("Source: hello\n"), "the file doesn't contain any binary paragraphs");
+        assert_eq!(err("Source: hello\n\nPackage: hello\nArchitecture: any\n\nDescription: foo\n"), "paragraph 3 is not a binary paragraph (missing the Package field)");
+        assert_eq!(err("Source: hello\n\nPackage: hello\n"), "invalid paragraph 2");
+    }
+}
@@ -0,0 +1,142 @@
+//! Dates in fields like `Date` and `Valid-Until`.
+//!
+//! This module requires the `chrono` feature.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+use chrono::{DateTime, FixedOffset, Utc};
+
+/// Error returned when parsing a date fails.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("invalid RFC 2822 date")]
+pub struct ParseError(#[source] chrono::ParseError);
+
+/// Date and time in the RFC 2822 format, e.g. `Sat, 14 Aug 2021 07:46:50 UTC`.
+///
+/// `Release` files use `UTC` as time zone name while other files use numeric offsets (`+0000`).
+/// Both are accepted and the original style is kept when the date is displayed.
+/// Comparisons only consider the instant in time.
+///
+/// # Example
+///
+/// ```
+/// use rfc822_like::debian::date::Date;
+///
+/// let date = "Sat, 14 Aug 2021 09:46:50 +0200".parse::<Date>().unwrap();
+/// assert_eq!(date, "Sat, 14 Aug 2021 07:46:50 UTC".parse().unwrap());
+/// assert_eq!(date.to_string(), "Sat, 14 Aug 2021 09:46:50 +0200");
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct Date {
+    date_time: DateTime<FixedOffset>,
+    utc_name: bool,
+}
+
+impl Date {
+    /// Returns the date as `chrono` type.
+    pub fn date_time(&self) -> DateTime<FixedOffset> {
+        self.date_time
+    }
+}
+
+impl From<DateTime<FixedOffset>> for Date {
+    fn from(date_time: DateTime<FixedOffset>) -> Self {
+        Date { date_time, utc_name: false, }
+    }
+}
+
+/// The date will be displayed with `UTC` time zone name as in `Release` files.
+impl From<DateTime<Utc>> for Date {
+    fn from(date_time: DateTime<Utc>) -> Self {
+        Date { date_time: date_time.into(), utc_name: true, }
+    }
+}
+
+impl From<Date> for DateTime<FixedOffset> {
+    fn from(date: Date) -> Self {
+        date.date_time
+    }
+}
+
+impl From<Date> for DateTime<Utc> {
+    fn from(date: Date) -> Self {
+        date.date_time.into()
+    }
+}
+
+impl FromStr for Date {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (date_time, utc_name) = if s.ends_with(" UTC") {
+            let s = format!("{} +0000", &s[..(s.len() - 4)]);
+            (DateTime::parse_from_rfc2822(&s), true)
+        } else {
+            (DateTime::parse_from_rfc2822(s), false)
+        };
+        Ok(Date { date_time: date_time.map_err(ParseError)?, utc_name, })
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.utc_name && self.date_time.offset().local_minus_utc() == 0 {
+            write!(f, "{}", self.date_time.format("%a, %d %b %Y %H:%M:%S UTC"))
+        } else {
+            write!(f, "{}", self.date_time.format("%a, %d %b %Y %H:%M:%S %z"))
+        }
+    }
+}
+
+impl PartialEq for Date {
+    fn eq(&self, other: &Self) -> bool {
+        self.date_time == other.date_time
+    }
+}
+
+impl Eq for Date {}
+
+impl Ord for Date {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.date_time.cmp(&other.date_time)
+    }
+}
+
+impl PartialOrd for Date {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Hash for Date {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.date_time.hash(state);
+    }
+}
+
+serde_via_str!(Date, "an RFC 2822 date");
+
+#[cfg(test)]
+mod tests {
+    use super::Date;
+
+    #[test]
+    fn serde() {
+        #[derive(serde_derive::Deserialize, serde_derive::Serialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct Release {
+            date: Date,
+            #[serde(rename = "Valid-Until")]
+            valid_until: Date,
+        }
+
+        let input = "Date: Sat, 14 Aug 2021 07:46:50 UTC\nValid-Until: Sat, 21 Aug 2021 09:46:50 +0200\n";
+        let release = crate::from_str::<Release>(input).unwrap();
+        assert!(release.date < release.valid_until);
+        assert_eq!(crate::to_string(&release).unwrap(), input);
+        assert!("Sat, 14 Aug 2021".parse::<Date>().is_err());
+    }
+}
@@ -0,0 +1,184 @@
+//! Reading control information from `.deb` packages.
+//!
+//! A `.deb` is an `ar` archive containing `control.tar` compressed using `gzip`, `xz`, `zstd` or
+//! not compressed at all.
+//! The `control` file inside it is the binary package paragraph this module extracts.
+//!
+//! [`packages_record`] additionally fills in `Filename`, `Size` and digests producing a record of
+//! the `Packages` index, which together with [`generate`](super::generate) allows generating
+//! indexes from a pool of `.deb`s.
+//!
+//! This module requires the `deb` feature.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use rfc822_like::debian::deb;
+//! use rfc822_like::debian::packages::Record;
+//!
+//! let control = deb::from_deb::<rfc822_like::debian::control::BinaryParagraph, _>("hello_2.10-2_amd64.deb").unwrap();
+//! println!("{}", control.package);
+//! let record: Record = deb::packages_record("pool/main/h/hello/hello_2.10-2_amd64.deb", "pool/main/h/hello/hello_2.10-2_amd64.deb").unwrap();
+//! ```
+
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::{Path, PathBuf};
+use serde::de::DeserializeOwned;
+use crate::digest::digest_reader;
+use super::packages::Record;
+
+#[derive(Debug, thiserror::Error)]
+enum ErrorInner {
+    #[error("failed to read {path}")]
+    Read { path: PathBuf, #[source] error: io::Error, },
+    #[error("failed to read the archive")]
+    Archive(#[source] io::Error),
+    #[error("the archive doesn't contain control.tar")]
+    MissingControlTar,
+    #[error("unsupported compression of control member {0}")]
+    UnsupportedCompression(String),
+    #[error("control.tar doesn't contain the control file")]
+    MissingControl,
+    #[error("the control file is not valid UTF-8")]
+    InvalidUtf8(#[source] std::string::FromUtf8Error),
+    #[error("failed to parse the control file")]
+    Parse(#[source] crate::document::Error),
+    #[error("the control file is empty")]
+    Empty,
+    #[error("failed to deserialize the control file")]
+    Deserialize(#[source] crate::de::Error),
+}
+
+/// Error returned when reading a `.deb` fails.
+#[derive(Debug, thiserror::Error)]
+#[error(transparent)]
+pub struct Error(#[from] ErrorInner);
+
+/// Returns the decompressing reader of the control member based on its name.
+fn decompress<'a, R: Read + 'a>(name: &str, reader: R) -> Result<Box<dyn Read + 'a>, Error> {
+    match name {
+        "control.tar" => Ok(Box::new(reader)),
+        "control.tar.gz" => Ok(Box::new(flate2::read::GzDecoder::new(reader))),
+        "control.tar.xz" => Ok(Box::new(xz2::read::XzDecoder::new(reader))),
+        "control.tar.zst" => {
+            let decoder = ruzstd::StreamingDecoder::new(reader)
+                .map_err(|error| ErrorInner::Archive(io::Error::new(io::ErrorKind::InvalidData, error)))?;
+            Ok(Box::new(decoder))
+        },
+        _ => Err(ErrorInner::UnsupportedCompression(name.to_owned()).into()),
+    }
+}
+
+/// Extracts the content of the `control` file from a `.deb` read from `reader`.
+pub fn read_control<R: Read>(reader: R) -> Result<String, Error> {
+    let mut archive = ar::Archive::new(reader);
+    while let Some(entry) = archive.next_entry() {
+        let entry = entry.map_err(ErrorInner::Archive)?;
+        let name = String::from_utf8_lossy(entry.header().identifier()).trim_end_matches('/').to_owned();
+        if !name.starts_with("control.tar") {
+            continue;
+        }
+
+        let mut tar = tar::Archive::new(decompress(&name, entry)?);
+        for file in tar.entries().map_err(ErrorInner::Archive)? {
+            let mut file = file.map_err(ErrorInner::Archive)?;
+            let path = file.path().map_err(ErrorInner::Archive)?;
+            if path == Path::new("./control") || path == Path::new("control") {
+                let mut control = Vec::new();
+                file.read_to_end(&mut control).map_err(ErrorInner::Archive)?;
+                return String::from_utf8(control).map_err(|error| ErrorInner::InvalidUtf8(error).into());
+            }
+        }
+        return Err(ErrorInner::MissingControl.into());
+    }
+    Err(ErrorInner::MissingControlTar.into())
+}
+
+fn read_control_file(path: &Path) -> Result<String, Error> {
+    let file = File::open(path).map_err(|error| ErrorInner::Read { path: path.to_owned(), error, })?;
+    read_control(BufReader::new(file))
+}
+
+/// Deserializes the `control` file of the `.deb` at `path`.
+pub fn from_deb<T: DeserializeOwned, P: AsRef<Path>>(path: P) -> Result<T, Error> {
+    let control = read_control_file(path.as_ref())?;
+    crate::from_str(&control).map_err(|error| ErrorInner::Deserialize(error).into())
+}
+
+/// Creates the `Packages` record of the `.deb` at `path`.
+///
+/// `filename` is the path relative to the repository root written into the `Filename` field.
+/// `Size`, `MD5sum` and `SHA256` are computed from the file.
+pub fn packages_record<P: AsRef<Path>, F: Into<String>>(path: P, filename: F) -> Result<Record, Error> {
+    let path = path.as_ref();
+    let control = read_control_file(path)?;
+    let file = File::open(path).map_err(|error| ErrorInner::Read { path: path.to_owned(), error, })?;
+    let (size, (md5, sha256)) = digest_reader((md5::Md5::default(), sha2::Sha256::default()), BufReader::new(file))
+        .map_err(|error| ErrorInner::Read { path: path.to_owned(), error, })?;
+
+    let mut document = crate::document::Document::parse(&control).map_err(ErrorInner::Parse)?;
+    let paragraph = document.paragraphs_mut().first_mut().ok_or(ErrorInner::Empty)?;
+    paragraph.set("Filename", filename);
+    paragraph.set("Size", size.to_string());
+    paragraph.set("MD5sum", md5.to_string());
+    paragraph.set("SHA256", sha256.to_string());
+    paragraph.deserialize_into().map_err(|error| ErrorInner::Deserialize(error).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    const CONTROL: &str = "Package: hello\nVersion: 2.10-2\nArchitecture: amd64\nMaintainer: Santiago Vila <sanvila@debian.org>\nInstalled-Size: 280\nDepends: libc6 (>= 2.14)\nDescription: example package based on GNU hello\n";
+
+    fn deb(member: &str, control: &[u8]) -> Vec<u8> {
+        let mut tar = tar::Builder::new(Vec::new());
+        let mut header = tar::Header::new_gnu();
+        header.set_size(control.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        tar.append_data(&mut header, "./control", control).unwrap();
+        let tar = tar.into_inner().unwrap();
+        let tar = match member {
+            "control.tar.gz" => {
+                let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(&tar).unwrap();
+                encoder.finish().unwrap()
+            },
+            _ => tar,
+        };
+
+        let mut ar = ar::Builder::new(Vec::new());
+        ar.append(&ar::Header::new(b"debian-binary".to_vec(), 4), &b"2.0\n"[..]).unwrap();
+        ar.append(&ar::Header::new(member.as_bytes().to_vec(), tar.len() as u64), &*tar).unwrap();
+        ar.into_inner().unwrap()
+    }
+
+    #[test]
+    fn read_control() {
+        assert_eq!(super::read_control(&*deb("control.tar.gz", CONTROL.as_bytes())).unwrap(), CONTROL);
+        assert_eq!(super::read_control(&*deb("control.tar", CONTROL.as_bytes())).unwrap(), CONTROL);
+        assert_eq!(super::read_control(&*deb("control.tar.bz2", CONTROL.as_bytes())).unwrap_err().to_string(), "unsupported compression of control member control.tar.bz2");
+    }
+
+    #[test]
+    fn packages_record() {
+        let data = deb("control.tar.gz", CONTROL.as_bytes());
+        let dir = crate::testing::temp_dir::TempDir::new("deb");
+        let path = dir.join("hello.deb");
+        std::fs::write(&path, &data).unwrap();
+        let record = super::packages_record(&path, "pool/main/h/hello/hello_2.10-2_amd64.deb");
+        let control = super::from_deb::<crate::debian::control::BinaryParagraph, _>(&path);
+
+        let record = record.unwrap();
+        assert_eq!(record.package, "hello");
+        assert_eq!(record.installed_size, Some(280));
+        assert_eq!(record.filename, "pool/main/h/hello/hello_2.10-2_amd64.deb");
+        assert_eq!(record.size, data.len() as u64);
+        let (_, (md5, sha256)) = crate::digest::digest_reader((md5::Md5::default(), sha2::Sha256::default()), &*data).unwrap();
+        assert_eq!(record.md5sum, Some(md5));
+        assert_eq!(record.sha256, Some(sha256));
+        assert_eq!(control.unwrap().package, "hello");
+    }
+}
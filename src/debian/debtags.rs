@@ -0,0 +1,188 @@
+//! The `Tag` field containing [debtags](https://wiki.debian.org/Debtags).
+//!
+//! Tags are written as `facet::value` separated by commas.
+//! Multiple values of the same facet can be abbreviated using braces: `role::{program,shared-lib}`.
+//!
+//! # Example
+//!
+//! ```
+//! use rfc822_like::debian::debtags::Tags;
+//!
+//! let tags = "implemented-in::c, role::{program,shared-lib}".parse::<Tags>().unwrap();
+//! assert_eq!(tags.0.len(), 3);
+//! assert!(tags.contains("role", "shared-lib"));
+//! assert_eq!(tags.to_string(), "implemented-in::c, role::{program,shared-lib}");
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+
+// Width after which the value is folded, the field name is included
+const FOLD_WIDTH: usize = 79;
+
+#[derive(Debug, Clone, thiserror::Error)]
+enum ErrorInner {
+    #[error("missing '::' in tag '{0}'")]
+    MissingSeparator(String),
+    #[error("empty facet or value in tag '{0}'")]
+    Empty(String),
+    #[error("unbalanced braces in '{0}'")]
+    UnbalancedBraces(String),
+}
+
+/// Error returned when parsing tags fails.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error(transparent)]
+pub struct ParseError(#[from] ErrorInner);
+
+/// A single tag.
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Tag {
+    /// The facet, e.g. `role`.
+    pub facet: String,
+    /// The value within the facet, e.g. `program`.
+    pub value: String,
+}
+
+impl fmt::Display for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}::{}", self.facet, self.value)
+    }
+}
+
+/// List of tags with brace expressions expanded.
+///
+/// When displayed consecutive tags of the same facet are grouped using braces and the value is
+/// folded into multiple lines if it's too long.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct Tags(pub Vec<Tag>);
+
+impl Tags {
+    /// Returns `true` if the list contains the tag.
+    pub fn contains(&self, facet: &str, value: &str) -> bool {
+        self.0.iter().any(|tag| tag.facet == facet && tag.value == value)
+    }
+
+    /// Iterates over values of given facet.
+    pub fn values<'a>(&'a self, facet: &'a str) -> impl Iterator<Item=&'a str> + 'a {
+        self.0.iter().filter(move |tag| tag.facet == facet).map(|tag| tag.value.as_str())
+    }
+}
+
+/// Splits the input at commas that are not inside braces.
+fn split_items(s: &str) -> Result<Vec<&str>, ParseError> {
+    let mut items = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (pos, c) in s.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth = depth.checked_sub(1).ok_or_else(|| ErrorInner::UnbalancedBraces(s.to_owned()))?,
+            ',' if depth == 0 => {
+                items.push(&s[start..pos]);
+                start = pos + 1;
+            },
+            _ => (),
+        }
+    }
+    if depth != 0 {
+        return Err(ErrorInner::UnbalancedBraces(s.to_owned()).into());
+    }
+    items.push(&s[start..]);
+    Ok(items)
+}
+
+impl FromStr for Tags {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut tags = Vec::new();
+        for item in split_items(s)? {
+            let item = item.trim();
+            if item.is_empty() {
+                continue;
+            }
+            let pos = item.find("::").ok_or_else(|| ErrorInner::MissingSeparator(item.to_owned()))?;
+            let facet = item[..pos].trim();
+            let value = item[(pos + 2)..].trim();
+            let values = if value.starts_with('{') && value.ends_with('}') {
+                value[1..(value.len() - 1)].split(',').map(str::trim).collect()
+            } else {
+                vec![value]
+            };
+            for value in values {
+                if facet.is_empty() || value.is_empty() {
+                    return Err(ErrorInner::Empty(item.to_owned()).into());
+                }
+                tags.push(Tag { facet: facet.to_owned(), value: value.to_owned(), });
+            }
+        }
+        Ok(Tags(tags))
+    }
+}
+
+impl fmt::Display for Tags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Includes `Tag: `
+        let mut width = 5;
+        let mut tags = &self.0[..];
+        let mut first = true;
+        while let Some(tag) = tags.first() {
+            let count = tags.iter().take_while(|other| other.facet == tag.facet).count();
+            let item = if count == 1 {
+                tag.to_string()
+            } else {
+                let values = tags[..count].iter().map(|tag| tag.value.as_str()).collect::<Vec<_>>();
+                format!("{}::{{{}}}", tag.facet, values.join(","))
+            };
+            tags = &tags[count..];
+
+            if !first {
+                if width + item.len() + 2 > FOLD_WIDTH {
+                    f.write_str(",\n")?;
+                    width = 1;
+                } else {
+                    f.write_str(", ")?;
+                    width += 2;
+                }
+            }
+            first = false;
+            f.write_str(&item)?;
+            width += item.len();
+        }
+        Ok(())
+    }
+}
+
+serde_via_str!(Tags, "a list of debtags");
+
+#[cfg(test)]
+mod tests {
+    use super::Tags;
+
+    #[test]
+    fn parse() {
+        let tags = "admin::configuring, implemented-in::{c,c++},\n role::program, ".parse::<Tags>().unwrap();
+        assert_eq!(tags.0.len(), 4);
+        assert_eq!(tags.values("implemented-in").collect::<Vec<_>>(), ["c", "c++"]);
+        assert_eq!(tags.to_string(), "admin::configuring, implemented-in::{c,c++}, role::program");
+
+        assert_eq!("role".parse::<Tags>().unwrap_err().to_string(), "missing '::' in tag 'role'");
+        assert_eq!("role::{a,".parse::<Tags>().unwrap_err().to_string(), "unbalanced braces in 'role::{a,'");
+        assert_eq!("role::{a,}".parse::<Tags>().unwrap_err().to_string(), "empty facet or value in tag 'role::{a,}'");
+    }
+
+    #[test]
+    fn serde() {
+        #[derive(serde_derive::Deserialize, serde_derive::Serialize)]
+        struct Package {
+            #[serde(rename = "Tag")]
+            tag: Tags,
+        }
+
+        let input = "Tag: admin::configuring, implemented-in::c, interface::commandline,\n role::program, scope::utility, use::{configuring,monitor}, works-with::file\n";
+        let package = crate::from_str::<Package>(input).unwrap();
+        assert_eq!(package.tag.0.len(), 8);
+        assert_eq!(crate::to_string(&package).unwrap(), input);
+    }
+}
@@ -0,0 +1,89 @@
+//! Computation of `Description-md5`.
+//!
+//! apt identifies descriptions in `Translation-*` files by the MD5 digest of the description as
+//! written in the `Packages` file: the synopsis followed by the continuation lines including their
+//! leading spaces and a trailing newline.
+//! Since deserializing a description trims continuation lines, [`from_unfolded`] can only
+//! reconstruct the original text if the lines were indented by a single space.
+//! Use [`from_field`] with a parsed [`Field`] to get the exact digest of verbatim lines too.
+//!
+//! This module requires the `rustcrypto` feature.
+//!
+//! # Example
+//!
+//! ```
+//! use rfc822_like::debian::description;
+//! use rfc822_like::document::Document;
+//!
+//! let document = Document::parse("Package: foo\nDescription: short\n long\n .\n   verbatim\n").unwrap();
+//! let field = document.paragraphs()[0].iter().find(|field| field.is_named("Description")).unwrap();
+//! assert_eq!(description::from_field(field), description::md5("short\n long\n .\n   verbatim"));
+//! assert_ne!(description::from_field(field), description::from_unfolded(field.value()));
+//! ```
+
+use md5::Digest;
+use crate::document::Field;
+use super::checksums::{Checksum, Md5};
+
+/// Computes the digest of the description written as in the file, without the field name.
+///
+/// The trailing newline is optional.
+pub fn md5(raw: &str) -> Md5 {
+    let mut hasher = md5::Md5::new();
+    hasher.update(raw.as_bytes());
+    if !raw.ends_with('\n') {
+        hasher.update(b"\n");
+    }
+    Md5::from_bytes(&hasher.finalize()).expect("invalid MD5 length")
+}
+
+/// Computes the digest of a deserialized description.
+///
+/// The description is folded the same way the serializer does it.
+pub fn from_unfolded(description: &str) -> Md5 {
+    let mut lines = description.split('\n');
+    let mut raw = lines.next().unwrap_or("").to_owned();
+    for line in lines {
+        raw.push_str("\n ");
+        raw.push_str(if line.is_empty() { "." } else { line });
+    }
+    md5(&raw)
+}
+
+/// Computes the digest of the `Description` field.
+///
+/// The original text is used if the field was parsed and not modified, comments are skipped.
+pub fn from_field(field: &Field) -> Md5 {
+    let raw = match field.raw() {
+        Some(raw) => raw,
+        None => return from_unfolded(field.value()),
+    };
+    let value = match raw.find(':') {
+        Some(pos) => raw[(pos + 1)..].trim_start_matches(|c| c == ' ' || c == '\t'),
+        None => raw,
+    };
+    let text = value
+        .split_terminator('\n')
+        .filter(|line| !line.starts_with('#'))
+        .collect::<Vec<_>>()
+        .join("\n");
+    md5(&text)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::document::Document;
+
+    #[test]
+    fn digests() {
+        // computed using `printf 'short\n long\n .\n more\n' | md5sum`
+        let expected = "33e1633f49893339a9eb932602dcf910";
+        let input = "Package: foo\nDescription: short\n long\n .\n# comment\n more\nSize: 1\n";
+        let document = Document::parse(input).unwrap();
+        let paragraph = &document.paragraphs()[0];
+        let field = paragraph.iter().find(|field| field.is_named("Description")).unwrap();
+        assert_eq!(super::from_field(field).to_string(), expected);
+        assert_eq!(super::from_unfolded(field.value()).to_string(), expected);
+        assert_eq!(super::md5("short\n long\n .\n more\n").to_string(), expected);
+    }
+}
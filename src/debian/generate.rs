@@ -0,0 +1,218 @@
+//! Generation of `Packages` indexes and the `Release` file of a repository.
+//!
+//! This is the same job `apt-ftparchive` does: the records are grouped by component and
+//! architecture, sorted and written into `<component>/binary-<arch>/Packages` (optionally
+//! compressed), then the digests of all written files are listed in the `Release` file.
+//! The digest is computed by any [`Digest`] implementation producing one of the checksums listed
+//! in `Release` files, apt requires SHA256.
+//!
+//! Packages with `Architecture: all` are written into `binary-all` indexes and the `Release` file
+//! announces it using `No-Support-for-Architecture-all: Packages`.
+//!
+//! This module requires the `generate` feature.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # #[cfg(feature = "rustcrypto")]
+//! # fn main() {
+//! use rfc822_like::debian::generate::Generator;
+//! use rfc822_like::debian::packages::Record;
+//! use rfc822_like::debian::release::Release;
+//!
+//! let records: Vec<Record> = rfc822_like::from_file("/srv/repo/packages.txt").unwrap();
+//! let release = rfc822_like::from_str::<Release>("Suite: stable\nCodename: example\n").unwrap();
+//! let release = Generator::new()
+//!     .generate("/srv/repo/dists/example", records.into_iter().map(|record| ("main", record)), release, sha2::Sha256::default)
+//!     .unwrap();
+//! println!("generated {} files", release.files().len());
+//! # }
+//! # #[cfg(not(feature = "rustcrypto"))]
+//! # fn main() {}
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use crate::digest::Digest;
+use super::checksums::{FileEntry, FileList};
+use super::packages::Record;
+use super::release::{Release, ReleaseChecksum};
+
+#[derive(Debug, thiserror::Error)]
+enum ErrorInner {
+    #[error("failed to write {path}")]
+    Write { path: PathBuf, #[source] error: io::Error, },
+    #[error("failed to compress {0}")]
+    Compress(PathBuf, #[source] io::Error),
+    #[error("failed to serialize records")]
+    Serialize(#[source] crate::ser::Error),
+}
+
+/// Error returned when generating the repository fails.
+#[derive(Debug, thiserror::Error)]
+#[error(transparent)]
+pub struct Error(#[from] ErrorInner);
+
+/// Compression of written indexes.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Compression {
+    /// Uncompressed file.
+    None,
+    /// `gzip` (`.gz`).
+    Gzip,
+    /// `xz` (`.xz`).
+    Xz,
+}
+
+impl Compression {
+    /// Returns the file name extension including the dot, empty for `None`.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Compression::None => "",
+            Compression::Gzip => ".gz",
+            Compression::Xz => ".xz",
+        }
+    }
+
+    fn compress(&self, data: &[u8]) -> io::Result<Vec<u8>> {
+        match self {
+            Compression::None => Ok(data.to_owned()),
+            Compression::Gzip => {
+                let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::best());
+                encoder.write_all(data)?;
+                encoder.finish()
+            },
+            Compression::Xz => {
+                let mut encoder = xz2::write::XzEncoder::new(Vec::new(), 6);
+                encoder.write_all(data)?;
+                encoder.finish()
+            },
+        }
+    }
+}
+
+/// Generator of repository indexes.
+#[derive(Debug, Clone)]
+pub struct Generator {
+    compressions: Vec<Compression>,
+}
+
+impl Default for Generator {
+    fn default() -> Self {
+        Generator::new()
+    }
+}
+
+impl Generator {
+    /// Creates the generator writing uncompressed, `gzip`ped and `xz`ed indexes.
+    pub fn new() -> Self {
+        Generator {
+            compressions: vec![Compression::None, Compression::Gzip, Compression::Xz],
+        }
+    }
+
+    /// Sets the compressions of written indexes.
+    pub fn compressions(mut self, compressions: &[Compression]) -> Self {
+        self.compressions = compressions.to_owned();
+        self
+    }
+
+    /// Writes the indexes and the `Release` file into `root` (the `dists/<codename>` directory).
+    ///
+    /// `records` yields the component each package belongs to together with its record.
+    /// `release` is the template of the `Release` file - metadata such as `Suite` or `Date` are
+    /// taken from it while `Architectures`, `Components` and checksum lists are filled in.
+    /// `new_digest` creates the digest for each written file, e.g. `sha2::Sha256::default`, only
+    /// the list of its algorithm is written, other lists of the template are removed.
+    /// The resulting `Release` is returned after it's written.
+    pub fn generate<C, I, P, D, F>(&self, root: P, records: I, mut release: Release, mut new_digest: F) -> Result<Release, Error>
+    where C: Into<String>, I: IntoIterator<Item=(C, Record)>, P: AsRef<Path>, D: Digest, D::Output: ReleaseChecksum, F: FnMut() -> D {
+        let root = root.as_ref();
+        let mut indexes = BTreeMap::<(String, String), Vec<Record>>::new();
+        for (component, record) in records {
+            indexes.entry((component.into(), record.architecture.clone())).or_default().push(record);
+        }
+
+        let mut entries = Vec::new();
+        let mut architectures = BTreeSet::new();
+        let mut components = BTreeSet::new();
+        for ((component, architecture), mut records) in indexes {
+            records.sort_by(|a, b| a.package.cmp(&b.package).then_with(|| a.version.cmp(&b.version)));
+            let mut content = Vec::new();
+            crate::to_writer(&mut content, &records).map_err(ErrorInner::Serialize)?;
+
+            let dir = format!("{}/binary-{}", component, architecture);
+            fs::create_dir_all(root.join(&dir)).map_err(|error| ErrorInner::Write { path: root.join(&dir), error, })?;
+            for compression in &self.compressions {
+                let path = format!("{}/Packages{}", dir, compression.extension());
+                let data = compression.compress(&content).map_err(|error| ErrorInner::Compress(root.join(&path), error))?;
+                fs::write(root.join(&path), &data).map_err(|error| ErrorInner::Write { path: root.join(&path), error, })?;
+
+                entries.push(FileEntry::compute(path, &data, new_digest()));
+            }
+            architectures.insert(architecture);
+            components.insert(component);
+        }
+
+        if architectures.contains("all") {
+            release.no_support_for_architecture_all = Some("Packages".to_owned());
+        }
+        release.architectures = Some(architectures.into_iter().collect::<Vec<_>>().join(" "));
+        release.components = Some(components.into_iter().collect::<Vec<_>>().join(" "));
+        release.md5sum = None;
+        release.sha1 = None;
+        release.sha256 = None;
+        release.sha512 = None;
+        *ReleaseChecksum::list_mut(&mut release) = Some(FileList(entries));
+
+        let path = root.join("Release");
+        let mut content = Vec::new();
+        crate::to_writer(&mut content, &release).map_err(ErrorInner::Serialize)?;
+        fs::write(&path, content).map_err(|error| ErrorInner::Write { path, error, })?;
+        Ok(release)
+    }
+}
+
+#[cfg(all(test, feature = "rustcrypto"))]
+mod tests {
+    use std::io::Read;
+    use super::{Compression, Generator};
+    use crate::debian::packages::Record;
+    use crate::debian::release::Release;
+    use crate::testing::packages::record;
+    use crate::testing::temp_dir::TempDir;
+
+    #[test]
+    fn generate() {
+        let root = TempDir::new("generate");
+        let records = vec![
+            ("main", record("foo", "1.0", "amd64", "")),
+            ("main", record("bar", "2.0", "amd64", "")),
+            ("main", record("bar", "1.0", "amd64", "")),
+            ("contrib", record("baz", "1.0", "all", "")),
+        ];
+        let release = crate::from_str::<Release>("Suite: stable\nCodename: example\n").unwrap();
+        let release = Generator::new()
+            .compressions(&[Compression::None, Compression::Gzip])
+            .generate(&root, records, release, sha2::Sha256::default)
+            .unwrap();
+
+        let packages = crate::from_str::<Vec<Record>>(&std::fs::read_to_string(root.join("main/binary-amd64/Packages")).unwrap()).unwrap();
+        let mut gz = String::new();
+        flate2::read::GzDecoder::new(std::fs::File::open(root.join("main/binary-amd64/Packages.gz")).unwrap()).read_to_string(&mut gz).unwrap();
+        let written = crate::from_str::<Release>(&std::fs::read_to_string(root.join("Release")).unwrap()).unwrap();
+        let report = crate::debian::verify::verify(&release, &root, sha2::Sha256::default);
+
+        let order = packages.iter().map(|record| (record.package.as_str(), record.version.to_string())).collect::<Vec<_>>();
+        assert_eq!(order, [("bar", "1.0".to_owned()), ("bar", "2.0".to_owned()), ("foo", "1.0".to_owned())]);
+        assert_eq!(crate::from_str::<Vec<Record>>(&gz).unwrap(), packages);
+        assert_eq!(written, release);
+        assert_eq!(release.architectures.as_deref(), Some("all amd64"));
+        assert_eq!(release.components.as_deref(), Some("contrib main"));
+        assert_eq!(release.no_support_for_architecture_all.as_deref(), Some("Packages"));
+        assert_eq!(release.files().len(), 4);
+        assert!(report.is_ok());
+    }
+}
@@ -0,0 +1,100 @@
+//! # Debian-specific field types
+//!
+//! This module contains types for values of fields commonly found in Debian control files.
+//! All of them implement `FromStr`, `Display`, `Serialize` and `Deserialize` so they can be used
+//! in structs deserialized using this crate (or any other serde format) directly.
+//!
+//! The module is only available with the `debian` feature enabled.
+//! Some types need additional features, e.g. `chrono` for dates or `url` for URL adapters.
+
+/// Implements `Serialize` and `Deserialize` using `Display` and `FromStr`.
+macro_rules! serde_via_str {
+    ($type:ty, $expecting:expr) => {
+        impl serde::Serialize for $type {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $type {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                struct StrVisitor;
+
+                impl<'de> serde::de::Visitor<'de> for StrVisitor {
+                    type Value = $type;
+
+                    fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                        f.write_str($expecting)
+                    }
+
+                    fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                        v.parse().map_err(E::custom)
+                    }
+                }
+
+                deserializer.deserialize_str(StrVisitor)
+            }
+        }
+    }
+}
+
+pub mod binary;
+#[cfg(feature = "chrono")]
+pub mod buildinfo;
+pub mod checksums;
+pub mod compare;
+pub mod conffiles;
+pub mod contact;
+pub mod control;
+pub mod debtags;
+#[cfg(feature = "rustcrypto")]
+pub mod description;
+#[cfg(feature = "generate")]
+pub mod generate;
+#[cfg(feature = "chrono")]
+pub mod date;
+#[cfg(feature = "deb")]
+pub mod deb;
+pub mod multi_arch;
+pub mod number;
+pub mod package_index;
+pub mod package_list;
+pub mod package_name;
+pub mod package_relations;
+pub mod packages;
+#[cfg(feature = "rustcrypto")]
+pub mod pdiff;
+pub mod priority;
+pub mod profiles;
+pub mod provides;
+pub mod relations;
+#[cfg(feature = "chrono")]
+pub mod release;
+pub mod resolve;
+pub mod section;
+pub mod source;
+pub mod sources;
+pub mod templates;
+pub mod translations;
+#[cfg(feature = "url")]
+pub mod url;
+pub mod vcs;
+#[cfg(feature = "verify")]
+pub mod verify;
+#[cfg(all(feature = "notify", feature = "fs"))]
+pub mod watch;
+pub mod version;
+pub mod yes_no;
+
+pub use binary::Binary;
+pub use contact::{Contact, Uploaders};
+pub use multi_arch::MultiArch;
+pub use package_list::PackageList;
+pub use package_name::PackageName;
+pub use priority::Priority;
+pub use provides::Provides;
+pub use relations::Relations;
+pub use section::Section;
+pub use source::Source;
+pub use version::Version;
+pub use yes_no::YesNo;
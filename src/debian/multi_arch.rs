@@ -0,0 +1,86 @@
+//! The `Multi-Arch` field.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// Error returned when the value is not a known `Multi-Arch` value.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("invalid Multi-Arch value '{0}', expected one of same, foreign, allowed, no")]
+pub struct ParseError(String);
+
+/// Value of the `Multi-Arch` field.
+///
+/// See [the Debian wiki](https://wiki.debian.org/Multiarch/Implementation) for the meaning.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum MultiArch {
+    /// `same` - the package is co-installable with itself for other architectures.
+    Same,
+    /// `foreign` - the package can satisfy dependencies of packages of other architectures.
+    Foreign,
+    /// `allowed` - dependent packages may choose using `:any`.
+    Allowed,
+    /// `no` - the default when the field is missing.
+    No,
+}
+
+impl MultiArch {
+    /// Returns the value as written in control files.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MultiArch::Same => "same",
+            MultiArch::Foreign => "foreign",
+            MultiArch::Allowed => "allowed",
+            MultiArch::No => "no",
+        }
+    }
+}
+
+impl Default for MultiArch {
+    fn default() -> Self {
+        MultiArch::No
+    }
+}
+
+impl FromStr for MultiArch {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "same" => Ok(MultiArch::Same),
+            "foreign" => Ok(MultiArch::Foreign),
+            "allowed" => Ok(MultiArch::Allowed),
+            "no" => Ok(MultiArch::No),
+            _ => Err(ParseError(s.to_owned())),
+        }
+    }
+}
+
+impl fmt::Display for MultiArch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+serde_via_str!(MultiArch, "one of same, foreign, allowed, no");
+
+#[cfg(test)]
+mod tests {
+    use super::MultiArch;
+
+    #[test]
+    fn serde() {
+        #[derive(serde_derive::Deserialize, serde_derive::Serialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct Package {
+            package: String,
+            #[serde(rename = "Multi-Arch", default)]
+            multi_arch: MultiArch,
+        }
+
+        let package = crate::from_str::<Package>("Package: foo\nMulti-Arch: same\n").unwrap();
+        assert_eq!(package.multi_arch, MultiArch::Same);
+        assert_eq!(crate::to_string(&package).unwrap(), "Package: foo\nMulti-Arch: same\n");
+        assert_eq!(crate::from_str::<Package>("Package: foo\n").unwrap().multi_arch, MultiArch::No);
+        assert!(crate::from_str::<Package>("Package: foo\nMulti-Arch: Same\n").is_err());
+    }
+}
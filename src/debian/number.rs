@@ -0,0 +1,89 @@
+//! Serde adapters for numeric fields such as `Size` and `Installed-Size`.
+//!
+//! The numbers are written as strings since the value is always textual in control files.
+//! Use them with `#[serde(with = "...")]` on any type implementing `FromStr` and `Display`.
+//!
+//! # Example
+//!
+//! ```
+//! #[derive(serde_derive::Deserialize, serde_derive::Serialize)]
+//! #[serde(rename_all = "PascalCase")]
+//! struct Package {
+//!     #[serde(with = "rfc822_like::debian::number")]
+//!     size: u64,
+//!     #[serde(rename = "Installed-Size", with = "rfc822_like::debian::number::option", default)]
+//!     installed_size: Option<u64>,
+//! }
+//!
+//! let package = rfc822_like::from_str::<Package>("Size: 4242\n").unwrap();
+//! assert_eq!(package.size, 4242);
+//! assert!(package.installed_size.is_none());
+//! assert!(rfc822_like::from_str::<Package>("Size: many\n").is_err());
+//! ```
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::str::FromStr;
+use serde::{Deserializer, Serializer};
+
+struct NumberVisitor<T>(PhantomData<T>);
+
+impl<'de, T: FromStr> serde::de::Visitor<'de> for NumberVisitor<T> where T::Err: fmt::Display {
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a number")
+    }
+
+    fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        v.trim().parse().map_err(E::custom)
+    }
+}
+
+/// Serializes the number as string.
+pub fn serialize<T: fmt::Display, S: Serializer>(number: &T, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(number)
+}
+
+/// Deserializes the number from string.
+pub fn deserialize<'de, T: FromStr, D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> where T::Err: fmt::Display {
+    deserializer.deserialize_str(NumberVisitor(PhantomData))
+}
+
+/// Adapters for optional numeric fields.
+///
+/// Combine them with `#[serde(default)]` so that missing fields are accepted.
+pub mod option {
+    use std::fmt;
+    use std::str::FromStr;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    struct Number<T>(T);
+
+    impl<'de, T: FromStr> Deserialize<'de> for Number<T> where T::Err: fmt::Display {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            super::deserialize(deserializer).map(Number)
+        }
+    }
+
+    struct Displayed<'a, T>(&'a T);
+
+    impl<'a, T: fmt::Display> serde::Serialize for Displayed<'a, T> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_str(self.0)
+        }
+    }
+
+    /// Serializes the number as string if present.
+    pub fn serialize<T: fmt::Display, S: Serializer>(number: &Option<T>, serializer: S) -> Result<S::Ok, S::Error> {
+        match number {
+            Some(number) => serializer.serialize_some(&Displayed(number)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    /// Deserializes the number if present.
+    pub fn deserialize<'de, T: FromStr, D: Deserializer<'de>>(deserializer: D) -> Result<Option<T>, D::Error> where T::Err: fmt::Display {
+        Option::<Number<T>>::deserialize(deserializer).map(|number| number.map(|number| number.0))
+    }
+}
@@ -0,0 +1,211 @@
+//! In-memory index of `Packages` records with `apt-cache`-like queries.
+//!
+//! The index can be deserialized directly from a `Packages` file, records are inserted as they
+//! are parsed so no intermediate `Vec` of the whole file is needed.
+//! Records are kept sorted by name, version and architecture.
+//!
+//! # Example
+//!
+//! ```
+//! use rfc822_like::debian::package_index::PackageIndex;
+//!
+//! let input = "Package: postfix\nVersion: 3.5.6-1\nMaintainer: Foo <foo@example.org>\nArchitecture: amd64\nProvides: mail-transport-agent\nDepends: libc6 (>= 2.14)\nSection: mail\nFilename: pool/main/p/postfix/postfix_3.5.6-1_amd64.deb\nSize: 1540384\n\nPackage: libc6\nVersion: 2.31-13\nMaintainer: Foo <foo@example.org>\nArchitecture: amd64\nSection: libs\nFilename: pool/main/g/glibc/libc6_2.31-13_amd64.deb\nSize: 2825908\n";
+//! let index = rfc822_like::from_str::<PackageIndex>(input).unwrap();
+//! assert_eq!(index.get("libc6")[0].version.to_string(), "2.31-13");
+//! assert_eq!(index.providers("mail-transport-agent").next().unwrap().package, "postfix");
+//! assert_eq!(index.reverse_depends("libc6").next().unwrap().package, "postfix");
+//! assert_eq!(index.iter().map(|record| record.package.as_str()).collect::<Vec<_>>(), ["libc6", "postfix"]);
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::iter::FromIterator;
+use super::packages::Record;
+
+/// Sorted `Packages` records indexed for queries.
+#[derive(Debug, Clone, Default)]
+pub struct PackageIndex {
+    records: Vec<Record>,
+    providers: HashMap<String, Vec<usize>>,
+    sections: HashMap<String, Vec<usize>>,
+    reverse_depends: HashMap<String, Vec<usize>>,
+}
+
+impl PackageIndex {
+    /// Parses the `Packages` file from the reader.
+    ///
+    /// This function requires the `io` feature.
+    #[cfg(feature = "io")]
+    pub fn from_reader<R: std::io::BufRead>(reader: R) -> Result<Self, crate::de::Error> {
+        crate::from_reader(reader)
+    }
+
+    /// Returns the number of records.
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Returns `true` if the index contains no records.
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Iterates over all records sorted by name, version and architecture.
+    pub fn iter(&self) -> std::slice::Iter<'_, Record> {
+        self.records.iter()
+    }
+
+    /// Returns all records of the package with given name sorted by version and architecture.
+    pub fn get(&self, name: &str) -> &[Record] {
+        let start = self.records.partition_point_by(|record| record.package.as_str() < name);
+        let end = self.records.partition_point_by(|record| record.package.as_str() <= name);
+        &self.records[start..end]
+    }
+
+    /// Returns the record with the highest version of the package.
+    pub fn newest(&self, name: &str) -> Option<&Record> {
+        self.get(name).iter().max_by(|a, b| a.version.cmp(&b.version))
+    }
+
+    /// Returns the records providing the (virtual) package.
+    pub fn providers<'a>(&'a self, name: &str) -> impl Iterator<Item=&'a Record> + 'a {
+        self.lookup(&self.providers, name)
+    }
+
+    /// Returns the records in given section ignoring the component.
+    pub fn section<'a>(&'a self, section: &str) -> impl Iterator<Item=&'a Record> + 'a {
+        self.lookup(&self.sections, section)
+    }
+
+    /// Returns the records which mention the package in `Depends` or `Pre-Depends`.
+    ///
+    /// Alternatives are included, versions and architectures are ignored.
+    pub fn reverse_depends<'a>(&'a self, name: &str) -> impl Iterator<Item=&'a Record> + 'a {
+        self.lookup(&self.reverse_depends, name)
+    }
+
+    fn lookup<'a>(&'a self, map: &'a HashMap<String, Vec<usize>>, key: &str) -> impl Iterator<Item=&'a Record> + 'a {
+        map.get(key).into_iter().flatten().map(move |index| &self.records[*index])
+    }
+
+    fn build(mut records: Vec<Record>) -> Self {
+        records.sort_by(|a, b| a.package.cmp(&b.package).then_with(|| a.version.cmp(&b.version)).then_with(|| a.architecture.cmp(&b.architecture)));
+        let mut providers = HashMap::<_, Vec<_>>::new();
+        let mut sections = HashMap::<_, Vec<_>>::new();
+        let mut reverse_depends = HashMap::<_, Vec<_>>::new();
+        for (index, record) in records.iter().enumerate() {
+            for provided in record.provides.iter().flat_map(|provides| &provides.0) {
+                push_unique(providers.entry(provided.name.clone()).or_default(), index);
+            }
+            if let Some(section) = &record.section {
+                sections.entry(section.section().to_owned()).or_default().push(index);
+            }
+            let dependencies = record.pre_depends.iter()
+                .chain(&record.depends)
+                .flat_map(|relations| &relations.0)
+                .flat_map(|relation| &relation.0);
+            for dependency in dependencies {
+                push_unique(reverse_depends.entry(dependency.name.clone()).or_default(), index);
+            }
+        }
+        PackageIndex { records, providers, sections, reverse_depends, }
+    }
+}
+
+/// Adds the index unless it was just added by the same record.
+fn push_unique(indices: &mut Vec<usize>, index: usize) {
+    if indices.last() != Some(&index) {
+        indices.push(index);
+    }
+}
+
+/// Binary search returning the first position not satisfying the predicate.
+///
+/// Equivalent of `slice::partition_point` which is not available in our MSRV.
+trait PartitionPoint<T> {
+    fn partition_point_by<F: FnMut(&T) -> bool>(&self, pred: F) -> usize;
+}
+
+impl<T> PartitionPoint<T> for [T] {
+    fn partition_point_by<F: FnMut(&T) -> bool>(&self, mut pred: F) -> usize {
+        let (mut low, mut high) = (0, self.len());
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if pred(&self[mid]) {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        low
+    }
+}
+
+impl FromIterator<Record> for PackageIndex {
+    fn from_iter<I: IntoIterator<Item=Record>>(iter: I) -> Self {
+        PackageIndex::build(iter.into_iter().collect())
+    }
+}
+
+impl<'a> IntoIterator for &'a PackageIndex {
+    type Item = &'a Record;
+    type IntoIter = std::slice::Iter<'a, Record>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Inserts records as they are deserialized.
+impl<'de> serde::Deserialize<'de> for PackageIndex {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct IndexVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for IndexVisitor {
+            type Value = PackageIndex;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a sequence of Packages records")
+            }
+
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut records = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+                while let Some(record) = seq.next_element()? {
+                    records.push(record);
+                }
+                Ok(PackageIndex::build(records))
+            }
+        }
+
+        deserializer.deserialize_seq(IndexVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PackageIndex;
+    use crate::testing::packages::record;
+
+    #[test]
+    fn queries() {
+        let index = vec![
+            record("foo", "2.0", "i386", "Depends: bar | baz, bar (>= 1)\nSection: contrib/utils\n"),
+            record("foo", "1.0", "amd64", "Section: utils\n"),
+            record("bar", "1.0", "amd64", "Provides: baz, qux\n"),
+            record("foo", "2.0", "amd64", "Pre-Depends: bar\n"),
+            record("quux", "1.0", "all", "Provides: qux\n"),
+        ].into_iter().collect::<PackageIndex>();
+
+        assert_eq!(index.len(), 5);
+        let versions = index.get("foo").iter().map(|record| format!("{} {}", record.version, record.architecture)).collect::<Vec<_>>();
+        assert_eq!(versions, ["1.0 amd64", "2.0 amd64", "2.0 i386"]);
+        assert!(index.get("baz").is_empty());
+        assert_eq!(index.newest("foo").unwrap().version.to_string(), "2.0");
+        assert!(index.newest("zzz").is_none());
+        assert_eq!(index.providers("qux").map(|record| record.package.as_str()).collect::<Vec<_>>(), ["bar", "quux"]);
+        assert_eq!(index.section("utils").count(), 2);
+        assert_eq!(index.reverse_depends("bar").map(|record| record.architecture.as_str()).collect::<Vec<_>>(), ["amd64", "i386"]);
+        assert_eq!(index.reverse_depends("baz").count(), 1);
+        assert_eq!(index.iter().map(|record| record.package.as_str()).collect::<Vec<_>>(), ["bar", "foo", "foo", "foo", "quux"]);
+    }
+}
@@ -0,0 +1,118 @@
+//! The `Package-List` field of `.dsc` files and `Sources` indices.
+//!
+//! Each line describes one binary package built from the source package:
+//!
+//! ```text
+//! Package-List:
+//!  foo deb utils optional arch=any
+//!  foo-udeb udeb debian-installer optional arch=any profile=!noudeb
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+use super::checksums::FileList;
+
+#[derive(Debug, Clone, thiserror::Error)]
+enum ErrorInner {
+    #[error("missing {0} in '{1}'")]
+    MissingColumn(&'static str, String),
+    #[error("invalid attribute '{0}', expected key=value")]
+    InvalidAttribute(String),
+}
+
+/// Error returned when parsing a package list fails.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error(transparent)]
+pub struct ParseError(#[from] ErrorInner);
+
+/// A single line of `Package-List`.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct PackageListEntry {
+    /// Name of the binary package.
+    pub name: String,
+    /// Type of the package, e.g. `deb` or `udeb`.
+    pub package_type: String,
+    /// Section of the package (may include component).
+    pub section: String,
+    /// Priority of the package.
+    pub priority: String,
+    /// Additional `key=value` attributes such as `arch=any` in their original order.
+    pub attributes: Vec<(String, String)>,
+}
+
+impl PackageListEntry {
+    /// Returns the value of the attribute with given key.
+    pub fn attribute(&self, key: &str) -> Option<&str> {
+        self.attributes.iter().find(|(name, _)| name == key).map(|(_, value)| value.as_str())
+    }
+
+    /// Returns the architectures from the `arch` attribute which are separated by commas.
+    pub fn architectures(&self) -> impl Iterator<Item=&str> {
+        self.attribute("arch").unwrap_or("").split(',').filter(|arch| !arch.is_empty())
+    }
+}
+
+impl FromStr for PackageListEntry {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut columns = s.split_whitespace();
+        let mut next = |name| columns.next().map(ToOwned::to_owned).ok_or_else(|| ErrorInner::MissingColumn(name, s.to_owned()));
+        let name = next("package name")?;
+        let package_type = next("package type")?;
+        let section = next("section")?;
+        let priority = next("priority")?;
+        let attributes = columns
+            .map(|attribute| match attribute.find('=') {
+                Some(pos) => Ok((attribute[..pos].to_owned(), attribute[(pos + 1)..].to_owned())),
+                None => Err(ErrorInner::InvalidAttribute(attribute.to_owned())),
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(PackageListEntry {
+            name,
+            package_type,
+            section,
+            priority,
+            attributes,
+        })
+    }
+}
+
+impl fmt::Display for PackageListEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {} {}", self.name, self.package_type, self.section, self.priority)?;
+        for (key, value) in &self.attributes {
+            write!(f, " {}={}", key, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// The whole value of `Package-List` - one entry per line.
+pub type PackageList = FileList<PackageListEntry>;
+
+#[cfg(test)]
+mod tests {
+    use super::PackageList;
+
+    #[test]
+    fn serde() {
+        #[derive(serde_derive::Deserialize, serde_derive::Serialize)]
+        struct Dsc {
+            #[serde(rename = "Package-List")]
+            package_list: PackageList,
+        }
+
+        let input = "Package-List:\n foo deb utils optional arch=any\n foo-udeb udeb debian-installer optional arch=amd64,i386 profile=!noudeb\n";
+        let dsc = crate::from_str::<Dsc>(input).unwrap();
+        let udeb = &dsc.package_list.0[1];
+        assert_eq!(udeb.package_type, "udeb");
+        assert_eq!(udeb.architectures().collect::<Vec<_>>(), ["amd64", "i386"]);
+        assert_eq!(udeb.attribute("profile"), Some("!noudeb"));
+        assert_eq!(crate::to_string(&dsc).unwrap(), input);
+
+        assert_eq!("foo deb utils".parse::<PackageList>().unwrap_err().to_string(), "missing priority in 'foo deb utils'");
+        assert_eq!("foo deb utils optional any".parse::<PackageList>().unwrap_err().to_string(), "invalid attribute 'any', expected key=value");
+    }
+}
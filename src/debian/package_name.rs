@@ -0,0 +1,154 @@
+//! Validated names of packages.
+
+use std::borrow::Borrow;
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, thiserror::Error)]
+enum ErrorInner {
+    #[error("package name '{0}' is too short, at least two characters are required")]
+    TooShort(String),
+    #[error("package name '{0}' must start with a lowercase letter or a digit")]
+    InvalidStart(String),
+    #[error("package name '{0}' contains invalid character '{1}'")]
+    InvalidChar(String, char),
+}
+
+/// Error returned when the package name is invalid.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error(transparent)]
+pub struct ParseError(#[from] ErrorInner);
+
+/// Name of a (source or binary) package.
+///
+/// The name is validated according to the Debian policy: it must be at least two characters long,
+/// consist only of lowercase letters, digits and `+`, `-`, `.` and start with a letter or digit.
+/// The type dereferences to `str` so it can be used wherever a string slice is expected.
+///
+/// # Example
+///
+/// ```
+/// use rfc822_like::debian::PackageName;
+///
+/// #[derive(serde_derive::Deserialize)]
+/// #[serde(rename_all = "PascalCase")]
+/// struct Package {
+///     package: PackageName,
+/// }
+///
+/// let package = rfc822_like::from_str::<Package>("Package: libstdc++6\n").unwrap();
+/// assert!(package.package.starts_with("lib"));
+/// assert!(rfc822_like::from_str::<Package>("Package: Foo\n").is_err());
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct PackageName(String);
+
+impl PackageName {
+    /// Checks whether the name is valid without allocating.
+    pub fn validate(name: &str) -> Result<(), ParseError> {
+        let first = match name.chars().next() {
+            Some(first) if name.len() >= 2 => first,
+            _ => return Err(ErrorInner::TooShort(name.to_owned()).into()),
+        };
+        if !(first.is_ascii_lowercase() || first.is_ascii_digit()) {
+            return Err(ErrorInner::InvalidStart(name.to_owned()).into());
+        }
+        match name.chars().find(|c| !(c.is_ascii_lowercase() || c.is_ascii_digit() || *c == '+' || *c == '-' || *c == '.')) {
+            Some(c) => Err(ErrorInner::InvalidChar(name.to_owned(), c).into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the name as string slice.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns the inner string.
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+impl std::ops::Deref for PackageName {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl AsRef<str> for PackageName {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for PackageName {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl PartialEq<str> for PackageName {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl<'a> PartialEq<&'a str> for PackageName {
+    fn eq(&self, other: &&'a str) -> bool {
+        self.0 == *other
+    }
+}
+
+impl TryFrom<String> for PackageName {
+    type Error = ParseError;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        PackageName::validate(&name)?;
+        Ok(PackageName(name))
+    }
+}
+
+impl From<PackageName> for String {
+    fn from(name: PackageName) -> Self {
+        name.0
+    }
+}
+
+impl FromStr for PackageName {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        PackageName::validate(s)?;
+        Ok(PackageName(s.to_owned()))
+    }
+}
+
+impl fmt::Display for PackageName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+serde_via_str!(PackageName, "a valid package name");
+
+#[cfg(test)]
+mod tests {
+    use super::PackageName;
+
+    #[test]
+    fn validate() {
+        for valid in &["foo", "libstdc++6", "0ad", "python3.11", "g++-12"] {
+            assert_eq!(valid.parse::<PackageName>().unwrap(), *valid);
+        }
+        let err = |name: &str| name.parse::<PackageName>().unwrap_err().to_string();
+        assert_eq!(err("a"), "package name 'a' is too short, at least two characters are required");
+        assert_eq!(err(""), "package name '' is too short, at least two characters are required");
+        assert_eq!(err("-foo"), "package name '-foo' must start with a lowercase letter or a digit");
+        assert_eq!(err("Foo"), "package name 'Foo' must start with a lowercase letter or a digit");
+        assert_eq!(err("foo_bar"), "package name 'foo_bar' contains invalid character '_'");
+    }
+}
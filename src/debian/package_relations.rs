@@ -0,0 +1,211 @@
+//! Relationship fields of a binary package grouped together.
+//!
+//! [`PackageRelations`] collects the relationship fields of a [`Record`] so that dependencies
+//! can be checked against a [`PackageSet`] - e.g. the installed packages or an index.
+//! Architecture qualifiers and restrictions are ignored.
+//!
+//! # Example
+//!
+//! ```
+//! use rfc822_like::debian::packages::Record;
+//! use rfc822_like::debian::package_relations::{PackageRelations, PackageSet};
+//!
+//! let input = "Package: hello\nVersion: 2.10-2\nMaintainer: Foo <foo@example.org>\nArchitecture: amd64\nDepends: libc6 (>= 2.14), mail-transport-agent\nFilename: pool/main/h/hello/hello_2.10-2_amd64.deb\nSize: 56132\n\nPackage: libc6\nVersion: 2.31-13\nMaintainer: Foo <foo@example.org>\nArchitecture: amd64\nFilename: pool/main/g/glibc/libc6_2.31-13_amd64.deb\nSize: 2825908\n\nPackage: postfix\nVersion: 3.5.6-1\nMaintainer: Foo <foo@example.org>\nArchitecture: amd64\nProvides: mail-transport-agent\nFilename: pool/main/p/postfix/postfix_3.5.6-1_amd64.deb\nSize: 1540384\n";
+//! let records = rfc822_like::from_str::<Vec<Record>>(input).unwrap();
+//! let set = records.iter().collect::<PackageSet>();
+//! assert!(PackageRelations::from(&records[0]).is_satisfied_by(&set));
+//!
+//! let reverse = rfc822_like::debian::package_relations::reverse_depends(&records[2], &records);
+//! assert_eq!(reverse[0].package, "hello");
+//! ```
+
+use std::collections::HashMap;
+use std::iter::FromIterator;
+use super::packages::Record;
+use super::provides::Provided;
+use super::relations::{ConstraintVersion, Dependency, Relation};
+use super::{Provides, Relations, Version};
+
+/// Relationship fields of a single binary package.
+///
+/// Missing fields are represented by empty lists.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct PackageRelations {
+    /// The `Depends` field.
+    pub depends: Relations,
+    /// The `Pre-Depends` field.
+    pub pre_depends: Relations,
+    /// The `Recommends` field.
+    pub recommends: Relations,
+    /// The `Suggests` field.
+    pub suggests: Relations,
+    /// The `Breaks` field.
+    pub breaks: Relations,
+    /// The `Conflicts` field.
+    pub conflicts: Relations,
+    /// The `Provides` field.
+    pub provides: Provides,
+    /// The `Replaces` field.
+    pub replaces: Relations,
+}
+
+impl PackageRelations {
+    /// Returns the relations which have to be satisfied for the package to be installed.
+    ///
+    /// These are `Pre-Depends` followed by `Depends`.
+    pub fn required(&self) -> impl Iterator<Item=&Relation> {
+        self.pre_depends.0.iter().chain(&self.depends.0)
+    }
+
+    /// Returns `true` if all `Pre-Depends` and `Depends` are satisfied by the `packages`.
+    pub fn is_satisfied_by(&self, packages: &PackageSet) -> bool {
+        self.required().all(|relation| packages.satisfies_relation(relation))
+    }
+
+    /// Returns the `Pre-Depends` and `Depends` relations not satisfied by the `packages`.
+    pub fn unsatisfied<'a>(&'a self, packages: &'a PackageSet) -> impl Iterator<Item=&'a Relation> {
+        self.required().filter(move |relation| !packages.satisfies_relation(relation))
+    }
+
+    /// Returns the `Breaks` and `Conflicts` entries matched by the `packages`.
+    pub fn conflicting<'a>(&'a self, packages: &'a PackageSet) -> impl Iterator<Item=&'a Dependency> {
+        self.breaks.0.iter()
+            .chain(&self.conflicts.0)
+            .flat_map(|relation| &relation.0)
+            .filter(move |dependency| packages.satisfies(dependency))
+    }
+
+    /// Returns `true` if the package (pre-)depends on a package matching `package`.
+    ///
+    /// The package matches if its name and version or any of its provides satisfy an alternative.
+    pub fn depends_on(&self, package: &str, version: &Version, provides: &Provides) -> bool {
+        self.required()
+            .flat_map(|relation| &relation.0)
+            .any(|dependency| {
+                let real = dependency.name == package && dependency.version.as_ref().map_or(true, |constraint| constraint.matches(version));
+                real || provides.satisfies(dependency)
+            })
+    }
+}
+
+fn relations_or_empty(relations: &Option<Relations>) -> Relations {
+    relations.clone().unwrap_or_default()
+}
+
+impl<'a> From<&'a Record> for PackageRelations {
+    fn from(record: &'a Record) -> Self {
+        PackageRelations {
+            depends: relations_or_empty(&record.depends),
+            pre_depends: relations_or_empty(&record.pre_depends),
+            recommends: relations_or_empty(&record.recommends),
+            suggests: relations_or_empty(&record.suggests),
+            breaks: relations_or_empty(&record.breaks),
+            conflicts: relations_or_empty(&record.conflicts),
+            provides: record.provides.clone().unwrap_or_default(),
+            replaces: relations_or_empty(&record.replaces),
+        }
+    }
+}
+
+/// Set of available packages including the virtual packages they provide.
+#[derive(Debug, Clone, Default)]
+pub struct PackageSet {
+    real: HashMap<String, Vec<Version>>,
+    virtual_packages: HashMap<String, Vec<Option<ConstraintVersion>>>,
+}
+
+impl PackageSet {
+    /// Creates an empty set.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Adds a package with given version and provides.
+    pub fn insert(&mut self, name: &str, version: Version, provides: &Provides) {
+        self.real.entry(name.to_owned()).or_default().push(version);
+        for provided in &provides.0 {
+            self.virtual_packages.entry(provided.name.clone()).or_default().push(provided.version.clone());
+        }
+    }
+
+    /// Returns `true` if a real package with given name is in the set.
+    pub fn contains(&self, name: &str) -> bool {
+        self.real.contains_key(name)
+    }
+
+    /// Returns `true` if a real or virtual package satisfies the dependency.
+    pub fn satisfies(&self, dependency: &Dependency) -> bool {
+        let real = self.real
+            .get(&dependency.name)
+            .map_or(false, |versions| versions.iter().any(|version| dependency.version.as_ref().map_or(true, |constraint| constraint.matches(version))));
+        real || self.virtual_packages
+            .get(&dependency.name)
+            .map_or(false, |versions| versions.iter().any(|version| Provided { name: dependency.name.clone(), version: version.clone(), }.satisfies(dependency)))
+    }
+
+    /// Returns `true` if any alternative of the relation is satisfied.
+    pub fn satisfies_relation(&self, relation: &Relation) -> bool {
+        relation.0.iter().any(|dependency| self.satisfies(dependency))
+    }
+}
+
+impl<'a> Extend<&'a Record> for PackageSet {
+    fn extend<I: IntoIterator<Item=&'a Record>>(&mut self, iter: I) {
+        for record in iter {
+            let empty = Provides::default();
+            self.insert(&record.package, record.version.clone(), record.provides.as_ref().unwrap_or(&empty));
+        }
+    }
+}
+
+impl<'a> FromIterator<&'a Record> for PackageSet {
+    fn from_iter<I: IntoIterator<Item=&'a Record>>(iter: I) -> Self {
+        let mut set = PackageSet::new();
+        set.extend(iter);
+        set
+    }
+}
+
+/// Returns the records which (pre-)depend on the `package`, directly or via its provides.
+pub fn reverse_depends<'a, I: IntoIterator<Item=&'a Record>>(package: &Record, records: I) -> Vec<&'a Record> {
+    let empty = Provides::default();
+    let provides = package.provides.as_ref().unwrap_or(&empty);
+    records
+        .into_iter()
+        .filter(|record| PackageRelations::from(*record).depends_on(&package.package, &package.version, provides))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PackageRelations, PackageSet};
+
+    #[test]
+    fn satisfaction() {
+        let mut set = PackageSet::new();
+        set.insert("libc6", "2.31-13".parse().unwrap(), &Default::default());
+        set.insert("postfix", "3.5.6-1".parse().unwrap(), &"mail-transport-agent, foo (= 1.0)".parse().unwrap());
+
+        let relations = PackageRelations {
+            depends: "libc6 (>= 2.14), mail-transport-agent | exim4, foo (>= 0.9)".parse().unwrap(),
+            pre_depends: "dpkg (>= 1.15)".parse().unwrap(),
+            conflicts: "postfix (<< 3.0), bar".parse().unwrap(),
+            breaks: "libc6 (<< 2.32)".parse().unwrap(),
+            ..Default::default()
+        };
+        assert!(!relations.is_satisfied_by(&set));
+        assert_eq!(relations.unsatisfied(&set).map(ToString::to_string).collect::<Vec<_>>(), ["dpkg (>= 1.15)"]);
+        assert_eq!(relations.conflicting(&set).map(ToString::to_string).collect::<Vec<_>>(), ["libc6 (<< 2.32)"]);
+
+        set.insert("dpkg", "1.20.9".parse().unwrap(), &Default::default());
+        assert!(relations.is_satisfied_by(&set));
+        assert!(set.contains("dpkg"));
+        assert!(!set.contains("mail-transport-agent"));
+
+        let relations = PackageRelations {
+            depends: "mail-transport-agent (>= 1.0)".parse().unwrap(),
+            ..Default::default()
+        };
+        assert!(!relations.is_satisfied_by(&set));
+    }
+}
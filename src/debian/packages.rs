@@ -0,0 +1,195 @@
+//! Records of apt `Packages` index files.
+//!
+//! # Example
+//!
+//! ```
+//! use rfc822_like::debian::packages::Record;
+//!
+//! let input = "Package: hello\nVersion: 2.10-2\nInstalled-Size: 280\nMaintainer: Santiago Vila <sanvila@debian.org>\nArchitecture: amd64\nDepends: libc6 (>= 2.14)\nDescription: example package based on GNU hello\nSection: devel\nPriority: optional\nFilename: pool/main/h/hello/hello_2.10-2_amd64.deb\nSize: 56132\n";
+//! let records = rfc822_like::from_str::<Vec<Record>>(input).unwrap();
+//! assert_eq!(records[0].package, "hello");
+//! assert_eq!(records[0].size, 56132);
+//! assert_eq!(rfc822_like::to_string(&records).unwrap(), input);
+//! ```
+
+use serde::{Deserialize, Serialize};
+use super::{Contact, MultiArch, Priority, Provides, Relations, Section, Source, Version, YesNo};
+use super::checksums::{Md5, Sha1, Sha256, Sha512};
+use super::debtags::Tags;
+
+/// A single record (paragraph) of the `Packages` file.
+///
+/// Fields are serialized in the order used by the Debian archive.
+/// Fields not covered by this struct are ignored, use [`Preserving`](crate::document::Preserving)
+/// if they need to be kept.
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Record {
+    /// Name of the binary package.
+    pub package: String,
+    /// Source package if it differs from the binary package name or version.
+    pub source: Option<Source>,
+    /// Version of the package.
+    pub version: Version,
+    /// Estimated disk space required to install the package in kibibytes.
+    #[serde(rename = "Installed-Size", with = "super::number::option", default)]
+    pub installed_size: Option<u64>,
+    /// Maintainer of the package.
+    pub maintainer: Contact,
+    /// Maintainer of the package in the distribution the package was taken from.
+    #[serde(rename = "Original-Maintainer")]
+    pub original_maintainer: Option<Contact>,
+    /// Architecture the package was built for.
+    pub architecture: String,
+    /// Whether the package is essential.
+    pub essential: Option<YesNo>,
+    /// Whether the package is protected from removal.
+    pub protected: Option<YesNo>,
+    /// Whether the package is build-essential.
+    #[serde(rename = "Build-Essential")]
+    pub build_essential: Option<YesNo>,
+    /// How the package behaves when multiple architectures are installed.
+    #[serde(rename = "Multi-Arch")]
+    pub multi_arch: Option<MultiArch>,
+    /// Packages replaced by this package.
+    pub replaces: Option<Relations>,
+    /// Virtual packages provided by this package.
+    pub provides: Option<Provides>,
+    /// Packages required to be configured before this package is configured.
+    pub depends: Option<Relations>,
+    /// Packages required to be fully installed before this package is unpacked.
+    #[serde(rename = "Pre-Depends")]
+    pub pre_depends: Option<Relations>,
+    /// Packages installed together with this package in all usual installations.
+    pub recommends: Option<Relations>,
+    /// Packages that may be useful together with this package.
+    pub suggests: Option<Relations>,
+    /// Packages enhanced by this package.
+    pub enhances: Option<Relations>,
+    /// Packages that can't be installed together with this package.
+    pub conflicts: Option<Relations>,
+    /// Packages broken by this package.
+    pub breaks: Option<Relations>,
+    /// Source packages incorporated into this package during build.
+    #[serde(rename = "Built-Using")]
+    pub built_using: Option<Relations>,
+    /// Source packages statically linked into this package.
+    #[serde(rename = "Static-Built-Using")]
+    pub static_built_using: Option<Relations>,
+    /// Synopsis optionally followed by the long description.
+    ///
+    /// The field may be missing if the description is only available in translation files.
+    pub description: Option<String>,
+    /// URL of the upstream project.
+    pub homepage: Option<String>,
+    /// MD5 digest of the english description used for looking up translations.
+    #[serde(rename = "Description-md5")]
+    pub description_md5: Option<String>,
+    /// Debtags of the package.
+    pub tag: Option<Tags>,
+    /// Section of the package.
+    pub section: Option<Section>,
+    /// Priority of the package.
+    pub priority: Option<Priority>,
+    /// Path to the `.deb` file relative to the root of the archive.
+    pub filename: String,
+    /// Size of the `.deb` file in bytes.
+    #[serde(with = "super::number")]
+    pub size: u64,
+    /// MD5 digest of the `.deb` file.
+    #[serde(rename = "MD5sum")]
+    pub md5sum: Option<Md5>,
+    /// SHA1 digest of the `.deb` file.
+    #[serde(rename = "SHA1")]
+    pub sha1: Option<Sha1>,
+    /// SHA256 digest of the `.deb` file.
+    #[serde(rename = "SHA256")]
+    pub sha256: Option<Sha256>,
+    /// SHA512 digest of the `.deb` file.
+    #[serde(rename = "SHA512")]
+    pub sha512: Option<Sha512>,
+}
+
+#[cfg(feature = "rustcrypto")]
+impl Record {
+    /// Computes `Description-md5` from the description the same way apt does.
+    ///
+    /// Returns `None` if the description is missing.
+    /// See [`description`](super::description) for limitations.
+    pub fn compute_description_md5(&self) -> Option<Md5> {
+        self.description.as_ref().map(|description| super::description::from_unfolded(description))
+    }
+
+    /// Fills in `Description-md5` computed from the description if it's missing.
+    pub fn fill_description_md5(&mut self) {
+        if self.description_md5.is_none() {
+            self.description_md5 = self.compute_description_md5().map(|digest| digest.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Record;
+
+    #[cfg(feature = "rustcrypto")]
+    #[test]
+    fn description_md5() {
+        let input = "Package: libssl3\nVersion: 3.0.11-1~deb12u2+b1\nMaintainer: Debian OpenSSL Team <pkg-openssl-devel@alioth-lists.debian.net>\nArchitecture: amd64\nDescription: Secure Sockets Layer toolkit - shared libraries\n This package is part of the OpenSSL project's implementation of the SSL\n and TLS cryptographic protocols for secure communication over the\n Internet.\n .\n It provides the libssl and libcrypto shared libraries.\nFilename: pool/main/o/openssl/libssl3_3.0.11-1~deb12u2+b1_amd64.deb\nSize: 2026100\n";
+        let mut record = crate::from_str::<Record>(input).unwrap();
+        record.fill_description_md5();
+        assert_eq!(record.description_md5.as_deref(), Some("88547c6206c7fbc4fcc7d09ce100d210"));
+    }
+
+    #[test]
+    fn key_mapping() {
+        use serde::Deserialize;
+
+        let input = "Package: foo\nVersion: 1.0\nMaintainer: Foo <foo@example.org>\nArchitecture: all\nInstalled-Size: 42\nMD5sum: d41d8cd98f00b204e9800998ecf8427e\nFilename: pool/foo.deb\nSize: 42\n";
+        let deserializer = crate::de::Deserializer::new(input.as_bytes()).key_mapping(crate::key_mapping::KeyMapping::debian());
+        let record = Record::deserialize(deserializer).unwrap();
+        assert_eq!(record, crate::from_str::<Record>(input).unwrap());
+        assert_eq!(record.installed_size, Some(42));
+    }
+
+    #[test]
+    fn archive_record() {
+        let input = "\
+Package: libssl3
+Source: openssl (3.0.11-1~deb12u2)
+Version: 3.0.11-1~deb12u2+b1
+Installed-Size: 6313
+Maintainer: Debian OpenSSL Team <pkg-openssl-devel@alioth-lists.debian.net>
+Architecture: amd64
+Multi-Arch: same
+Depends: libc6 (>= 2.34)
+Suggests: libssl-doc
+Breaks: s390-tools (<< 2.3.0)
+Description: Secure Sockets Layer toolkit - shared libraries
+Homepage: https://www.openssl.org/
+Description-md5: 88547c6206c7fbc4fcc7d09ce100d210
+Tag: implemented-in::c, protocol::ssl, role::shared-lib, security::cryptography
+Section: libs
+Priority: optional
+Filename: pool/main/o/openssl/libssl3_3.0.11-1~deb12u2+b1_amd64.deb
+Size: 2027184
+MD5sum: e3a1b4bd8b0ebb5e2b45c2c2a77b1ee8
+SHA256: 5c2bfd6c2c6f0e9c1e4a5a2a3f3f0c1e1b0b8a9d3c0f1e2d3c4b5a6978877665
+";
+        let record = crate::from_str::<Record>(input).unwrap();
+        assert_eq!(record.source.as_ref().unwrap().name, "openssl");
+        assert_eq!(record.installed_size, Some(6313));
+        assert_eq!(record.size, 2027184);
+        assert_eq!(record.depends.as_ref().unwrap().0.len(), 1);
+        assert!(record.tag.as_ref().unwrap().contains("role", "shared-lib"));
+        assert!(record.pre_depends.is_none());
+        assert!(record.sha512.is_none());
+        assert_eq!(crate::to_string(&record).unwrap(), input);
+    }
+
+    #[test]
+    fn invalid_size() {
+        let input = "Package: foo\nVersion: 1.0\nMaintainer: Foo <foo@example.org>\nArchitecture: all\nFilename: foo.deb\nSize: big\n";
+        assert!(crate::from_str::<Record>(input).is_err());
+    }
+}
@@ -0,0 +1,312 @@
+//! Incremental index updates using ed-style diffs (pdiffs).
+//!
+//! Mirrors publish the differences between successive versions of an index in
+//! `Packages.diff/` (or similar) directory together with the `Index` file describing them.
+//! Clients that have an older version of the index can download only the patches and apply them
+//! instead of downloading the whole index.
+//!
+//! The patches use the `ed` script format as produced by `diff --ed`.
+//! Note that the format can't represent lines consisting of a single dot, which never appear in
+//! valid control files.
+//!
+//! This module requires the `rustcrypto` feature.
+//!
+//! # Example
+//!
+//! ```
+//! use rfc822_like::debian::pdiff::{self, Index};
+//!
+//! let old = "Package: foo\nVersion: 1.0\n";
+//! let new = "Package: foo\nVersion: 1.1\n";
+//! let mut index = Index::default();
+//! let patch = index.add_patch(old, new, "2023-10-07-0812.31");
+//! assert_eq!(patch, "2c\nVersion: 1.1\n.\n");
+//! assert_eq!(pdiff::apply(old, &patch).unwrap(), new);
+//!
+//! let updated = index.apply_chain(old, |_name| Ok(patch.clone())).unwrap();
+//! assert_eq!(updated, new);
+//! ```
+
+use std::fmt;
+use std::io;
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
+use crate::digest::digest_reader;
+use crate::line_diff::common_lines;
+use super::checksums::{self, FileEntry, FileList, Sha256};
+
+#[derive(Debug, thiserror::Error)]
+enum ErrorInner {
+    #[error("invalid ed command '{1}' on line {0}")]
+    InvalidCommand(usize, String),
+    #[error("ed command on line {0} refers to lines outside of the file")]
+    OutOfRange(usize),
+    #[error("text of ed command on line {0} is not terminated by '.'")]
+    Unterminated(usize),
+    #[error("the index doesn't know the version of the file")]
+    UnknownVersion,
+    #[error("failed to fetch patch {0}")]
+    Fetch(String, #[source] io::Error),
+    #[error("digest of patch {0} doesn't match")]
+    PatchMismatch(String),
+    #[error("digest of the patched file doesn't match")]
+    ResultMismatch,
+}
+
+/// Error returned when applying patches fails.
+#[derive(Debug, thiserror::Error)]
+#[error(transparent)]
+pub struct Error(#[from] ErrorInner);
+
+/// Digest and size of the current version of the index (`SHA256-Current`).
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Current {
+    /// SHA256 digest of the file.
+    pub digest: Sha256,
+    /// Size of the file in bytes.
+    pub size: u64,
+}
+
+impl Current {
+    fn of(data: &str) -> Self {
+        let (size, digest) = digest_reader(sha2::Sha256::default(), data.as_bytes()).expect("reading from slice never fails");
+        Current { digest, size, }
+    }
+
+    fn entry(self, path: &str) -> FileEntry<Sha256> {
+        FileEntry { digest: self.digest, size: self.size, path: path.to_owned(), }
+    }
+}
+
+impl FromStr for Current {
+    type Err = checksums::ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Reuse the parser of file lists which also validates the columns.
+        let entry = format!("{} -", s.trim()).parse::<FileEntry<Sha256>>()?;
+        Ok(Current { digest: entry.digest, size: entry.size, })
+    }
+}
+
+impl fmt::Display for Current {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.digest, self.size)
+    }
+}
+
+serde_via_str!(Current, "a SHA256 digest followed by size");
+
+/// The `Index` file describing available patches.
+///
+/// `history` lists the versions of the index the patches apply to and `patches` lists the
+/// (uncompressed) patches themselves, both ordered from the oldest.
+/// `download` lists the compressed patches.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Deserialize, Serialize)]
+pub struct Index {
+    /// The current version of the index.
+    #[serde(rename = "SHA256-Current")]
+    pub current: Option<Current>,
+    /// Versions of the index, named by the patch that applies to them.
+    #[serde(rename = "SHA256-History", default, skip_serializing_if = "is_empty")]
+    pub history: FileList<FileEntry<Sha256>>,
+    /// Uncompressed patches.
+    #[serde(rename = "SHA256-Patches", default, skip_serializing_if = "is_empty")]
+    pub patches: FileList<FileEntry<Sha256>>,
+    /// Compressed patches.
+    #[serde(rename = "SHA256-Download", default, skip_serializing_if = "is_empty")]
+    pub download: FileList<FileEntry<Sha256>>,
+}
+
+fn is_empty(list: &FileList<FileEntry<Sha256>>) -> bool {
+    list.0.is_empty()
+}
+
+impl Index {
+    /// Records the change from `old` to `new` version of the index and returns the patch.
+    ///
+    /// The caller is responsible for storing the patch under `name` and removing old patches.
+    pub fn add_patch(&mut self, old: &str, new: &str, name: &str) -> String {
+        let patch = diff(old, new);
+        self.history.0.push(Current::of(old).entry(name));
+        self.patches.0.push(Current::of(&patch).entry(name));
+        self.current = Some(Current::of(new));
+        patch
+    }
+
+    /// Updates the `old` version of the index to the current one.
+    ///
+    /// `fetch` is called with the name of each needed patch and returns its uncompressed content.
+    /// The digests of the patches and of the result are verified.
+    pub fn apply_chain<F: FnMut(&str) -> io::Result<String>>(&self, old: &str, mut fetch: F) -> Result<String, Error> {
+        let version = Current::of(old);
+        if self.current == Some(version) {
+            return Ok(old.to_owned());
+        }
+        let start = self.history.0.iter()
+            .position(|entry| entry.digest == version.digest && entry.size == version.size)
+            .ok_or(ErrorInner::UnknownVersion)?;
+
+        let mut data = old.to_owned();
+        for entry in &self.history.0[start..] {
+            let patch = fetch(&entry.path).map_err(|error| ErrorInner::Fetch(entry.path.clone(), error))?;
+            if let Some(expected) = self.patches.get(&entry.path) {
+                if Current::of(&patch) != (Current { digest: expected.digest, size: expected.size, }) {
+                    return Err(ErrorInner::PatchMismatch(entry.path.clone()).into());
+                }
+            }
+            data = apply(&data, &patch)?;
+        }
+        if self.current != Some(Current::of(&data)) {
+            return Err(ErrorInner::ResultMismatch.into());
+        }
+        Ok(data)
+    }
+}
+
+/// Computes the `ed` script changing `old` into `new`.
+///
+/// The commands are ordered from the end of the file so that they can be applied sequentially.
+pub fn diff(old: &str, new: &str) -> String {
+    let old_lines = old.split_terminator('\n').collect::<Vec<_>>();
+    let new_lines = new.split_terminator('\n').collect::<Vec<_>>();
+    let mut common = common_lines(&old_lines, &new_lines);
+    common.push((old_lines.len(), new_lines.len()));
+
+    let mut hunks = Vec::new();
+    let (mut x, mut y) = (0, 0);
+    for (common_x, common_y) in common {
+        if common_x > x || common_y > y {
+            hunks.push((x, common_x, y, common_y));
+        }
+        x = common_x + 1;
+        y = common_y + 1;
+    }
+
+    let mut script = String::new();
+    for (old_start, old_end, new_start, new_end) in hunks.into_iter().rev() {
+        let range = if old_end - old_start > 1 {
+            format!("{},{}", old_start + 1, old_end)
+        } else {
+            (old_start + 1).to_string()
+        };
+        if new_start == new_end {
+            script.push_str(&range);
+            script.push_str("d\n");
+            continue;
+        }
+        if old_start == old_end {
+            script.push_str(&format!("{}a\n", old_start));
+        } else {
+            script.push_str(&range);
+            script.push_str("c\n");
+        }
+        for line in &new_lines[new_start..new_end] {
+            script.push_str(line);
+            script.push('\n');
+        }
+        script.push_str(".\n");
+    }
+    script
+}
+
+/// Applies the `ed` script to `old`.
+///
+/// Supported commands are `a`, `c` and `d` with line number or range as produced by `diff --ed`.
+pub fn apply(old: &str, script: &str) -> Result<String, Error> {
+    let mut lines = old.split_terminator('\n').collect::<Vec<_>>();
+    let mut commands = script.split_terminator('\n').enumerate().map(|(i, line)| (i + 1, line));
+
+    while let Some((line_number, command)) = commands.next() {
+        let invalid = || ErrorInner::InvalidCommand(line_number, command.to_owned());
+        let (range, action) = match command.chars().last() {
+            Some(action @ 'a') | Some(action @ 'c') | Some(action @ 'd') => (&command[..(command.len() - 1)], action),
+            _ => return Err(invalid().into()),
+        };
+        let (start, end) = match range.find(',') {
+            Some(pos) => (range[..pos].parse::<usize>().map_err(|_| invalid())?, range[(pos + 1)..].parse::<usize>().map_err(|_| invalid())?),
+            None => {
+                let line = range.parse::<usize>().map_err(|_| invalid())?;
+                (line, line)
+            },
+        };
+        if end < start || end > lines.len() || (action != 'a' && start == 0) {
+            return Err(ErrorInner::OutOfRange(line_number).into());
+        }
+
+        let mut text = Vec::new();
+        if action != 'd' {
+            loop {
+                match commands.next() {
+                    Some((_, ".")) => break,
+                    Some((_, line)) => text.push(line),
+                    None => return Err(ErrorInner::Unterminated(line_number).into()),
+                }
+            }
+        }
+        match action {
+            'a' => { lines.splice(end..end, text); },
+            _ => { lines.splice((start - 1)..end, text); },
+        }
+    }
+
+    let mut result = lines.join("\n");
+    if !lines.is_empty() {
+        result.push('\n');
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apply, diff, Index};
+
+    #[test]
+    fn diff_apply() {
+        let cases = [
+            ("", ""),
+            ("a\nb\nc\n", "a\nb\nc\n"),
+            ("", "a\nb\n"),
+            ("a\nb\n", ""),
+            ("a\nb\nc\nd\n", "a\nx\nc\nd\ny\n"),
+            ("a\nb\nc\nd\ne\n", "b\nc\nx\ny\ne\n"),
+            ("Package: foo\n\nPackage: bar\nVersion: 1\n\nPackage: baz\n", "Package: bar\nVersion: 2\n\nPackage: baz\n\nPackage: qux\n"),
+        ];
+        for (old, new) in &cases {
+            let script = diff(old, new);
+            assert_eq!(apply(old, &script).unwrap(), *new, "script:\n{}", script);
+        }
+        assert_eq!(diff("a\nb\nc\nd\n", "a\nx\nc\n"), "4d\n2c\nx\n.\n");
+        assert_eq!(diff("a\n", "a\nb\nc\n"), "1a\nb\nc\n.\n");
+        assert_eq!(diff("a\nb\nc\n", "c\n"), "1,2d\n");
+    }
+
+    #[test]
+    fn apply_invalid() {
+        assert_eq!(apply("a\n", "1x\n").unwrap_err().to_string(), "invalid ed command '1x' on line 1");
+        assert_eq!(apply("a\n", "3d\n").unwrap_err().to_string(), "ed command on line 1 refers to lines outside of the file");
+        assert_eq!(apply("a\n", "1c\nb\n").unwrap_err().to_string(), "text of ed command on line 1 is not terminated by '.'");
+    }
+
+    #[test]
+    fn chain() {
+        let versions = ["Package: foo\nVersion: 1\n", "Package: foo\nVersion: 2\n", "Package: foo\nVersion: 2\n\nPackage: bar\n"];
+        let mut index = Index::default();
+        let mut patches = Vec::new();
+        for (i, pair) in versions.windows(2).enumerate() {
+            let name = format!("patch-{}", i);
+            patches.push((name.clone(), index.add_patch(pair[0], pair[1], &name)));
+        }
+        let fetch = |name: &str| Ok(patches.iter().find(|(patch, _)| patch == name).unwrap().1.clone());
+        assert_eq!(index.apply_chain(versions[0], fetch).unwrap(), versions[2]);
+        assert_eq!(index.apply_chain(versions[1], fetch).unwrap(), versions[2]);
+        assert_eq!(index.apply_chain(versions[2], fetch).unwrap(), versions[2]);
+        assert_eq!(index.apply_chain("Package: baz\n", fetch).unwrap_err().to_string(), "the index doesn't know the version of the file");
+        assert_eq!(index.apply_chain(versions[0], |_| Ok("1d\n".to_owned())).unwrap_err().to_string(), "digest of patch patch-0 doesn't match");
+
+        let serialized = crate::to_string(&index).unwrap();
+        assert!(serialized.starts_with("SHA256-Current: "));
+        assert!(serialized.contains("\nSHA256-History:\n "));
+        assert!(!serialized.contains("SHA256-Download"));
+        assert_eq!(crate::from_str::<Index>(&serialized).unwrap(), index);
+    }
+}
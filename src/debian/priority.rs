@@ -0,0 +1,95 @@
+//! The `Priority` field.
+
+use std::convert::Infallible;
+use std::fmt;
+use std::str::FromStr;
+
+/// Value of the `Priority` field.
+///
+/// Unknown values are kept in [`Priority::Other`] so parsing never fails.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum Priority {
+    /// `required`
+    Required,
+    /// `important`
+    Important,
+    /// `standard`
+    Standard,
+    /// `optional`
+    Optional,
+    /// `extra` - deprecated since Debian policy 4.0.1, equivalent to `optional`.
+    Extra,
+    /// Any other value.
+    Other(String),
+}
+
+impl Priority {
+    /// Returns the value as written in control files.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Priority::Required => "required",
+            Priority::Important => "important",
+            Priority::Standard => "standard",
+            Priority::Optional => "optional",
+            Priority::Extra => "extra",
+            Priority::Other(other) => other,
+        }
+    }
+
+    /// Returns `true` if the priority is deprecated or unknown.
+    pub fn is_deprecated(&self) -> bool {
+        match self {
+            Priority::Extra | Priority::Other(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Replaces deprecated `extra` with `optional` which has the same meaning.
+    pub fn normalize(self) -> Self {
+        match self {
+            Priority::Extra => Priority::Optional,
+            other => other,
+        }
+    }
+}
+
+impl FromStr for Priority {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let priority = match s {
+            "required" => Priority::Required,
+            "important" => Priority::Important,
+            "standard" => Priority::Standard,
+            "optional" => Priority::Optional,
+            "extra" => Priority::Extra,
+            other => Priority::Other(other.to_owned()),
+        };
+        Ok(priority)
+    }
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+serde_via_str!(Priority, "a package priority");
+
+#[cfg(test)]
+mod tests {
+    use super::Priority;
+
+    #[test]
+    fn parse() {
+        assert_eq!("standard".parse::<Priority>().unwrap(), Priority::Standard);
+        let extra = "extra".parse::<Priority>().unwrap();
+        assert!(extra.is_deprecated());
+        assert_eq!(extra.normalize(), Priority::Optional);
+        let other = "source".parse::<Priority>().unwrap();
+        assert_eq!(other, Priority::Other("source".to_owned()));
+        assert_eq!(other.to_string(), "source");
+        assert_eq!(crate::from_str::<std::collections::HashMap<String, Priority>>("Priority: important\n").unwrap()["Priority"], Priority::Important);
+    }
+}
@@ -0,0 +1,114 @@
+//! Build profile restriction formulas.
+//!
+//! The formula is a list of restriction lists in angle brackets, e.g. `<!nocheck> <cross>`, as
+//! described in the [build profiles specification](https://wiki.debian.org/BuildProfileSpec).
+//! It's used in relationship fields and in the `Build-Profiles` field of binary packages.
+//!
+//! # Example
+//!
+//! ```
+//! use rfc822_like::debian::profiles::RestrictionFormula;
+//!
+//! let formula = "<!nocheck !cross> <stage1>".parse::<RestrictionFormula>().unwrap();
+//! assert!(formula.matches(&[] as &[&str]));
+//! assert!(formula.matches(&["cross", "stage1"]));
+//! assert!(!formula.matches(&["nocheck"]));
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+use super::relations::{Parser, ParseError, write_separated};
+
+/// Restriction formula - satisfied if any of the restriction lists is satisfied.
+///
+/// Empty formula (no restrictions) is always satisfied.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct RestrictionFormula(pub Vec<RestrictionList>);
+
+/// Build profile restriction list such as `<!nocheck cross>`.
+///
+/// The list is satisfied if all the terms are satisfied.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct RestrictionList(pub Vec<ProfileTerm>);
+
+/// A single term of a restriction list, e.g. `!nocheck`.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct ProfileTerm {
+    /// `true` if the profile must not be active.
+    pub negated: bool,
+    /// Name of the build profile.
+    pub name: String,
+}
+
+impl RestrictionFormula {
+    /// Returns `true` if the formula is satisfied when the `active` profiles are enabled.
+    pub fn matches<S: AsRef<str>>(&self, active: &[S]) -> bool {
+        self.0.is_empty() || self.0.iter().any(|list| list.matches(active))
+    }
+}
+
+impl RestrictionList {
+    /// Returns `true` if all terms are satisfied when the `active` profiles are enabled.
+    pub fn matches<S: AsRef<str>>(&self, active: &[S]) -> bool {
+        self.0.iter().all(|term| term.matches(active))
+    }
+}
+
+impl ProfileTerm {
+    /// Returns `true` if the term is satisfied when the `active` profiles are enabled.
+    pub fn matches<S: AsRef<str>>(&self, active: &[S]) -> bool {
+        active.iter().any(|profile| profile.as_ref() == self.name) != self.negated
+    }
+}
+
+impl FromStr for RestrictionFormula {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser::new(s);
+        let formula = parser.restriction_formula()?;
+        parser.finish(formula)
+    }
+}
+
+impl fmt::Display for RestrictionFormula {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_separated(f, &self.0, " ")
+    }
+}
+
+impl fmt::Display for RestrictionList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("<")?;
+        write_separated(f, &self.0, " ")?;
+        f.write_str(">")
+    }
+}
+
+impl fmt::Display for ProfileTerm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.negated {
+            f.write_str("!")?;
+        }
+        f.write_str(&self.name)
+    }
+}
+
+serde_via_str!(RestrictionFormula, "a build profile restriction formula");
+
+#[cfg(test)]
+mod tests {
+    use super::RestrictionFormula;
+
+    #[test]
+    fn parse() {
+        let formula = "<!nocheck>\n<cross stage1>".parse::<RestrictionFormula>().unwrap();
+        assert_eq!(formula.0.len(), 2);
+        assert_eq!(formula.to_string(), "<!nocheck> <cross stage1>");
+        assert!(formula.matches(&["cross", "stage1", "nocheck"]));
+        assert!(!formula.matches(&["cross", "nocheck"]));
+        assert!("".parse::<RestrictionFormula>().unwrap().matches(&["nocheck"]));
+        assert!("<nocheck".parse::<RestrictionFormula>().is_err());
+        assert!("nocheck".parse::<RestrictionFormula>().is_err());
+    }
+}
@@ -0,0 +1,158 @@
+//! The `Provides` field including versioned provides.
+//!
+//! Unlike other relationship fields `Provides` doesn't allow alternatives and the only allowed
+//! version relation is `=`.
+//! Architecture qualifiers and restrictions are rejected.
+//! A versioned dependency on a virtual package can only be satisfied by a versioned provide.
+//!
+//! # Example
+//!
+//! ```
+//! use rfc822_like::debian::{Provides, relations::Dependency};
+//!
+//! let provides = "foo (= 1.2), bar".parse::<Provides>().unwrap();
+//! assert!(provides.satisfies(&"foo (>= 1.0)".parse::<Dependency>().unwrap()));
+//! assert!(provides.satisfies(&"bar".parse::<Dependency>().unwrap()));
+//! assert!(!provides.satisfies(&"bar (>= 1.0)".parse::<Dependency>().unwrap()));
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+use super::relations::{self, ConstraintVersion, Dependency, Relations, VersionConstraint, VersionRelation};
+
+#[derive(Debug, Clone, thiserror::Error)]
+enum ErrorInner {
+    #[error("invalid syntax")]
+    Syntax(#[source] relations::ParseError),
+    #[error("alternatives are not allowed in Provides ({0})")]
+    Alternatives(String),
+    #[error("only exact version is allowed in Provides ({0})")]
+    NonExactVersion(String),
+    #[error("restrictions are not allowed in Provides ({0})")]
+    Restrictions(String),
+    #[error("architecture qualifiers are not allowed in Provides ({0})")]
+    ArchQualifier(String),
+}
+
+/// Error returned when parsing `Provides` fails.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error(transparent)]
+pub struct ParseError(#[from] ErrorInner);
+
+/// Comma-separated list of provided (virtual) packages.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct Provides(pub Vec<Provided>);
+
+/// A single provided package, e.g. `foo (= 1.2)`.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Provided {
+    /// Name of the provided package.
+    pub name: String,
+    /// Exact version of the provided package, if any.
+    ///
+    /// The version may contain substitution variables, e.g. `${binary:Version}`.
+    pub version: Option<ConstraintVersion>,
+}
+
+impl Provided {
+    /// Returns `true` if the provided package satisfies the dependency.
+    ///
+    /// Architecture qualifiers and restrictions of the dependency are ignored.
+    pub fn satisfies(&self, dependency: &Dependency) -> bool {
+        if self.name != dependency.name {
+            return false;
+        }
+        match (&dependency.version, &self.version) {
+            (None, _) => true,
+            (Some(constraint), Some(version)) => version.as_version().map_or(false, |version| constraint.matches(version)),
+            (Some(_), None) => false,
+        }
+    }
+}
+
+impl Provides {
+    /// Returns `true` if any of the provided packages satisfies the dependency.
+    pub fn satisfies(&self, dependency: &Dependency) -> bool {
+        self.0.iter().any(|provided| provided.satisfies(dependency))
+    }
+
+    /// Returns the provided package with given name.
+    pub fn get(&self, name: &str) -> Option<&Provided> {
+        self.0.iter().find(|provided| provided.name == name)
+    }
+}
+
+impl FromStr for Provides {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let relations = s.parse::<Relations>().map_err(ErrorInner::Syntax)?;
+        relations.0
+            .into_iter()
+            .map(|mut relation| {
+                if relation.0.len() > 1 {
+                    return Err(ErrorInner::Alternatives(relation.to_string()).into());
+                }
+                let dependency = relation.0.remove(0);
+                if !dependency.architectures.is_empty() || !dependency.profiles.0.is_empty() {
+                    return Err(ErrorInner::Restrictions(dependency.to_string()).into());
+                }
+                if dependency.arch_qualifier.is_some() {
+                    return Err(ErrorInner::ArchQualifier(dependency.to_string()).into());
+                }
+                let version = match dependency.version {
+                    Some(VersionConstraint { relation: VersionRelation::Exact, version, }) => Some(version),
+                    Some(_) => return Err(ErrorInner::NonExactVersion(dependency.to_string()).into()),
+                    None => None,
+                };
+                Ok(Provided { name: dependency.name, version, })
+            })
+            .collect::<Result<_, _>>()
+            .map(Provides)
+    }
+}
+
+impl fmt::Display for Provided {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)?;
+        if let Some(version) = &self.version {
+            write!(f, " (= {})", version)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Provides {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        relations::write_separated(f, &self.0, ", ")
+    }
+}
+
+serde_via_str!(Provides, "a list of provided packages");
+
+#[cfg(test)]
+mod tests {
+    use super::Provides;
+    use crate::debian::relations::Dependency;
+
+    #[test]
+    fn provides() {
+        let provides = "foo (= 1:1.2-1), ${misc:Provides}, foo-abi (= ${binary:Version})".parse::<Provides>().unwrap();
+        assert_eq!(provides.0.len(), 3);
+        assert_eq!(provides.to_string(), "foo (= 1:1.2-1), ${misc:Provides}, foo-abi (= ${binary:Version})");
+        let satisfies = |dependency: &str| provides.satisfies(&dependency.parse::<Dependency>().unwrap());
+        assert!(satisfies("foo"));
+        assert!(satisfies("foo (>= 1:1.0)"));
+        assert!(satisfies("foo (= 1:1.2-1)"));
+        assert!(!satisfies("foo (<< 1:1.0)"));
+        assert!(!satisfies("bar"));
+        assert!(satisfies("foo-abi"));
+        assert!(!satisfies("foo-abi (>= 1.0)"));
+        assert!(provides.get("foo").unwrap().version.is_some());
+
+        assert_eq!("foo | bar".parse::<Provides>().unwrap_err().to_string(), "alternatives are not allowed in Provides (foo | bar)");
+        assert_eq!("foo (>= 1.0)".parse::<Provides>().unwrap_err().to_string(), "only exact version is allowed in Provides (foo (>= 1.0))");
+        assert_eq!("foo [amd64]".parse::<Provides>().unwrap_err().to_string(), "restrictions are not allowed in Provides (foo [amd64])");
+        assert_eq!("foo:any (= 1.0)".parse::<Provides>().unwrap_err().to_string(), "architecture qualifiers are not allowed in Provides (foo:any (= 1.0))");
+    }
+}
@@ -0,0 +1,579 @@
+//! Package relationship fields.
+//!
+//! These are the fields like `Depends`, `Recommends`, `Breaks` or `Build-Depends` described in
+//! [Debian policy](https://www.debian.org/doc/debian-policy/ch-relationships.html).
+//! The value is a comma-separated list of [`Relation`]s, each being a `|`-separated list of
+//! alternative [`Dependency`]s.
+//!
+//! Substitution variables (e.g. `${misc:Depends}`) are accepted as package names and versions
+//! (e.g. `(= ${binary:Version})`) so that `debian/control` can be processed as well.
+//!
+//! # Example
+//!
+//! ```
+//! use rfc822_like::debian::relations::{Relations, VersionRelation};
+//!
+//! let relations = "libc6 (>= 2.31), foo | bar:any [amd64 !armel] <!nocheck>".parse::<Relations>().unwrap();
+//! assert_eq!(relations.0.len(), 2);
+//! let constraint = relations.0[0].0[0].version.as_ref().unwrap();
+//! assert_eq!(constraint.relation, VersionRelation::LaterEqual);
+//! assert_eq!(relations.0[1].0[1].arch_qualifier.as_deref(), Some("any"));
+//! assert_eq!(relations.to_string(), "libc6 (>= 2.31), foo | bar:any [amd64 !armel] <!nocheck>");
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+use super::Version;
+use super::profiles::{RestrictionFormula, RestrictionList, ProfileTerm};
+
+#[derive(Debug, Clone, thiserror::Error)]
+enum ErrorInner {
+    #[error("missing package name at position {0}")]
+    MissingName(usize),
+    #[error("unexpected character '{1}' at position {0}")]
+    UnexpectedChar(usize, char),
+    #[error("unexpected end of input, expected '{0}'")]
+    UnexpectedEnd(char),
+    #[error("invalid version relation '{0}'")]
+    InvalidRelation(String),
+    #[error("empty version at position {0}")]
+    EmptyVersion(usize),
+    #[error("invalid version")]
+    InvalidVersion(#[source] super::version::ParseError),
+    #[error("empty list at position {0}")]
+    EmptyList(usize),
+}
+
+/// Error returned when parsing relations fails.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error(transparent)]
+pub struct ParseError(#[from] ErrorInner);
+
+/// Comma-separated list of relations - the whole value of a relationship field.
+///
+/// All relations have to be satisfied.
+/// Empty items (e.g. caused by a trailing comma) are skipped when parsing.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct Relations(pub Vec<Relation>);
+
+/// A group of alternative dependencies separated by `|`.
+///
+/// The relation is satisfied if any of the alternatives is satisfied.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Relation(pub Vec<Dependency>);
+
+/// A single package possibly restricted by version, architecture and build profiles.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Dependency {
+    /// Name of the package.
+    pub name: String,
+    /// Architecture qualifier following the colon (e.g. `any` in `python3:any`).
+    pub arch_qualifier: Option<String>,
+    /// Version constraint in parentheses.
+    pub version: Option<VersionConstraint>,
+    /// Architecture restriction list in square brackets.
+    ///
+    /// Empty if the dependency is not restricted.
+    pub architectures: Vec<ArchRestriction>,
+    /// Build profile restriction formula - lists in angle brackets.
+    ///
+    /// Empty if the dependency is not restricted.
+    pub profiles: RestrictionFormula,
+}
+
+impl Dependency {
+    /// Returns `true` if the dependency applies when building with the `active` profiles.
+    pub fn applies_to_profiles<S: AsRef<str>>(&self, active: &[S]) -> bool {
+        self.profiles.matches(active)
+    }
+}
+
+/// Version constraint such as `(>= 1.0)`.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct VersionConstraint {
+    /// The comparison operator.
+    pub relation: VersionRelation,
+    /// The version to compare with.
+    pub version: ConstraintVersion,
+}
+
+impl VersionConstraint {
+    /// Returns `true` if the `version` satisfies the constraint.
+    ///
+    /// Constraints containing substitution variables never match because their version is not
+    /// known.
+    pub fn matches(&self, version: &Version) -> bool {
+        let constraint = match &self.version {
+            ConstraintVersion::Version(constraint) => constraint,
+            ConstraintVersion::Substvar(_) => return false,
+        };
+        match self.relation {
+            VersionRelation::Earlier => version < constraint,
+            VersionRelation::EarlierEqual => version <= constraint,
+            VersionRelation::Exact => version == constraint,
+            VersionRelation::LaterEqual => version >= constraint,
+            VersionRelation::Later => version > constraint,
+        }
+    }
+}
+
+/// Version in a [`VersionConstraint`].
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum ConstraintVersion {
+    /// A valid Debian version.
+    Version(Version),
+    /// Version containing substitution variables, e.g. `${binary:Version}`, kept verbatim.
+    ///
+    /// These are used in `debian/control` and replaced when building the package.
+    Substvar(String),
+}
+
+impl ConstraintVersion {
+    /// Returns the version unless it contains substitution variables.
+    pub fn as_version(&self) -> Option<&Version> {
+        match self {
+            ConstraintVersion::Version(version) => Some(version),
+            ConstraintVersion::Substvar(_) => None,
+        }
+    }
+}
+
+impl From<Version> for ConstraintVersion {
+    fn from(version: Version) -> Self {
+        ConstraintVersion::Version(version)
+    }
+}
+
+impl fmt::Display for ConstraintVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConstraintVersion::Version(version) => fmt::Display::fmt(version, f),
+            ConstraintVersion::Substvar(version) => f.write_str(version),
+        }
+    }
+}
+
+/// Comparison operator of a version constraint.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum VersionRelation {
+    /// `<<`
+    Earlier,
+    /// `<=` (also parsed from obsolete `<`)
+    EarlierEqual,
+    /// `=`
+    Exact,
+    /// `>=` (also parsed from obsolete `>`)
+    LaterEqual,
+    /// `>>`
+    Later,
+}
+
+/// Architecture in an architecture restriction list, e.g. `!armel`.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct ArchRestriction {
+    /// `true` if the architecture is excluded.
+    pub negated: bool,
+    /// Name of the architecture or architecture wildcard.
+    pub arch: String,
+}
+
+impl VersionRelation {
+    /// Returns the operator as written in control files.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VersionRelation::Earlier => "<<",
+            VersionRelation::EarlierEqual => "<=",
+            VersionRelation::Exact => "=",
+            VersionRelation::LaterEqual => ">=",
+            VersionRelation::Later => ">>",
+        }
+    }
+}
+
+impl FromStr for VersionRelation {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "<<" => Ok(VersionRelation::Earlier),
+            "<=" | "<" => Ok(VersionRelation::EarlierEqual),
+            "=" => Ok(VersionRelation::Exact),
+            ">=" | ">" => Ok(VersionRelation::LaterEqual),
+            ">>" => Ok(VersionRelation::Later),
+            _ => Err(ErrorInner::InvalidRelation(s.to_owned()).into()),
+        }
+    }
+}
+
+impl fmt::Display for VersionRelation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Simple cursor over the input.
+pub(super) struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+fn is_special(c: char) -> bool {
+    c.is_whitespace() || "(),|[]<>!".contains(c)
+}
+
+impl<'a> Parser<'a> {
+    pub(super) fn new(input: &'a str) -> Self {
+        Parser { input, pos: 0, }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.rest().chars().next()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), ParseError> {
+        if self.eat(c) {
+            return Ok(());
+        }
+        match self.peek() {
+            Some(found) => Err(ErrorInner::UnexpectedChar(self.pos, found).into()),
+            None => Err(ErrorInner::UnexpectedEnd(c).into()),
+        }
+    }
+
+    /// Reads a token until a special character or whitespace.
+    fn token(&mut self) -> &'a str {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let len = rest.find(is_special).unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    fn name(&mut self) -> Result<&'a str, ParseError> {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let len = if rest.starts_with("${") {
+            match rest.find('}') {
+                Some(end) => end + 1,
+                None => return Err(ErrorInner::UnexpectedEnd('}').into()),
+            }
+        } else {
+            rest.find(|c| is_special(c) || c == ':').unwrap_or(rest.len())
+        };
+        if len == 0 {
+            return Err(ErrorInner::MissingName(self.pos).into());
+        }
+        self.pos += len;
+        Ok(&rest[..len])
+    }
+
+    fn negatable(&mut self) -> Result<(bool, String), ParseError> {
+        let negated = self.eat('!');
+        let pos = self.pos;
+        let name = self.token();
+        if name.is_empty() {
+            return Err(ErrorInner::MissingName(pos).into());
+        }
+        Ok((negated, name.to_owned()))
+    }
+
+    fn version_constraint(&mut self) -> Result<VersionConstraint, ParseError> {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let len = rest.find(|c| !"<=>".contains(c)).unwrap_or(rest.len());
+        let relation = rest[..len].parse()?;
+        self.pos += len;
+        let pos = self.pos;
+        self.skip_whitespace();
+        let rest = self.rest();
+        let len = rest.find(|c: char| c == ')' || c.is_whitespace()).unwrap_or(rest.len());
+        if len == 0 {
+            return Err(ErrorInner::EmptyVersion(pos).into());
+        }
+        let version = &rest[..len];
+        let version = if version.contains("${") {
+            ConstraintVersion::Substvar(version.to_owned())
+        } else {
+            ConstraintVersion::Version(version.parse().map_err(ErrorInner::InvalidVersion)?)
+        };
+        self.pos += len;
+        self.expect(')')?;
+        Ok(VersionConstraint { relation, version, })
+    }
+
+    fn list<T, F: FnMut(&mut Self) -> Result<T, ParseError>>(&mut self, end: char, mut item: F) -> Result<Vec<T>, ParseError> {
+        let pos = self.pos;
+        let mut items = Vec::new();
+        while !self.eat(end) {
+            if self.peek().is_none() {
+                return Err(ErrorInner::UnexpectedEnd(end).into());
+            }
+            items.push(item(self)?);
+        }
+        if items.is_empty() {
+            return Err(ErrorInner::EmptyList(pos).into());
+        }
+        Ok(items)
+    }
+
+    fn dependency(&mut self) -> Result<Dependency, ParseError> {
+        let name = self.name()?.to_owned();
+        let arch_qualifier = if self.rest().starts_with(':') {
+            self.pos += 1;
+            Some(self.name()?.to_owned())
+        } else {
+            None
+        };
+        let version = if self.eat('(') {
+            Some(self.version_constraint()?)
+        } else {
+            None
+        };
+        let architectures = if self.eat('[') {
+            self.list(']', |parser| parser.negatable().map(|(negated, arch)| ArchRestriction { negated, arch, }))?
+        } else {
+            Vec::new()
+        };
+        let profiles = self.restriction_formula()?;
+
+        Ok(Dependency {
+            name,
+            arch_qualifier,
+            version,
+            architectures,
+            profiles,
+        })
+    }
+
+    pub(super) fn restriction_formula(&mut self) -> Result<RestrictionFormula, ParseError> {
+        let mut lists = Vec::new();
+        while self.eat('<') {
+            let terms = self.list('>', |parser| parser.negatable().map(|(negated, name)| ProfileTerm { negated, name, }))?;
+            lists.push(RestrictionList(terms));
+        }
+        Ok(RestrictionFormula(lists))
+    }
+
+    fn relation(&mut self) -> Result<Relation, ParseError> {
+        let mut alternatives = vec![self.dependency()?];
+        while self.eat('|') {
+            alternatives.push(self.dependency()?);
+        }
+        Ok(Relation(alternatives))
+    }
+
+    fn relations(&mut self) -> Result<Relations, ParseError> {
+        let mut relations = Vec::new();
+        loop {
+            match self.peek() {
+                None => break,
+                Some(',') => (),
+                Some(_) => relations.push(self.relation()?),
+            }
+            match self.peek() {
+                None => break,
+                Some(',') => self.pos += 1,
+                Some(c) => return Err(ErrorInner::UnexpectedChar(self.pos, c).into()),
+            }
+        }
+        Ok(Relations(relations))
+    }
+
+    pub(super) fn finish<T>(mut self, value: T) -> Result<T, ParseError> {
+        match self.peek() {
+            None => Ok(value),
+            Some(c) => Err(ErrorInner::UnexpectedChar(self.pos, c).into()),
+        }
+    }
+}
+
+impl FromStr for Relations {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser::new(s);
+        let relations = parser.relations()?;
+        parser.finish(relations)
+    }
+}
+
+impl FromStr for Relation {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser::new(s);
+        let relation = parser.relation()?;
+        parser.finish(relation)
+    }
+}
+
+impl FromStr for Dependency {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser::new(s);
+        let dependency = parser.dependency()?;
+        parser.finish(dependency)
+    }
+}
+
+pub(super) fn write_separated<T: fmt::Display>(f: &mut fmt::Formatter<'_>, items: &[T], separator: &str) -> fmt::Result {
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            f.write_str(separator)?;
+        }
+        write!(f, "{}", item)?;
+    }
+    Ok(())
+}
+
+/// The alternate form (`{:#}`) writes each relation on its own line, starting with an empty line,
+/// as in `Installed-Build-Depends`.
+impl fmt::Display for Relations {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            for (i, relation) in self.0.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(",")?;
+                }
+                write!(f, "\n{}", relation)?;
+            }
+            Ok(())
+        } else {
+            write_separated(f, &self.0, ", ")
+        }
+    }
+}
+
+impl fmt::Display for Relation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_separated(f, &self.0, " | ")
+    }
+}
+
+impl fmt::Display for Dependency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)?;
+        if let Some(qualifier) = &self.arch_qualifier {
+            write!(f, ":{}", qualifier)?;
+        }
+        if let Some(version) = &self.version {
+            write!(f, " {}", version)?;
+        }
+        if !self.architectures.is_empty() {
+            f.write_str(" [")?;
+            write_separated(f, &self.architectures, " ")?;
+            f.write_str("]")?;
+        }
+        if !self.profiles.0.is_empty() {
+            write!(f, " {}", self.profiles)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for VersionConstraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({} {})", self.relation, self.version)
+    }
+}
+
+impl fmt::Display for ArchRestriction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.negated {
+            f.write_str("!")?;
+        }
+        f.write_str(&self.arch)
+    }
+}
+
+serde_via_str!(Relations, "a list of package relations");
+serde_via_str!(Relation, "a package relation");
+serde_via_str!(Dependency, "a package dependency");
+
+#[cfg(test)]
+mod tests {
+    use super::{ConstraintVersion, Relations, VersionRelation};
+
+    #[test]
+    fn parse() {
+        let relations = "${misc:Depends}, libfoo-dev:native (>= 1:2.0~rc1-1) | libbar (<<3),\n  baz [!i386 linux-any] <!nocheck cross> <stage1>,".parse::<Relations>().unwrap();
+        assert_eq!(relations.0.len(), 3);
+        assert_eq!(relations.0[0].0[0].name, "${misc:Depends}");
+        let libfoo = &relations.0[1].0[0];
+        assert_eq!(libfoo.arch_qualifier.as_deref(), Some("native"));
+        let constraint = libfoo.version.as_ref().unwrap();
+        assert_eq!(constraint.version.as_version().unwrap().epoch(), 1);
+        assert!(constraint.matches(&"1:2.0-1".parse().unwrap()));
+        assert!(!constraint.matches(&"2.1".parse().unwrap()));
+        assert_eq!(relations.0[1].0[1].version.as_ref().unwrap().relation, VersionRelation::Earlier);
+        let baz = &relations.0[2].0[0];
+        assert!(baz.architectures[0].negated);
+        assert_eq!(baz.architectures[1].arch, "linux-any");
+        assert_eq!(baz.profiles.0.len(), 2);
+        assert!(!baz.profiles.0[0].0[1].negated);
+        assert!(baz.applies_to_profiles(&["stage1"]));
+        assert!(!baz.applies_to_profiles(&["nocheck"]));
+        assert!(relations.0[0].0[0].applies_to_profiles(&[] as &[&str]));
+        assert_eq!(relations.to_string(), "${misc:Depends}, libfoo-dev:native (>= 1:2.0~rc1-1) | libbar (<< 3), baz [!i386 linux-any] <!nocheck cross> <stage1>");
+        assert_eq!("".parse::<Relations>().unwrap(), Relations::default());
+    }
+
+    #[test]
+    fn substvar_versions() {
+        let input = "foo (= ${binary:Version}), foo-data (>= ${source:Version}), bar (<< ${source:Version}.1~)";
+        let relations = input.parse::<Relations>().unwrap();
+        let constraint = relations.0[0].0[0].version.as_ref().unwrap();
+        assert_eq!(constraint.version, ConstraintVersion::Substvar("${binary:Version}".to_owned()));
+        assert!(constraint.version.as_version().is_none());
+        assert!(!constraint.matches(&"1.0".parse().unwrap()));
+        assert_eq!(relations.0[1].0[0].version.as_ref().unwrap().version.to_string(), "${source:Version}");
+        assert_eq!(relations.to_string(), input);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!("foo (>= )".parse::<Relations>().unwrap_err().to_string(), "empty version at position 7");
+        assert_eq!("foo (~ 1)".parse::<Relations>().unwrap_err().to_string(), "invalid version relation ''");
+        assert_eq!("foo (= 1_0)".parse::<Relations>().unwrap_err().to_string(), "invalid version");
+        assert_eq!("foo | , bar".parse::<Relations>().unwrap_err().to_string(), "missing package name at position 6");
+        assert_eq!("foo bar".parse::<Relations>().unwrap_err().to_string(), "unexpected character 'b' at position 4");
+        assert_eq!("foo [amd64".parse::<Relations>().unwrap_err().to_string(), "unexpected end of input, expected ']'");
+        assert_eq!("foo <>".parse::<Relations>().unwrap_err().to_string(), "empty list at position 5");
+    }
+
+    #[test]
+    fn serde() {
+        #[derive(serde_derive::Deserialize, serde_derive::Serialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct Package {
+            package: String,
+            depends: Relations,
+        }
+
+        let input = "Package: foo\nDepends: bar (>= 1.0),\n baz | qux\n";
+        let package = crate::from_str::<Package>(input).unwrap();
+        assert_eq!(package.depends.0.len(), 2);
+        assert_eq!(crate::to_string(&package).unwrap(), "Package: foo\nDepends: bar (>= 1.0), baz | qux\n");
+        assert!(crate::from_str::<Package>("Package: foo\nDepends: bar (\n").is_err());
+    }
+}
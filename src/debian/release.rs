@@ -0,0 +1,433 @@
+//! `Release` and `InRelease` files describing a repository.
+//!
+//! Note that the signature of `InRelease` files has to be stripped before parsing, e.g. using
+//! the [`clearsigned`](crate::clearsigned) module.
+//! `Release` files signed by a detached `Release.gpg` can be loaded using
+//! [`from_release_with_detached_sig`] which lets you verify the signature before parsing.
+//!
+//! This module requires the `chrono` feature.
+//!
+//! # Example
+//!
+//! ```
+//! use rfc822_like::debian::release::Release;
+//!
+//! let input = "Suite: stable\nCodename: bookworm\nDate: Sat, 07 Oct 2023 09:42:15 UTC\nSHA256:\n 3957f28db16e3f28c7b34ae84f1c929c567de6970f3f1b95dac9b498dd80fe63 738242 main/binary-all/Packages\n";
+//! let release = rfc822_like::from_str::<Release>(input).unwrap();
+//! assert_eq!(release.codename.as_deref(), Some("bookworm"));
+//! assert_eq!(release.file("main/binary-all/Packages").unwrap().size, 738242);
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+#[cfg(feature = "fs")]
+use std::path::{Path, PathBuf};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use super::YesNo;
+use super::checksums::{Checksum, FileEntry, FileList, Md5, Sha1, Sha256, Sha512};
+use super::date::Date;
+
+/// Contents of the `Release` file.
+///
+/// Fields are serialized in the order used by the Debian archive.
+/// The size column of hash lists is right-aligned the same way `apt-ftparchive` does it.
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Release {
+    /// Origin of the repository, e.g. `Debian`.
+    pub origin: Option<String>,
+    /// Label of the repository, e.g. `Debian`.
+    pub label: Option<String>,
+    /// Suite of the distribution, e.g. `stable`.
+    pub suite: Option<String>,
+    /// Version of the distribution, e.g. `12.2`.
+    pub version: Option<String>,
+    /// Codename of the distribution, e.g. `bookworm`.
+    pub codename: Option<String>,
+    /// URL template of package changelogs.
+    pub changelogs: Option<String>,
+    /// Time the file was created.
+    pub date: Option<Date>,
+    /// Time after which the file should be considered expired.
+    #[serde(rename = "Valid-Until")]
+    pub valid_until: Option<Date>,
+    /// Whether packages from this repository should not be installed automatically.
+    #[serde(rename = "NotAutomatic")]
+    pub not_automatic: Option<YesNo>,
+    /// Whether upgrades from this repository should be installed automatically despite
+    /// `NotAutomatic`.
+    #[serde(rename = "ButAutomaticUpgrades")]
+    pub but_automatic_upgrades: Option<YesNo>,
+    /// Whether index files can be downloaded from `by-hash` directories.
+    #[serde(rename = "Acquire-By-Hash")]
+    pub acquire_by_hash: Option<YesNo>,
+    /// Whether architecture-independent packages are listed in architecture-specific indexes.
+    #[serde(rename = "No-Support-for-Architecture-all")]
+    pub no_support_for_architecture_all: Option<String>,
+    /// Space-separated list of architectures.
+    pub architectures: Option<String>,
+    /// Space-separated list of components.
+    pub components: Option<String>,
+    /// Description of the distribution.
+    pub description: Option<String>,
+    /// MD5 digests of index files.
+    #[serde(rename = "MD5Sum", serialize_with = "serialize_hashes")]
+    pub md5sum: Option<FileList<FileEntry<Md5>>>,
+    /// SHA1 digests of index files.
+    #[serde(rename = "SHA1", serialize_with = "serialize_hashes")]
+    pub sha1: Option<FileList<FileEntry<Sha1>>>,
+    /// SHA256 digests of index files.
+    #[serde(rename = "SHA256", serialize_with = "serialize_hashes")]
+    pub sha256: Option<FileList<FileEntry<Sha256>>>,
+    /// SHA512 digests of index files.
+    #[serde(rename = "SHA512", serialize_with = "serialize_hashes")]
+    pub sha512: Option<FileList<FileEntry<Sha512>>>,
+}
+
+/// Writes the hash list with sizes padded to 16 characters.
+fn serialize_hashes<C: Checksum, S: serde::Serializer>(list: &Option<FileList<FileEntry<C>>>, serializer: S) -> Result<S::Ok, S::Error> {
+    match list {
+        Some(list) => serializer.serialize_some(&PaddedHashes(list)),
+        None => serializer.serialize_none(),
+    }
+}
+
+struct PaddedHashes<'a, C>(&'a FileList<FileEntry<C>>);
+
+impl<'a, C: Checksum> fmt::Display for PaddedHashes<'a, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &(self.0).0 {
+            write!(f, "\n{} {:>16} {}", entry.digest, entry.size, entry.path)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a, C: Checksum> Serialize for PaddedHashes<'a, C> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Index file listed in the `Release` file with all its known digests.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct IndexFile<'a> {
+    /// Path relative to the directory containing the `Release` file.
+    pub path: &'a str,
+    /// Size of the file in bytes.
+    pub size: u64,
+    /// MD5 digest if present.
+    pub md5: Option<&'a Md5>,
+    /// SHA1 digest if present.
+    pub sha1: Option<&'a Sha1>,
+    /// SHA256 digest if present.
+    pub sha256: Option<&'a Sha256>,
+    /// SHA512 digest if present.
+    pub sha512: Option<&'a Sha512>,
+}
+
+impl<'a> IndexFile<'a> {
+    fn new(path: &'a str, size: u64) -> Self {
+        IndexFile {
+            path,
+            size,
+            md5: None,
+            sha1: None,
+            sha256: None,
+            sha512: None,
+        }
+    }
+
+    /// Returns the paths of the file in `by-hash` directories, strongest digest first.
+    ///
+    /// The paths are relative to the directory containing the `Release` file, e.g.
+    /// `main/binary-all/by-hash/SHA256/3957f2...`.
+    pub fn by_hash_paths(&self) -> Vec<String> {
+        let dir = match self.path.rfind('/') {
+            Some(pos) => &self.path[..=pos],
+            None => "",
+        };
+        let mut paths = Vec::new();
+        paths.extend(self.sha512.map(|digest| format!("{}by-hash/SHA512/{}", dir, digest)));
+        paths.extend(self.sha256.map(|digest| format!("{}by-hash/SHA256/{}", dir, digest)));
+        paths.extend(self.sha1.map(|digest| format!("{}by-hash/SHA1/{}", dir, digest)));
+        paths.extend(self.md5.map(|digest| format!("{}by-hash/MD5Sum/{}", dir, digest)));
+        paths
+    }
+}
+
+/// Checksum which can be listed in the `Release` file.
+///
+/// This connects checksum types with their fields so that verification and generation can be
+/// generic over the digest algorithm.
+pub trait ReleaseChecksum: Checksum + PartialEq + Sized {
+    /// Returns the listed digest of `file` if present.
+    fn of_file<'a>(file: &IndexFile<'a>) -> Option<&'a Self>;
+
+    /// Returns the list of the algorithm in `release`.
+    fn list_mut(release: &mut Release) -> &mut Option<FileList<FileEntry<Self>>>;
+}
+
+macro_rules! impl_release_checksum {
+    ($checksum:ty, $file_field:ident, $release_field:ident) => {
+        impl ReleaseChecksum for $checksum {
+            fn of_file<'a>(file: &IndexFile<'a>) -> Option<&'a Self> {
+                file.$file_field
+            }
+
+            fn list_mut(release: &mut Release) -> &mut Option<FileList<FileEntry<Self>>> {
+                &mut release.$release_field
+            }
+        }
+    }
+}
+
+impl_release_checksum!(Md5, md5, md5sum);
+impl_release_checksum!(Sha1, sha1, sha1);
+impl_release_checksum!(Sha256, sha256, sha256);
+impl_release_checksum!(Sha512, sha512, sha512);
+
+fn entries<C>(list: &Option<FileList<FileEntry<C>>>) -> &[FileEntry<C>] {
+    list.as_ref().map_or(&[], |list| &list.0)
+}
+
+impl Release {
+    /// Iterates over architectures.
+    pub fn architectures(&self) -> impl Iterator<Item=&str> {
+        self.architectures.as_deref().unwrap_or("").split_whitespace()
+    }
+
+    /// Iterates over components.
+    pub fn components(&self) -> impl Iterator<Item=&str> {
+        self.components.as_deref().unwrap_or("").split_whitespace()
+    }
+
+    /// Returns `true` if index files can be downloaded from `by-hash` directories.
+    pub fn acquire_by_hash(&self) -> bool {
+        self.acquire_by_hash.map_or(false, |by_hash| by_hash.is_yes())
+    }
+
+    /// Returns `true` if architecture-independent packages are only listed in `binary-all`
+    /// indexes and not in the indexes of each architecture.
+    pub fn separate_architecture_all(&self) -> bool {
+        self.no_support_for_architecture_all.as_deref() == Some("Packages")
+    }
+
+    /// Returns `true` if `time` is past `Valid-Until`.
+    ///
+    /// Files without `Valid-Until` never expire.
+    pub fn is_expired_at(&self, time: DateTime<Utc>) -> bool {
+        self.valid_until.map_or(false, |valid_until| DateTime::<Utc>::from(valid_until) < time)
+    }
+
+    /// Returns `true` if the file isn't expired at `time` and isn't dated after it.
+    ///
+    /// A `Date` in the future usually means that the clock of the client or the server is
+    /// wrong so such files are rejected like expired ones.
+    pub fn is_valid_at(&self, time: DateTime<Utc>) -> bool {
+        let from_future = self.date.map_or(false, |date| DateTime::<Utc>::from(date) > time);
+        !from_future && !self.is_expired_at(time)
+    }
+
+    /// Returns the time elapsed between `Date` and `time`, `None` if the file isn't dated.
+    ///
+    /// This is useful for enforcing the maximum age of files without `Valid-Until`.
+    pub fn age_at(&self, time: DateTime<Utc>) -> Option<Duration> {
+        self.date.map(|date| time.signed_duration_since(DateTime::<Utc>::from(date)))
+    }
+
+    /// Returns the index file with given path combining entries from all checksum lists.
+    ///
+    /// The size is taken from the strongest digest list containing the file.
+    pub fn file(&self, path: &str) -> Option<IndexFile<'_>> {
+        let sha512 = self.sha512.as_ref().and_then(|list| list.get(path));
+        let sha256 = self.sha256.as_ref().and_then(|list| list.get(path));
+        let sha1 = self.sha1.as_ref().and_then(|list| list.get(path));
+        let md5 = self.md5sum.as_ref().and_then(|list| list.get(path));
+        let (path, size) = sha512.map(|entry| (&entry.path, entry.size))
+            .or_else(|| sha256.map(|entry| (&entry.path, entry.size)))
+            .or_else(|| sha1.map(|entry| (&entry.path, entry.size)))
+            .or_else(|| md5.map(|entry| (&entry.path, entry.size)))?;
+
+        Some(IndexFile {
+            path,
+            size,
+            md5: md5.map(|entry| &entry.digest),
+            sha1: sha1.map(|entry| &entry.digest),
+            sha256: sha256.map(|entry| &entry.digest),
+            sha512: sha512.map(|entry| &entry.digest),
+        })
+    }
+
+    /// Returns all index files keyed by path.
+    pub fn files(&self) -> BTreeMap<&str, IndexFile<'_>> {
+        let mut files = BTreeMap::new();
+        for entry in entries(&self.md5sum) {
+            files.entry(entry.path.as_str()).or_insert_with(|| IndexFile::new(&entry.path, entry.size)).md5 = Some(&entry.digest);
+        }
+        for entry in entries(&self.sha1) {
+            files.entry(entry.path.as_str()).or_insert_with(|| IndexFile::new(&entry.path, entry.size)).sha1 = Some(&entry.digest);
+        }
+        for entry in entries(&self.sha256) {
+            files.entry(entry.path.as_str()).or_insert_with(|| IndexFile::new(&entry.path, entry.size)).sha256 = Some(&entry.digest);
+        }
+        for entry in entries(&self.sha512) {
+            files.entry(entry.path.as_str()).or_insert_with(|| IndexFile::new(&entry.path, entry.size)).sha512 = Some(&entry.digest);
+        }
+        files
+    }
+}
+
+#[cfg(feature = "fs")]
+#[derive(Debug, thiserror::Error)]
+enum LoadErrorInner {
+    #[error("failed to read {path}")]
+    Read { path: PathBuf, #[source] error: std::io::Error, },
+    #[error("signature {sig_path} of {path} is invalid")]
+    Verification { path: PathBuf, sig_path: PathBuf, #[source] error: Box<dyn std::error::Error + Send + Sync>, },
+    #[error("failed to parse {path}")]
+    Parse { path: PathBuf, #[source] error: crate::de::Error, },
+}
+
+/// Error returned when loading a `Release` file with a detached signature fails.
+#[cfg(feature = "fs")]
+#[derive(Debug, thiserror::Error)]
+#[error(transparent)]
+pub struct LoadError(#[from] LoadErrorInner);
+
+/// Reads the `Release` file and its detached signature, verifies it and parses the file.
+///
+/// The `verifier` receives the exact bytes of the `Release` file and the contents of the
+/// signature (usually `Release.gpg`).
+/// The file is parsed from the same bytes only if the verifier succeeds so that the contents
+/// can't change between verification and parsing.
+/// Checking the signature is up to the caller, e.g. using `sequoia-openpgp` or by running `gpgv`.
+///
+/// This function requires the `fs` feature.
+///
+/// # Example
+///
+/// ```no_run
+/// use rfc822_like::debian::release::from_release_with_detached_sig;
+///
+/// let release = from_release_with_detached_sig("Release", "Release.gpg", |data, signature| {
+///     // Replace with a real check of the signature
+///     if signature.is_empty() || data.is_empty() {
+///         return Err("missing signature");
+///     }
+///     Ok(())
+/// }).unwrap();
+/// println!("{:?}", release.suite);
+/// ```
+#[cfg(feature = "fs")]
+pub fn from_release_with_detached_sig<P, S, F, E>(release_path: P, sig_path: S, verifier: F) -> Result<Release, LoadError>
+where P: AsRef<Path>, S: AsRef<Path>, F: FnOnce(&[u8], &[u8]) -> Result<(), E>, E: Into<Box<dyn std::error::Error + Send + Sync>> {
+    let read = |path: &Path| std::fs::read(path).map_err(|error| LoadErrorInner::Read { path: path.to_owned(), error, });
+    let path = release_path.as_ref();
+    let sig_path = sig_path.as_ref();
+    let data = read(path)?;
+    let signature = read(sig_path)?;
+    verifier(&data, &signature).map_err(|error| LoadErrorInner::Verification { path: path.to_owned(), sig_path: sig_path.to_owned(), error: error.into(), })?;
+    crate::from_bytes(&data).map_err(|error| LoadErrorInner::Parse { path: path.to_owned(), error, }.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Duration, Utc};
+    use super::Release;
+
+    const RELEASE: &str = "\
+Origin: Debian
+Label: Debian
+Suite: stable
+Version: 12.2
+Codename: bookworm
+Changelogs: https://metadata.ftp-master.debian.org/changelogs/@CHANGEPATH@_changelog
+Date: Sat, 07 Oct 2023 09:42:15 UTC
+Acquire-By-Hash: yes
+No-Support-for-Architecture-all: Packages
+Architectures: all amd64 arm64
+Components: main contrib non-free-firmware
+Description: Debian 12.2 Released 07 October 2023
+MD5Sum:
+ 0ed6d4c8891eb86358b94bb35d9e4da4          1484322 contrib/Contents-all
+ 7f4d4cbf4e4ee5c5b9cbbbf5d2bbdc57           738242 main/binary-all/Packages
+SHA256:
+ d6c9c82f4e61b4662f9ba16b9ebb379c57b4943f8b7813091d1f637325ddfb79          1484322 contrib/Contents-all
+ 3957f28db16e3f28c7b34ae84f1c929c567de6970f3f1b95dac9b498dd80fe63           738242 main/binary-all/Packages
+ a3b1c0e6b1f0d0f2d8c3b6b1f2e3a9c1b0e3d2f7a6b5c4d3e2f1a0b9c8d7e6f5               45 main/i18n/Index
+";
+
+    #[test]
+    fn parse() {
+        let release = crate::from_str::<Release>(RELEASE).unwrap();
+        assert_eq!(release.suite.as_deref(), Some("stable"));
+        assert!(release.acquire_by_hash.unwrap().is_yes());
+        assert_eq!(release.architectures().collect::<Vec<_>>(), ["all", "amd64", "arm64"]);
+        assert_eq!(release.components().count(), 3);
+        assert!(release.valid_until.is_none());
+
+        let packages = release.file("main/binary-all/Packages").unwrap();
+        assert_eq!(packages.size, 738242);
+        assert_eq!(packages.md5.unwrap().to_string(), "7f4d4cbf4e4ee5c5b9cbbbf5d2bbdc57");
+        assert!(packages.sha1.is_none());
+        assert_eq!(packages.by_hash_paths(), [
+            "main/binary-all/by-hash/SHA256/3957f28db16e3f28c7b34ae84f1c929c567de6970f3f1b95dac9b498dd80fe63",
+            "main/binary-all/by-hash/MD5Sum/7f4d4cbf4e4ee5c5b9cbbbf5d2bbdc57",
+        ]);
+        assert!(release.file("main/binary-amd64/Packages").is_none());
+
+        let files = release.files();
+        assert_eq!(files.len(), 3);
+        assert!(files["main/i18n/Index"].md5.is_none());
+        assert!(files["main/i18n/Index"].sha256.is_some());
+
+        assert_eq!(crate::to_string(&release).unwrap(), RELEASE);
+    }
+
+    #[test]
+    fn validity() {
+        let time = |s: &str| DateTime::<Utc>::from(s.parse::<crate::debian::date::Date>().unwrap());
+        let mut release = crate::from_str::<Release>(RELEASE).unwrap();
+        assert!(release.acquire_by_hash());
+        assert!(release.separate_architecture_all());
+        assert!(!release.is_expired_at(time("Mon, 07 Oct 2030 09:42:15 UTC")));
+        assert!(release.is_valid_at(time("Sat, 07 Oct 2023 09:42:15 UTC")));
+        assert!(!release.is_valid_at(time("Sat, 07 Oct 2023 09:42:14 UTC")));
+        assert_eq!(release.age_at(time("Sun, 08 Oct 2023 09:42:15 UTC")), Some(Duration::days(1)));
+
+        release.valid_until = Some("Sat, 14 Oct 2023 09:42:15 UTC".parse().unwrap());
+        assert!(release.is_valid_at(time("Sat, 14 Oct 2023 09:42:15 UTC")));
+        assert!(release.is_expired_at(time("Sat, 14 Oct 2023 09:42:16 UTC")));
+        assert!(!release.is_valid_at(time("Sat, 14 Oct 2023 09:42:16 UTC")));
+
+        let release = crate::from_str::<Release>("Suite: stable\n").unwrap();
+        assert!(!release.acquire_by_hash());
+        assert!(!release.separate_architecture_all());
+        assert!(release.is_valid_at(time("Sat, 07 Oct 2023 09:42:15 UTC")));
+        assert!(release.age_at(time("Sat, 07 Oct 2023 09:42:15 UTC")).is_none());
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn detached_signature() {
+        let root = crate::testing::temp_dir::TempDir::new("detached-sig");
+        std::fs::write(root.join("Release"), RELEASE).unwrap();
+        std::fs::write(root.join("Release.gpg"), "signature").unwrap();
+
+        let load = |signature: &'static [u8]| super::from_release_with_detached_sig(root.join("Release"), root.join("Release.gpg"), move |data: &[u8], sig: &[u8]| {
+            assert_eq!(data, RELEASE.as_bytes());
+            if sig == signature { Ok(()) } else { Err("bad signature") }
+        });
+        assert_eq!(load(b"signature").unwrap().codename.as_deref(), Some("bookworm"));
+        let error = load(b"other").unwrap_err();
+        assert!(error.to_string().starts_with("signature "));
+        assert_eq!(std::error::Error::source(&error).unwrap().to_string(), "bad signature");
+
+        std::fs::write(root.join("Release"), "Date: never\n").unwrap();
+        assert!(super::from_release_with_detached_sig(root.join("Release"), root.join("Release.gpg"), |_, _| Err("bad signature")).unwrap_err().to_string().starts_with("signature "));
+        assert!(super::from_release_with_detached_sig(root.join("Release"), root.join("Release.gpg"), |_, _| Ok::<_, String>(())).unwrap_err().to_string().starts_with("failed to parse"));
+        assert!(super::from_release_with_detached_sig(root.join("Release"), root.join("missing.gpg"), |_, _| Ok::<_, String>(())).unwrap_err().to_string().starts_with("failed to read"));
+    }
+}
@@ -0,0 +1,356 @@
+//! Dependency resolution over an in-memory `Packages` index.
+//!
+//! [`Resolver`] searches for a set of records satisfying given relations including the
+//! dependencies of the selected records.
+//! Alternatives are tried in order, preferring real packages with higher versions over
+//! packages providing a virtual package.
+//! At most one version of each package is selected and `Conflicts` and `Breaks` are respected.
+//! Architecture qualifiers and restrictions are ignored.
+//!
+//! # Example
+//!
+//! ```
+//! use rfc822_like::debian::packages::Record;
+//! use rfc822_like::debian::resolve::Resolver;
+//!
+//! let input = "Package: hello\nVersion: 2.10-2\nMaintainer: Foo <foo@example.org>\nArchitecture: amd64\nDepends: libc6 (>= 2.14)\nFilename: pool/main/h/hello/hello_2.10-2_amd64.deb\nSize: 56132\n\nPackage: libc6\nVersion: 2.31-13\nMaintainer: Foo <foo@example.org>\nArchitecture: amd64\nFilename: pool/main/g/glibc/libc6_2.31-13_amd64.deb\nSize: 2825908\n";
+//! let records = rfc822_like::from_str::<Vec<Record>>(input).unwrap();
+//! let resolver = Resolver::new(&records);
+//! let solution = resolver.resolve(&"hello".parse().unwrap()).unwrap();
+//! assert_eq!(solution.iter().map(|record| record.package.as_str()).collect::<Vec<_>>(), ["hello", "libc6"]);
+//!
+//! let error = resolver.resolve(&"libc6 (>= 2.32)".parse().unwrap()).unwrap_err();
+//! assert_eq!(error.to_string(), "dependencies can not be satisfied: no package satisfies libc6 (>= 2.32)");
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use super::packages::Record;
+use super::relations::{Dependency, Relation};
+use super::{Relations, Version};
+
+/// A reason why a relation could not be satisfied.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Problem {
+    /// No package in the index satisfies any of the alternatives.
+    NotFound {
+        /// The unsatisfiable relation.
+        relation: Relation,
+        /// Name of the package requiring the relation, `None` for the requested relations.
+        required_by: Option<String>,
+    },
+    /// A candidate could not be selected because of another selected package.
+    ///
+    /// This is the case if any of the packages conflicts with or breaks the other one or if a
+    /// different version of the candidate was already selected.
+    Conflict {
+        /// Name of the rejected candidate.
+        package: String,
+        /// Version of the rejected candidate.
+        version: Version,
+        /// Name of the already selected package.
+        with: String,
+    },
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Problem::NotFound { relation, required_by: None, } => write!(f, "no package satisfies {}", relation),
+            Problem::NotFound { relation, required_by: Some(package), } => write!(f, "no package satisfies {} required by {}", relation, package),
+            Problem::Conflict { package, version, with, } => write!(f, "{} {} conflicts with {}", package, version, with),
+        }
+    }
+}
+
+/// Error returned when no solution exists.
+///
+/// It contains all problems encountered during the search.
+#[derive(Debug, Clone)]
+pub struct Unsatisfiable {
+    problems: Vec<Problem>,
+}
+
+impl Unsatisfiable {
+    /// Returns the problems encountered during the search in the order they were found.
+    pub fn problems(&self) -> &[Problem] {
+        &self.problems
+    }
+}
+
+impl fmt::Display for Unsatisfiable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("dependencies can not be satisfied")?;
+        let mut separator = ": ";
+        for problem in &self.problems {
+            write!(f, "{}{}", separator, problem)?;
+            separator = "; ";
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Unsatisfiable {}
+
+/// Resolves dependencies using records of an index.
+#[derive(Debug, Clone)]
+pub struct Resolver<'a> {
+    by_name: HashMap<&'a str, Vec<&'a Record>>,
+    providers: HashMap<&'a str, Vec<&'a Record>>,
+}
+
+/// Packages selected so far.
+struct State<'a> {
+    selected: HashMap<&'a str, &'a Record>,
+    order: Vec<&'a Record>,
+}
+
+impl<'a> Resolver<'a> {
+    /// Creates the resolver indexing the `records`.
+    pub fn new<I: IntoIterator<Item=&'a Record>>(records: I) -> Self {
+        let mut by_name = HashMap::<_, Vec<_>>::new();
+        let mut providers = HashMap::<_, Vec<_>>::new();
+        for record in records {
+            by_name.entry(record.package.as_str()).or_default().push(record);
+            for provided in record.provides.iter().flat_map(|provides| &provides.0) {
+                providers.entry(provided.name.as_str()).or_default().push(record);
+            }
+        }
+        for records in by_name.values_mut().chain(providers.values_mut()) {
+            records.sort_by(|a, b| a.package.cmp(&b.package).then_with(|| b.version.cmp(&a.version)));
+        }
+        Resolver { by_name, providers, }
+    }
+
+    /// Finds records satisfying the `relations` and all their `Pre-Depends` and `Depends`.
+    ///
+    /// The records are returned in the order they were selected.
+    pub fn resolve(&self, relations: &Relations) -> Result<Vec<&'a Record>, Unsatisfiable> {
+        let mut state = State { selected: HashMap::new(), order: Vec::new(), };
+        let mut problems = Vec::new();
+        if self.search(&mut state, relations, &mut problems) {
+            Ok(state.order)
+        } else {
+            Err(Unsatisfiable { problems, })
+        }
+    }
+
+    /// Returns the records satisfying any alternative of the `relation` in order of preference.
+    fn candidates(&self, relation: &Relation) -> Vec<&'a Record> {
+        let mut candidates = Vec::<&Record>::new();
+        for dependency in &relation.0 {
+            let real = self.by_name.get(dependency.name.as_str()).into_iter().flatten();
+            let provided = self.providers.get(dependency.name.as_str()).into_iter().flatten();
+            for &record in real.chain(provided) {
+                if satisfies(record, dependency) && !candidates.iter().any(|candidate| std::ptr::eq(*candidate, record)) {
+                    candidates.push(record);
+                }
+            }
+        }
+        candidates
+    }
+
+    /// Depth-first search selecting candidates of unsatisfied relations.
+    ///
+    /// Each relation with candidates gets a frame remembering the next candidate to try so the
+    /// search backtracks without recursion.
+    fn search<'b>(&self, state: &mut State<'a>, relations: &'b Relations, problems: &mut Vec<Problem>) -> bool where 'a: 'b {
+        let mut pending = Pending { nodes: Vec::new(), head: None, };
+        for relation in relations.0.iter().rev() {
+            pending.push(None, relation);
+        }
+        let mut frames = Vec::<Frame<'a>>::new();
+
+        'search: loop {
+            match pending.pop_unsatisfied(state) {
+                None => return true,
+                Some((required_by, relation)) => {
+                    let candidates = self.candidates(relation);
+                    if candidates.is_empty() {
+                        add_problem(problems, Problem::NotFound { relation: relation.clone(), required_by: required_by.map(ToOwned::to_owned), });
+                    } else {
+                        frames.push(Frame { candidates, next: 0, selected: None, head: pending.head, nodes_len: pending.nodes.len(), });
+                    }
+                },
+            }
+
+            // Selects the next candidate of the innermost frame, dropping exhausted frames
+            while let Some(frame) = frames.last_mut() {
+                if let Some(previous) = frame.selected.take() {
+                    state.order.pop();
+                    state.selected.remove(previous.package.as_str());
+                }
+                pending.restore(frame.head, frame.nodes_len);
+                let candidate = match frame.candidates.get(frame.next) {
+                    Some(candidate) => *candidate,
+                    None => {
+                        frames.pop();
+                        continue;
+                    },
+                };
+                frame.next += 1;
+                if let Some(with) = conflict(state, candidate) {
+                    add_problem(problems, Problem::Conflict { package: candidate.package.clone(), version: candidate.version.clone(), with: with.to_owned(), });
+                    continue;
+                }
+                frame.selected = Some(candidate);
+                state.selected.insert(&candidate.package, candidate);
+                state.order.push(candidate);
+
+                let required = candidate.depends.iter().rev().chain(candidate.pre_depends.iter().rev());
+                for relation in required.flat_map(|relations| relations.0.iter().rev()) {
+                    pending.push(Some(candidate.package.as_str()), relation);
+                }
+                continue 'search;
+            }
+            return false;
+        }
+    }
+}
+
+/// Choice point of the search.
+struct Frame<'a> {
+    candidates: Vec<&'a Record>,
+    // Index of the candidate to try after the current one fails
+    next: usize,
+    selected: Option<&'a Record>,
+    // State of `Pending` after the relation was taken
+    head: Option<usize>,
+    nodes_len: usize,
+}
+
+/// Stack of relations to satisfy along with the name of the package requiring them.
+///
+/// The stack is a linked list stored in a vector so that the state of each frame can be restored
+/// by resetting the head and truncating the nodes pushed since.
+struct Pending<'a, 'b> {
+    nodes: Vec<(Option<&'a str>, &'b Relation, Option<usize>)>,
+    head: Option<usize>,
+}
+
+impl<'a, 'b> Pending<'a, 'b> {
+    fn push(&mut self, required_by: Option<&'a str>, relation: &'b Relation) {
+        self.nodes.push((required_by, relation, self.head));
+        self.head = Some(self.nodes.len() - 1);
+    }
+
+    /// Removes relations from the top until one not satisfied by the selected packages is found.
+    fn pop_unsatisfied(&mut self, state: &State<'_>) -> Option<(Option<&'a str>, &'b Relation)> {
+        while let Some(index) = self.head {
+            let (required_by, relation, next) = self.nodes[index];
+            self.head = next;
+            let is_satisfied = relation.0.iter().any(|dependency| state.order.iter().any(|record| satisfies(record, dependency)));
+            if !is_satisfied {
+                return Some((required_by, relation));
+            }
+        }
+        None
+    }
+
+    fn restore(&mut self, head: Option<usize>, nodes_len: usize) {
+        self.head = head;
+        self.nodes.truncate(nodes_len);
+    }
+}
+
+fn add_problem(problems: &mut Vec<Problem>, problem: Problem) {
+    if !problems.contains(&problem) {
+        problems.push(problem);
+    }
+}
+
+/// Returns the name of a selected package preventing the selection of the `candidate`.
+fn conflict<'a>(state: &State<'a>, candidate: &Record) -> Option<&'a str> {
+    if let Some(selected) = state.selected.get(candidate.package.as_str()) {
+        return Some(&selected.package);
+    }
+    state.order
+        .iter()
+        .find(|selected| breaks(candidate, selected) || breaks(selected, candidate))
+        .map(|selected| selected.package.as_str())
+}
+
+/// Returns `true` if the `record` conflicts with or breaks the `other` record.
+fn breaks(record: &Record, other: &Record) -> bool {
+    record.conflicts.iter()
+        .chain(&record.breaks)
+        .flat_map(|relations| &relations.0)
+        .flat_map(|relation| &relation.0)
+        .any(|dependency| satisfies(other, dependency))
+}
+
+/// Returns `true` if the `record` or any of its provides satisfies the `dependency`.
+fn satisfies(record: &Record, dependency: &Dependency) -> bool {
+    if record.package == dependency.name {
+        dependency.version.as_ref().map_or(true, |constraint| constraint.matches(&record.version))
+    } else {
+        record.provides.as_ref().map_or(false, |provides| provides.satisfies(dependency))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Problem, Resolver};
+    use crate::debian::packages::Record;
+    use crate::testing::packages::record;
+
+    fn names(records: Vec<&Record>) -> Vec<String> {
+        records.into_iter().map(|record| format!("{} {}", record.package, record.version)).collect()
+    }
+
+    #[test]
+    fn alternatives_and_virtual() {
+        let records = vec![
+            record("app", "1.0", "amd64", "Depends: mail-transport-agent, libfoo (>= 2) | libfoo-compat\n"),
+            record("libfoo", "1.0", "amd64", ""),
+            record("libfoo-compat", "1.0", "amd64", ""),
+            record("postfix", "3.5", "amd64", "Provides: mail-transport-agent\nDepends: libc6\n"),
+            record("libc6", "2.31", "amd64", ""),
+            record("libc6", "2.28", "amd64", ""),
+        ];
+        let resolver = Resolver::new(&records);
+        let solution = resolver.resolve(&"app".parse().unwrap()).unwrap();
+        assert_eq!(names(solution), ["app 1.0", "postfix 3.5", "libc6 2.31", "libfoo-compat 1.0"]);
+
+        let solution = resolver.resolve(&"libc6 (<< 2.30), postfix".parse().unwrap()).unwrap();
+        assert_eq!(names(solution), ["libc6 2.28", "postfix 3.5"]);
+    }
+
+    #[test]
+    fn backtracking() {
+        let records = vec![
+            record("app", "1.0", "amd64", "Depends: mta, libbar\n"),
+            record("exim4", "4.94", "amd64", "Provides: mta\n"),
+            record("postfix", "3.5", "amd64", "Provides: mta\n"),
+            record("libbar", "1.0", "amd64", "Conflicts: exim4\n"),
+        ];
+        let solution = Resolver::new(&records).resolve(&"app".parse().unwrap()).unwrap();
+        assert_eq!(names(solution), ["app 1.0", "postfix 3.5", "libbar 1.0"]);
+    }
+
+    #[test]
+    fn long_chain() {
+        let records = (0..3_000).map(|i| record(&format!("pkg{}", i), "1.0", "amd64", &format!("Depends: pkg{}\n", i + 1))).chain(Some(record("pkg3000", "1.0", "amd64", ""))).collect::<Vec<_>>();
+        let resolver = Resolver::new(&records);
+        let resolved = resolver.resolve(&"pkg0".parse().unwrap()).unwrap();
+        assert_eq!(resolved.len(), 3_001);
+        assert_eq!(resolved.last().unwrap().package, "pkg3000");
+    }
+
+    #[test]
+    fn unsatisfiable() {
+        let records = vec![
+            record("app", "1.0", "amd64", "Depends: libfoo (>= 2), libbar\n"),
+            record("libfoo", "1.0", "amd64", ""),
+            record("libbar", "1.0", "amd64", "Breaks: app\n"),
+        ];
+        let resolver = Resolver::new(&records);
+        let error = resolver.resolve(&"app".parse().unwrap()).unwrap_err();
+        assert_eq!(error.problems(), [Problem::NotFound { relation: "libfoo (>= 2)".parse().unwrap(), required_by: Some("app".to_owned()), }]);
+        assert_eq!(error.to_string(), "dependencies can not be satisfied: no package satisfies libfoo (>= 2) required by app");
+
+        let error = resolver.resolve(&"libbar, app".parse().unwrap()).unwrap_err();
+        assert_eq!(error.problems(), [Problem::Conflict { package: "app".to_owned(), version: "1.0".parse().unwrap(), with: "libbar".to_owned(), }]);
+        assert_eq!(error.to_string(), "dependencies can not be satisfied: app 1.0 conflicts with libbar");
+    }
+}
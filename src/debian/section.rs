@@ -0,0 +1,104 @@
+//! The `Section` field.
+
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, thiserror::Error)]
+enum ErrorInner {
+    #[error("empty section")]
+    EmptySection,
+    #[error("empty component")]
+    EmptyComponent,
+    #[error("section '{0}' contains more than one slash")]
+    TooManySlashes(String),
+}
+
+/// Error returned when parsing a section fails.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error(transparent)]
+pub struct ParseError(#[from] ErrorInner);
+
+/// Value of the `Section` field - section optionally prefixed by archive area (component).
+///
+/// For example `contrib/net` has component `contrib` and section `net`.
+/// Packages in `main` usually don't specify the component.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Section {
+    component: Option<String>,
+    section: String,
+}
+
+impl Section {
+    /// Creates the section from its parts.
+    pub fn new<S: Into<String>>(component: Option<String>, section: S) -> Result<Self, ParseError> {
+        let section = section.into();
+        if section.is_empty() {
+            return Err(ErrorInner::EmptySection.into());
+        }
+        if component.as_ref().map_or(false, String::is_empty) {
+            return Err(ErrorInner::EmptyComponent.into());
+        }
+        if section.contains('/') || component.as_ref().map_or(false, |component| component.contains('/')) {
+            return Err(ErrorInner::TooManySlashes(section).into());
+        }
+        Ok(Section { component, section, })
+    }
+
+    /// Returns the component (archive area) if present.
+    pub fn component(&self) -> Option<&str> {
+        self.component.as_ref().map(AsRef::as_ref)
+    }
+
+    /// Returns the component defaulting to `main` if it's not present.
+    pub fn component_or_main(&self) -> &str {
+        self.component().unwrap_or("main")
+    }
+
+    /// Returns the section without the component.
+    pub fn section(&self) -> &str {
+        &self.section
+    }
+}
+
+impl FromStr for Section {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.find('/') {
+            Some(pos) if s[(pos + 1)..].contains('/') => Err(ErrorInner::TooManySlashes(s.to_owned()).into()),
+            Some(pos) => Section::new(Some(s[..pos].to_owned()), &s[(pos + 1)..]),
+            None => Section::new(None, s),
+        }
+    }
+}
+
+impl fmt::Display for Section {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(component) = &self.component {
+            write!(f, "{}/", component)?;
+        }
+        f.write_str(&self.section)
+    }
+}
+
+serde_via_str!(Section, "a section optionally prefixed with component");
+
+#[cfg(test)]
+mod tests {
+    use super::Section;
+
+    #[test]
+    fn parse() {
+        let section = "contrib/net".parse::<Section>().unwrap();
+        assert_eq!(section.component(), Some("contrib"));
+        assert_eq!(section.section(), "net");
+        assert_eq!(section.to_string(), "contrib/net");
+        let section = "utils".parse::<Section>().unwrap();
+        assert_eq!(section.component_or_main(), "main");
+        assert_eq!(section.to_string(), "utils");
+
+        assert_eq!("".parse::<Section>().unwrap_err().to_string(), "empty section");
+        assert_eq!("/net".parse::<Section>().unwrap_err().to_string(), "empty component");
+        assert_eq!("a/b/c".parse::<Section>().unwrap_err().to_string(), "section 'a/b/c' contains more than one slash");
+    }
+}
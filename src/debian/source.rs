@@ -0,0 +1,151 @@
+//! The `Source` field of binary packages.
+
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+use super::Version;
+
+#[derive(Debug, Clone, thiserror::Error)]
+enum ErrorInner {
+    #[error("empty source package name")]
+    EmptyName,
+    #[error("unterminated version")]
+    UnterminatedVersion,
+    #[error("unexpected text after version")]
+    TrailingText,
+    #[error("invalid version")]
+    InvalidVersion(#[source] super::version::ParseError),
+}
+
+/// Error returned when parsing the `Source` field fails.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error(transparent)]
+pub struct ParseError(#[from] ErrorInner);
+
+/// Name of the source package optionally followed by its version in parentheses.
+///
+/// The version is present if it differs from the version of the binary package, e.g.
+/// `src (1.2-1)`.
+/// A parsed version is displayed exactly as written (e.g. with `0:` epoch) until it's replaced
+/// using [`set_version`](Source::set_version); the written form doesn't affect equality.
+#[derive(Debug, Clone)]
+pub struct Source {
+    /// Name of the source package.
+    pub name: String,
+    version: Option<Version>,
+    // The version as written in the parsed input
+    written_version: Option<String>,
+}
+
+impl Source {
+    /// Creates the value from the name and optional version of the source package.
+    pub fn new<N: Into<String>>(name: N, version: Option<Version>) -> Self {
+        Source { name: name.into(), version, written_version: None, }
+    }
+
+    /// Version of the source package if it differs from the binary package.
+    pub fn version(&self) -> Option<&Version> {
+        self.version.as_ref()
+    }
+
+    /// Replaces the version of the source package.
+    pub fn set_version(&mut self, version: Option<Version>) {
+        self.version = version;
+        self.written_version = None;
+    }
+
+    /// Returns the version of the source package falling back to the version of the binary one.
+    pub fn version_or<'a>(&'a self, binary_version: &'a Version) -> &'a Version {
+        self.version.as_ref().unwrap_or(binary_version)
+    }
+}
+
+impl FromStr for Source {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let mut written_version = None;
+        let (name, version) = match s.find('(') {
+            Some(pos) => {
+                let rest = s[(pos + 1)..].trim_end();
+                if !rest.ends_with(')') {
+                    return Err(ErrorInner::UnterminatedVersion.into());
+                }
+                let version = &rest[..(rest.len() - 1)];
+                if version.contains(')') {
+                    return Err(ErrorInner::TrailingText.into());
+                }
+                let version = version.trim();
+                written_version = Some(version.to_owned());
+                let version = version.parse().map_err(ErrorInner::InvalidVersion)?;
+                (s[..pos].trim_end(), Some(version))
+            },
+            None => (s, None),
+        };
+        if name.is_empty() {
+            return Err(ErrorInner::EmptyName.into());
+        }
+        Ok(Source { name: name.to_owned(), version, written_version, })
+    }
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)?;
+        match (&self.written_version, &self.version) {
+            (Some(written), _) => write!(f, " ({})", written),
+            (None, Some(version)) => write!(f, " ({})", version),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+impl PartialEq for Source {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && self.version == other.version
+    }
+}
+
+impl Eq for Source {}
+
+impl Hash for Source {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+        self.version.hash(state);
+    }
+}
+
+serde_via_str!(Source, "a source package name optionally followed by version");
+
+#[cfg(test)]
+mod tests {
+    use super::Source;
+
+    #[test]
+    fn parse() {
+        let source = "foo (1:1.2-1)".parse::<Source>().unwrap();
+        assert_eq!(source.name, "foo");
+        assert_eq!(source.version().unwrap().to_string(), "1:1.2-1");
+        assert_eq!(source.to_string(), "foo (1:1.2-1)");
+        let binary_version = "2.0".parse().unwrap();
+        assert_eq!(*source.version_or(&binary_version), "1:1.2-1".parse().unwrap());
+
+        let source = "bar".parse::<Source>().unwrap();
+        assert_eq!(source.version(), None);
+        assert_eq!(*source.version_or(&binary_version), binary_version);
+        assert_eq!(source.to_string(), "bar");
+
+        let mut source = "foo (0:1.2-1)".parse::<Source>().unwrap();
+        assert_eq!(source.to_string(), "foo (0:1.2-1)");
+        assert_eq!(source, "foo (1.2-1)".parse().unwrap());
+        assert_eq!("foo (00:1.0)".parse::<Source>().unwrap().to_string(), "foo (00:1.0)");
+        source.set_version(Some("2.0".parse().unwrap()));
+        assert_eq!(source.to_string(), "foo (2.0)");
+        assert_eq!(Source::new("foo", Some("1.2-1".parse().unwrap())).to_string(), "foo (1.2-1)");
+
+        assert_eq!("foo (1.0".parse::<Source>().unwrap_err().to_string(), "unterminated version");
+        assert_eq!("foo (1.0) (2.0)".parse::<Source>().unwrap_err().to_string(), "unexpected text after version");
+        assert_eq!(" (1.0)".parse::<Source>().unwrap_err().to_string(), "empty source package name");
+    }
+}
@@ -0,0 +1,173 @@
+//! Records of apt `Sources` index files.
+//!
+//! # Example
+//!
+//! ```
+//! use rfc822_like::debian::sources::Record;
+//!
+//! let input = "Package: hello\nBinary: hello\nVersion: 2.10-3\nMaintainer: Santiago Vila <sanvila@debian.org>\nArchitecture: any\nFormat: 3.0 (quilt)\nDirectory: pool/main/h/hello\n";
+//! let records = rfc822_like::from_str::<Vec<Record>>(input).unwrap();
+//! assert!(records[0].binary.contains("hello"));
+//! assert_eq!(rfc822_like::to_string(&records).unwrap(), input);
+//! ```
+
+use serde::{Deserialize, Serialize};
+use super::{Binary, Contact, PackageList, Priority, Relations, Section, Uploaders, Version};
+use super::checksums::{FileEntry, FileList, Md5, Sha1, Sha256, Sha512};
+use super::vcs::VcsLocation;
+
+/// A single record (paragraph) of the `Sources` file.
+///
+/// Fields are serialized in the order used by the Debian archive.
+/// Fields not covered by this struct are ignored, use [`Preserving`](crate::document::Preserving)
+/// if they need to be kept.
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Record {
+    /// Name of the source package.
+    pub package: String,
+    /// Binary packages built from this source package.
+    pub binary: Binary,
+    /// Version of the source package.
+    pub version: Version,
+    /// Maintainer of the package.
+    pub maintainer: Contact,
+    /// Co-maintainers of the package.
+    pub uploaders: Option<Uploaders>,
+    /// Packages required to build the package.
+    #[serde(rename = "Build-Depends")]
+    pub build_depends: Option<Relations>,
+    /// Packages required to build architecture-independent packages.
+    #[serde(rename = "Build-Depends-Indep")]
+    pub build_depends_indep: Option<Relations>,
+    /// Packages required to build architecture-dependent packages.
+    #[serde(rename = "Build-Depends-Arch")]
+    pub build_depends_arch: Option<Relations>,
+    /// Packages that must not be installed when building the package.
+    #[serde(rename = "Build-Conflicts")]
+    pub build_conflicts: Option<Relations>,
+    /// Packages that must not be installed when building architecture-independent packages.
+    #[serde(rename = "Build-Conflicts-Indep")]
+    pub build_conflicts_indep: Option<Relations>,
+    /// Packages that must not be installed when building architecture-dependent packages.
+    #[serde(rename = "Build-Conflicts-Arch")]
+    pub build_conflicts_arch: Option<Relations>,
+    /// Space-separated list of architectures the package can be built for.
+    pub architecture: String,
+    /// Version of the Debian policy the package complies with.
+    #[serde(rename = "Standards-Version")]
+    pub standards_version: Option<String>,
+    /// Format of the source package, e.g. `3.0 (quilt)`.
+    pub format: String,
+    /// MD5 digests of the files comprising the source package.
+    pub files: Option<FileList<FileEntry<Md5>>>,
+    /// URL of the web interface of the packaging repository.
+    #[serde(rename = "Vcs-Browser")]
+    pub vcs_browser: Option<String>,
+    /// Arch repository.
+    #[serde(rename = "Vcs-Arch")]
+    pub vcs_arch: Option<VcsLocation>,
+    /// Bazaar repository.
+    #[serde(rename = "Vcs-Bzr")]
+    pub vcs_bzr: Option<VcsLocation>,
+    /// CVS repository.
+    #[serde(rename = "Vcs-Cvs")]
+    pub vcs_cvs: Option<VcsLocation>,
+    /// Darcs repository.
+    #[serde(rename = "Vcs-Darcs")]
+    pub vcs_darcs: Option<VcsLocation>,
+    /// Git repository.
+    #[serde(rename = "Vcs-Git")]
+    pub vcs_git: Option<VcsLocation>,
+    /// Mercurial repository.
+    #[serde(rename = "Vcs-Hg")]
+    pub vcs_hg: Option<VcsLocation>,
+    /// Monotone repository.
+    #[serde(rename = "Vcs-Mtn")]
+    pub vcs_mtn: Option<VcsLocation>,
+    /// Subversion repository.
+    #[serde(rename = "Vcs-Svn")]
+    pub vcs_svn: Option<VcsLocation>,
+    /// SHA1 digests of the files comprising the source package.
+    #[serde(rename = "Checksums-Sha1")]
+    pub checksums_sha1: Option<FileList<FileEntry<Sha1>>>,
+    /// SHA256 digests of the files comprising the source package.
+    #[serde(rename = "Checksums-Sha256")]
+    pub checksums_sha256: Option<FileList<FileEntry<Sha256>>>,
+    /// SHA512 digests of the files comprising the source package.
+    #[serde(rename = "Checksums-Sha512")]
+    pub checksums_sha512: Option<FileList<FileEntry<Sha512>>>,
+    /// URL of the upstream project.
+    pub homepage: Option<String>,
+    /// Binary packages built from this source package including their attributes.
+    #[serde(rename = "Package-List")]
+    pub package_list: Option<PackageList>,
+    /// Comma-separated list of test suites, e.g. `autopkgtest`.
+    pub testsuite: Option<String>,
+    /// Packages triggering the test suites.
+    #[serde(rename = "Testsuite-Triggers")]
+    pub testsuite_triggers: Option<String>,
+    /// Path to the directory containing the files relative to the root of the archive.
+    pub directory: String,
+    /// Priority of the package, usually `source`.
+    pub priority: Option<Priority>,
+    /// Section of the package.
+    pub section: Option<Section>,
+}
+
+impl Record {
+    /// Returns the first repository of the package found in `Vcs-*` fields.
+    ///
+    /// The fields are searched in the canonical order, see [`vcs::find`](super::vcs::find).
+    pub fn vcs(&self) -> Option<&VcsLocation> {
+        self.vcs_arch.as_ref()
+            .or(self.vcs_bzr.as_ref())
+            .or(self.vcs_cvs.as_ref())
+            .or(self.vcs_darcs.as_ref())
+            .or(self.vcs_git.as_ref())
+            .or(self.vcs_hg.as_ref())
+            .or(self.vcs_mtn.as_ref())
+            .or(self.vcs_svn.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Record;
+
+    #[test]
+    fn archive_record() {
+        let input = "\
+Package: hello
+Binary: hello
+Version: 2.10-3
+Maintainer: Santiago Vila <sanvila@debian.org>
+Build-Depends: debhelper-compat (= 13), help2man <!nodoc>
+Architecture: any
+Standards-Version: 4.6.2
+Format: 3.0 (quilt)
+Files:
+ d0b3a0e9dfbc4e2b7e5c0ec2d1b1ae6c 1183 hello_2.10-3.dsc
+ 6cd0ffea3884a4e79330338dcc2987d6 725946 hello_2.10.orig.tar.gz
+Vcs-Browser: https://salsa.debian.org/sanvila/hello
+Vcs-Git: https://salsa.debian.org/sanvila/hello.git
+Checksums-Sha256:
+ f7b7a1b9e5dd0cdcf1d0a5d1e7e8d0f9b0e5b0a7f4a5b1c4e0e9f8d7c6b5a4f3 1183 hello_2.10-3.dsc
+ 31e066137a962676e89f69d1b65382de95a7ef7d914b8cb956f41ea72e0f516b 725946 hello_2.10.orig.tar.gz
+Homepage: https://www.gnu.org/software/hello/
+Package-List:
+ hello deb devel optional arch=any
+Testsuite: autopkgtest
+Directory: pool/main/h/hello
+Priority: source
+Section: devel
+";
+        let record = crate::from_str::<Record>(input).unwrap();
+        assert_eq!(record.files.as_ref().unwrap().0[1].size, 725946);
+        assert_eq!(record.checksums_sha256.as_ref().unwrap().0[0].path, "hello_2.10-3.dsc");
+        assert_eq!(record.package_list.as_ref().unwrap().0[0].name, "hello");
+        assert_eq!(record.build_depends.as_ref().unwrap().0.len(), 2);
+        assert_eq!(record.vcs().unwrap().url, "https://salsa.debian.org/sanvila/hello.git");
+        assert_eq!(crate::to_string(&record).unwrap(), input);
+    }
+}
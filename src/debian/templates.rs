@@ -0,0 +1,224 @@
+//! Debconf `templates` files.
+//!
+//! Each paragraph describes one question. Translated fields have the language (and optionally the
+//! encoding) appended to their name, e.g. `Description-de.UTF-8`.
+//!
+//! # Example
+//!
+//! ```
+//! use rfc822_like::debian::templates::Template;
+//!
+//! let input = "Template: foo/enable\nType: boolean\nDefault: true\nDescription: Enable foo?\nDescription-de.UTF-8: Foo aktivieren?\n";
+//! let templates = rfc822_like::from_str::<Vec<Template>>(input).unwrap();
+//! assert_eq!(templates[0].description("de_AT"), "Foo aktivieren?");
+//! assert_eq!(templates[0].description("fr"), "Enable foo?");
+//! assert_eq!(rfc822_like::to_string(&templates).unwrap(), input);
+//! ```
+
+use serde::{Deserialize, Serialize};
+use crate::document::Paragraph;
+
+#[derive(Debug, Clone, thiserror::Error)]
+enum ErrorInner {
+    #[error("missing field {0}")]
+    MissingField(&'static str),
+}
+
+/// Error returned when a paragraph is not a valid template.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error(transparent)]
+pub struct ParseError(#[from] ErrorInner);
+
+/// Translated value of a field.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Translation {
+    /// Name of the translated field, e.g. `Description`.
+    pub field: String,
+    /// Language code, e.g. `pt_BR`.
+    pub language: String,
+    /// Encoding if specified, e.g. `UTF-8`.
+    pub encoding: Option<String>,
+    /// The translated value.
+    pub value: String,
+}
+
+impl Translation {
+    fn field_name(&self) -> String {
+        match &self.encoding {
+            Some(encoding) => format!("{}-{}.{}", self.field, self.language, encoding),
+            None => format!("{}-{}", self.field, self.language),
+        }
+    }
+}
+
+/// A single debconf template.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Template {
+    /// Name of the template, e.g. `foo/enable`.
+    pub name: String,
+    /// Type of the question, e.g. `boolean` or `select`.
+    pub template_type: String,
+    /// Comma-separated choices of `select` and `multiselect` questions.
+    pub choices: Option<String>,
+    /// Default value.
+    pub default: Option<String>,
+    /// Short description followed by the extended description.
+    pub description: String,
+    /// Translated fields in their original order.
+    pub translations: Vec<Translation>,
+}
+
+const LOCALIZABLE: &[&str] = &["Default", "Choices", "Description"];
+
+/// Splits `Description-pt_BR.UTF-8` into field, language and encoding.
+fn split_localized(name: &str) -> Option<(&str, &str, Option<&str>)> {
+    let pos = name.find('-')?;
+    let field = LOCALIZABLE.iter().find(|field| field.eq_ignore_ascii_case(&name[..pos]))?;
+    let rest = &name[(pos + 1)..];
+    match rest.find('.') {
+        Some(pos) => Some((field, &rest[..pos], Some(&rest[(pos + 1)..]))),
+        None => Some((field, rest, None)),
+    }
+}
+
+impl Template {
+    /// Converts the paragraph into template.
+    ///
+    /// Unknown fields are ignored.
+    pub fn from_paragraph(paragraph: &Paragraph) -> Result<Self, ParseError> {
+        let required = |name: &'static str| paragraph.get(name).map(ToOwned::to_owned).ok_or(ErrorInner::MissingField(name));
+        let translations = paragraph.iter()
+            .filter_map(|field| {
+                let (name, language, encoding) = split_localized(field.name())?;
+                Some(Translation {
+                    field: name.to_owned(),
+                    language: language.to_owned(),
+                    encoding: encoding.map(ToOwned::to_owned),
+                    value: field.value().to_owned(),
+                })
+            })
+            .collect();
+
+        Ok(Template {
+            name: required("Template")?,
+            template_type: required("Type")?,
+            choices: paragraph.get("Choices").map(ToOwned::to_owned),
+            default: paragraph.get("Default").map(ToOwned::to_owned),
+            description: required("Description")?,
+            translations,
+        })
+    }
+
+    /// Converts the template into paragraph placing translations after the original fields.
+    pub fn to_paragraph(&self) -> Paragraph {
+        let mut paragraph = Paragraph::new();
+        paragraph.set("Template", self.name.as_str());
+        paragraph.set("Type", self.template_type.as_str());
+        if let Some(choices) = &self.choices {
+            paragraph.set("Choices", choices.as_str());
+        }
+        if let Some(default) = &self.default {
+            paragraph.set("Default", default.as_str());
+        }
+        paragraph.set("Description", self.description.as_str());
+        for translation in &self.translations {
+            paragraph.set(translation.field_name(), translation.value.as_str());
+        }
+        paragraph
+    }
+
+    /// Returns the value of the field translated to the language.
+    ///
+    /// If there's no translation for the language with territory (`pt_BR`) the translation for
+    /// the language alone (`pt`) is used.
+    /// Returns `None` if neither is available.
+    pub fn translated(&self, field: &str, language: &str) -> Option<&str> {
+        let find = |language: &str| self.translations
+            .iter()
+            .find(|translation| translation.field.eq_ignore_ascii_case(field) && translation.language == language)
+            .map(|translation| translation.value.as_str());
+
+        find(language).or_else(|| {
+            let pos = language.find(|c| c == '_' || c == '.' || c == '@')?;
+            find(&language[..pos])
+        })
+    }
+
+    /// Returns the description translated to the language falling back to the original.
+    pub fn description(&self, language: &str) -> &str {
+        self.translated("Description", language).unwrap_or(&self.description)
+    }
+
+    /// Returns the choices translated to the language falling back to the original.
+    ///
+    /// The choices are separated by commas, `\,` is an escaped comma.
+    pub fn choices(&self, language: &str) -> Vec<String> {
+        let choices = match self.translated("Choices", language).or(self.choices.as_deref()) {
+            Some(choices) => choices,
+            None => return Vec::new(),
+        };
+        let mut result = Vec::new();
+        let mut current = String::new();
+        let mut chars = choices.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => current.extend(chars.next()),
+                ',' => result.push(std::mem::take(&mut current).trim().to_owned()),
+                c => current.push(c),
+            }
+        }
+        result.push(current.trim().to_owned());
+        result
+    }
+}
+
+impl Serialize for Template {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_paragraph().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Template {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let paragraph = Paragraph::deserialize(deserializer)?;
+        Template::from_paragraph(&paragraph).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Template;
+
+    #[test]
+    fn templates() {
+        let input = "\
+Template: foo/mode
+Type: select
+Choices: fast, slow\\, but safe
+Choices-pt_BR.UTF-8: rápido, lento\\, mas seguro
+Default: fast
+Description: Mode of operation:
+ Choose how foo should work.
+Description-de.UTF-8: Betriebsart:
+ Wählen Sie, wie foo arbeiten soll.
+Description-pt_BR.UTF-8: Modo de operação:
+ Escolha como foo deve funcionar.
+";
+        let templates = crate::from_str::<Vec<Template>>(input).unwrap();
+        let template = &templates[0];
+        assert_eq!(template.template_type, "select");
+        assert_eq!(template.translations.len(), 3);
+        assert_eq!(template.translations[0].encoding.as_deref(), Some("UTF-8"));
+        assert_eq!(template.description("de_DE.UTF-8"), "Betriebsart:\nWählen Sie, wie foo arbeiten soll.");
+        assert_eq!(template.description("pt"), "Mode of operation:\nChoose how foo should work.");
+        assert_eq!(template.choices("C"), ["fast", "slow, but safe"]);
+        assert_eq!(template.choices("pt_BR"), ["rápido", "lento, mas seguro"]);
+
+        // translations are moved after the original fields
+        let expected = input.replace("Choices-pt_BR.UTF-8: rápido, lento\\, mas seguro\nDefault: fast\n", "Default: fast\n");
+        let expected = expected.replace("Description-de.UTF-8", "Choices-pt_BR.UTF-8: rápido, lento\\, mas seguro\nDescription-de.UTF-8");
+        assert_eq!(crate::to_string(&templates).unwrap(), expected);
+
+        assert_eq!(crate::from_str::<Template>("Template: foo/bar\nType: note\n").unwrap_err().to_string(), "missing field Description");
+    }
+}
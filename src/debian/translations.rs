@@ -0,0 +1,242 @@
+//! apt `Translation-<lang>` index files.
+//!
+//! The records contain translated descriptions of packages identified by the name of the package
+//! and the MD5 digest of the original description (`Description-md5`).
+//! The name of the description field contains the language, e.g. `Description-de`.
+//!
+//! # Example
+//!
+//! ```
+//! use rfc822_like::debian::translations::{Record, Translations};
+//!
+//! let input = "Package: hello\nDescription-md5: 2b3b4a8d5e2a3f4e5d6c7b8a9f0e1d2c\nDescription-de: Beispielpaket\n";
+//! let records = rfc822_like::from_str::<Vec<Record>>(input).unwrap();
+//! assert_eq!(records[0].language, "de");
+//! let translations = records.into_iter().collect::<Translations>();
+//! assert_eq!(translations.get("hello", "2b3b4a8d5e2a3f4e5d6c7b8a9f0e1d2c").unwrap().description, "Beispielpaket");
+//! ```
+
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use crate::document::Paragraph;
+use super::packages;
+
+#[derive(Debug, Clone, thiserror::Error)]
+enum ErrorInner {
+    #[error("missing field {0}")]
+    MissingField(&'static str),
+    #[error("missing translated description (Description-<language>)")]
+    MissingDescription,
+}
+
+/// Error returned when a paragraph is not a valid translation record.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error(transparent)]
+pub struct ParseError(#[from] ErrorInner);
+
+/// A single record (paragraph) of a translation file.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Record {
+    /// Name of the binary package.
+    pub package: String,
+    /// MD5 digest of the original description.
+    pub description_md5: String,
+    /// Language of the description, e.g. `de` or `pt_BR`.
+    pub language: String,
+    /// The translated description.
+    pub description: String,
+}
+
+impl Record {
+    /// Converts the paragraph into translation record.
+    pub fn from_paragraph(paragraph: &Paragraph) -> Result<Self, ParseError> {
+        let required = |name: &'static str| paragraph.get(name).map(ToOwned::to_owned).ok_or(ErrorInner::MissingField(name));
+        let (language, description) = paragraph.iter()
+            .find(|field| {
+                let name = field.name();
+                name.len() > 12 && name[..12].eq_ignore_ascii_case("Description-") && !name.eq_ignore_ascii_case("Description-md5")
+            })
+            .map(|field| (field.name()[12..].to_owned(), field.value().to_owned()))
+            .ok_or(ErrorInner::MissingDescription)?;
+
+        Ok(Record {
+            package: required("Package")?,
+            description_md5: required("Description-md5")?,
+            language,
+            description,
+        })
+    }
+
+    /// Converts the record into paragraph.
+    pub fn to_paragraph(&self) -> Paragraph {
+        let mut paragraph = Paragraph::new();
+        paragraph.set("Package", self.package.as_str());
+        paragraph.set("Description-md5", self.description_md5.as_str());
+        paragraph.set(format!("Description-{}", self.language), self.description.as_str());
+        paragraph
+    }
+}
+
+impl Serialize for Record {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_paragraph().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Record {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let paragraph = Paragraph::deserialize(deserializer)?;
+        Record::from_paragraph(&paragraph).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Translation records indexed by package name and description digest.
+#[derive(Debug, Clone, Default)]
+pub struct Translations {
+    records: HashMap<(String, String), Record>,
+}
+
+impl Translations {
+    /// Creates an empty index.
+    pub fn new() -> Self {
+        Translations::default()
+    }
+
+    /// Adds the record replacing the previous one for the same package and description.
+    ///
+    /// The digest is matched case-insensitively.
+    pub fn insert(&mut self, record: Record) -> Option<Record> {
+        self.records.insert((record.package.clone(), record.description_md5.to_ascii_lowercase()), record)
+    }
+
+    /// Returns the translation of the description of the package.
+    pub fn get(&self, package: &str, description_md5: &str) -> Option<&Record> {
+        // Allocation could be avoided using a custom borrowed key but the lookup is not hot.
+        self.records.get(&(package.to_owned(), description_md5.to_ascii_lowercase()))
+    }
+
+    /// Returns the number of records.
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Returns `true` if there are no records.
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Returns the translation of the description of the package from `Packages` index.
+    ///
+    /// If the record doesn't have `Description-md5` it's computed from the description with the
+    /// `rustcrypto` feature enabled, otherwise `None` is returned.
+    pub fn lookup(&self, package: &packages::Record) -> Option<&Record> {
+        match &package.description_md5 {
+            Some(description_md5) => self.get(&package.package, description_md5),
+            #[cfg(feature = "rustcrypto")]
+            None => self.get(&package.package, &package.compute_description_md5()?.to_string()),
+            #[cfg(not(feature = "rustcrypto"))]
+            None => None,
+        }
+    }
+
+    /// Pairs each record of `Packages` index with its translation.
+    pub fn join<'a>(&'a self, packages: &'a [packages::Record]) -> impl Iterator<Item=(&'a packages::Record, Option<&'a Record>)> + 'a {
+        packages.iter().map(move |package| (package, self.lookup(package)))
+    }
+
+    /// Replaces descriptions of packages with their translations.
+    ///
+    /// Packages without translation are left intact.
+    pub fn translate(&self, packages: &mut [packages::Record]) {
+        for package in packages {
+            if let Some(translation) = self.lookup(package) {
+                package.description = Some(translation.description.clone());
+            }
+        }
+    }
+}
+
+impl std::iter::FromIterator<Record> for Translations {
+    fn from_iter<I: IntoIterator<Item=Record>>(iter: I) -> Self {
+        let mut translations = Translations::new();
+        translations.extend(iter);
+        translations
+    }
+}
+
+impl Extend<Record> for Translations {
+    fn extend<I: IntoIterator<Item=Record>>(&mut self, iter: I) {
+        for record in iter {
+            self.insert(record);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Record, Translations};
+    use crate::debian::packages;
+
+    #[test]
+    fn join() {
+        let translation = "\
+Package: hello
+Description-md5: 2b3b4a8d5e2a3f4e5d6c7b8a9f0e1d2c
+Description-pt_BR: pacote de exemplo
+ O programa GNU hello produz uma saudação familiar.
+
+Package: world
+Description-md5: 0f0e0d0c0b0a09080706050403020100
+Description-pt_BR: mundo
+";
+        let records = crate::from_str::<Vec<Record>>(translation).unwrap();
+        assert_eq!(records[0].description, "pacote de exemplo\nO programa GNU hello produz uma saudação familiar.");
+        assert_eq!(crate::to_string(&records).unwrap(), translation);
+        let translations = records.into_iter().collect::<Translations>();
+        assert_eq!(translations.len(), 2);
+
+        let packages = "\
+Package: hello
+Version: 2.10-3
+Maintainer: Santiago Vila <sanvila@debian.org>
+Architecture: amd64
+Description: example package based on GNU hello
+Description-md5: 2b3b4a8d5e2a3f4e5d6c7b8a9f0e1d2c
+Filename: pool/main/h/hello/hello_2.10-3_amd64.deb
+Size: 53072
+
+Package: world
+Version: 1.0
+Maintainer: Santiago Vila <sanvila@debian.org>
+Architecture: amd64
+Description: world
+Description-md5: ffffffffffffffffffffffffffffffff
+Filename: pool/main/w/world/world_1.0_amd64.deb
+Size: 42
+";
+        let mut packages = crate::from_str::<Vec<packages::Record>>(packages).unwrap();
+        let joined = translations.join(&packages).map(|(_, translation)| translation.is_some()).collect::<Vec<_>>();
+        assert_eq!(joined, [true, false]);
+
+        translations.translate(&mut packages);
+        assert_eq!(packages[0].description.as_deref().unwrap().lines().next(), Some("pacote de exemplo"));
+        assert_eq!(packages[1].description.as_deref(), Some("world"));
+
+        #[cfg(feature = "rustcrypto")]
+        {
+            let mut record = packages[1].clone();
+            record.description = Some("world\n long".to_owned());
+            record.description_md5 = None;
+            let translation = Record {
+                package: "world".to_owned(),
+                description_md5: record.compute_description_md5().unwrap().to_string(),
+                language: "de".to_owned(),
+                description: "Welt".to_owned(),
+            };
+            let translations = std::iter::once(translation).collect::<Translations>();
+            assert_eq!(translations.lookup(&record).unwrap().description, "Welt");
+        }
+
+        assert_eq!(crate::from_str::<Record>("Package: foo\nDescription-md5: 00\n").unwrap_err().to_string(), "missing translated description (Description-<language>)");
+    }
+}
@@ -0,0 +1,107 @@
+//! Serde adapters for URL fields such as `Homepage` and `Vcs-Browser`.
+//!
+//! Use them with `#[serde(with = "...")]` on `url::Url` fields or `Option<url::Url>` fields.
+//! The value is validated when deserializing.
+//! Note that `Url` normalizes the value so e.g. `https://example.org` is written back as
+//! `https://example.org/`.
+//!
+//! This module requires the `url` feature.
+//!
+//! # Example
+//!
+//! ```
+//! #[derive(serde_derive::Deserialize, serde_derive::Serialize)]
+//! #[serde(rename_all = "PascalCase")]
+//! struct Source {
+//!     #[serde(with = "rfc822_like::debian::url")]
+//!     homepage: url::Url,
+//!     #[serde(rename = "Vcs-Browser", with = "rfc822_like::debian::url::option", default)]
+//!     vcs_browser: Option<url::Url>,
+//! }
+//!
+//! let source = rfc822_like::from_str::<Source>("Homepage: https://example.org/foo\n").unwrap();
+//! assert_eq!(source.homepage.host_str(), Some("example.org"));
+//! assert!(source.vcs_browser.is_none());
+//! assert!(rfc822_like::from_str::<Source>("Homepage: example.org\n").is_err());
+//! ```
+
+use std::fmt;
+use ::url::Url;
+use serde::{Deserializer, Serializer};
+
+struct UrlVisitor;
+
+impl<'de> serde::de::Visitor<'de> for UrlVisitor {
+    type Value = Url;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("an URL")
+    }
+
+    fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Url::parse(v.trim()).map_err(E::custom)
+    }
+}
+
+/// Serializes the URL as string.
+pub fn serialize<S: Serializer>(url: &Url, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(url.as_str())
+}
+
+/// Deserializes and validates the URL.
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Url, D::Error> {
+    deserializer.deserialize_str(UrlVisitor)
+}
+
+/// Adapters for optional URL fields.
+///
+/// Combine them with `#[serde(default)]` so that missing fields are accepted.
+pub mod option {
+    use ::url::Url;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    struct UrlValue(Url);
+
+    impl<'de> Deserialize<'de> for UrlValue {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            super::deserialize(deserializer).map(UrlValue)
+        }
+    }
+
+    /// Serializes the URL as string if present.
+    pub fn serialize<S: Serializer>(url: &Option<Url>, serializer: S) -> Result<S::Ok, S::Error> {
+        match url {
+            Some(url) => serializer.serialize_some(url.as_str()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    /// Deserializes and validates the URL if present.
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Url>, D::Error> {
+        Option::<UrlValue>::deserialize(deserializer).map(|url| url.map(|url| url.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[derive(serde_derive::Deserialize, serde_derive::Serialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct Source {
+        #[serde(with = "super")]
+        homepage: url::Url,
+        #[serde(rename = "Vcs-Browser", with = "super::option", default, skip_serializing_if = "Option::is_none")]
+        vcs_browser: Option<url::Url>,
+    }
+
+    #[test]
+    fn serde() {
+        let input = "Homepage: https://example.org/\nVcs-Browser: https://salsa.debian.org/foo\n";
+        let source = crate::from_str::<Source>(input).unwrap();
+        assert_eq!(source.vcs_browser.as_ref().unwrap().path(), "/foo");
+        assert_eq!(crate::to_string(&source).unwrap(), input);
+
+        let source = crate::from_str::<Source>("Homepage: https://example.org\n").unwrap();
+        assert_eq!(crate::to_string(&source).unwrap(), "Homepage: https://example.org/\n");
+        assert!(crate::from_str::<Source>("Homepage: https://example.org\nVcs-Browser: foo\n").is_err());
+    }
+}
@@ -0,0 +1,179 @@
+//! The `Vcs-*` fields pointing to the packaging repository.
+//!
+//! The value of `Vcs-Git` (and some other fields) may contain the branch (`-b branch`) and the
+//! path to the package inside the repository (`[path]`):
+//!
+//! ```text
+//! Vcs-Git: https://salsa.debian.org/foo/bar.git -b debian/latest [packages/bar]
+//! ```
+//!
+//! # Example
+//!
+//! ```
+//! use rfc822_like::debian::vcs::{self, VcsKind};
+//! use rfc822_like::document::Document;
+//!
+//! let document = Document::parse("Source: bar\nVcs-Git: https://salsa.debian.org/bar.git -b main\n").unwrap();
+//! let (kind, location) = vcs::find(&document.paragraphs()[0]).unwrap().unwrap();
+//! assert_eq!(kind, VcsKind::Git);
+//! assert_eq!(location.branch.as_deref(), Some("main"));
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+use crate::document::Paragraph;
+
+#[derive(Debug, Clone, thiserror::Error)]
+enum ErrorInner {
+    #[error("missing repository URL")]
+    MissingUrl,
+    #[error("missing branch name after -b")]
+    MissingBranch,
+    #[error("unterminated path")]
+    UnterminatedPath,
+    #[error("unexpected '{0}'")]
+    Unexpected(String),
+}
+
+/// Error returned when parsing a VCS location fails.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error(transparent)]
+pub struct ParseError(#[from] ErrorInner);
+
+/// Version control system of the repository.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum VcsKind {
+    /// `Vcs-Arch`
+    Arch,
+    /// `Vcs-Bzr`
+    Bzr,
+    /// `Vcs-Cvs`
+    Cvs,
+    /// `Vcs-Darcs`
+    Darcs,
+    /// `Vcs-Git`
+    Git,
+    /// `Vcs-Hg`
+    Hg,
+    /// `Vcs-Mtn`
+    Mtn,
+    /// `Vcs-Svn`
+    Svn,
+}
+
+impl VcsKind {
+    /// All kinds in the canonical order.
+    pub const ALL: [VcsKind; 8] = [VcsKind::Arch, VcsKind::Bzr, VcsKind::Cvs, VcsKind::Darcs, VcsKind::Git, VcsKind::Hg, VcsKind::Mtn, VcsKind::Svn];
+
+    /// Returns the name of the field, e.g. `Vcs-Git`.
+    pub fn field_name(&self) -> &'static str {
+        match self {
+            VcsKind::Arch => "Vcs-Arch",
+            VcsKind::Bzr => "Vcs-Bzr",
+            VcsKind::Cvs => "Vcs-Cvs",
+            VcsKind::Darcs => "Vcs-Darcs",
+            VcsKind::Git => "Vcs-Git",
+            VcsKind::Hg => "Vcs-Hg",
+            VcsKind::Mtn => "Vcs-Mtn",
+            VcsKind::Svn => "Vcs-Svn",
+        }
+    }
+
+    /// Returns the kind corresponding to the field name (case-insensitive).
+    ///
+    /// `Vcs-Browser` is not a repository location so it returns `None`.
+    pub fn from_field_name(name: &str) -> Option<Self> {
+        VcsKind::ALL.iter().copied().find(|kind| kind.field_name().eq_ignore_ascii_case(name))
+    }
+}
+
+/// Location of a repository - URL with optional branch and path.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct VcsLocation {
+    /// The URL of the repository.
+    pub url: String,
+    /// The branch specified using `-b`.
+    pub branch: Option<String>,
+    /// The path inside the repository specified in square brackets.
+    pub path: Option<String>,
+}
+
+impl FromStr for VcsLocation {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (s, path) = match s.find('[') {
+            Some(start) => {
+                let end = s[start..].find(']').ok_or(ErrorInner::UnterminatedPath)? + start;
+                if !s[(end + 1)..].trim().is_empty() {
+                    return Err(ErrorInner::Unexpected(s[(end + 1)..].trim().to_owned()).into());
+                }
+                (&s[..start], Some(s[(start + 1)..end].trim().to_owned()))
+            },
+            None => (s, None),
+        };
+        let mut words = s.split_whitespace();
+        let url = words.next().ok_or(ErrorInner::MissingUrl)?.to_owned();
+        let branch = match words.next() {
+            Some("-b") => Some(words.next().ok_or(ErrorInner::MissingBranch)?.to_owned()),
+            Some(other) => return Err(ErrorInner::Unexpected(other.to_owned()).into()),
+            None => None,
+        };
+        if let Some(word) = words.next() {
+            return Err(ErrorInner::Unexpected(word.to_owned()).into());
+        }
+        Ok(VcsLocation { url, branch, path, })
+    }
+}
+
+impl fmt::Display for VcsLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.url)?;
+        if let Some(branch) = &self.branch {
+            write!(f, " -b {}", branch)?;
+        }
+        if let Some(path) = &self.path {
+            write!(f, " [{}]", path)?;
+        }
+        Ok(())
+    }
+}
+
+serde_via_str!(VcsLocation, "a repository URL optionally followed by branch and path");
+
+/// Finds the first `Vcs-*` field (except `Vcs-Browser`) in the paragraph and parses it.
+pub fn find(paragraph: &Paragraph) -> Option<Result<(VcsKind, VcsLocation), ParseError>> {
+    paragraph.iter().find_map(|field| {
+        let kind = VcsKind::from_field_name(field.name())?;
+        Some(field.value().parse().map(|location| (kind, location)))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{VcsKind, VcsLocation};
+
+    #[test]
+    fn parse() {
+        let location = "https://salsa.debian.org/foo/bar.git -b debian/latest [packages/bar]".parse::<VcsLocation>().unwrap();
+        assert_eq!(location.url, "https://salsa.debian.org/foo/bar.git");
+        assert_eq!(location.branch.as_deref(), Some("debian/latest"));
+        assert_eq!(location.path.as_deref(), Some("packages/bar"));
+        assert_eq!(location.to_string(), "https://salsa.debian.org/foo/bar.git -b debian/latest [packages/bar]");
+        let location = "svn://svn.debian.org/foo [trunk]".parse::<VcsLocation>().unwrap();
+        assert_eq!(location.branch, None);
+        assert_eq!(location.path.as_deref(), Some("trunk"));
+
+        assert_eq!("".parse::<VcsLocation>().unwrap_err().to_string(), "missing repository URL");
+        assert_eq!("https://x -b".parse::<VcsLocation>().unwrap_err().to_string(), "missing branch name after -b");
+        assert_eq!("https://x [a".parse::<VcsLocation>().unwrap_err().to_string(), "unterminated path");
+        assert_eq!("https://x main".parse::<VcsLocation>().unwrap_err().to_string(), "unexpected 'main'");
+    }
+
+    #[test]
+    fn kind() {
+        assert_eq!(VcsKind::from_field_name("vcs-git"), Some(VcsKind::Git));
+        assert_eq!(VcsKind::from_field_name("Vcs-Browser"), None);
+        assert_eq!(VcsKind::Svn.field_name(), "Vcs-Svn");
+    }
+}
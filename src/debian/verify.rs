@@ -0,0 +1,380 @@
+//! Verification of index files listed in `Release` files.
+//!
+//! The verifier checks the size and the digest of every file listed in the `Release` file.
+//! The digest is computed by any [`Digest`] implementation producing one of the checksums listed
+//! in `Release` files, so the crypto library is up to you.
+//! The `rustcrypto` feature provides implementations for the RustCrypto crates.
+//! If the repository uses `by-hash` directories (`Acquire-By-Hash: yes`) and the file is not found
+//! at its canonical path the `by-hash` locations are tried, strongest digest first.
+//!
+//! [`open_by_hash`] opens a single index for reading the way apt acquires it from modern mirrors.
+//!
+//! This module requires the `verify` feature.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # #[cfg(feature = "rustcrypto")]
+//! # fn main() {
+//! use rfc822_like::debian::release::Release;
+//! use rfc822_like::debian::verify::verify;
+//!
+//! let dir = "/var/lib/apt/mirror/dists/bookworm";
+//! let release = std::fs::read_to_string(format!("{}/Release", dir)).unwrap();
+//! let release = rfc822_like::from_str::<Release>(&release).unwrap();
+//! let report = verify(&release, dir, sha2::Sha256::default);
+//! for file in report.mismatches() {
+//!     eprintln!("{} is corrupted: {}", file.path, file.status);
+//! }
+//! # }
+//! # #[cfg(not(feature = "rustcrypto"))]
+//! # fn main() {}
+//! ```
+
+use std::fmt;
+use std::fs;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use crate::digest::{digest_reader, Digest};
+use super::checksums::Checksum;
+use super::release::{IndexFile, Release, ReleaseChecksum};
+
+/// Result of verification of a single file.
+#[derive(Debug)]
+pub enum FileStatus {
+    /// The size and the digest match.
+    Match,
+    /// The file doesn't exist.
+    Missing,
+    /// The size of the file is different.
+    SizeMismatch {
+        /// Size listed in the `Release` file.
+        expected: u64,
+        /// Size of the file on disk.
+        actual: u64,
+    },
+    /// The digest of the file is different.
+    DigestMismatch {
+        /// Name of the algorithm, e.g. `SHA256`.
+        algorithm: &'static str,
+    },
+    /// The `Release` file doesn't list the digest of the file for the algorithm used.
+    DigestMissing {
+        /// Name of the algorithm, e.g. `SHA256`.
+        algorithm: &'static str,
+    },
+    /// Reading the file failed.
+    Io(io::Error),
+}
+
+impl FileStatus {
+    /// Returns `true` if the file matches.
+    pub fn is_match(&self) -> bool {
+        match self {
+            FileStatus::Match => true,
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if the file is missing.
+    pub fn is_missing(&self) -> bool {
+        match self {
+            FileStatus::Missing => true,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for FileStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FileStatus::Match => f.write_str("ok"),
+            FileStatus::Missing => f.write_str("missing"),
+            FileStatus::SizeMismatch { expected, actual, } => write!(f, "size mismatch: expected {} bytes, found {} bytes", expected, actual),
+            FileStatus::DigestMismatch { algorithm, } => write!(f, "{} digest mismatch", algorithm),
+            FileStatus::DigestMissing { algorithm, } => write!(f, "{} digest not listed", algorithm),
+            FileStatus::Io(error) => write!(f, "I/O error: {}", error),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+enum OpenErrorInner {
+    #[error("{0} is not listed in the Release file")]
+    NotListed(String),
+    #[error("{0} is missing")]
+    Missing(String),
+    #[error("{location} is invalid: {status}")]
+    Invalid { location: PathBuf, status: FileStatus, },
+}
+
+/// Error returned by [`open_by_hash`].
+#[derive(Debug, thiserror::Error)]
+#[error(transparent)]
+pub struct OpenError(#[from] OpenErrorInner);
+
+/// Verification result of a single file listed in the `Release` file.
+#[derive(Debug)]
+pub struct FileReport {
+    /// Path as listed in the `Release` file.
+    pub path: String,
+    /// Path of the file that was checked, `None` if the file is missing.
+    pub location: Option<PathBuf>,
+    /// The result.
+    pub status: FileStatus,
+}
+
+/// Verification results of all files listed in the `Release` file, sorted by path.
+#[derive(Debug, Default)]
+pub struct Report {
+    /// Individual results.
+    pub files: Vec<FileReport>,
+}
+
+impl Report {
+    /// Returns `true` if all listed files exist and match.
+    pub fn is_ok(&self) -> bool {
+        self.files.iter().all(|file| file.status.is_match())
+    }
+
+    /// Iterates over matching files.
+    pub fn matches(&self) -> impl Iterator<Item=&FileReport> {
+        self.files.iter().filter(|file| file.status.is_match())
+    }
+
+    /// Iterates over files that exist but don't match or can't be read or verified.
+    pub fn mismatches(&self) -> impl Iterator<Item=&FileReport> {
+        self.files.iter().filter(|file| !(file.status.is_match() || file.status.is_missing()))
+    }
+
+    /// Iterates over missing files.
+    ///
+    /// Note that repositories often list uncompressed indexes without providing them.
+    pub fn missing(&self) -> impl Iterator<Item=&FileReport> {
+        self.files.iter().filter(|file| file.status.is_missing())
+    }
+}
+
+/// Verifies all files listed in the `Release` file.
+///
+/// `root` is the directory containing the `Release` file.
+/// `new_digest` creates the digest for each file, e.g. `sha2::Sha256::default`.
+/// Files not listing the digest of its algorithm are reported as [`FileStatus::DigestMissing`].
+pub fn verify<D, F, P>(release: &Release, root: P, mut new_digest: F) -> Report
+where D: Digest, D::Output: ReleaseChecksum, F: FnMut() -> D, P: AsRef<Path> {
+    let root = root.as_ref();
+    let by_hash = release.acquire_by_hash();
+    let files = release.files()
+        .into_iter()
+        .map(|(_, file)| verify_file(&file, root, by_hash, new_digest()))
+        .collect();
+    Report { files, }
+}
+
+/// Verifies a single file listed in the `Release` file using `digest`.
+///
+/// `root` is the directory containing the `Release` file.
+/// If `by_hash` is `true` the `by-hash` locations are tried when the file is missing.
+pub fn verify_file<D>(file: &IndexFile<'_>, root: &Path, by_hash: bool, digest: D) -> FileReport
+where D: Digest, D::Output: ReleaseChecksum {
+    let mut candidates = vec![root.join(file.path)];
+    if by_hash {
+        candidates.extend(file.by_hash_paths().into_iter().map(|path| root.join(path)));
+    }
+
+    for candidate in candidates {
+        match fs::File::open(&candidate) {
+            Ok(opened) => {
+                let status = check(file, opened, digest).unwrap_or_else(FileStatus::Io);
+                return FileReport { path: file.path.to_owned(), location: Some(candidate), status, };
+            },
+            Err(error) if error.kind() == io::ErrorKind::NotFound => (),
+            Err(error) => return FileReport { path: file.path.to_owned(), location: Some(candidate), status: FileStatus::Io(error), },
+        }
+    }
+    FileReport { path: file.path.to_owned(), location: None, status: FileStatus::Missing, }
+}
+
+/// Opens the index file `path` listed in the `Release` file and verifies it.
+///
+/// `root` is the directory containing the `Release` file.
+/// The `by-hash` locations are tried first, strongest digest first, falling back to the canonical
+/// path.
+/// Unlike canonical paths, `by-hash` files don't change while the mirror is being updated, so
+/// the index can't be replaced between fetching the `Release` file and the index.
+///
+/// The whole file is read to verify its size and `digest`, the returned file is positioned at
+/// the start, ready to be read or decompressed.
+///
+/// # Example
+///
+/// ```no_run
+/// # #[cfg(feature = "rustcrypto")]
+/// # fn main() {
+/// use std::io::Read;
+/// use rfc822_like::debian::release::Release;
+/// use rfc822_like::debian::verify::open_by_hash;
+///
+/// let dir = "/var/lib/apt/mirror/dists/bookworm";
+/// let release = std::fs::read_to_string(format!("{}/Release", dir)).unwrap();
+/// let release = rfc822_like::from_str::<Release>(&release).unwrap();
+/// let mut index = String::new();
+/// open_by_hash(&release, dir, "main/i18n/Index", sha2::Sha256::default()).unwrap().read_to_string(&mut index).unwrap();
+/// # }
+/// # #[cfg(not(feature = "rustcrypto"))]
+/// # fn main() {}
+/// ```
+pub fn open_by_hash<D, P>(release: &Release, root: P, path: &str, digest: D) -> Result<fs::File, OpenError>
+where D: Digest, D::Output: ReleaseChecksum, P: AsRef<Path> {
+    let file = release.file(path).ok_or_else(|| OpenErrorInner::NotListed(path.to_owned()))?;
+    let root = root.as_ref();
+    let candidates = file.by_hash_paths()
+        .into_iter()
+        .map(|path| root.join(path))
+        .chain(std::iter::once(root.join(file.path)));
+
+    for candidate in candidates {
+        match fs::File::open(&candidate) {
+            Ok(mut opened) => {
+                let status = check(&file, &mut opened, digest)
+                    .and_then(|status| opened.seek(SeekFrom::Start(0)).map(|_| status))
+                    .unwrap_or_else(FileStatus::Io);
+                if !status.is_match() {
+                    return Err(OpenErrorInner::Invalid { location: candidate, status, }.into());
+                }
+                return Ok(opened);
+            },
+            Err(error) if error.kind() == io::ErrorKind::NotFound => (),
+            Err(error) => return Err(OpenErrorInner::Invalid { location: candidate, status: FileStatus::Io(error), }.into()),
+        }
+    }
+    Err(OpenErrorInner::Missing(path.to_owned()).into())
+}
+
+fn check<D, R>(file: &IndexFile<'_>, reader: R, digest: D) -> io::Result<FileStatus>
+where D: Digest, D::Output: ReleaseChecksum, R: Read {
+    let algorithm = <D::Output as Checksum>::NAME;
+    let expected = match <D::Output as ReleaseChecksum>::of_file(file) {
+        Some(expected) => expected,
+        None => return Ok(FileStatus::DigestMissing { algorithm, }),
+    };
+    let (size, actual) = digest_reader(digest, reader)?;
+    if size != file.size {
+        return Ok(FileStatus::SizeMismatch { expected: file.size, actual: size, });
+    }
+    if actual != *expected {
+        return Ok(FileStatus::DigestMismatch { algorithm, });
+    }
+    Ok(FileStatus::Match)
+}
+
+#[cfg(all(test, feature = "rustcrypto"))]
+mod tests {
+    use std::io::Read;
+    use super::{open_by_hash, verify};
+    use crate::debian::release::Release;
+    use crate::testing::temp_dir::TempDir;
+
+    #[test]
+    fn verify_dir() {
+        let root = TempDir::new("verify");
+        let by_hash = root.join("main/binary-all/by-hash/SHA256");
+        std::fs::create_dir_all(&by_hash).unwrap();
+        std::fs::write(root.join("main/binary-all/Packages"), "hello\n").unwrap();
+        std::fs::write(root.join("main/binary-all/Release"), "hello!\n").unwrap();
+        std::fs::write(root.join("main/binary-all/Sources"), "hel\n").unwrap();
+        std::fs::write(by_hash.join("98ea6e4f216f2fb4b69fff9b3a44842c38686ca685f3f55dc48c5d3fb1107be4"), "hi\n").unwrap();
+
+        let input = "\
+Acquire-By-Hash: yes
+MD5Sum:
+ b1946ac92492d2347c6235b4d2611184 6 main/binary-all/Packages
+ b1946ac92492d2347c6235b4d2611184 7 main/binary-all/Release
+SHA256:
+ 5891b5b522d5df086d0ff0b110fbd9d21bb4fc7163af34d08286a2e846f6be03 6 main/binary-all/Packages
+ 5891b5b522d5df086d0ff0b110fbd9d21bb4fc7163af34d08286a2e846f6be03 7 main/binary-all/Release
+ 5891b5b522d5df086d0ff0b110fbd9d21bb4fc7163af34d08286a2e846f6be03 5 main/binary-all/Sources
+ 98ea6e4f216f2fb4b69fff9b3a44842c38686ca685f3f55dc48c5d3fb1107be4 3 main/binary-all/Packages.xz
+ e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855 0 main/i18n/Index
+";
+        let release = crate::from_str::<Release>(input).unwrap();
+        let report = verify(&release, &root, sha2::Sha256::default);
+        let md5 = verify(&release, &root, md5::Md5::default);
+
+        let statuses = report.files.iter().map(|file| (file.path.as_str(), file.status.to_string())).collect::<Vec<_>>();
+        assert_eq!(statuses, [
+            ("main/binary-all/Packages", "ok".to_owned()),
+            ("main/binary-all/Packages.xz", "ok".to_owned()),
+            ("main/binary-all/Release", "SHA256 digest mismatch".to_owned()),
+            ("main/binary-all/Sources", "size mismatch: expected 5 bytes, found 4 bytes".to_owned()),
+            ("main/i18n/Index", "missing".to_owned()),
+        ]);
+        assert!(report.files[1].location.as_ref().unwrap().ends_with("by-hash/SHA256/98ea6e4f216f2fb4b69fff9b3a44842c38686ca685f3f55dc48c5d3fb1107be4"));
+        assert!(!report.is_ok());
+        assert_eq!(report.matches().count(), 2);
+        assert_eq!(report.mismatches().count(), 2);
+        assert_eq!(report.missing().next().unwrap().path, "main/i18n/Index");
+
+        let statuses = md5.files.iter().map(|file| (file.path.as_str(), file.status.to_string())).collect::<Vec<_>>();
+        assert_eq!(statuses, [
+            ("main/binary-all/Packages", "ok".to_owned()),
+            ("main/binary-all/Packages.xz", "MD5 digest not listed".to_owned()),
+            ("main/binary-all/Release", "MD5 digest mismatch".to_owned()),
+            ("main/binary-all/Sources", "MD5 digest not listed".to_owned()),
+            ("main/i18n/Index", "missing".to_owned()),
+        ]);
+    }
+
+    #[test]
+    fn pluggable_digest() {
+        use crate::debian::checksums::{FileEntry, Sha256};
+
+        let entry = FileEntry::compute("Packages".to_owned(), b"hello\n", sha2::Sha256::default());
+        assert_eq!(entry.digest, "5891b5b522d5df086d0ff0b110fbd9d21bb4fc7163af34d08286a2e846f6be03".parse::<Sha256>().unwrap());
+        assert_eq!(entry.size, 6);
+        assert!(entry.verify(sha2::Sha256::default(), &b"hello\n"[..]).unwrap());
+        assert!(!entry.verify(sha2::Sha256::default(), &b"hello!\n"[..]).unwrap());
+
+        let document = crate::document::Document::parse("# comment\nPackage: foo\n").unwrap();
+        let digest = document.paragraphs()[0].digest(md5::Md5::default());
+        assert_eq!(digest, FileEntry::compute(String::new(), b"Package: foo\n", md5::Md5::default()).digest);
+    }
+
+    #[test]
+    fn open_index_by_hash() {
+        let root = TempDir::new("by-hash");
+        let by_hash = root.join("main/binary-all/by-hash/SHA256");
+        std::fs::create_dir_all(&by_hash).unwrap();
+        std::fs::write(root.join("main/binary-all/Packages"), "changed\n").unwrap();
+        std::fs::write(root.join("main/binary-all/Sources"), "hello\n").unwrap();
+        std::fs::write(by_hash.join("5891b5b522d5df086d0ff0b110fbd9d21bb4fc7163af34d08286a2e846f6be03"), "hello\n").unwrap();
+        std::fs::write(by_hash.join("98ea6e4f216f2fb4b69fff9b3a44842c38686ca685f3f55dc48c5d3fb1107be4"), "hx\n").unwrap();
+
+        let input = "\
+SHA256:
+ 5891b5b522d5df086d0ff0b110fbd9d21bb4fc7163af34d08286a2e846f6be03 6 main/binary-all/Packages
+ 98ea6e4f216f2fb4b69fff9b3a44842c38686ca685f3f55dc48c5d3fb1107be4 3 main/binary-all/Packages.xz
+ 5891b5b522d5df086d0ff0b110fbd9d21bb4fc7163af34d08286a2e846f6be03 6 main/binary-all/Sources
+ e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855 0 main/i18n/Index
+";
+        let release = crate::from_str::<Release>(input).unwrap();
+        let read = |path| {
+            let mut contents = String::new();
+            open_by_hash(&release, &root, path, sha2::Sha256::default()).map(|mut file| {
+                file.read_to_string(&mut contents).unwrap();
+                contents
+            })
+        };
+        let packages = read("main/binary-all/Packages");
+        let sources = read("main/binary-all/Sources");
+        let corrupted = read("main/binary-all/Packages.xz");
+        let missing = read("main/i18n/Index");
+        let unlisted = read("main/binary-all/Release");
+
+        assert_eq!(packages.unwrap(), "hello\n");
+        assert_eq!(sources.unwrap(), "hello\n");
+        assert!(corrupted.unwrap_err().to_string().ends_with("98ea6e4f216f2fb4b69fff9b3a44842c38686ca685f3f55dc48c5d3fb1107be4 is invalid: SHA256 digest mismatch"));
+        assert_eq!(missing.unwrap_err().to_string(), "main/i18n/Index is missing");
+        assert_eq!(unlisted.unwrap_err().to_string(), "main/binary-all/Release is not listed in the Release file");
+    }
+}
@@ -0,0 +1,282 @@
+//! Debian package versions.
+//!
+//! The format is `[epoch:]upstream_version[-debian_revision]` as described in
+//! [Debian policy](https://www.debian.org/doc/debian-policy/ch-controlfields.html#version).
+//!
+//! # Example
+//!
+//! ```
+//! use rfc822_like::debian::Version;
+//!
+//! let version = "1:2.0~rc1-3".parse::<Version>().unwrap();
+//! assert_eq!(version.epoch(), 1);
+//! assert_eq!(version.upstream(), "2.0~rc1");
+//! assert_eq!(version.revision(), Some("3"));
+//! assert!(version < "1:2.0-1".parse().unwrap());
+//! ```
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, thiserror::Error)]
+enum ErrorInner {
+    #[error("invalid epoch '{0}'")]
+    InvalidEpoch(String),
+    #[error("empty upstream version")]
+    EmptyUpstream,
+    #[error("empty Debian revision")]
+    EmptyRevision,
+    #[error("invalid character '{0}' in version")]
+    InvalidChar(char),
+}
+
+/// Error returned when parsing a version fails.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error(transparent)]
+pub struct ParseError(#[from] ErrorInner);
+
+/// Debian package version.
+///
+/// Versions are ordered using the same algorithm `dpkg` uses.
+/// Equality is consistent with the ordering so e.g. `1.0` and `1.00` are equal.
+/// Zero epoch is not written when the version is displayed.
+#[derive(Debug, Clone)]
+pub struct Version {
+    epoch: u32,
+    upstream: String,
+    revision: Option<String>,
+}
+
+fn check_chars(s: &str, allowed: &str) -> Result<(), ParseError> {
+    match s.chars().find(|c| !c.is_ascii_alphanumeric() && !allowed.contains(*c)) {
+        Some(c) => Err(ErrorInner::InvalidChar(c).into()),
+        None => Ok(()),
+    }
+}
+
+impl Version {
+    /// Creates the version from its components checking they are valid.
+    pub fn new<U: Into<String>>(epoch: u32, upstream: U, revision: Option<String>) -> Result<Self, ParseError> {
+        let upstream = upstream.into();
+        if upstream.is_empty() {
+            return Err(ErrorInner::EmptyUpstream.into());
+        }
+        let upstream_allowed = match (epoch, &revision) {
+            (0, None) => ".+~",
+            (0, Some(_)) => ".+~-",
+            (_, None) => ".+~:",
+            (_, Some(_)) => ".+~-:",
+        };
+        check_chars(&upstream, upstream_allowed)?;
+        if let Some(revision) = &revision {
+            if revision.is_empty() {
+                return Err(ErrorInner::EmptyRevision.into());
+            }
+            check_chars(revision, ".+~")?;
+        }
+        Ok(Version { epoch, upstream, revision, })
+    }
+
+    /// The epoch, zero if not present.
+    pub fn epoch(&self) -> u32 {
+        self.epoch
+    }
+
+    /// The upstream part of the version.
+    pub fn upstream(&self) -> &str {
+        &self.upstream
+    }
+
+    /// The Debian revision if present.
+    ///
+    /// Native packages don't have a revision.
+    pub fn revision(&self) -> Option<&str> {
+        self.revision.as_ref().map(AsRef::as_ref)
+    }
+}
+
+impl FromStr for Version {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (epoch, rest) = match s.find(':') {
+            Some(pos) => {
+                let epoch = &s[..pos];
+                let epoch = epoch.parse().map_err(|_| ErrorInner::InvalidEpoch(epoch.to_owned()))?;
+                (epoch, &s[(pos + 1)..])
+            },
+            None => (0, s),
+        };
+        let (upstream, revision) = match rest.rfind('-') {
+            Some(pos) => (&rest[..pos], Some(rest[(pos + 1)..].to_owned())),
+            None => (rest, None),
+        };
+        Version::new(epoch, upstream, revision)
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.epoch != 0 {
+            write!(f, "{}:", self.epoch)?;
+        }
+        f.write_str(&self.upstream)?;
+        if let Some(revision) = &self.revision {
+            write!(f, "-{}", revision)?;
+        }
+        Ok(())
+    }
+}
+
+// Weight of a non-digit character, end of string and digits have 0
+fn order(c: Option<&u8>) -> i32 {
+    match c {
+        None => 0,
+        Some(b'~') => -1,
+        Some(c) if c.is_ascii_digit() => 0,
+        Some(c) if c.is_ascii_alphabetic() => i32::from(*c),
+        Some(c) => i32::from(*c) + 256,
+    }
+}
+
+fn starts_with_digit(s: &[u8]) -> bool {
+    s.first().map_or(false, u8::is_ascii_digit)
+}
+
+fn skip_zeros(mut s: &[u8]) -> &[u8] {
+    while s.first() == Some(&b'0') {
+        s = &s[1..];
+    }
+    s
+}
+
+/// The comparison algorithm of `dpkg` (`verrevcmp`).
+fn compare_part(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a.as_bytes(), b.as_bytes());
+    while !a.is_empty() || !b.is_empty() {
+        while (!a.is_empty() && !starts_with_digit(a)) || (!b.is_empty() && !starts_with_digit(b)) {
+            let (ac, bc) = (order(a.first()), order(b.first()));
+            if ac != bc {
+                return ac.cmp(&bc);
+            }
+            // Equal weights outside of digits means both have a character
+            a = &a[1..];
+            b = &b[1..];
+        }
+        a = skip_zeros(a);
+        b = skip_zeros(b);
+        let mut first_diff = Ordering::Equal;
+        while starts_with_digit(a) && starts_with_digit(b) {
+            if first_diff == Ordering::Equal {
+                first_diff = a[0].cmp(&b[0]);
+            }
+            a = &a[1..];
+            b = &b[1..];
+        }
+        if starts_with_digit(a) {
+            return Ordering::Greater;
+        }
+        if starts_with_digit(b) {
+            return Ordering::Less;
+        }
+        if first_diff != Ordering::Equal {
+            return first_diff;
+        }
+    }
+    Ordering::Equal
+}
+
+/// Hashes the part consistently with `compare_part`.
+fn hash_part<H: Hasher>(s: &str, state: &mut H) {
+    // Strings consisting of zeros only are equal to an empty string
+    if s.bytes().all(|c| c == b'0') {
+        return;
+    }
+    let mut s = s.as_bytes();
+    while !s.is_empty() {
+        let len = s.iter().position(u8::is_ascii_digit).unwrap_or(s.len());
+        s[..len].hash(state);
+        s = skip_zeros(&s[len..]);
+        let len = s.iter().position(|c| !c.is_ascii_digit()).unwrap_or(s.len());
+        s[..len].hash(state);
+        s = &s[len..];
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.epoch.cmp(&other.epoch)
+            .then_with(|| compare_part(&self.upstream, &other.upstream))
+            .then_with(|| compare_part(self.revision().unwrap_or(""), other.revision().unwrap_or("")))
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Version {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Version {}
+
+impl Hash for Version {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.epoch.hash(state);
+        hash_part(&self.upstream, state);
+        hash_part(self.revision().unwrap_or(""), state);
+    }
+}
+
+serde_via_str!(Version, "a Debian version");
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use super::Version;
+
+    fn v(s: &str) -> Version {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn ordering() {
+        let ordered = ["1.0~rc1", "1.0", "1.0-0.1", "1.0-1", "1.0a", "1.0+b1", "1.0.1", "1.2", "1.10", "2:0.1"];
+        for pair in ordered.windows(2) {
+            assert!(v(pair[0]) < v(pair[1]), "{} < {}", pair[0], pair[1]);
+        }
+        assert!(v("1.0~~") < v("1.0~"));
+        assert_eq!(v("1.0"), v("1.00"));
+        assert_eq!(v("1.0"), v("0:1.0-0"));
+        assert_eq!(v("a"), v("a0"));
+
+        let set = ["1.0", "1.00", "0:1.0-0", "01.0-00"].iter().map(|s| v(s)).collect::<HashSet<_>>();
+        assert_eq!(set.len(), 1);
+    }
+
+    #[test]
+    fn parse() {
+        let version = v("2:1.0-2-3");
+        assert_eq!(version.epoch(), 2);
+        assert_eq!(version.upstream(), "1.0-2");
+        assert_eq!(version.revision(), Some("3"));
+        assert_eq!(version.to_string(), "2:1.0-2-3");
+        assert_eq!(v("0:1.0").to_string(), "1.0");
+        assert_eq!(v("1:2:3").upstream(), "2:3");
+
+        assert_eq!("".parse::<Version>().unwrap_err().to_string(), "empty upstream version");
+        assert_eq!("1.0-".parse::<Version>().unwrap_err().to_string(), "empty Debian revision");
+        assert_eq!("x:1.0".parse::<Version>().unwrap_err().to_string(), "invalid epoch 'x'");
+        assert_eq!("1_0".parse::<Version>().unwrap_err().to_string(), "invalid character '_' in version");
+        assert!("1:0-1+b1".parse::<Version>().is_ok());
+        assert!("1:0-1:1".parse::<Version>().is_err());
+    }
+}
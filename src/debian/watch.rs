@@ -0,0 +1,292 @@
+//! Watching index files and reporting changes of packages.
+//!
+//! Daemons tracking the state of repositories need to react when apt updates its lists or a
+//! mirror syncs.
+//! [`Watcher`] monitors index files or whole directories such as `/var/lib/apt/lists`, parses
+//! the files again when they change and sends an [`Update`] to all subscribers.
+//! Each update carries the old and new records which [`Update::diff`] compares using the
+//! [`compare`](super::compare) module.
+//!
+//! Files are watched through their directory so they are still tracked after being replaced by
+//! renaming a new file over them, which is what apt does.
+//!
+//! This module requires the `notify` and `fs` features.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use std::time::Duration;
+//! use rfc822_like::debian::packages::Record;
+//! use rfc822_like::debian::watch::Watcher;
+//!
+//! let mut watcher = Watcher::<Record>::new(Duration::from_secs(1)).unwrap();
+//! watcher.watch_dir("/var/lib/apt/lists", "_Packages").unwrap();
+//! loop {
+//!     let update = watcher.next_update().unwrap();
+//!     for change in update.diff().upgraded {
+//!         println!("{}", change);
+//!     }
+//! }
+//! ```
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc};
+use std::time::{Duration, Instant};
+use notify::{DebouncedEvent, RecursiveMode};
+use serde::de::DeserializeOwned;
+use super::compare::{compare, Identity, IndexDiff};
+
+#[derive(Debug, thiserror::Error)]
+enum ErrorInner {
+    #[error("failed to access {path}")]
+    Access { path: PathBuf, #[source] error: std::io::Error, },
+    #[error("failed to watch {path}")]
+    Watch { path: PathBuf, #[source] error: notify::Error, },
+    #[error("failed to load an index")]
+    Load(#[source] crate::ReadFileError),
+    #[error("failed to receive file system events")]
+    Notify(#[source] notify::Error),
+    #[error("the file system watcher stopped")]
+    Disconnected,
+}
+
+/// Error returned when watching or loading indexes fails.
+#[derive(Debug, thiserror::Error)]
+#[error(transparent)]
+pub struct Error(#[from] ErrorInner);
+
+/// Change of a watched index.
+#[derive(Debug)]
+pub struct Update<R> {
+    /// Path of the index.
+    pub path: PathBuf,
+    /// Records before the change, empty if the file was created.
+    pub old: Arc<Vec<R>>,
+    /// Records after the change, empty if the file was removed.
+    pub new: Arc<Vec<R>>,
+}
+
+impl<R: Identity> Update<R> {
+    /// Compares the old and new records.
+    pub fn diff(&self) -> IndexDiff<'_, R> {
+        compare(&self.old, &self.new)
+    }
+}
+
+/// Watcher of index files sending updates to subscribers.
+///
+/// The watcher doesn't spawn any thread processing the changes, call
+/// [`next_update`](Self::next_update) in a loop to process them.
+pub struct Watcher<R> {
+    watcher: notify::RecommendedWatcher,
+    events: mpsc::Receiver<DebouncedEvent>,
+    // Explicitly watched files
+    files: Vec<PathBuf>,
+    // Watched directories and suffixes of names of index files in them
+    dirs: Vec<(PathBuf, String)>,
+    indexes: BTreeMap<PathBuf, Arc<Vec<R>>>,
+    subscribers: Vec<mpsc::Sender<Arc<Update<R>>>>,
+    // Updates caused by a single event are returned one by one
+    pending: VecDeque<Arc<Update<R>>>,
+}
+
+fn canonicalize(path: &Path) -> Result<PathBuf, Error> {
+    fs::canonicalize(path).map_err(|error| ErrorInner::Access { path: path.to_owned(), error, }.into())
+}
+
+impl<R: Identity + DeserializeOwned> Watcher<R> {
+    /// Creates a watcher waiting `delay` after a change so that writes in progress are finished.
+    pub fn new(delay: Duration) -> Result<Self, Error> {
+        let (sender, events) = mpsc::channel();
+        let watcher = notify::watcher(sender, delay).map_err(ErrorInner::Notify)?;
+        Ok(Watcher {
+            watcher,
+            events,
+            files: Vec::new(),
+            dirs: Vec::new(),
+            indexes: BTreeMap::new(),
+            subscribers: Vec::new(),
+            pending: VecDeque::new(),
+        })
+    }
+
+    fn watch(&mut self, dir: &Path) -> Result<(), Error> {
+        notify::Watcher::watch(&mut self.watcher, dir, RecursiveMode::NonRecursive)
+            .map_err(|error| ErrorInner::Watch { path: dir.to_owned(), error, }.into())
+    }
+
+    /// Loads the index file at `path` and starts watching it.
+    ///
+    /// The file doesn't have to exist yet, its directory does.
+    pub fn watch_file<P: AsRef<Path>>(&mut self, path: P) -> Result<(), Error> {
+        let path = path.as_ref();
+        let dir = match path.parent() {
+            Some(dir) if dir != Path::new("") => canonicalize(dir)?,
+            _ => canonicalize(Path::new("."))?,
+        };
+        let name = path.file_name().ok_or_else(|| ErrorInner::Access { path: path.to_owned(), error: std::io::ErrorKind::InvalidInput.into(), })?;
+        self.watch(&dir)?;
+        let path = dir.join(name);
+        self.load(&path)?;
+        self.files.push(path);
+        Ok(())
+    }
+
+    /// Loads index files in `dir` with names ending with `suffix` and starts watching them.
+    ///
+    /// Files created later are watched too.
+    pub fn watch_dir<P: AsRef<Path>>(&mut self, dir: P, suffix: &str) -> Result<(), Error> {
+        let dir = canonicalize(dir.as_ref())?;
+        self.watch(&dir)?;
+        self.dirs.push((dir.clone(), suffix.to_owned()));
+        let entries = fs::read_dir(&dir).map_err(|error| ErrorInner::Access { path: dir.clone(), error, })?;
+        for entry in entries {
+            let path = entry.map_err(|error| ErrorInner::Access { path: dir.clone(), error, })?.path();
+            if self.is_watched(&path) {
+                self.load(&path)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns a receiver of updates of all watched indexes.
+    ///
+    /// Dropping the receiver unsubscribes.
+    pub fn subscribe(&mut self) -> mpsc::Receiver<Arc<Update<R>>> {
+        let (sender, receiver) = mpsc::channel();
+        self.subscribers.push(sender);
+        receiver
+    }
+
+    /// Iterates over the current records of all loaded indexes.
+    pub fn indexes(&self) -> impl Iterator<Item=(&Path, &[R])> {
+        self.indexes.iter().map(|(path, records)| (path.as_path(), records.as_slice()))
+    }
+
+    /// Waits for the next change of packages, sends it to subscribers and returns it.
+    ///
+    /// Changes not affecting the versions of packages (e.g. touching the file) are not reported.
+    /// If an index fails to load an error is returned and its old records are kept.
+    pub fn next_update(&mut self) -> Result<Arc<Update<R>>, Error> {
+        loop {
+            if let Some(update) = self.pending.pop_front() {
+                return Ok(update);
+            }
+            let event = self.events.recv().map_err(|_| ErrorInner::Disconnected)?;
+            self.handle(event)?;
+        }
+    }
+
+    /// Like [`next_update`](Self::next_update) but returns `None` if nothing changed during
+    /// `timeout`.
+    pub fn next_update_timeout(&mut self, timeout: Duration) -> Result<Option<Arc<Update<R>>>, Error> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(update) = self.pending.pop_front() {
+                return Ok(Some(update));
+            }
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match self.events.recv_timeout(remaining) {
+                Ok(event) => self.handle(event)?,
+                Err(mpsc::RecvTimeoutError::Timeout) => return Ok(None),
+                Err(mpsc::RecvTimeoutError::Disconnected) => return Err(ErrorInner::Disconnected.into()),
+            }
+        }
+    }
+
+    fn is_watched(&self, path: &Path) -> bool {
+        self.files.iter().any(|file| file == path) || self.dirs.iter().any(|(dir, suffix)| {
+            path.parent() == Some(dir) && path.file_name().and_then(|name| name.to_str()).map_or(false, |name| name.ends_with(suffix.as_str()))
+        })
+    }
+
+    fn handle(&mut self, event: DebouncedEvent) -> Result<(), Error> {
+        match event {
+            DebouncedEvent::Create(path) | DebouncedEvent::Write(path) | DebouncedEvent::Chmod(path) | DebouncedEvent::Remove(path) => self.reload(path),
+            DebouncedEvent::Rename(from, to) => {
+                self.reload(from)?;
+                self.reload(to)
+            },
+            DebouncedEvent::Rescan => {
+                let mut paths = self.indexes.keys().cloned().collect::<Vec<_>>();
+                paths.extend(self.files.iter().cloned());
+                for (dir, _) in &self.dirs {
+                    let entries = fs::read_dir(dir).map_err(|error| ErrorInner::Access { path: dir.clone(), error, })?;
+                    paths.extend(entries.filter_map(Result::ok).map(|entry| entry.path()));
+                }
+                paths.sort();
+                paths.dedup();
+                for path in paths {
+                    self.reload(path)?;
+                }
+                Ok(())
+            },
+            DebouncedEvent::Error(error, _) => Err(ErrorInner::Notify(error).into()),
+            DebouncedEvent::NoticeWrite(_) | DebouncedEvent::NoticeRemove(_) => Ok(()),
+        }
+    }
+
+    /// Loads the index, returning the previous records.
+    fn load(&mut self, path: &Path) -> Result<Arc<Vec<R>>, Error> {
+        let empty = || Arc::new(Vec::new());
+        if !path.exists() {
+            return Ok(self.indexes.remove(path).unwrap_or_else(empty));
+        }
+        let records = crate::from_file::<Vec<R>, _>(path).map_err(ErrorInner::Load)?;
+        Ok(self.indexes.insert(path.to_owned(), Arc::new(records)).unwrap_or_else(empty))
+    }
+
+    fn reload(&mut self, path: PathBuf) -> Result<(), Error> {
+        if !self.is_watched(&path) {
+            return Ok(());
+        }
+        let old = self.load(&path)?;
+        let new = self.indexes.get(&path).cloned().unwrap_or_default();
+        let update = Arc::new(Update { path, old, new, });
+        if !update.diff().is_empty() {
+            self.subscribers.retain(|subscriber| subscriber.send(Arc::clone(&update)).is_ok());
+            self.pending.push_back(update);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use super::Watcher;
+    use crate::debian::packages::Record;
+    use crate::testing::packages::paragraph;
+    use crate::testing::temp_dir::TempDir;
+
+    fn packages(version: &str) -> String {
+        paragraph("foo", version, "all", "")
+    }
+
+    #[test]
+    fn watch_dir() {
+        let root = TempDir::new("watch");
+        std::fs::write(root.join("a_Packages"), packages("1.0")).unwrap();
+
+        let mut watcher = Watcher::<Record>::new(Duration::from_millis(50)).unwrap();
+        watcher.watch_dir(&root, "_Packages").unwrap();
+        let subscriber = watcher.subscribe();
+        assert_eq!(watcher.indexes().count(), 1);
+
+        std::fs::write(root.join("b_Sources"), "not an index").unwrap();
+        std::fs::write(root.join("b_Packages.tmp"), packages("1.1")).unwrap();
+        std::fs::rename(root.join("b_Packages.tmp"), root.join("a_Packages")).unwrap();
+        let update = watcher.next_update_timeout(Duration::from_secs(10)).unwrap().unwrap();
+        assert_eq!(update.diff().upgraded[0].to_string(), "foo:all 1.0 -> 1.1");
+        assert!(update.path.ends_with("a_Packages"));
+        assert_eq!(subscriber.try_recv().unwrap().new[0].version.to_string(), "1.1");
+
+        std::fs::remove_file(root.join("a_Packages")).unwrap();
+        let update = watcher.next_update_timeout(Duration::from_secs(10)).unwrap().unwrap();
+        assert_eq!(update.diff().removed.len(), 1);
+        assert_eq!(watcher.indexes().count(), 0);
+        assert!(watcher.next_update_timeout(Duration::from_millis(200)).unwrap().is_none());
+    }
+}
@@ -0,0 +1,102 @@
+//! Boolean fields such as `Essential`, `Protected` or `Build-Essential`.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// Error returned when the value is neither `yes` nor `no`.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("invalid value '{0}', expected yes or no")]
+pub struct ParseError(String);
+
+/// Boolean written as `yes` or `no`.
+///
+/// The default is `no` which matches the semantics of missing fields, so the type can be used
+/// with `#[serde(default)]`.
+/// Use `#[serde(skip_serializing_if = "YesNo::is_no")]` to not write the field in such case.
+///
+/// # Example
+///
+/// ```
+/// use rfc822_like::debian::YesNo;
+///
+/// #[derive(serde_derive::Deserialize, serde_derive::Serialize)]
+/// #[serde(rename_all = "PascalCase")]
+/// struct Package {
+///     package: String,
+///     #[serde(default, skip_serializing_if = "YesNo::is_no")]
+///     essential: YesNo,
+/// }
+///
+/// let package = rfc822_like::from_str::<Package>("Package: foo\nEssential: yes\n").unwrap();
+/// assert!(*package.essential);
+/// let package = rfc822_like::from_str::<Package>("Package: foo\n").unwrap();
+/// assert_eq!(rfc822_like::to_string(&package).unwrap(), "Package: foo\n");
+/// ```
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct YesNo(pub bool);
+
+impl YesNo {
+    /// Returns `true` if the value is `yes`.
+    pub fn is_yes(&self) -> bool {
+        self.0
+    }
+
+    /// Returns `true` if the value is `no`.
+    pub fn is_no(&self) -> bool {
+        !self.0
+    }
+}
+
+impl std::ops::Deref for YesNo {
+    type Target = bool;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<bool> for YesNo {
+    fn from(value: bool) -> Self {
+        YesNo(value)
+    }
+}
+
+impl From<YesNo> for bool {
+    fn from(value: YesNo) -> Self {
+        value.0
+    }
+}
+
+impl FromStr for YesNo {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "yes" => Ok(YesNo(true)),
+            "no" => Ok(YesNo(false)),
+            _ => Err(ParseError(s.to_owned())),
+        }
+    }
+}
+
+impl fmt::Display for YesNo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(if self.0 { "yes" } else { "no" })
+    }
+}
+
+serde_via_str!(YesNo, "yes or no");
+
+#[cfg(test)]
+mod tests {
+    use super::YesNo;
+
+    #[test]
+    fn parse() {
+        assert_eq!("yes".parse::<YesNo>().unwrap(), YesNo(true));
+        assert_eq!("no".parse::<YesNo>().unwrap(), YesNo(false));
+        assert_eq!("Yes".parse::<YesNo>().unwrap_err().to_string(), "invalid value 'Yes', expected yes or no");
+        assert_eq!(YesNo(true).to_string(), "yes");
+        assert!(YesNo::default().is_no());
+    }
+}
@@ -0,0 +1,88 @@
+//! Abstraction over cryptographic hash functions.
+//!
+//! The crate doesn't hard-code any crypto library for hashing stanzas or checking files.
+//! Instead the functions needing a digest accept any type implementing [`Digest`] so you can
+//! plug in `ring`, `openssl` or anything else your application already depends on.
+//! The `rustcrypto` feature implements the trait for the `md-5`, `sha-1` and `sha2` crates.
+//!
+//! # Example
+//!
+//! ```
+//! use rfc822_like::digest::Digest;
+//! use rfc822_like::document::Document;
+//!
+//! /// Toy checksum, use a real hash function instead.
+//! #[derive(Default)]
+//! struct Sum(u32);
+//!
+//! impl Digest for Sum {
+//!     type Output = u32;
+//!
+//!     fn update(&mut self, data: &[u8]) {
+//!         self.0 = data.iter().fold(self.0, |sum, byte| sum.wrapping_add(u32::from(*byte)));
+//!     }
+//!
+//!     fn finalize(self) -> u32 {
+//!         self.0
+//!     }
+//! }
+//!
+//! let document = Document::parse("Package: foo\n\nPackage: bar\n").unwrap();
+//! assert_eq!(document.paragraphs()[1].digest(Sum::default()), "Package: bar\n".bytes().map(u32::from).sum::<u32>());
+//! ```
+
+use std::fmt;
+use std::io;
+
+/// Incremental computation of a digest.
+pub trait Digest {
+    /// The computed digest.
+    type Output;
+
+    /// Feeds `data` into the digest.
+    fn update(&mut self, data: &[u8]);
+
+    /// Returns the digest of all data fed so far.
+    fn finalize(self) -> Self::Output;
+}
+
+/// Computes both digests at once.
+impl<A: Digest, B: Digest> Digest for (A, B) {
+    type Output = (A::Output, B::Output);
+
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+        self.1.update(data);
+    }
+
+    fn finalize(self) -> Self::Output {
+        (self.0.finalize(), self.1.finalize())
+    }
+}
+
+/// Computes the digest of the whole content of the reader, returning its size too.
+pub fn digest_reader<D: Digest, R: io::Read>(mut digest: D, mut reader: R) -> io::Result<(u64, D::Output)> {
+    let mut buf = [0; 8192];
+    let mut size = 0;
+    loop {
+        let amount = match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(amount) => amount,
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+            Err(error) => return Err(error),
+        };
+        digest.update(&buf[..amount]);
+        size += amount as u64;
+    }
+    Ok((size, digest.finalize()))
+}
+
+/// Feeds formatted text into a digest.
+pub(crate) struct DigestWriter<'a, D>(pub(crate) &'a mut D);
+
+impl<'a, D: Digest> fmt::Write for DigestWriter<'a, D> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0.update(s.as_bytes());
+        Ok(())
+    }
+}
@@ -0,0 +1,88 @@
+//! `Arbitrary` implementations generating only valid documents.
+//!
+//! Writing a generated value and parsing it back always produces an equal value.
+
+use arbitrary::{Arbitrary, Result, Unstructured};
+use super::{Document, Field, Paragraph};
+
+// Printable ASCII except colon
+fn name_char(u: &mut Unstructured<'_>) -> Result<char> {
+    let c = u.int_in_range(b'!'..=b'~')?;
+    Ok(if c == b':' { '_' } else { c as char })
+}
+
+fn name(u: &mut Unstructured<'_>) -> Result<String> {
+    let len = u.int_in_range(0..=15)?;
+    let first = name_char(u)?;
+    // these would be parsed as a comment or look like a continuation in some tools
+    let first = if first == '#' || first == '-' { 'X' } else { first };
+    let mut name = String::with_capacity(len + 1);
+    name.push(first);
+    for _ in 0..len {
+        name.push(name_char(u)?);
+    }
+    Ok(name)
+}
+
+// A line of an unfolded value - no surrounding whitespace, no control characters and not `.`
+fn value_line(u: &mut Unstructured<'_>) -> Result<String> {
+    let line = String::arbitrary(u)?;
+    let line = line.chars().filter(|c| !c.is_control()).collect::<String>();
+    let line = line.trim();
+    Ok(if line == "." { String::new() } else { line.to_owned() })
+}
+
+impl<'a> Arbitrary<'a> for Field {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let name = name(u)?;
+        let mut value = value_line(u)?;
+        let continuations = if value.is_empty() { 0 } else { u.int_in_range(0..=3)? };
+        for _ in 0..continuations {
+            value.push('\n');
+            value.push_str(&value_line(u)?);
+        }
+        Ok(Field::new(name, value))
+    }
+}
+
+impl<'a> Arbitrary<'a> for Paragraph {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        // Empty paragraphs can't be represented in text
+        let mut paragraph = Paragraph::new();
+        paragraph.push(Field::arbitrary(u)?);
+        for field in u.arbitrary_iter::<Field>()? {
+            paragraph.push(field?);
+        }
+        Ok(paragraph)
+    }
+}
+
+impl<'a> Arbitrary<'a> for Document {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        u.arbitrary_iter::<Paragraph>()?.collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arbitrary::{Arbitrary, Unstructured};
+    use crate::document::Document;
+
+    #[test]
+    fn roundtrip() {
+        // Simple xorshift to get varied input without more dependencies
+        let mut state = 0x2545_f491_4f6c_dd1d_u64;
+        let data = std::iter::repeat_with(|| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as u8
+        }).take(1 << 16).collect::<Vec<_>>();
+
+        for chunk in data.chunks(1024) {
+            let document = Document::arbitrary(&mut Unstructured::new(chunk)).unwrap();
+            let text = document.to_string();
+            assert_eq!(Document::parse(&text).unwrap(), document, "{}", text);
+        }
+    }
+}
@@ -0,0 +1,405 @@
+//! Memory-efficient storage of large documents.
+
+use std::collections::HashMap;
+use std::fmt::{self, Write};
+use serde::de::{DeserializeSeed, MapAccess, SeqAccess, Visitor};
+use serde::ser::{SerializeMap, SerializeSeq};
+use crate::events::{Event, Parser};
+use crate::ser::FieldWriter;
+use super::{Document, Error, Field, Paragraph};
+
+#[derive(Debug, Copy, Clone)]
+struct CompactField {
+    // Index into `names`
+    name: usize,
+    // The value starts where the value of the previous field ends
+    value_end: usize,
+}
+
+/// Read-only document storing all values in a single buffer.
+///
+/// [`Document`] keeps separate allocations for the name, value and original text of each field,
+/// which adds up when keeping whole archive indexes in memory.
+/// `CompactDocument` stores each distinct field name once and the unfolded values of all fields
+/// back-to-back in one string so a field costs only a few words on top of its value.
+///
+/// Only the data is stored - comments, spans and the original formatting are dropped.
+/// The document can be parsed, deserialized (e.g. using [`crate::from_reader`] without holding
+/// the whole input in memory) or converted from [`Document`], and serialized or written back.
+///
+/// # Example
+///
+/// ```
+/// use rfc822_like::document::CompactDocument;
+///
+/// let input = "Package: foo\nDescription: The Foo\n It's very foo.\n\nPackage: bar\n";
+/// let document = CompactDocument::parse(input).unwrap();
+/// assert_eq!(document.len(), 2);
+/// assert_eq!(document.get(0).unwrap().get("description"), Some("The Foo\nIt's very foo."));
+/// assert_eq!(document.iter().map(|paragraph| paragraph.get("Package").unwrap()).collect::<Vec<_>>(), ["foo", "bar"]);
+/// assert_eq!(document.to_string(), input);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CompactDocument {
+    names: Vec<String>,
+    name_indices: HashMap<String, usize>,
+    values: String,
+    fields: Vec<CompactField>,
+    // Index one past the last field of each paragraph
+    paragraph_ends: Vec<usize>,
+}
+
+impl CompactDocument {
+    /// Creates an empty document.
+    pub fn new() -> Self {
+        CompactDocument::default()
+    }
+
+    /// Parses the document from a string.
+    ///
+    /// The syntax is the same as in [`Document::parse`].
+    pub fn parse(input: &str) -> Result<Self, Error> {
+        let mut document = CompactDocument::new();
+        for event in Parser::new(input) {
+            match event? {
+                Event::Field { name, value, .. } => document.push_field(name, &value),
+                Event::ParagraphEnd => document.end_paragraph(),
+                Event::ParagraphStart { .. } | Event::Comment { .. } => (),
+            }
+        }
+        document.shrink_to_fit();
+        Ok(document)
+    }
+
+    /// Returns the number of paragraphs.
+    pub fn len(&self) -> usize {
+        self.paragraph_ends.len()
+    }
+
+    /// Returns `true` if the document has no paragraphs.
+    pub fn is_empty(&self) -> bool {
+        self.paragraph_ends.is_empty()
+    }
+
+    /// Returns the paragraph at `index`.
+    pub fn get(&self, index: usize) -> Option<CompactParagraph<'_>> {
+        let end = *self.paragraph_ends.get(index)?;
+        let start = if index == 0 { 0 } else { self.paragraph_ends[index - 1] };
+        Some(CompactParagraph {
+            document: self,
+            start,
+            end,
+        })
+    }
+
+    /// Iterates over the paragraphs.
+    pub fn iter(&self) -> impl Iterator<Item=CompactParagraph<'_>> + '_ {
+        (0..self.len()).map(move |index| self.get(index).expect("index is in range"))
+    }
+
+    /// Appends a paragraph consisting of the given fields.
+    ///
+    /// Values are expected to be unfolded.
+    /// Paragraphs without fields are skipped because they can't be represented in text.
+    pub fn push<N: AsRef<str>, V: AsRef<str>, I: IntoIterator<Item=(N, V)>>(&mut self, fields: I) {
+        for (name, value) in fields {
+            self.push_field(name.as_ref(), value.as_ref());
+        }
+        self.end_paragraph();
+    }
+
+    /// Shrinks the buffers to fit the stored data.
+    pub fn shrink_to_fit(&mut self) {
+        self.values.shrink_to_fit();
+        self.fields.shrink_to_fit();
+        self.paragraph_ends.shrink_to_fit();
+    }
+
+    /// Converts the document to the regular representation.
+    pub fn to_document(&self) -> Document {
+        self.iter().map(|paragraph| paragraph.to_paragraph()).collect()
+    }
+
+    fn intern(&mut self, name: &str) -> usize {
+        if let Some(index) = self.name_indices.get(name) {
+            return *index;
+        }
+        let index = self.names.len();
+        self.names.push(name.to_owned());
+        self.name_indices.insert(name.to_owned(), index);
+        index
+    }
+
+    fn push_field(&mut self, name: &str, value: &str) {
+        let name = self.intern(name);
+        self.values.push_str(value);
+        self.fields.push(CompactField { name, value_end: self.values.len(), });
+    }
+
+    fn end_paragraph(&mut self) {
+        let start = self.paragraph_ends.last().copied().unwrap_or(0);
+        if self.fields.len() > start {
+            self.paragraph_ends.push(self.fields.len());
+        }
+    }
+
+    fn value(&self, index: usize) -> &str {
+        let start = if index == 0 { 0 } else { self.fields[index - 1].value_end };
+        &self.values[start..self.fields[index].value_end]
+    }
+}
+
+impl fmt::Display for CompactDocument {
+    /// Writes the document in the canonical format.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, paragraph) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_str("\n")?;
+            }
+            fmt::Display::fmt(&paragraph, f)?;
+        }
+        Ok(())
+    }
+}
+
+impl PartialEq for CompactDocument {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().zip(other.iter()).all(|(a, b)| a == b)
+    }
+}
+
+impl Eq for CompactDocument {}
+
+impl<'a> From<&'a Document> for CompactDocument {
+    fn from(value: &'a Document) -> Self {
+        let mut document = CompactDocument::new();
+        for paragraph in value {
+            document.push(paragraph.iter().map(|field| (field.name(), field.value())));
+        }
+        document.shrink_to_fit();
+        document
+    }
+}
+
+impl From<Document> for CompactDocument {
+    fn from(value: Document) -> Self {
+        CompactDocument::from(&value)
+    }
+}
+
+/// Paragraph borrowed from [`CompactDocument`].
+#[derive(Debug, Copy, Clone)]
+pub struct CompactParagraph<'a> {
+    document: &'a CompactDocument,
+    start: usize,
+    end: usize,
+}
+
+impl<'a> CompactParagraph<'a> {
+    /// Returns the number of fields.
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// Returns `true` if the paragraph has no fields.
+    ///
+    /// This is never the case for paragraphs of a document, the method exists for consistency.
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// Iterates over names and values of the fields in their original order.
+    pub fn iter(&self) -> impl Iterator<Item=(&'a str, &'a str)> + 'a {
+        let document = self.document;
+        (self.start..self.end).map(move |index| (&*document.names[document.fields[index].name], document.value(index)))
+    }
+
+    /// Returns the value of the first field with given name.
+    ///
+    /// Field names are matched case-insensitively, the same way `dpkg` does it.
+    pub fn get(&self, name: &str) -> Option<&'a str> {
+        self.iter().find(|(field, _)| field.eq_ignore_ascii_case(name)).map(|(_, value)| value)
+    }
+
+    /// Returns the value of the first field with exactly the given name.
+    pub fn get_exact(&self, name: &str) -> Option<&'a str> {
+        self.iter().find(|(field, _)| *field == name).map(|(_, value)| value)
+    }
+
+    /// Converts the paragraph to the regular representation.
+    pub fn to_paragraph(&self) -> Paragraph {
+        self.iter().map(|(name, value)| Field::new(name, value)).collect()
+    }
+}
+
+impl fmt::Display for CompactParagraph<'_> {
+    /// Writes the fields of the paragraph in the canonical format.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, value) in self.iter() {
+            write!(f, "{}: ", name)?;
+            let mut writer = FieldWriter::new(&mut *f, false);
+            writer.write_str(value)?;
+            writer.finish()?;
+        }
+        Ok(())
+    }
+}
+
+impl PartialEq for CompactParagraph<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl Eq for CompactParagraph<'_> {}
+
+impl serde::Serialize for CompactParagraph<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.len()))?;
+        for (name, value) in self.iter() {
+            map.serialize_entry(name, value)?;
+        }
+        map.end()
+    }
+}
+
+impl serde::Serialize for CompactDocument {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.len()))?;
+        for paragraph in self.iter() {
+            seq.serialize_element(&paragraph)?;
+        }
+        seq.end()
+    }
+}
+
+/// Interns the deserialized field name.
+struct NameSeed<'a>(&'a mut CompactDocument);
+
+impl<'de> Visitor<'de> for NameSeed<'_> {
+    type Value = usize;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a field name")
+    }
+
+    fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        Ok(self.0.intern(value))
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for NameSeed<'_> {
+    type Value = usize;
+
+    fn deserialize<D: serde::Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_str(self)
+    }
+}
+
+/// Deserializes the value directly into the buffer of the document.
+struct ValueSeed<'a> {
+    document: &'a mut CompactDocument,
+    name: usize,
+}
+
+impl<'de> Visitor<'de> for ValueSeed<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a string")
+    }
+
+    fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        self.document.values.push_str(value);
+        let value_end = self.document.values.len();
+        self.document.fields.push(CompactField { name: self.name, value_end, });
+        Ok(())
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for ValueSeed<'_> {
+    type Value = ();
+
+    fn deserialize<D: serde::Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_str(self)
+    }
+}
+
+struct ParagraphSeed<'a>(&'a mut CompactDocument);
+
+impl<'de> Visitor<'de> for ParagraphSeed<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a paragraph of fields")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let document = self.0;
+        while let Some(name) = map.next_key_seed(NameSeed(&mut *document))? {
+            map.next_value_seed(ValueSeed { document: &mut *document, name, })?;
+        }
+        document.end_paragraph();
+        Ok(())
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for ParagraphSeed<'_> {
+    type Value = ();
+
+    fn deserialize<D: serde::Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+struct CompactDocumentVisitor;
+
+impl<'de> Visitor<'de> for CompactDocumentVisitor {
+    type Value = CompactDocument;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a sequence of paragraphs")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut document = CompactDocument::new();
+        while seq.next_element_seed(ParagraphSeed(&mut document))?.is_some() {}
+        document.shrink_to_fit();
+        Ok(document)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for CompactDocument {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_seq(CompactDocumentVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CompactDocument;
+    use crate::document::Document;
+
+    #[test]
+    fn compact() {
+        let input = "# comment\nPackage: foo\nDescription: The Foo\n It's very foo.\n .\n Really.\n\nPackage: bar\nVersion: 1.0\n";
+        let document = CompactDocument::parse(input).unwrap();
+        let expected = Document::parse(input).unwrap();
+        assert_eq!(document.to_document(), expected);
+        assert_eq!(CompactDocument::from(&expected), document);
+        assert_eq!(document.names, ["Package", "Description", "Version"]);
+        assert_eq!(document.get(1).unwrap().iter().collect::<Vec<_>>(), [("Package", "bar"), ("Version", "1.0")]);
+        assert_eq!(document.get(0).unwrap().get_exact("description"), None);
+        assert!(document.get(2).is_none());
+        assert_eq!(document.to_string(), "Package: foo\nDescription: The Foo\n It's very foo.\n .\n Really.\n\nPackage: bar\nVersion: 1.0\n");
+
+        let deserialized = crate::from_str::<CompactDocument>(&input[10..]).unwrap();
+        assert_eq!(deserialized, document);
+        assert_eq!(crate::to_string(&deserialized).unwrap(), document.to_string());
+
+        let mut built = CompactDocument::new();
+        built.push(vec![("Package", "foo")]);
+        built.push(Vec::<(&str, &str)>::new());
+        assert_eq!(built.len(), 1);
+    }
+}
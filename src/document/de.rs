@@ -0,0 +1,146 @@
+//! Deserialization of typed values from paragraphs.
+
+use serde::de::{Visitor, MapAccess, DeserializeSeed, IntoDeserializer};
+use crate::de::{Error, ValueDeserializer, ValueSettings};
+use super::Field;
+
+/// Deserializer reading fields of a paragraph.
+///
+/// It records which fields were ignored by the deserialized type.
+pub(crate) struct ParagraphDeserializer<'a> {
+    fields: &'a [Field],
+    settings: &'a ValueSettings,
+    pos: usize,
+    ignored: Vec<bool>,
+}
+
+impl<'a> ParagraphDeserializer<'a> {
+    pub(crate) fn new(fields: &'a [Field], settings: &'a ValueSettings) -> Self {
+        ParagraphDeserializer {
+            fields,
+            settings,
+            pos: 0,
+            ignored: vec![false; fields.len()],
+        }
+    }
+
+    /// Returns a flag for each field which is `true` if the field was ignored.
+    pub(crate) fn into_ignored(self) -> Vec<bool> {
+        self.ignored
+    }
+}
+
+impl<'a, 'b, 'de> serde::Deserializer<'de> for &'b mut ParagraphDeserializer<'a> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_map(self)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+impl<'a, 'b, 'de> MapAccess<'de> for &'b mut ParagraphDeserializer<'a> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error> where K: DeserializeSeed<'de> {
+        match self.fields.get(self.pos) {
+            Some(field) => seed.deserialize(field.name().into_deserializer()).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error> where V: DeserializeSeed<'de> {
+        let field = &self.fields[self.pos];
+        let result = seed.deserialize(TrackingDeserializer {
+            value: field.value(),
+            settings: self.settings,
+            ignored: &mut self.ignored[self.pos],
+        });
+        self.pos += 1;
+        result
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.fields.len() - self.pos)
+    }
+}
+
+/// Value deserializer recording whether the value was ignored.
+struct TrackingDeserializer<'a, 'b> {
+    value: &'a str,
+    settings: &'a ValueSettings,
+    ignored: &'b mut bool,
+}
+
+impl<'a, 'b> TrackingDeserializer<'a, 'b> {
+    fn value_deserializer(self) -> ValueDeserializer<'a> {
+        ValueDeserializer {
+            value: self.value,
+            settings: self.settings,
+            scratch: None,
+        }
+    }
+}
+
+macro_rules! forward_to_value_deserializer {
+    ($($method:ident($($arg:ident: $arg_type:ty),*);)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, $($arg: $arg_type,)* visitor: V) -> Result<V::Value, Self::Error> {
+                self.value_deserializer().$method($($arg,)* visitor)
+            }
+        )*
+    }
+}
+
+impl<'a, 'b, 'de> serde::Deserializer<'de> for TrackingDeserializer<'a, 'b> {
+    type Error = Error;
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        *self.ignored = true;
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, name: &'static str, visitor: V) -> Result<V::Value, Self::Error> {
+        // Values of paragraphs are unfolded so `RawValue` gets an ordinary string
+        if name == crate::de::raw_value::TOKEN {
+            visitor.visit_str(self.value)
+        } else {
+            self.value_deserializer().deserialize_newtype_struct(name, visitor)
+        }
+    }
+
+    forward_to_value_deserializer! {
+        deserialize_any();
+        deserialize_bool();
+        deserialize_i8();
+        deserialize_i16();
+        deserialize_i32();
+        deserialize_i64();
+        deserialize_u8();
+        deserialize_u16();
+        deserialize_u32();
+        deserialize_u64();
+        deserialize_f32();
+        deserialize_f64();
+        deserialize_char();
+        deserialize_str();
+        deserialize_string();
+        deserialize_bytes();
+        deserialize_byte_buf();
+        deserialize_option();
+        deserialize_unit();
+        deserialize_unit_struct(name: &'static str);
+        deserialize_seq();
+        deserialize_tuple(len: usize);
+        deserialize_tuple_struct(name: &'static str, len: usize);
+        deserialize_map();
+        deserialize_struct(name: &'static str, fields: &'static [&'static str]);
+        deserialize_enum(name: &'static str, variants: &'static [&'static str]);
+        deserialize_identifier();
+    }
+}
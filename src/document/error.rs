@@ -0,0 +1,37 @@
+//! Error types related to parsing documents.
+
+use crate::report::{Location, Problem};
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum ErrorInner {
+    #[error("Line {0} doesn't contain a colon")]
+    MissingColon(usize),
+    #[error("Line {0} is a continuation line but there is no field to continue")]
+    OrphanContinuation(usize),
+    #[error("Line {0} contains a field with empty name")]
+    EmptyFieldName(usize),
+    #[cfg(feature = "io")]
+    #[error("I/O error")]
+    IoError(#[from] std::io::Error),
+}
+
+/// Error that can happen during parsing of a document.
+///
+/// The error is encapsulated for the same reasons [`crate::de::Error`] is.
+#[derive(Debug, thiserror::Error)]
+#[error(transparent)]
+pub struct Error(#[from] ErrorInner);
+
+impl Error {
+    /// Converts the error into a problem located at the offending line.
+    pub(crate) fn to_problem(&self) -> Problem {
+        let (line, message) = match &self.0 {
+            ErrorInner::MissingColon(line) => (*line, "line doesn't contain a colon"),
+            ErrorInner::OrphanContinuation(line) => (*line, "continuation line without a field to continue"),
+            ErrorInner::EmptyFieldName(line) => (*line, "field with empty name"),
+            #[cfg(feature = "io")]
+            ErrorInner::IoError(_) => return Problem::error(self.to_string()),
+        };
+        Problem::error(message).at(Location::new().with_line(line))
+    }
+}
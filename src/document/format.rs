@@ -0,0 +1,174 @@
+//! Rendering paragraphs using `dpkg-query --showformat`-style format strings.
+//!
+//! The format string contains literal text and field references `${Field}`, optionally with
+//! width: `${Field;width}`.
+//! Positive width aligns the value to the right, negative width to the left; longer values are
+//! not truncated.
+//! Field names are case-insensitive and missing fields render as empty strings.
+//! Multi-line values are written folded - the same way they appear in the file.
+//! The escape sequences `\n`, `\t` and `\\` are recognized, other characters following `\` are
+//! written as-is.
+//!
+//! # Example
+//!
+//! ```
+//! use rfc822_like::document::{Document, format::Format};
+//!
+//! let document = "Package: foo\nVersion: 1.0\n\nPackage: libbar\nVersion: 2.1-3\n".parse::<Document>().unwrap();
+//! let format = "${Package;-8}|${Version;6}|${Status}\\n".parse::<Format>().unwrap();
+//! assert_eq!(format.render_document(&document), "foo     |   1.0|\nlibbar  | 2.1-3|\n");
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+use super::{Document, Paragraph};
+
+#[derive(Debug, Clone, thiserror::Error)]
+enum ErrorInner {
+    #[error("unterminated field reference starting at position {0}")]
+    Unterminated(usize),
+    #[error("empty field name at position {0}")]
+    EmptyName(usize),
+    #[error("invalid width '{1}' at position {0}")]
+    InvalidWidth(usize, String),
+}
+
+/// Error returned when parsing a format string fails.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error(transparent)]
+pub struct ParseError(#[from] ErrorInner);
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+enum Item {
+    Literal(String),
+    Field { name: String, width: isize, },
+}
+
+/// Parsed format string.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Format {
+    items: Vec<Item>,
+}
+
+impl Format {
+    /// Writes the paragraph formatted according to the format string.
+    pub fn write_to<W: fmt::Write>(&self, paragraph: &Paragraph, mut writer: W) -> fmt::Result {
+        for item in &self.items {
+            match item {
+                Item::Literal(literal) => writer.write_str(literal)?,
+                Item::Field { name, width, } => {
+                    let value = paragraph.get(name).map(fold).unwrap_or_default();
+                    if *width < 0 {
+                        write!(writer, "{:<1$}", value, (-*width) as usize)?;
+                    } else {
+                        write!(writer, "{:>1$}", value, *width as usize)?;
+                    }
+                },
+            }
+        }
+        Ok(())
+    }
+
+    /// Renders the paragraph into a string.
+    pub fn render(&self, paragraph: &Paragraph) -> String {
+        let mut output = String::new();
+        self.write_to(paragraph, &mut output).expect("writing to string never fails");
+        output
+    }
+
+    /// Renders all paragraphs of the document one after another.
+    pub fn render_document(&self, document: &Document) -> String {
+        let mut output = String::new();
+        for paragraph in document.paragraphs() {
+            self.write_to(paragraph, &mut output).expect("writing to string never fails");
+        }
+        output
+    }
+
+    /// Serializes the value and renders it.
+    ///
+    /// The value has to serialize as a struct or a map, see [`Paragraph::from_serialize`].
+    pub fn render_value<T: serde::Serialize + ?Sized>(&self, value: &T) -> Result<String, crate::ser::Error> {
+        Paragraph::from_serialize(value).map(|paragraph| self.render(&paragraph))
+    }
+}
+
+/// Folds the unfolded value back into the form used in files.
+fn fold(value: &str) -> String {
+    let mut lines = value.split('\n');
+    let mut folded = lines.next().unwrap_or("").to_owned();
+    for line in lines {
+        folded.push_str("\n ");
+        folded.push_str(if line.is_empty() { "." } else { line });
+    }
+    folded
+}
+
+impl FromStr for Format {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut items = Vec::new();
+        let mut literal = String::new();
+        let mut chars = s.char_indices().peekable();
+        while let Some((pos, c)) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some((_, 'n')) => literal.push('\n'),
+                    Some((_, 't')) => literal.push('\t'),
+                    Some((_, c)) => literal.push(c),
+                    None => literal.push('\\'),
+                },
+                '$' if chars.peek().map(|(_, c)| *c) == Some('{') => {
+                    let end = s[pos..].find('}').ok_or(ErrorInner::Unterminated(pos))? + pos;
+                    let reference = &s[(pos + 2)..end];
+                    let (name, width) = match reference.find(';') {
+                        Some(separator) => {
+                            let width = reference[(separator + 1)..].trim();
+                            let width = width.parse::<isize>().map_err(|_| ErrorInner::InvalidWidth(pos, width.to_owned()))?;
+                            (reference[..separator].trim(), width)
+                        },
+                        None => (reference.trim(), 0),
+                    };
+                    if name.is_empty() {
+                        return Err(ErrorInner::EmptyName(pos).into());
+                    }
+                    if !literal.is_empty() {
+                        items.push(Item::Literal(std::mem::take(&mut literal)));
+                    }
+                    items.push(Item::Field { name: name.to_owned(), width, });
+                    while chars.peek().map_or(false, |(next, _)| *next <= end) {
+                        chars.next();
+                    }
+                },
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            items.push(Item::Literal(literal));
+        }
+        Ok(Format { items, })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Format;
+    use crate::document::Paragraph;
+
+    #[test]
+    fn render() {
+        let mut paragraph = Paragraph::new();
+        paragraph.set("Package", "foo");
+        paragraph.set("Description", "short\nlong\n\nmore");
+
+        let format = "${package}\\t${Missing;3}$ {x} \\\\ $x\\n${Description}".parse::<Format>().unwrap();
+        assert_eq!(format.render(&paragraph), "foo\t   $ {x} \\ $x\nshort\n long\n .\n more");
+        assert_eq!("${Package;-5}|".parse::<Format>().unwrap().render(&paragraph), "foo  |");
+        assert_eq!("${Package;2}".parse::<Format>().unwrap().render(&paragraph), "foo");
+
+        assert_eq!("x ${Package".parse::<Format>().unwrap_err().to_string(), "unterminated field reference starting at position 2");
+        assert_eq!("${;5}".parse::<Format>().unwrap_err().to_string(), "empty field name at position 0");
+        assert_eq!("${Package;x}".parse::<Format>().unwrap_err().to_string(), "invalid width 'x' at position 0");
+    }
+}
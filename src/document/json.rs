@@ -0,0 +1,102 @@
+//! Conversions between the document model and `serde_json::Value`.
+//!
+//! A [`Paragraph`] maps to a JSON object with string values and a [`Document`] maps to an array of
+//! such objects.
+//! JSON objects can't hold duplicate keys so only the last one of duplicate fields is kept.
+//! Key order is preserved only if `serde_json` has the `preserve_order` feature enabled.
+
+use std::convert::TryFrom;
+use serde_json::{Map, Value};
+use super::{Document, Field, Paragraph};
+
+#[derive(Debug, thiserror::Error)]
+enum ErrorInner {
+    #[error("expected an array of objects")]
+    NotArray,
+    #[error("expected an object (paragraph {0})")]
+    NotObject(usize),
+    #[error("the value of field {0} is not a string")]
+    NonStringValue(String),
+}
+
+/// Error returned when a JSON value doesn't have the shape of a document or paragraph.
+#[derive(Debug, thiserror::Error)]
+#[error(transparent)]
+pub struct Error(#[from] ErrorInner);
+
+impl From<Paragraph> for Value {
+    fn from(paragraph: Paragraph) -> Self {
+        let map = paragraph.fields
+            .into_iter()
+            .map(|field| (field.name, Value::String(field.value)))
+            .collect::<Map<_, _>>();
+        Value::Object(map)
+    }
+}
+
+impl From<Document> for Value {
+    fn from(document: Document) -> Self {
+        Value::Array(document.paragraphs.into_iter().map(Into::into).collect())
+    }
+}
+
+fn paragraph_from_map(map: Map<String, Value>) -> Result<Paragraph, Error> {
+    map.into_iter()
+        .map(|(name, value)| match value {
+            Value::String(value) => Ok(Field::new(name, value)),
+            _ => Err(ErrorInner::NonStringValue(name).into()),
+        })
+        .collect()
+}
+
+impl TryFrom<Value> for Paragraph {
+    type Error = Error;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Object(map) => paragraph_from_map(map),
+            _ => Err(ErrorInner::NotObject(0).into()),
+        }
+    }
+}
+
+impl TryFrom<Value> for Document {
+    type Error = Error;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        let paragraphs = match value {
+            Value::Array(paragraphs) => paragraphs,
+            _ => return Err(ErrorInner::NotArray.into()),
+        };
+        paragraphs
+            .into_iter()
+            .enumerate()
+            .map(|(i, paragraph)| match paragraph {
+                Value::Object(map) => paragraph_from_map(map),
+                _ => Err(ErrorInner::NotObject(i).into()),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+    use serde_json::{json, Value};
+    use crate::document::{Document, Paragraph};
+
+    #[test]
+    fn roundtrip() {
+        let document = Document::parse("Description: short\n long\nPackage: foo\n\nPackage: bar\n").unwrap();
+        let json = Value::from(document.clone());
+        assert_eq!(json, json!([{ "Package": "foo", "Description": "short\nlong" }, { "Package": "bar" }]));
+        assert_eq!(Document::try_from(json).unwrap(), document);
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(Document::try_from(json!({})).unwrap_err().to_string(), "expected an array of objects");
+        assert_eq!(Document::try_from(json!([{}, 42])).unwrap_err().to_string(), "expected an object (paragraph 1)");
+        assert_eq!(Paragraph::try_from(json!({ "Version": 1 })).unwrap_err().to_string(), "the value of field Version is not a string");
+    }
+}
@@ -0,0 +1,240 @@
+//! Selection of translated fields such as `Description-de`.
+
+use std::marker::PhantomData;
+use serde::de::DeserializeOwned;
+use super::Paragraph;
+
+/// Expands the language preferences into the list of suffixes to try, most preferred first.
+fn candidates<L: AsRef<str>>(languages: &[L]) -> Vec<&str> {
+    let mut candidates = Vec::new();
+    for language in languages {
+        let mut language = language.as_ref().trim();
+        loop {
+            if !language.is_empty() && !candidates.contains(&language) {
+                candidates.push(language);
+            }
+            match language.rfind(&['@', '.', '_'] as &[char]) {
+                Some(pos) => language = &language[..pos],
+                None => break,
+            }
+        }
+    }
+    candidates
+}
+
+/// Returns the base name and rank of the translation if `name` is a translated field.
+fn translation<'a>(name: &'a str, candidates: &[&str]) -> Option<(&'a str, usize)> {
+    candidates.iter().enumerate().find_map(|(rank, candidate)| {
+        if name.len() <= candidate.len() + 1 {
+            return None;
+        }
+        let split = name.len() - candidate.len() - 1;
+        let suffix = name.get((split + 1)..)?;
+        if name[split..].starts_with('-') && suffix.eq_ignore_ascii_case(candidate) {
+            Some((&name[..split], rank))
+        } else {
+            None
+        }
+    })
+}
+
+impl Paragraph {
+    /// Returns the value of the field translated to the most preferred available language.
+    ///
+    /// Translations are stored in fields suffixed with the language, e.g. `Description-de.UTF-8`
+    /// or `Description-de`.
+    /// Each language expands into progressively less specific variants - `de_DE.UTF-8@euro` is
+    /// tried as `de_DE.UTF-8@euro`, `de_DE.UTF-8`, `de_DE` and `de` - before the next language is
+    /// considered and the untranslated field is the final fallback.
+    /// Names are matched case-insensitively.
+    pub fn get_localized<L: AsRef<str>>(&self, name: &str, languages: &[L]) -> Option<&str> {
+        let candidates = candidates(languages);
+        self.fields
+            .iter()
+            .filter_map(|field| match translation(field.name(), &candidates) {
+                Some((base, rank)) if base.eq_ignore_ascii_case(name) => Some((rank, field.value())),
+                _ => None,
+            })
+            .min_by_key(|(rank, _)| *rank)
+            .map(|(_, value)| value)
+            .or_else(|| self.get(name))
+    }
+
+    /// Returns the paragraph with each translated field replacing its untranslated version.
+    ///
+    /// The best translation of each field is selected the same way as in
+    /// [`get_localized`](Self::get_localized).
+    /// Translations to any of the preferred languages are removed, translations to other
+    /// languages are kept as they are.
+    pub fn localized<L: AsRef<str>>(&self, languages: &[L]) -> Paragraph {
+        let candidates = candidates(languages);
+        let translations = self.fields
+            .iter()
+            .enumerate()
+            .filter_map(|(index, field)| translation(field.name(), &candidates).map(|(base, rank)| (index, base, rank)))
+            .collect::<Vec<_>>();
+        let best = |name: &str| translations
+            .iter()
+            .filter(|(_, base, _)| base.eq_ignore_ascii_case(name))
+            .min_by_key(|(_, _, rank)| *rank)
+            .map(|(index, _, _)| *index);
+
+        let mut fields = Vec::with_capacity(self.fields.len());
+        for (index, field) in self.fields.iter().enumerate() {
+            match translations.iter().find(|(translation, _, _)| *translation == index) {
+                // Translations without the untranslated field take its place
+                Some((_, base, _)) => if best(base) == Some(index) && self.get(base).is_none() {
+                    let mut field = field.clone();
+                    field.name = (*base).to_owned();
+                    field.discard_formatting();
+                    fields.push(field);
+                },
+                None => match best(field.name()) {
+                    Some(best) => {
+                        let mut field = field.clone();
+                        field.set_value(self.fields[best].value());
+                        fields.push(field);
+                    },
+                    None => fields.push(field.clone()),
+                },
+            }
+        }
+        Paragraph {
+            fields,
+            comments: self.comments.clone(),
+            leading: self.leading.clone(),
+            span: self.span,
+        }
+    }
+}
+
+/// Typed value with translated fields resolved on demand.
+///
+/// Deserializing a paragraph into `T` directly would ignore the translations so `Localized<T>`
+/// keeps the paragraph and deserializes `T` once the language preferences are known.
+///
+/// # Example
+///
+/// ```
+/// use rfc822_like::document::Localized;
+///
+/// #[derive(serde_derive::Deserialize)]
+/// #[serde(rename_all = "PascalCase")]
+/// struct Package {
+///     package: String,
+///     description: String,
+/// }
+///
+/// let input = "Package: foo\nDescription: Example\nDescription-de: Beispiel\n";
+/// let packages = rfc822_like::from_str::<Vec<Localized<Package>>>(input).unwrap();
+/// assert_eq!(packages[0].get(&["de_DE.UTF-8"]).unwrap().description, "Beispiel");
+/// assert_eq!(packages[0].get(&["fr"]).unwrap().description, "Example");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Localized<T> {
+    paragraph: Paragraph,
+    _phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Localized<T> {
+    /// Wraps the paragraph.
+    pub fn from_paragraph(paragraph: Paragraph) -> Self {
+        Localized {
+            paragraph,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Returns the paragraph including all translations.
+    pub fn paragraph(&self) -> &Paragraph {
+        &self.paragraph
+    }
+
+    /// Returns the paragraph including all translations.
+    pub fn into_paragraph(self) -> Paragraph {
+        self.paragraph
+    }
+}
+
+impl<T: DeserializeOwned> Localized<T> {
+    /// Deserializes the value using the most preferred available translations.
+    pub fn get<L: AsRef<str>>(&self, languages: &[L]) -> Result<T, crate::de::Error> {
+        self.paragraph.localized(languages).deserialize_into()
+    }
+
+    /// Deserializes the value like [`get`](Self::get) using the `options`.
+    ///
+    /// The settings of the deserializer that produced `Localized` are not remembered, pass the
+    /// same options to interpret values, e.g. `bool_values`, the same way.
+    pub fn get_with<L: AsRef<str>>(&self, languages: &[L], options: &crate::de::Options) -> Result<T, crate::de::Error> {
+        self.paragraph.localized(languages).deserialize_into_with(options)
+    }
+}
+
+impl<'de, T> serde::Deserialize<'de> for Localized<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Paragraph::deserialize(deserializer).map(Localized::from_paragraph)
+    }
+}
+
+/// Returns the language preferences from the environment the same way `gettext` does it.
+///
+/// The colon-separated `LANGUAGE` variable is used first followed by the first non-empty
+/// variable of `LC_ALL`, `LC_MESSAGES` and `LANG`.
+/// The `C` and `POSIX` locales are skipped.
+pub fn languages_from_env() -> Vec<String> {
+    let mut languages = std::env::var("LANGUAGE")
+        .unwrap_or_default()
+        .split(':')
+        .map(ToOwned::to_owned)
+        .collect::<Vec<_>>();
+    let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty());
+    languages.extend(locale);
+    languages.retain(|language| !language.is_empty() && language != "C" && language != "POSIX" && !language.starts_with("C."));
+    languages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::candidates;
+    use crate::document::Document;
+
+    #[test]
+    fn fallback() {
+        assert_eq!(candidates(&["de_DE.UTF-8@euro", "de", "pt_BR"]), ["de_DE.UTF-8@euro", "de_DE.UTF-8", "de_DE", "de", "pt_BR", "pt"]);
+
+        let input = "Package: foo\nDescription: Example\nDescription-de: Beispiel\nDescription-de.UTF-8: Beispiel UTF-8\nSummary-fr: Exemple\n";
+        let document = Document::parse(input).unwrap();
+        let paragraph = &document.paragraphs()[0];
+        assert_eq!(paragraph.get_localized("Description", &["de.UTF-8"]), Some("Beispiel UTF-8"));
+        assert_eq!(paragraph.get_localized("description", &["de_AT"]), Some("Beispiel"));
+        assert_eq!(paragraph.get_localized("Description", &["cs", "de"]), Some("Beispiel"));
+        assert_eq!(paragraph.get_localized("Description", &["cs"]), Some("Example"));
+        assert_eq!(paragraph.get_localized::<&str>("Description", &[]), Some("Example"));
+        assert_eq!(paragraph.get_localized("Summary", &["fr_FR"]), Some("Exemple"));
+        assert_eq!(paragraph.get_localized("Summary", &["de"]), None);
+
+        assert_eq!(paragraph.localized(&["de"]).to_string(), "Package: foo\nDescription: Beispiel\nDescription-de.UTF-8: Beispiel UTF-8\nSummary-fr: Exemple\n");
+        assert_eq!(paragraph.localized(&["fr", "de.UTF-8"]).to_string(), "Package: foo\nDescription: Beispiel UTF-8\nSummary: Exemple\n");
+    }
+
+    #[test]
+    fn options() {
+        #[derive(serde_derive::Deserialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct Package {
+            essential: bool,
+        }
+
+        let input = "Essential: on\nEssential-de: ein\n";
+        let options = crate::de::Options { bool_values: Some(vec![("on".to_owned(), "off".to_owned()), ("ein".to_owned(), "aus".to_owned())]), ..Default::default() };
+        let deserializer = crate::de::Deserializer::with_options(input.as_bytes(), options.clone());
+        let packages = <Vec<super::Localized<Package>> as serde::Deserialize>::deserialize(deserializer).unwrap();
+        assert!(packages[0].get(&["de"]).is_err());
+        assert!(packages[0].get_with(&["de"], &options).unwrap().essential);
+        assert!(packages[0].get_with::<&str>(&[], &options).unwrap().essential);
+    }
+}
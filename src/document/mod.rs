@@ -0,0 +1,1493 @@
+//! # Generic document model
+//!
+//! This module contains types representing RFC822-like data without knowing its schema.
+//! A [`Document`] is a list of [`Paragraph`]s and a paragraph is an ordered list of [`Field`]s.
+//!
+//! Unlike the serde-based API the document can be parsed and written without specifying the type
+//! which makes it useful for inspecting and editing arbitrary files.
+//! The types also implement `Serialize` and `Deserialize` so they can be used as self-describing
+//! values with the serde-based API too.
+//! With the `serde_json` feature enabled they can also be converted to and from
+//! `serde_json::Value` (see the `json` module).
+//! The `arbitrary` feature implements `arbitrary::Arbitrary` generating only documents that
+//! survive a write and parse round trip, which is useful for fuzzing.
+//! Equivalent `proptest` strategies are available in the `strategy` module with the `proptest`
+//! feature.
+//!
+//! Field values are stored *unfolded* - continuation lines are joined with `\n`, leading
+//! whitespace is removed and lines containing a single `.` become empty lines.
+//! This is the same representation the deserializer produces for strings.
+//!
+//! Parsed documents remember their original formatting.
+//! Writing a document produces the parsed input byte-for-byte except for fields and paragraphs
+//! that were modified - only these get re-rendered.
+//! Fields with changed values keep their layout - the spacing after the colon, the indentation
+//! of continuation lines and whether the value starts on the next line.
+//! This keeps diffs small when editing files such as `debian/control` programmatically.
+//!
+//! Comments (lines starting with `#`) are kept too.
+//! Comments directly preceding a field belong to the field, other comments belong to the
+//! following paragraph or to the document if they are at the end.
+//! They are written back in place even if the owner is re-rendered.
+//!
+//! Parsed fields and paragraphs also remember their [`Span`] - position in the parsed text.
+//! This is useful for reporting problems or editing the original text in-place.
+//! Editors can keep the document up to date using [`Document::reparse`] which only parses the
+//! paragraphs affected by an edit of the text.
+//!
+//! Comparisons of the types ignore the formatting, comments and spans.
+//!
+//! Translated fields such as `Description-de` can be selected according to language preferences
+//! using [`Paragraph::get_localized`] or the [`Localized`] wrapper.
+//!
+//! Large read-only documents such as whole archive indexes can be kept in [`CompactDocument`]
+//! which needs much less memory at the cost of dropping comments and formatting.
+//!
+//! # Example
+//!
+//! ```
+//! use rfc822_like::document::Document;
+//!
+//! let input = "Package: foo
+//! Description: The Foo
+//!  It's very foo.
+//!
+//! Package: bar
+//! ";
+//!
+//! let mut document = input.parse::<Document>().unwrap();
+//! assert_eq!(document.paragraphs().len(), 2);
+//! assert_eq!(document.paragraphs()[0].get("Description"), Some("The Foo\nIt's very foo."));
+//!
+//! document.paragraphs_mut()[1].set("Version", "1.0");
+//! assert_eq!(document.to_string(), "Package: foo
+//! Description: The Foo
+//!  It's very foo.
+//!
+//! Package: bar
+//! Version: 1.0
+//! ");
+//! ```
+
+use std::fmt::{self, Write};
+use std::str::FromStr;
+use serde::de::{Visitor, MapAccess, SeqAccess};
+use serde::ser::{SerializeMap, SerializeSeq};
+use crate::events::Event;
+use crate::report::Report;
+use crate::ser::FieldWriter;
+pub use error::Error;
+
+pub mod error;
+pub mod format;
+pub mod order;
+#[cfg(feature = "serde_json")]
+pub mod json;
+#[cfg(feature = "arbitrary")]
+mod arbitrary;
+#[cfg(feature = "proptest")]
+pub mod strategy;
+mod de;
+mod ser;
+mod preserving;
+mod localized;
+mod compact;
+
+pub use preserving::Preserving;
+pub use localized::{Localized, languages_from_env};
+pub use compact::{CompactDocument, CompactParagraph};
+
+/// Position of a parsed item in the original text.
+///
+/// Offsets are in bytes and line numbers are 1-based.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Span {
+    start: usize,
+    end: usize,
+    first_line: usize,
+    last_line: usize,
+}
+
+impl Span {
+    pub(crate) fn new(start: usize, end: usize, first_line: usize, last_line: usize) -> Self {
+        Span { start, end, first_line, last_line, }
+    }
+
+    /// Byte offset of the first byte of the item.
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// Byte offset one past the last byte of the item including the terminating newline.
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    /// The byte range of the item suitable for indexing the original text.
+    pub fn range(&self) -> std::ops::Range<usize> {
+        self.start..self.end
+    }
+
+    /// Number of the first line of the item.
+    pub fn first_line(&self) -> usize {
+        self.first_line
+    }
+
+    /// Number of the last line of the item.
+    pub fn last_line(&self) -> usize {
+        self.last_line
+    }
+
+    fn shift(&mut self, offset: isize, lines: isize) {
+        self.start = (self.start as isize + offset) as usize;
+        self.end = (self.end as isize + offset) as usize;
+        self.first_line = (self.first_line as isize + lines) as usize;
+        self.last_line = (self.last_line as isize + lines) as usize;
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.first_line == self.last_line {
+            write!(f, "line {}", self.first_line)
+        } else {
+            write!(f, "lines {}-{}", self.first_line, self.last_line)
+        }
+    }
+}
+
+/// A single `Name: value` pair.
+#[derive(Debug, Clone)]
+pub struct Field {
+    name: String,
+    value: String,
+    comments: Vec<String>,
+    // Original text of the field if it wasn't modified
+    raw: Option<String>,
+    // Layout of the original text used to render the modified value
+    layout: Option<Layout>,
+    span: Option<Span>,
+}
+
+impl Field {
+    /// Creates a field with given name and (unfolded) value.
+    ///
+    /// The name must not be empty and must not contain `:` or newlines, otherwise the output
+    /// will be invalid.
+    pub fn new<N: Into<String>, V: Into<String>>(name: N, value: V) -> Self {
+        Field {
+            name: name.into(),
+            value: value.into(),
+            comments: Vec::new(),
+            raw: None,
+            layout: None,
+            span: None,
+        }
+    }
+
+    /// Returns the name of the field.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns `true` if the field has given name ignoring ASCII case.
+    pub fn is_named(&self, name: &str) -> bool {
+        self.name.eq_ignore_ascii_case(name)
+    }
+
+    /// Returns the unfolded value of the field.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Replaces the value of the field.
+    ///
+    /// This causes the field to be re-rendered when writing.
+    /// A parsed field keeps its layout: the spacing after the colon, the indentation of
+    /// continuation lines and whether the value starts on the next line.
+    pub fn set_value<V: Into<String>>(&mut self, value: V) {
+        let layout = self.raw.as_ref().map(|raw| Layout::from_raw(raw)).or_else(|| self.layout.take());
+        self.value = value.into();
+        self.discard_formatting();
+        self.layout = layout;
+    }
+
+    /// Returns the comments preceding the field without the leading `#`.
+    pub fn comments(&self) -> &[String] {
+        &self.comments
+    }
+
+    /// Returns the comments preceding the field mutably.
+    pub fn comments_mut(&mut self) -> &mut Vec<String> {
+        &mut self.comments
+    }
+
+    /// Returns the position of the field in the parsed text.
+    ///
+    /// The span covers the field line and all continuation lines but not the preceding comments.
+    /// It's `None` if the field wasn't parsed from text.
+    /// Modifying the field doesn't change the span.
+    pub fn span(&self) -> Option<Span> {
+        self.span
+    }
+
+    /// Returns the original text of the field if it was parsed and not modified since.
+    ///
+    /// The text includes the trailing newline and comments interleaved with continuation lines
+    /// but not the comments preceding the field.
+    pub fn raw(&self) -> Option<&str> {
+        self.raw.as_ref().map(AsRef::as_ref)
+    }
+
+    /// Forgets the original formatting causing the field to be re-rendered canonically.
+    ///
+    /// Comments interleaved with continuation lines are moved before the field.
+    pub fn discard_formatting(&mut self) {
+        self.layout = None;
+        if let Some(raw) = self.raw.take() {
+            let interleaved = Lines { input: &raw, offset: 0, }
+                .filter(|(_, line)| line.starts_with('#'))
+                .map(|(_, line)| comment_text(line).to_owned());
+            self.comments.extend(interleaved);
+        }
+    }
+
+    /// Replaces value and formatting with those from `other` keeping the comments.
+    fn replace_keeping_comments(&mut self, other: Field) {
+        self.discard_formatting();
+        self.value = other.value;
+        self.raw = other.raw;
+    }
+}
+
+/// Layout of a parsed field applied to its new value.
+#[derive(Debug, Clone)]
+struct Layout {
+    // Whitespace between the colon and the value
+    separator: String,
+    // Leading whitespace of continuation lines
+    indent: String,
+    value_on_next_line: bool,
+}
+
+impl Layout {
+    fn from_raw(raw: &str) -> Self {
+        let mut lines = Lines { input: raw, offset: 0, }.map(|(_, line)| line);
+        let first = lines.next().unwrap_or("");
+        let first = first[(first.find(':').map_or(0, |colon| colon + 1))..].trim_end_matches(&['\n', '\r'] as &[char]);
+        let continuation = lines.find(|line| !line.starts_with('#'));
+        let value = first.trim_start();
+        let separator = if value.is_empty() { " " } else { &first[..(first.len() - value.len())] };
+        let indent = continuation.map_or(" ", |line| &line[..(line.len() - line.trim_start().len())]);
+        Layout {
+            separator: separator.to_owned(),
+            indent: indent.to_owned(),
+            value_on_next_line: value.is_empty() && continuation.is_some(),
+        }
+    }
+
+    /// Writes the field, the value must not be empty nor start with an empty line.
+    fn write<W: Write>(&self, mut output: W, name: &str, value: &str) -> fmt::Result {
+        write!(output, "{}:", name)?;
+        let mut lines = value.split('\n');
+        if !self.value_on_next_line {
+            let first = lines.next().expect("split() returned an empty iterator");
+            write!(output, "{}{}", self.separator, first)?;
+        }
+        for line in lines {
+            let line = if line.is_empty() { "." } else { line };
+            write!(output, "\n{}{}", self.indent, line)?;
+        }
+        output.write_str("\n")
+    }
+}
+
+/// Strips the `#` and newline from a comment line.
+pub(crate) fn comment_text(line: &str) -> &str {
+    line[1..].trim_end_matches(&['\n', '\r'] as &[char])
+}
+
+fn write_comments<W: fmt::Write>(mut writer: W, comments: &[String]) -> fmt::Result {
+    for comment in comments {
+        writeln!(writer, "#{}", comment)?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for Field {
+    /// Writes the field including the preceding comments.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_comments(&mut *f, &self.comments)?;
+        self.write_without_comments(f)
+    }
+}
+
+impl Field {
+    fn write_without_comments<W: Write>(&self, mut output: W) -> fmt::Result {
+        if let Some(raw) = &self.raw {
+            return output.write_str(raw);
+        }
+        match &self.layout {
+            // Empty first line would be skipped when parsing
+            Some(layout) if !self.value.is_empty() && !self.value.starts_with('\n') => return layout.write(output, &self.name, &self.value),
+            _ => (),
+        }
+        write!(output, "{}: ", self.name)?;
+        let mut writer = FieldWriter::new(output, false);
+        writer.write_str(&self.value)?;
+        writer.finish()
+    }
+}
+
+impl PartialEq for Field {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && self.value == other.value
+    }
+}
+
+impl Eq for Field {}
+
+impl std::hash::Hash for Field {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+        self.value.hash(state);
+    }
+}
+
+/// Ordered list of fields.
+///
+/// Paragraphs are called "stanzas" or "records" in some places.
+#[derive(Debug, Clone, Default)]
+pub struct Paragraph {
+    fields: Vec<Field>,
+    comments: Vec<String>,
+    // Original text between the previous paragraph and this one
+    leading: Option<String>,
+    span: Option<Span>,
+}
+
+impl Paragraph {
+    /// Creates an empty paragraph.
+    pub fn new() -> Self {
+        Paragraph::default()
+    }
+
+    /// Serializes a typed value into a paragraph without going through text.
+    ///
+    /// The value has to serialize as a struct or a map.
+    /// Fields are formatted the same way [`crate::to_string`] would format them.
+    pub fn from_serialize<T: serde::Serialize + ?Sized>(value: &T) -> Result<Self, crate::ser::Error> {
+        value.serialize(ser::ParagraphSerializer).map(|fields| fields.into_iter().collect())
+    }
+
+    /// Deserializes a typed value from the fields of the paragraph without going through text.
+    ///
+    /// This behaves the same as deserializing the written paragraph using [`crate::from_str`].
+    pub fn deserialize_into<T: serde::de::DeserializeOwned>(&self) -> Result<T, crate::de::Error> {
+        self.deserialize_into_with(&crate::de::Options::default())
+    }
+
+    /// Deserializes a typed value from the fields of the paragraph using the `options`.
+    ///
+    /// Only the options affecting values, `human_readable` and `bool_values`, apply since the
+    /// paragraph is already parsed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rfc822_like::de::Options;
+    /// use rfc822_like::document::Document;
+    ///
+    /// #[derive(serde_derive::Deserialize)]
+    /// #[serde(rename_all = "PascalCase")]
+    /// struct Package {
+    ///     essential: bool,
+    /// }
+    ///
+    /// let document = Document::parse("Essential: on\n").unwrap();
+    /// assert!(document.paragraphs()[0].deserialize_into::<Package>().is_err());
+    /// let options = Options { bool_values: Some(vec![("on".to_owned(), "off".to_owned())]), ..Options::default() };
+    /// assert!(document.paragraphs()[0].deserialize_into_with::<Package>(&options).unwrap().essential);
+    /// ```
+    pub fn deserialize_into_with<T: serde::de::DeserializeOwned>(&self, options: &crate::de::Options) -> Result<T, crate::de::Error> {
+        let settings = crate::de::ValueSettings::from_options(options);
+        T::deserialize(&mut de::ParagraphDeserializer::new(&self.fields, &settings))
+    }
+
+    /// Computes the digest of the fields as they're displayed.
+    ///
+    /// Comments are not hashed, so this identifies the content of the stanza, e.g. for detecting
+    /// changed records between two versions of an index.
+    pub fn digest<D: crate::digest::Digest>(&self, mut digest: D) -> D::Output {
+        for field in &self.fields {
+            field.write_without_comments(crate::digest::DigestWriter(&mut digest)).expect("digests never fail");
+        }
+        digest.finalize()
+    }
+
+    /// Returns the fields in their original order.
+    pub fn fields(&self) -> &[Field] {
+        &self.fields
+    }
+
+    /// Returns the fields mutably.
+    pub fn fields_mut(&mut self) -> &mut Vec<Field> {
+        &mut self.fields
+    }
+
+    /// Iterates over the fields.
+    pub fn iter(&self) -> std::slice::Iter<'_, Field> {
+        self.fields.iter()
+    }
+
+    /// Returns the number of fields.
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    /// Returns `true` if the paragraph has no fields.
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// Returns the value of the first field with given name.
+    ///
+    /// Field names are matched case-insensitively, the same way `dpkg` does it.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.fields.iter().find(|field| field.is_named(name)).map(Field::value)
+    }
+
+    /// Returns the value of the first field with exactly the given name.
+    pub fn get_exact(&self, name: &str) -> Option<&str> {
+        self.fields.iter().find(|field| field.name == name).map(Field::value)
+    }
+
+    /// Iterates over values of all fields with given name (case-insensitive).
+    ///
+    /// Duplicate fields are invalid in Debian files but some dialects use them.
+    pub fn get_all<'a>(&'a self, name: &'a str) -> impl Iterator<Item=&'a str> + 'a {
+        self.fields.iter().filter(move |field| field.is_named(name)).map(Field::value)
+    }
+
+    /// Returns the first field with given name (case-insensitive) mutably.
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Field> {
+        self.fields.iter_mut().find(|field| field.is_named(name))
+    }
+
+    /// Sets the value of the first field with given name (case-insensitive) or appends a new
+    /// field if there's none.
+    ///
+    /// The name of an existing field is not changed.
+    pub fn set<N: Into<String> + AsRef<str>, V: Into<String>>(&mut self, name: N, value: V) {
+        match self.get_mut(name.as_ref()) {
+            Some(field) => field.set_value(value),
+            None => self.fields.push(Field::new(name, value)),
+        }
+    }
+
+    /// Appends the field at the end even if a field with the same name already exists.
+    pub fn push(&mut self, field: Field) {
+        self.fields.push(field);
+    }
+
+    /// Removes the first field with given name (case-insensitive) and returns it.
+    pub fn remove(&mut self, name: &str) -> Option<Field> {
+        let pos = self.fields.iter().position(|field| field.is_named(name))?;
+        Some(self.fields.remove(pos))
+    }
+
+    /// Sorts the fields according to the order of names in `order`.
+    ///
+    /// Field names are matched case-insensitively.
+    /// Fields not present in `order` are moved after the listed ones keeping their relative
+    /// order, so are duplicate fields.
+    /// The fields keep their formatting.
+    /// The [`order`] module contains canonical orders of common Debian files.
+    pub fn sort_fields<N: AsRef<str>>(&mut self, order: &[N]) {
+        self.fields.sort_by_key(|field| order.iter().position(|name| field.is_named(name.as_ref())).unwrap_or(order.len()));
+    }
+
+    /// Moves the first field with given name (case-insensitive) to position `index`.
+    ///
+    /// The index is clamped to the last position.
+    /// Returns `false` if there's no such field.
+    pub fn move_field(&mut self, name: &str, index: usize) -> bool {
+        let pos = match self.fields.iter().position(|field| field.is_named(name)) {
+            Some(pos) => pos,
+            None => return false,
+        };
+        let field = self.fields.remove(pos);
+        let index = index.min(self.fields.len());
+        self.fields.insert(index, field);
+        true
+    }
+
+    /// Merges fields from `other` into this paragraph.
+    ///
+    /// Field names are matched case-insensitively.
+    /// Fields missing in this paragraph are appended in the order they appear in `other`.
+    /// Fields present in both paragraphs are resolved according to `policy`.
+    /// Fields that don't change keep their formatting.
+    ///
+    /// This is useful for applying overrides to package metadata.
+    pub fn merge(&mut self, other: &Paragraph, policy: MergePolicy) {
+        for field in &other.fields {
+            let existing = match self.get_mut(&field.name) {
+                Some(existing) => existing,
+                None => {
+                    self.fields.push(Field::new(field.name.clone(), field.value.clone()));
+                    continue;
+                },
+            };
+
+            match policy {
+                MergePolicy::Override => if existing.value != field.value {
+                    existing.set_value(field.value.clone());
+                },
+                MergePolicy::FillMissing => (),
+                MergePolicy::AppendLists if !is_list_field(&field.name) => if existing.value != field.value {
+                    existing.set_value(field.value.clone());
+                },
+                MergePolicy::AppendLists => {
+                    let mut items = list_items(&existing.value).collect::<Vec<_>>();
+                    let len = items.len();
+                    for item in list_items(&field.value) {
+                        if !items.contains(&item) {
+                            items.push(item);
+                        }
+                    }
+                    if items.len() != len {
+                        let value = items.join(", ");
+                        existing.set_value(value);
+                    }
+                },
+            }
+        }
+    }
+
+    /// Returns the position of the paragraph in the parsed text.
+    ///
+    /// The span starts at the first field (including its comments) and ends after the last one.
+    /// It's `None` if the paragraph wasn't parsed from text.
+    /// Modifying the paragraph doesn't change the span.
+    pub fn span(&self) -> Option<Span> {
+        self.span
+    }
+
+    /// Returns the comments preceding the paragraph without the leading `#`.
+    ///
+    /// These are the comments separated from the first field by an empty line.
+    pub fn comments(&self) -> &[String] {
+        &self.comments
+    }
+
+    /// Returns the comments preceding the paragraph mutably.
+    ///
+    /// This causes the text separating the paragraph from the previous one to be re-rendered.
+    pub fn comments_mut(&mut self) -> &mut Vec<String> {
+        self.leading = None;
+        &mut self.comments
+    }
+
+    /// Forgets the original formatting of the paragraph and all its fields.
+    pub fn discard_formatting(&mut self) {
+        self.leading = None;
+        for field in &mut self.fields {
+            field.discard_formatting();
+        }
+    }
+
+    /// Moves the spans of the paragraph and its fields.
+    fn shift(&mut self, offset: isize, lines: isize) {
+        let spans = self.span.iter_mut().chain(self.fields.iter_mut().flat_map(|field| field.span.iter_mut()));
+        for span in spans {
+            span.shift(offset, lines);
+        }
+    }
+}
+
+/// Fields holding comma-separated lists in Debian control files.
+const LIST_FIELDS: &[&str] = &[
+    "Depends",
+    "Pre-Depends",
+    "Recommends",
+    "Suggests",
+    "Enhances",
+    "Breaks",
+    "Conflicts",
+    "Replaces",
+    "Provides",
+    "Built-Using",
+    "Static-Built-Using",
+    "Build-Depends",
+    "Build-Depends-Indep",
+    "Build-Depends-Arch",
+    "Build-Conflicts",
+    "Build-Conflicts-Indep",
+    "Build-Conflicts-Arch",
+    "Uploaders",
+    "Binary",
+    "Testsuite-Triggers",
+];
+
+fn is_list_field(name: &str) -> bool {
+    LIST_FIELDS.iter().any(|list_field| list_field.eq_ignore_ascii_case(name))
+}
+
+/// Iterates over non-empty items of a comma-separated list.
+fn list_items(value: &str) -> impl Iterator<Item=&str> {
+    value.split(',').map(str::trim).filter(|item| !item.is_empty())
+}
+
+/// Determines how [`Paragraph::merge`] resolves fields present in both paragraphs.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum MergePolicy {
+    /// The value from the other paragraph wins.
+    Override,
+    /// The existing value is kept - only missing fields are added.
+    FillMissing,
+    /// Items missing in the existing value are appended to it if the field is a known
+    /// comma-separated list such as `Depends` or `Uploaders`, other fields are overridden.
+    AppendLists,
+}
+
+impl fmt::Display for Paragraph {
+    /// Writes the fields of the paragraph.
+    ///
+    /// The text separating the paragraph from the previous one is not written.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for field in &self.fields {
+            fmt::Display::fmt(field, f)?;
+        }
+        Ok(())
+    }
+}
+
+impl PartialEq for Paragraph {
+    fn eq(&self, other: &Self) -> bool {
+        self.fields == other.fields
+    }
+}
+
+impl Eq for Paragraph {}
+
+impl std::hash::Hash for Paragraph {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.fields.hash(state);
+    }
+}
+
+impl std::iter::FromIterator<Field> for Paragraph {
+    fn from_iter<I: IntoIterator<Item=Field>>(iter: I) -> Self {
+        Paragraph {
+            fields: iter.into_iter().collect(),
+            comments: Vec::new(),
+            leading: None,
+            span: None,
+        }
+    }
+}
+
+impl IntoIterator for Paragraph {
+    type Item = Field;
+    type IntoIter = std::vec::IntoIter<Field>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.fields.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Paragraph {
+    type Item = &'a Field;
+    type IntoIter = std::slice::Iter<'a, Field>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.fields.iter()
+    }
+}
+
+/// List of paragraphs separated by empty lines.
+#[derive(Debug, Clone, Default)]
+pub struct Document {
+    paragraphs: Vec<Paragraph>,
+    comments: Vec<String>,
+    // Original text after the last paragraph
+    trailing: Option<String>,
+    missing_final_newline: bool,
+}
+
+impl Document {
+    /// Creates an empty document.
+    pub fn new() -> Self {
+        Document::default()
+    }
+
+    /// Parses the document from a string.
+    ///
+    /// Lines consisting only of whitespace separate paragraphs as allowed by Debian Policy.
+    /// Lines starting with `#` are comments - they are not a part of the data but they are
+    /// preserved in the output.
+    pub fn parse(input: &str) -> Result<Self, Error> {
+        Document::parse_with(input, Err)
+    }
+
+    /// Parses the document skipping invalid lines instead of failing.
+    ///
+    /// Each skipped line is reported as an error in the returned report.
+    /// Skipped lines between paragraphs are written back unchanged, those inside paragraphs are
+    /// dropped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rfc822_like::document::Document;
+    ///
+    /// let (document, report) = Document::parse_tolerant("Package: foo\ninvalid\nVersion: 1.0\n");
+    /// assert_eq!(document.paragraphs()[0].get("Version"), Some("1.0"));
+    /// assert_eq!(report.to_string(), "error: line 2: line doesn't contain a colon\n");
+    /// ```
+    pub fn parse_tolerant(input: &str) -> (Self, Report) {
+        let mut report = Report::new();
+        let document = Document::parse_with(input, |error| {
+            report.push(error.to_problem());
+            Ok(())
+        });
+        (document.expect("errors are reported"), report)
+    }
+
+    fn parse_with<F: FnMut(Error) -> Result<(), Error>>(input: &str, on_error: F) -> Result<Self, Error> {
+        if input.is_empty() || input.ends_with('\n') {
+            Parser::new().parse(input, false, on_error)
+        } else {
+            Parser::new().parse(&format!("{}\n", input), true, on_error)
+        }
+    }
+
+    /// Reads the whole reader and parses the document from it.
+    ///
+    /// This function requires the `io` feature.
+    #[cfg(feature = "io")]
+    pub fn from_reader<R: std::io::Read>(mut reader: R) -> Result<Self, Error> {
+        let mut input = String::new();
+        reader.read_to_string(&mut input).map_err(error::ErrorInner::from)?;
+        Document::parse(&input)
+    }
+
+    /// Updates the parsed document after its text was changed by replacing `edit` with
+    /// `replacement`.
+    ///
+    /// `edit` is the byte range of the replaced text in the input the document was parsed from
+    /// and `input` is the whole text after the change.
+    /// Only the paragraphs around the edit are parsed again, the other paragraphs are kept and
+    /// their spans are moved.
+    /// This keeps editors responsive when the text of large files changes on every keystroke.
+    ///
+    /// The kept paragraphs must not be modified since parsing, otherwise they wouldn't match
+    /// `input`.
+    /// Documents containing paragraphs without spans are parsed entirely.
+    ///
+    /// Returns the range of indices of the parsed paragraphs in the updated document.
+    /// The document is not changed if parsing fails.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rfc822_like::document::Document;
+    ///
+    /// let mut input = "Package: foo\n\nPackage: bar\n\nPackage: baz\n".to_owned();
+    /// let mut document = Document::parse(&input).unwrap();
+    ///
+    /// input.replace_range(23..26, "qux");
+    /// let reparsed = document.reparse(23..26, "qux", &input).unwrap();
+    /// assert_eq!(document.paragraphs()[1].get("Package"), Some("qux"));
+    /// assert_eq!(reparsed, 1..3);
+    /// assert_eq!(document.paragraphs()[2].span().unwrap().start(), 28);
+    /// ```
+    pub fn reparse(&mut self, edit: std::ops::Range<usize>, replacement: &str, input: &str) -> Result<std::ops::Range<usize>, Error> {
+        let spans = match self.paragraphs.iter().map(Paragraph::span).collect::<Option<Vec<_>>>() {
+            Some(spans) if edit.start <= edit.end => spans,
+            _ => return self.parse_all(input),
+        };
+        // Paragraphs preceding the one containing the start of the edit are kept.
+        let first = spans.iter().rposition(|span| span.start <= edit.start).unwrap_or(0);
+        let (start, start_line) = match first {
+            0 => (0, 1),
+            _ => (spans[first - 1].end, spans[first - 1].last_line + 1),
+        };
+        // The paragraph following the edit is parsed too because the edit could remove the empty
+        // line separating it.
+        let last = spans.iter().position(|span| span.start > edit.end).map_or(spans.len(), |next| (next + 1).min(spans.len()));
+        let offset = replacement.len() as isize - (edit.end - edit.start) as isize;
+
+        let parsed = if last < spans.len() {
+            let end = (spans[last].start as isize + offset) as usize;
+            let parsed = match input.get(start..end) {
+                Some(text) => Parser::new().parse(text, false, Err).map(|parsed| (parsed, text.matches('\n').count())),
+                None => return self.parse_all(input),
+            };
+            match parsed {
+                Ok(parsed) => parsed,
+                // Reports the error at the correct line
+                Err(_) => return self.parse_all(input),
+            }
+        } else {
+            match input.get(start..).map(Document::parse) {
+                Some(Ok(parsed)) => (parsed, 0),
+                _ => return self.parse_all(input),
+            }
+        };
+        let (mut parsed, lines) = parsed;
+
+        if last < spans.len() {
+            let line_offset = (start_line + lines) as isize - spans[last].first_line as isize;
+            for paragraph in &mut self.paragraphs[last..] {
+                paragraph.shift(offset, line_offset);
+            }
+            // The text between the parsed paragraphs and the next one
+            self.paragraphs[last].leading = parsed.trailing.take();
+            self.paragraphs[last].comments = std::mem::take(&mut parsed.comments);
+        } else {
+            self.comments = std::mem::take(&mut parsed.comments);
+            self.trailing = parsed.trailing.take();
+            self.missing_final_newline = parsed.missing_final_newline;
+        }
+        for paragraph in &mut parsed.paragraphs {
+            paragraph.shift(start as isize, start_line as isize - 1);
+        }
+        let count = parsed.paragraphs.len();
+        self.paragraphs.splice(first..last, parsed.paragraphs);
+        Ok(first..(first + count))
+    }
+
+    fn parse_all(&mut self, input: &str) -> Result<std::ops::Range<usize>, Error> {
+        *self = Document::parse(input)?;
+        Ok(0..self.paragraphs.len())
+    }
+
+    /// Returns the paragraphs.
+    pub fn paragraphs(&self) -> &[Paragraph] {
+        &self.paragraphs
+    }
+
+    /// Returns the paragraphs mutably.
+    pub fn paragraphs_mut(&mut self) -> &mut Vec<Paragraph> {
+        &mut self.paragraphs
+    }
+
+    /// Iterates over the paragraphs.
+    pub fn iter(&self) -> std::slice::Iter<'_, Paragraph> {
+        self.paragraphs.iter()
+    }
+
+    /// Appends a paragraph at the end of the document.
+    pub fn push(&mut self, paragraph: Paragraph) {
+        self.paragraphs.push(paragraph);
+    }
+
+    /// Returns the number of paragraphs.
+    pub fn len(&self) -> usize {
+        self.paragraphs.len()
+    }
+
+    /// Returns `true` if the document has no paragraphs.
+    pub fn is_empty(&self) -> bool {
+        self.paragraphs.is_empty()
+    }
+
+    /// Writes the document into `fmt::Write`r.
+    pub fn write_to<W: fmt::Write>(&self, mut writer: W) -> fmt::Result {
+        write!(writer, "{}", self)
+    }
+
+    /// Returns the comments after the last paragraph without the leading `#`.
+    pub fn trailing_comments(&self) -> &[String] {
+        &self.comments
+    }
+
+    /// Returns the comments after the last paragraph mutably.
+    pub fn trailing_comments_mut(&mut self) -> &mut Vec<String> {
+        self.trailing = None;
+        &mut self.comments
+    }
+
+    /// Forgets the original formatting of the whole document.
+    ///
+    /// Writing the document afterwards produces canonical output.
+    /// Comments are kept.
+    pub fn discard_formatting(&mut self) {
+        self.trailing = None;
+        self.missing_final_newline = false;
+        for paragraph in &mut self.paragraphs {
+            paragraph.discard_formatting();
+        }
+    }
+
+    fn write_formatted<W: fmt::Write>(&self, mut writer: W) -> fmt::Result {
+        for (i, paragraph) in self.paragraphs.iter().enumerate() {
+            match &paragraph.leading {
+                Some(leading) => writer.write_str(leading)?,
+                None => {
+                    if i > 0 {
+                        writer.write_str("\n")?;
+                    }
+                    if !paragraph.comments.is_empty() {
+                        write_comments(&mut writer, &paragraph.comments)?;
+                        writer.write_str("\n")?;
+                    }
+                },
+            }
+            write!(writer, "{}", paragraph)?;
+        }
+        match &self.trailing {
+            Some(trailing) => writer.write_str(trailing),
+            None if self.comments.is_empty() => Ok(()),
+            None => {
+                if !self.paragraphs.is_empty() {
+                    writer.write_str("\n")?;
+                }
+                write_comments(writer, &self.comments)
+            },
+        }
+    }
+}
+
+impl fmt::Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.missing_final_newline {
+            let mut output = String::new();
+            self.write_formatted(&mut output)?;
+            if output.ends_with('\n') {
+                output.pop();
+            }
+            f.write_str(&output)
+        } else {
+            self.write_formatted(f)
+        }
+    }
+}
+
+impl PartialEq for Document {
+    fn eq(&self, other: &Self) -> bool {
+        self.paragraphs == other.paragraphs
+    }
+}
+
+impl Eq for Document {}
+
+impl std::hash::Hash for Document {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.paragraphs.hash(state);
+    }
+}
+
+impl FromStr for Document {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Document::parse(s)
+    }
+}
+
+impl std::iter::FromIterator<Paragraph> for Document {
+    fn from_iter<I: IntoIterator<Item=Paragraph>>(iter: I) -> Self {
+        Document {
+            paragraphs: iter.into_iter().collect(),
+            comments: Vec::new(),
+            trailing: None,
+            missing_final_newline: false,
+        }
+    }
+}
+
+impl IntoIterator for Document {
+    type Item = Paragraph;
+    type IntoIter = std::vec::IntoIter<Paragraph>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.paragraphs.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Document {
+    type Item = &'a Paragraph;
+    type IntoIter = std::slice::Iter<'a, Paragraph>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.paragraphs.iter()
+    }
+}
+
+/// Iterator over lines including the terminating newline and their offsets.
+struct Lines<'a> {
+    input: &'a str,
+    offset: usize,
+}
+
+impl<'a> Iterator for Lines<'a> {
+    type Item = (usize, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.input.is_empty() {
+            return None;
+        }
+        let end = self.input.find('\n').map(|pos| pos + 1).unwrap_or(self.input.len());
+        let (line, rest) = self.input.split_at(end);
+        let offset = self.offset;
+        self.input = rest;
+        self.offset += end;
+        Some((offset, line))
+    }
+}
+
+/// Joins continuation lines of a field into the logical value.
+pub(crate) fn unfold<'a, I: Iterator<Item=&'a str>>(first: &str, continuations: I) -> String {
+    let mut value = first.trim().to_owned();
+    // Empty first line is not a part of the value
+    let mut skip_newline = value.is_empty();
+    for line in continuations {
+        if !skip_newline {
+            value.push('\n');
+        }
+        skip_newline = false;
+        let line = line.trim();
+        if line != "." {
+            value.push_str(line);
+        }
+    }
+    value
+}
+
+/// Builds the document from the events of [`crate::events::Parser`].
+struct Parser<'a> {
+    input: &'a str,
+    // Length of the input excluding the added final newline
+    len: usize,
+    paragraphs: Vec<Paragraph>,
+    fields: Vec<Field>,
+    // Start of the text between paragraphs
+    gap_start: usize,
+    // Comments that may belong to the following field, offset and line of the first one and
+    // line of the last one
+    comments: Vec<String>,
+    comments_start: Option<(usize, usize)>,
+    comments_last_line: usize,
+    // Comments between paragraphs
+    gap_comments: Vec<String>,
+    leading: Option<String>,
+    // Start offset and line of the paragraph including comments of the first field
+    paragraph_start: (usize, usize),
+}
+
+impl<'a> Parser<'a> {
+    fn new() -> Self {
+        Parser {
+            input: "",
+            len: 0,
+            paragraphs: Vec::new(),
+            fields: Vec::new(),
+            gap_start: 0,
+            comments: Vec::new(),
+            comments_start: None,
+            comments_last_line: 0,
+            gap_comments: Vec::new(),
+            leading: None,
+            paragraph_start: (0, 0),
+        }
+    }
+
+    /// Moves the pending comments between paragraphs unless they end right before `line`.
+    fn separate_comments(&mut self, line: Option<usize>) {
+        if line != Some(self.comments_last_line + 1) {
+            self.gap_comments.append(&mut self.comments);
+            self.comments_start = None;
+        }
+    }
+
+    fn comment(&mut self, text: &str, span: Span) {
+        // Comments interleaved with continuation lines are a part of the raw text of the field
+        if span.start < self.gap_start {
+            return;
+        }
+        self.separate_comments(Some(span.first_line));
+        if self.comments_start.is_none() {
+            self.comments_start = Some((span.start, span.first_line));
+        }
+        self.comments.push(text.to_owned());
+        self.comments_last_line = span.last_line;
+    }
+
+    fn field(&mut self, name: &str, value: String, mut span: Span) {
+        self.separate_comments(Some(span.first_line));
+        let comments_start = self.comments_start.take().unwrap_or((span.start, span.first_line));
+        if self.fields.is_empty() {
+            self.leading = Some(self.input[self.gap_start..comments_start.0].to_owned());
+            self.paragraph_start = comments_start;
+        }
+        let raw = Some(self.input[span.start..span.end].to_owned());
+        self.gap_start = span.end;
+        span.end = span.end.min(self.len);
+        let comments = std::mem::take(&mut self.comments);
+        self.fields.push(Field { name: name.to_owned(), value, comments, raw, layout: None, span: Some(span), });
+    }
+
+    fn finish_paragraph(&mut self) {
+        if !self.fields.is_empty() {
+            let fields = std::mem::take(&mut self.fields);
+            let comments = std::mem::take(&mut self.gap_comments);
+            let last = fields.last().and_then(Field::span).expect("parsed fields have spans");
+            let span = Span {
+                start: self.paragraph_start.0,
+                end: last.end,
+                first_line: self.paragraph_start.1,
+                last_line: last.last_line,
+            };
+            self.paragraphs.push(Paragraph { fields, comments, leading: self.leading.take(), span: Some(span), });
+        }
+        self.separate_comments(None);
+    }
+
+    /// Parses the input, `on_error` decides whether to fail or to skip the invalid line.
+    fn parse<F: FnMut(Error) -> Result<(), Error>>(mut self, input: &'a str, missing_final_newline: bool, mut on_error: F) -> Result<Document, Error> {
+        self.input = input;
+        self.len = if missing_final_newline { input.len() - 1 } else { input.len() };
+        let mut events = crate::events::Parser::new(input);
+        while let Some(event) = events.next() {
+            match event {
+                Ok(Event::Field { name, value, span, }) => self.field(name, value.into_owned(), span),
+                Ok(Event::Comment { text, span, }) => self.comment(text, span),
+                Ok(Event::ParagraphEnd) => self.finish_paragraph(),
+                Ok(Event::ParagraphStart { .. }) => (),
+                Err(error) => {
+                    on_error(error)?;
+                    events.skip_line();
+                },
+            }
+        }
+        self.separate_comments(None);
+        Ok(Document {
+            paragraphs: self.paragraphs,
+            comments: self.gap_comments,
+            trailing: Some(input[self.gap_start..].to_owned()),
+            missing_final_newline,
+        })
+    }
+}
+
+impl serde::Serialize for Paragraph {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.fields.len()))?;
+        for field in &self.fields {
+            map.serialize_entry(&field.name, &field.value)?;
+        }
+        map.end()
+    }
+}
+
+impl serde::Serialize for Document {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.paragraphs.len()))?;
+        for paragraph in &self.paragraphs {
+            seq.serialize_element(paragraph)?;
+        }
+        seq.end()
+    }
+}
+
+struct ParagraphVisitor;
+
+impl<'de> Visitor<'de> for ParagraphVisitor {
+    type Value = Paragraph;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a paragraph of fields")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut fields = Vec::with_capacity(map.size_hint().unwrap_or(0));
+        while let Some((name, value)) = map.next_entry::<String, String>()? {
+            fields.push(Field::new(name, value));
+        }
+        Ok(fields.into_iter().collect())
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Paragraph {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(ParagraphVisitor)
+    }
+}
+
+struct DocumentVisitor;
+
+impl<'de> Visitor<'de> for DocumentVisitor {
+    type Value = Document;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a sequence of paragraphs")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut paragraphs = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(paragraph) = seq.next_element()? {
+            paragraphs.push(paragraph);
+        }
+        Ok(paragraphs.into_iter().collect())
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Document {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_seq(DocumentVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Document, Paragraph, Field, Span};
+
+    #[test]
+    fn parse_multiple() {
+        let input = "Package: foo\nVersion: 1.0\n\n\nPackage: bar\n";
+        let document = Document::parse(input).unwrap();
+        assert_eq!(document.len(), 2);
+        assert_eq!(document.paragraphs()[0].get("Package"), Some("foo"));
+        assert_eq!(document.paragraphs()[0].get("Version"), Some("1.0"));
+        assert_eq!(document.paragraphs()[1].get("Package"), Some("bar"));
+        assert_eq!(document.paragraphs()[1].get("Version"), None);
+    }
+
+    #[test]
+    fn parse_multiline() {
+        let input = "Description: short\n long\n .\n\tmore\nFiles:\n abc 42 foo.tar.gz\n";
+        let document = Document::parse(input).unwrap();
+        let paragraph = &document.paragraphs()[0];
+        assert_eq!(paragraph.get("Description"), Some("short\nlong\n\nmore"));
+        assert_eq!(paragraph.get("Files"), Some("abc 42 foo.tar.gz"));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(Document::parse("Foo: bar\nbaz\n").unwrap_err().to_string(), "Line 2 doesn't contain a colon");
+        assert_eq!(Document::parse(" foo\n").unwrap_err().to_string(), "Line 1 is a continuation line but there is no field to continue");
+        assert_eq!(Document::parse(": foo\n").unwrap_err().to_string(), "Line 1 contains a field with empty name");
+    }
+
+    #[test]
+    fn parse_tolerant() {
+        let (document, report) = Document::parse_tolerant(" foo\nPackage: foo\nbar\n: baz\nVersion: 1.0\n\nPackage: bar");
+        assert_eq!(document.len(), 2);
+        assert_eq!(document.paragraphs()[0].get("Package"), Some("foo"));
+        assert_eq!(document.paragraphs()[0].get("Version"), Some("1.0"));
+        assert_eq!(document.paragraphs()[1].get("Package"), Some("bar"));
+        let expected = "\
+error: line 1: continuation line without a field to continue
+error: line 3: line doesn't contain a colon
+error: line 4: field with empty name
+";
+        assert_eq!(report.to_string(), expected);
+        assert_eq!(document.to_string(), " foo\nPackage: foo\nVersion: 1.0\n\nPackage: bar");
+        let (document, report) = Document::parse_tolerant("Package: foo\n");
+        assert_eq!(document.to_string(), "Package: foo\n");
+        assert!(report.is_empty());
+    }
+
+    #[test]
+    fn write() {
+        let mut paragraph = Paragraph::new();
+        paragraph.push(Field::new("Package", "foo"));
+        paragraph.set("Description", "short\nlong\n\nmore");
+        let document = vec![paragraph.clone(), paragraph].into_iter().collect::<Document>();
+        let expected = "Package: foo\nDescription: short\n long\n .\n more\n\nPackage: foo\nDescription: short\n long\n .\n more\n";
+        assert_eq!(document.to_string(), expected);
+        assert_eq!(Document::parse(expected).unwrap(), document);
+    }
+
+    #[test]
+    fn case_insensitive() {
+        let mut document = Document::parse("Package: foo\nX-Tag: a\nx-tag: b\n").unwrap();
+        let paragraph = &mut document.paragraphs_mut()[0];
+        assert_eq!(paragraph.get("package"), Some("foo"));
+        assert_eq!(paragraph.get_exact("package"), None);
+        assert_eq!(paragraph.get_exact("x-tag"), Some("b"));
+        assert_eq!(paragraph.get_all("X-TAG").collect::<Vec<_>>(), ["a", "b"]);
+        paragraph.set("PACKAGE", "bar");
+        assert_eq!(paragraph.remove("x-TAG").unwrap().value(), "a");
+        assert_eq!(paragraph.to_string(), "Package: bar\nx-tag: b\n");
+    }
+
+    #[test]
+    fn merge() {
+        let base = Document::parse("Package: foo\nPriority:  optional\nDepends: a, b\n").unwrap().paragraphs()[0].clone();
+        let overrides = Document::parse("Priority: important\ndepends: b, c\nSection: utils\n").unwrap().paragraphs()[0].clone();
+
+        let mut merged = base.clone();
+        merged.merge(&overrides, super::MergePolicy::Override);
+        assert_eq!(merged.to_string(), "Package: foo\nPriority:  important\nDepends: b, c\nSection: utils\n");
+
+        let mut merged = base.clone();
+        merged.merge(&overrides, super::MergePolicy::FillMissing);
+        assert_eq!(merged.to_string(), "Package: foo\nPriority:  optional\nDepends: a, b\nSection: utils\n");
+
+        let mut merged = base;
+        merged.merge(&overrides, super::MergePolicy::AppendLists);
+        assert_eq!(merged.to_string(), "Package: foo\nPriority:  important\nDepends: a, b, c\nSection: utils\n");
+    }
+
+    #[test]
+    fn preserve_formatting() {
+        let input = "# Leading comment\n\nSource:   foo\nBuild-Depends: a,\n# interleaved\n               b\n\n\n \nPackage: foo\n# before field\nDescription: short\n\tlong\n\n# trailing comment";
+        let mut document = Document::parse(input).unwrap();
+        assert_eq!(document.paragraphs()[0].get("Build-Depends"), Some("a,\nb"));
+        assert_eq!(document.to_string(), input);
+        document.paragraphs_mut()[0].set("Source", "bar");
+        document.paragraphs_mut()[1].set("Architecture", "any");
+        let expected = "# Leading comment\n\nSource:   bar\nBuild-Depends: a,\n# interleaved\n               b\n\n\n \nPackage: foo\n# before field\nDescription: short\n\tlong\nArchitecture: any\n\n# trailing comment";
+        assert_eq!(document.to_string(), expected);
+        document.discard_formatting();
+        assert_eq!(document.to_string(), "# Leading comment\n\nSource: bar\n# interleaved\nBuild-Depends: a,\n b\n\nPackage: foo\n# before field\nDescription: short\n long\nArchitecture: any\n\n# trailing comment\n");
+    }
+
+    #[test]
+    fn edit_keeps_layout() {
+        let input = "Source: foo\n# bump me\nStandards-Version:\t4.5.0\nBuild-Depends:\n  debhelper-compat (= 13),\n# needed for tests\n  python3\nUploaders: A <a@example.com>,\n           B <b@example.com>\n\nPackage: foo\nDescription: short\n long\n";
+        let mut document = Document::parse(input).unwrap();
+        let source = &mut document.paragraphs_mut()[0];
+        source.set("Standards-Version", "4.6.2");
+        source.set("Build-Depends", "debhelper-compat (= 13),\npython3,\npython3-pytest");
+        source.set("Uploaders", "A <a@example.com>");
+        source.set("Rules-Requires-Root", "no");
+        let expected = "Source: foo\n# bump me\nStandards-Version:\t4.6.2\n# needed for tests\nBuild-Depends:\n  debhelper-compat (= 13),\n  python3,\n  python3-pytest\nUploaders: A <a@example.com>\nRules-Requires-Root: no\n\nPackage: foo\nDescription: short\n long\n";
+        assert_eq!(document.to_string(), expected);
+        assert_eq!(Document::parse(expected).unwrap(), document);
+
+        let field = document.paragraphs_mut()[0].get_mut("Uploaders").unwrap();
+        field.set_value("A <a@example.com>,\nC <c@example.com>");
+        assert_eq!(field.to_string(), "Uploaders: A <a@example.com>,\n           C <c@example.com>\n");
+        field.set_value("");
+        assert_eq!(field.to_string(), "Uploaders: \n");
+        field.discard_formatting();
+        field.set_value("A <a@example.com>,\nC <c@example.com>");
+        assert_eq!(field.to_string(), "Uploaders: A <a@example.com>,\n C <c@example.com>\n");
+    }
+
+    #[test]
+    fn sort() {
+        let input = "Description: foo\n bar\nX-Custom: 1\nDepends: baz\n# the name\npackage: foo\nArchitecture: any\n";
+        let mut paragraph = Document::parse(input).unwrap().paragraphs()[0].clone();
+        paragraph.sort_fields(super::order::BINARY_CONTROL);
+        assert_eq!(paragraph.to_string(), "# the name\npackage: foo\nArchitecture: any\nDepends: baz\nDescription: foo\n bar\nX-Custom: 1\n");
+
+        assert!(paragraph.move_field("x-custom", 0));
+        assert!(paragraph.move_field("Package", 42));
+        assert!(!paragraph.move_field("Version", 0));
+        let names = paragraph.iter().map(super::Field::name).collect::<Vec<_>>();
+        assert_eq!(names, ["X-Custom", "Architecture", "Depends", "Description", "package"]);
+    }
+
+    #[test]
+    fn typed() {
+        #[derive(serde_derive::Deserialize, serde_derive::Serialize, Debug, Eq, PartialEq)]
+        #[serde(rename_all = "PascalCase")]
+        struct Package {
+            package: String,
+            version: String,
+            #[serde(default)]
+            depends: Vec<String>,
+        }
+
+        let paragraph = Document::parse("Package: foo\nX-Custom: bar\nVersion: 42\n").unwrap().paragraphs()[0].clone();
+        let package = paragraph.deserialize_into::<Package>().unwrap();
+        assert_eq!(package, Package { package: "foo".to_owned(), version: "42".to_owned(), depends: Vec::new(), });
+        assert!(paragraph.deserialize_into::<std::collections::HashMap<String, u32>>().is_err());
+
+        let package = Package { package: "foo".to_owned(), version: "1.0".to_owned(), depends: vec!["a".to_owned(), "b".to_owned()], };
+        let paragraph = Paragraph::from_serialize(&package).unwrap();
+        assert_eq!(paragraph.get("Depends"), Some("a,\nb"));
+        assert_eq!(paragraph.to_string(), crate::to_string(&package).unwrap());
+        assert_eq!(paragraph.deserialize_into::<Package>().unwrap(), package);
+        assert!(Paragraph::from_serialize(&42).is_err());
+    }
+
+    #[test]
+    fn reparse() {
+        fn spans(document: &Document) -> Vec<Option<Span>> {
+            document.iter().flat_map(|paragraph| std::iter::once(paragraph.span()).chain(paragraph.iter().map(Field::span))).collect()
+        }
+
+        fn check(input: &str, edit: std::ops::Range<usize>, replacement: &str) -> std::ops::Range<usize> {
+            let mut document = Document::parse(input).unwrap();
+            let mut changed = input.to_owned();
+            changed.replace_range(edit.clone(), replacement);
+            let reparsed = document.reparse(edit, replacement, &changed).unwrap();
+            let expected = Document::parse(&changed).unwrap();
+            assert_eq!(document, expected);
+            assert_eq!(document.to_string(), changed);
+            assert_eq!(spans(&document), spans(&expected));
+            assert_eq!(document.trailing_comments(), expected.trailing_comments());
+            reparsed
+        }
+
+        let input = "# head\nA: 1\n\nB: 2\n b\n  \n# gap\n\n# c\nC: 3\n\nD: 4\n\nE: 5\n# end\n";
+        // Value in the middle
+        assert_eq!(check(input, 38..39, "x\n c"), 2..4);
+        // Removing the empty line merges paragraphs
+        assert_eq!(check(input, 12..13, ""), 0..2);
+        assert_eq!(check(input, 40..41, ""), 2..4);
+        // Making the whitespace-only line a field
+        assert_eq!(check(input, 21..23, "X:"), 1..3);
+        // Inserting a paragraph
+        assert_eq!(check(input, 41..41, "F: 6\n\n"), 3..6);
+        check(input, 0..0, "Z: 0\n\n");
+        check(input, 0..7, "");
+        check(input, input.len()..input.len(), "F: 6");
+        check(input, (input.len() - 6)..input.len(), "");
+        check(input, 0..input.len(), "");
+        check("", 0..0, "A: 1\n\nB: 2");
+        check("A: 1", 4..4, "\n\nB: 2\n");
+
+        let mut document = Document::parse(input).unwrap();
+        let error = document.reparse(31..31, " x\n", &format!("{} x\n{}", &input[..31], &input[31..])).unwrap_err();
+        assert_eq!(error.to_string(), "Line 9 is a continuation line but there is no field to continue");
+        assert_eq!(document.to_string(), input);
+    }
+
+    #[test]
+    fn spans() {
+        let input = "Package: foo\n# about bar\nDescription: bar\n baz\n\n\n# comment\nPackage: bar";
+        let document = Document::parse(input).unwrap();
+        let first = &document.paragraphs()[0];
+        let description = first.fields()[1].span().unwrap();
+        assert_eq!(&input[description.range()], "Description: bar\n baz\n");
+        assert_eq!((description.first_line(), description.last_line()), (3, 4));
+        assert_eq!(description.to_string(), "lines 3-4");
+        assert_eq!(&input[first.span().unwrap().range()], "Package: foo\n# about bar\nDescription: bar\n baz\n");
+
+        let second = document.paragraphs()[1].span().unwrap();
+        assert_eq!(&input[second.range()], "# comment\nPackage: bar");
+        assert_eq!(second.to_string(), "lines 7-8");
+        assert_eq!(Paragraph::new().span(), None);
+    }
+
+    #[test]
+    fn comments() {
+        let input = "# about foo\n\n# the name\nPackage: foo\n#commented: out\nVersion: 1.0\n\n# end\n";
+        let mut document = Document::parse(input).unwrap();
+        assert_eq!(document.paragraphs()[0].comments(), [" about foo"]);
+        assert_eq!(document.paragraphs()[0].fields()[0].comments(), [" the name"]);
+        assert_eq!(document.paragraphs()[0].fields()[1].comments(), ["commented: out"]);
+        assert_eq!(document.trailing_comments(), [" end"]);
+
+        document.paragraphs_mut()[0].set("package", "bar");
+        document.paragraphs_mut()[0].comments_mut().push(" more".to_owned());
+        document.trailing_comments_mut().clear();
+        assert_eq!(document.to_string(), "# about foo\n# more\n\n# the name\nPackage: bar\n#commented: out\nVersion: 1.0\n");
+    }
+
+    #[test]
+    fn serde() {
+        let input = "Package: foo\nDescription: short\n long\n\nPackage: bar\n";
+        let document = crate::from_str::<Document>(input).unwrap();
+        assert_eq!(document, Document::parse(input).unwrap());
+        assert_eq!(crate::to_string(&document).unwrap(), input);
+    }
+}
@@ -0,0 +1,82 @@
+//! Canonical field orders for use with [`Paragraph::sort_fields`](super::Paragraph::sort_fields).
+//!
+//! The orders follow the conventions used in Debian packaging tools and documentation.
+//! Fields not listed here are supposed to go after the listed ones.
+
+/// Order of fields in the source paragraph of `debian/control`.
+pub const SOURCE_CONTROL: &[&str] = &[
+    "Source",
+    "Section",
+    "Priority",
+    "Maintainer",
+    "Uploaders",
+    "Standards-Version",
+    "Build-Depends",
+    "Build-Depends-Arch",
+    "Build-Depends-Indep",
+    "Build-Conflicts",
+    "Build-Conflicts-Arch",
+    "Build-Conflicts-Indep",
+    "Rules-Requires-Root",
+    "Homepage",
+    "Vcs-Arch",
+    "Vcs-Bzr",
+    "Vcs-Cvs",
+    "Vcs-Darcs",
+    "Vcs-Git",
+    "Vcs-Hg",
+    "Vcs-Mtn",
+    "Vcs-Svn",
+    "Vcs-Browser",
+    "Testsuite",
+    "Testsuite-Triggers",
+];
+
+/// Order of fields in binary package paragraphs of `debian/control`.
+pub const BINARY_CONTROL: &[&str] = &[
+    "Package",
+    "Architecture",
+    "Multi-Arch",
+    "Section",
+    "Priority",
+    "Essential",
+    "Build-Profiles",
+    "Built-Using",
+    "Pre-Depends",
+    "Depends",
+    "Recommends",
+    "Suggests",
+    "Enhances",
+    "Breaks",
+    "Conflicts",
+    "Provides",
+    "Replaces",
+    "Package-Type",
+    "Description",
+];
+
+/// Order of fields in `DEBIAN/control` of binary packages and in `Packages` files.
+pub const DEB_CONTROL: &[&str] = &[
+    "Package",
+    "Source",
+    "Version",
+    "Architecture",
+    "Multi-Arch",
+    "Essential",
+    "Maintainer",
+    "Installed-Size",
+    "Pre-Depends",
+    "Depends",
+    "Recommends",
+    "Suggests",
+    "Enhances",
+    "Breaks",
+    "Conflicts",
+    "Provides",
+    "Replaces",
+    "Built-Using",
+    "Section",
+    "Priority",
+    "Homepage",
+    "Description",
+];
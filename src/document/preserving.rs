@@ -0,0 +1,184 @@
+//! Typed values remembering the paragraph they were loaded from.
+
+use std::ops::{Deref, DerefMut};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use super::{Paragraph, de::ParagraphDeserializer, ser::ParagraphSerializer};
+
+/// Typed value that preserves unknown fields, field order and formatting.
+///
+/// This is a middle ground between typed structs and [`Paragraph`].
+/// The value is deserialized from a paragraph as usual but the paragraph is kept around.
+/// When the value is converted back into a paragraph fields ignored by `T` are retained, fields
+/// that didn't change keep their original formatting and position, and only changed fields are
+/// re-rendered.
+/// Fields that `T` knows about but doesn't serialize anymore (e.g. `None`) are removed and new
+/// fields are appended at the end.
+///
+/// The original formatting is only available when the paragraph comes from
+/// [`Document::parse`](super::Document::parse).
+/// `Preserving<T>` also implements `Deserialize` and `Serialize` which retain unknown fields and
+/// their order but not formatting.
+///
+/// # Example
+///
+/// ```
+/// use rfc822_like::document::{Document, Preserving};
+///
+/// #[derive(serde_derive::Deserialize, serde_derive::Serialize)]
+/// #[serde(rename_all = "PascalCase")]
+/// struct Source {
+///     source: String,
+///     #[serde(rename = "Standards-Version")]
+///     standards_version: String,
+/// }
+///
+/// let input = "Source: foo\nSection:    utils\nStandards-Version: 4.5.0\n";
+/// let mut document = Document::parse(input).unwrap();
+/// let paragraph = document.paragraphs_mut().remove(0);
+/// let mut source = Preserving::<Source>::from_paragraph(paragraph).unwrap();
+/// source.standards_version = "4.6.0".to_owned();
+/// document.push(source.to_paragraph().unwrap());
+/// assert_eq!(document.to_string(), "Source: foo\nSection:    utils\nStandards-Version: 4.6.0\n");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Preserving<T> {
+    value: T,
+    original: Paragraph,
+    ignored: Vec<bool>,
+}
+
+impl<T: DeserializeOwned> Preserving<T> {
+    /// Deserializes the value from the paragraph and remembers the paragraph.
+    pub fn from_paragraph(paragraph: Paragraph) -> Result<Self, crate::de::Error> {
+        let settings = crate::de::ValueSettings::default();
+        let mut deserializer = ParagraphDeserializer::new(&paragraph.fields, &settings);
+        let value = T::deserialize(&mut deserializer)?;
+        let ignored = deserializer.into_ignored();
+        Ok(Preserving {
+            value,
+            original: paragraph,
+            ignored,
+        })
+    }
+}
+
+impl<T: Serialize> Preserving<T> {
+    /// Serializes the value into a paragraph preserving the original one as much as possible.
+    pub fn to_paragraph(&self) -> Result<Paragraph, crate::ser::Error> {
+        let mut new_fields = self.value.serialize(ParagraphSerializer)?
+            .into_iter()
+            .map(Some)
+            .collect::<Vec<_>>();
+        let mut fields = Vec::with_capacity(new_fields.len());
+
+        for (field, ignored) in self.original.fields.iter().zip(&self.ignored) {
+            if *ignored {
+                fields.push(field.clone());
+                continue;
+            }
+
+            let new_field = new_fields
+                .iter_mut()
+                .find(|new_field| new_field.as_ref().map_or(false, |new_field| new_field.name == field.name))
+                .and_then(Option::take);
+
+            match new_field {
+                Some(new_field) if new_field.value == field.value => fields.push(field.clone()),
+                Some(new_field) => {
+                    let mut field = field.clone();
+                    field.replace_keeping_comments(new_field);
+                    fields.push(field);
+                },
+                None => (),
+            }
+        }
+        fields.extend(new_fields.into_iter().flatten());
+
+        Ok(Paragraph {
+            fields,
+            comments: self.original.comments.clone(),
+            leading: self.original.leading.clone(),
+            span: self.original.span,
+        })
+    }
+}
+
+impl<T> Preserving<T> {
+    /// Returns the paragraph the value was deserialized from.
+    pub fn original(&self) -> &Paragraph {
+        &self.original
+    }
+
+    /// Returns the value discarding the paragraph.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T> Deref for Preserving<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for Preserving<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.value
+    }
+}
+
+impl<'de, T: DeserializeOwned> serde::Deserialize<'de> for Preserving<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let paragraph = Paragraph::deserialize(deserializer)?;
+        Preserving::from_paragraph(paragraph).map_err(serde::de::Error::custom)
+    }
+}
+
+impl<T: Serialize> Serialize for Preserving<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_paragraph()
+            .map_err(serde::ser::Error::custom)?
+            .serialize(serializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Preserving;
+    use crate::document::Document;
+
+    #[derive(serde_derive::Deserialize, serde_derive::Serialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct Package {
+        package: String,
+        version: Option<String>,
+        #[serde(default)]
+        depends: Vec<String>,
+    }
+
+    #[test]
+    fn preserve() {
+        let input = "Package: foo\nX-Custom:   keep me\nVersion: 1.0\nDepends: bar,\n         baz\n";
+        let document = Document::parse(input).unwrap();
+        let mut package = Preserving::<Package>::from_paragraph(document.paragraphs()[0].clone()).unwrap();
+        assert_eq!(package.to_paragraph().unwrap().to_string(), input);
+
+        package.version = None;
+        package.depends.push("qux".to_owned());
+        assert_eq!(package.to_paragraph().unwrap().to_string(), "Package: foo\nX-Custom:   keep me\nDepends: bar,\n         baz,\n         qux\n");
+
+        package.version = Some("2.0".to_owned());
+        assert_eq!(package.to_paragraph().unwrap().to_string(), "Package: foo\nX-Custom:   keep me\nVersion: 2.0\nDepends: bar,\n         baz,\n         qux\n");
+    }
+
+    #[test]
+    fn serde() {
+        let input = "Package: foo\nX-Custom: keep me\nDepends: bar\n\nPackage: baz\n";
+        let packages = crate::from_str::<Vec<Preserving<Package>>>(input).unwrap();
+        assert_eq!(packages[1].package, "baz");
+        assert_eq!(crate::to_string(&packages).unwrap(), input);
+    }
+}
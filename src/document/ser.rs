@@ -0,0 +1,129 @@
+//! Serialization of typed values into paragraphs.
+
+use serde::ser;
+use crate::ser::{Error, KeySerializer, write_field};
+use super::{Field, Lines, unfold};
+
+/// Serializer collecting fields of a struct or map.
+pub(crate) struct ParagraphSerializer;
+
+impl ParagraphSerializer {
+    fn field<T: ?Sized + ser::Serialize>(fields: &mut Vec<Field>, name: &str, value: &T) -> Result<(), Error> {
+        // Reusing the serializer guarantees the value is the same as if it was written and
+        // parsed back.
+        let mut text = String::new();
+        write_field(&mut text, name, value)?;
+        if text.is_empty() {
+            return Ok(());
+        }
+        let mut lines = Lines { input: &text[(name.len() + 1)..], offset: 0, };
+        let (_, first) = lines.next().expect("the serializer didn't write the value");
+        let value = unfold(first, lines.map(|(_, line)| line));
+        // Keeping the text as raw makes the field look exactly as serializer would write it.
+        fields.push(Field { name: name.to_owned(), value, comments: Vec::new(), raw: Some(text), layout: None, span: None, });
+        Ok(())
+    }
+}
+
+impl serde::Serializer for ParagraphSerializer {
+    type Ok = Vec<Field>;
+    type Error = Error;
+    type SerializeSeq = ser::Impossible<Self::Ok, Self::Error>;
+    type SerializeTuple = ser::Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleStruct = ser::Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleVariant = ser::Impossible<Self::Ok, Self::Error>;
+    type SerializeMap = FieldsSerializer;
+    type SerializeStruct = FieldsSerializer;
+    type SerializeStructVariant = ser::Impossible<Self::Ok, Self::Error>;
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<Self::SerializeStruct, Self::Error> {
+        Ok(FieldsSerializer {
+            fields: Vec::with_capacity(len),
+            key: String::new(),
+            has_key: false,
+        })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Ok(FieldsSerializer {
+            fields: Vec::with_capacity(len.unwrap_or(0)),
+            key: String::new(),
+            has_key: false,
+        })
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<Self::Ok, Self::Error> where T: ?Sized + ser::Serialize {
+        value.serialize(self)
+    }
+
+    unsupported_types! {
+        fn serialize_bool(self, v: bool) -> Result<Self::Ok>;
+        fn serialize_i8(self, v: i8) -> Result<Self::Ok>;
+        fn serialize_i16(self, v: i16) -> Result<Self::Ok>;
+        fn serialize_i32(self, v: i32) -> Result<Self::Ok>;
+        fn serialize_i64(self, v: i64) -> Result<Self::Ok>;
+        fn serialize_u8(self, v: u8) -> Result<Self::Ok>;
+        fn serialize_u16(self, v: u16) -> Result<Self::Ok>;
+        fn serialize_u32(self, v: u32) -> Result<Self::Ok>;
+        fn serialize_u64(self, v: u64) -> Result<Self::Ok>;
+        fn serialize_f32(self, v: f32) -> Result<Self::Ok>;
+        fn serialize_f64(self, v: f64) -> Result<Self::Ok>;
+        fn serialize_char(self, v: char) -> Result<Self::Ok>;
+        fn serialize_str(self, v: &str) -> Result<Self::Ok>;
+        fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok>;
+        fn serialize_none(self) -> Result<Self::Ok>;
+        fn serialize_some<T>(self, value: &T) -> Result<Self::Ok> where T: ?Sized + Serialize;
+        fn serialize_unit(self) -> Result<Self::Ok>;
+        fn serialize_unit_struct(self, name: &'static str) -> Result<Self::Ok>;
+        fn serialize_unit_variant(self, name: &'static str, variant_index: u32, variant: &'static str) -> Result<Self::Ok>;
+        fn serialize_newtype_variant<T>(self, name: &'static str, variant_index: u32, variant: &'static str, value: &T) -> Result<Self::Ok>
+        where
+            T: ?Sized + Serialize;
+        fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq>;
+        fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple>;
+        fn serialize_tuple_struct(self, name: &'static str, len: usize) -> Result<Self::SerializeTupleStruct>;
+        fn serialize_tuple_variant(self, name: &'static str, variant_index: u32, variant: &'static str, len: usize) -> Result<Self::SerializeTupleVariant>;
+        fn serialize_struct_variant(self, name: &'static str, variant_index: u32, variant: &'static str, len: usize) -> Result<Self::SerializeStructVariant>;
+    }
+}
+
+pub(crate) struct FieldsSerializer {
+    fields: Vec<Field>,
+    // Reused for all keys to avoid allocating for each field
+    key: String,
+    has_key: bool,
+}
+
+impl ser::SerializeStruct for FieldsSerializer {
+    type Ok = Vec<Field>;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error> where T: ?Sized + ser::Serialize {
+        ParagraphSerializer::field(&mut self.fields, key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(self.fields)
+    }
+}
+
+impl ser::SerializeMap for FieldsSerializer {
+    type Ok = Vec<Field>;
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), Self::Error> where T: ?Sized + ser::Serialize {
+        key.serialize(KeySerializer { key: &mut self.key, human_readable: true, })?;
+        self.has_key = true;
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Self::Error> where T: ?Sized + ser::Serialize {
+        assert!(self.has_key, "serialize_value() called before serialize_key()");
+        self.has_key = false;
+        ParagraphSerializer::field(&mut self.fields, &self.key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(self.fields)
+    }
+}
@@ -0,0 +1,121 @@
+//! `proptest` strategies generating only valid documents.
+//!
+//! The strategies follow the same rules as the `Arbitrary` implementations: writing a generated
+//! value and parsing it back always produces an equal value.
+//! This makes them useful for property-testing round trips of types built on top of this crate.
+//!
+//! This module requires the `proptest` feature.
+//!
+//! # Example
+//!
+//! ```
+//! use proptest::prelude::*;
+//! use rfc822_like::document::{Document, strategy};
+//!
+//! proptest! {
+//!     fn roundtrip(document in strategy::document()) {
+//!         prop_assert_eq!(Document::parse(&document.to_string()).unwrap(), document);
+//!     }
+//! }
+//! # roundtrip();
+//! ```
+
+use proptest::prelude::*;
+use proptest::collection::vec;
+use super::{Document, Field, Paragraph};
+
+/// Generates valid field names.
+///
+/// The names consist of printable ASCII characters except colon and don't start with `#` or `-`.
+pub fn field_name() -> impl Strategy<Value=String> {
+    let first = prop::char::range('!', '~').prop_filter("not a valid start of a field name", |c| !":#-".contains(*c));
+    let rest = vec(prop::char::range('!', '~').prop_filter("colon", |c| *c != ':'), 0..16);
+    (first, rest).prop_map(|(first, rest)| std::iter::once(first).chain(rest).collect())
+}
+
+/// Generates a single line of an unfolded value.
+///
+/// The line contains no control characters, no surrounding whitespace and is not `.`.
+pub fn value_line() -> impl Strategy<Value=String> {
+    any::<String>().prop_map(|line| {
+        let line = line.chars().filter(|c| !c.is_control()).collect::<String>();
+        let line = line.trim();
+        if line == "." { String::new() } else { line.to_owned() }
+    })
+}
+
+/// Generates unfolded values - the representation stored in [`Field`] and produced by the
+/// deserializer.
+///
+/// Multi-line values never start with an empty line.
+pub fn value() -> impl Strategy<Value=String> {
+    (value_line(), vec(value_line(), 0..4)).prop_map(|(first, continuations)| {
+        let mut value = first;
+        if !value.is_empty() {
+            for line in continuations {
+                value.push('\n');
+                value.push_str(&line);
+            }
+        }
+        value
+    })
+}
+
+/// Generates pairs of folded and unfolded values.
+///
+/// The folded value is the text following the colon up to and including the final newline as
+/// it may appear in a file - with varying indentation of continuation lines.
+/// Parsing the folded value yields the unfolded one.
+pub fn folded_value() -> impl Strategy<Value=(String, String)> {
+    value().prop_flat_map(|unfolded| {
+        let lines = unfolded.split('\n').count();
+        let indents = vec(prop_oneof![Just(" "), Just("  "), Just("\t")], lines);
+        (Just(unfolded), indents)
+    }).prop_map(|(unfolded, indents)| {
+        let mut lines = unfolded.split('\n');
+        let mut folded = format!(" {}\n", lines.next().unwrap_or(""));
+        for (line, indent) in lines.zip(indents) {
+            folded.push_str(indent);
+            folded.push_str(if line.is_empty() { "." } else { line });
+            folded.push('\n');
+        }
+        (folded, unfolded)
+    })
+}
+
+/// Generates fields with valid names and values.
+pub fn field() -> impl Strategy<Value=Field> {
+    (field_name(), value()).prop_map(|(name, value)| Field::new(name, value))
+}
+
+/// Generates non-empty paragraphs.
+///
+/// Empty paragraphs can't be represented in text.
+pub fn paragraph() -> impl Strategy<Value=Paragraph> {
+    vec(field(), 1..8).prop_map(|fields| fields.into_iter().collect())
+}
+
+/// Generates documents with up to four paragraphs.
+pub fn document() -> impl Strategy<Value=Document> {
+    vec(paragraph(), 0..5).prop_map(|paragraphs| paragraphs.into_iter().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+    use crate::document::Document;
+
+    proptest! {
+        #[test]
+        fn document_roundtrip(document in super::document()) {
+            let text = document.to_string();
+            prop_assert_eq!(Document::parse(&text).unwrap(), document);
+        }
+
+        #[test]
+        fn folded_value_parses(name in super::field_name(), (folded, unfolded) in super::folded_value()) {
+            let document = Document::parse(&format!("{}:{}", name, folded)).unwrap();
+            prop_assert_eq!(document.paragraphs()[0].get_exact(&name), Some(&*unfolded));
+        }
+    }
+}
@@ -0,0 +1,421 @@
+//! Pull-based parsing into a stream of events.
+//!
+//! The [`Parser`] is an iterator over [`Event`]s describing the structure of the input without
+//! building any data model or involving serde.
+//! This is useful for tools that need to see the input exactly as it's written, e.g. syntax
+//! highlighters or streaming transformers processing huge files.
+//!
+//! The parser follows the same rules as [`Document::parse`](crate::document::Document::parse):
+//! lines consisting only of whitespace separate paragraphs and lines starting with `#` are
+//! comments.
+//! Each paragraph is reported as `ParagraphStart`, its fields and `ParagraphEnd`.
+//! Comments are reported in the order they appear except comments interleaved with
+//! continuation lines of a field, which are reported right after the field.
+//! Comments preceding a paragraph are reported before its `ParagraphStart`.
+//!
+//! The [`Emitter`] is the counterpart writing correctly formatted output from the same kind of
+//! calls, so events can be transformed and written back without going through serde values.
+//!
+//! # Example
+//!
+//! ```
+//! use rfc822_like::events::{Event, Parser};
+//!
+//! let input = "# header\nPackage: foo\nDescription: short\n long\n\nPackage: bar\n";
+//! let events = Parser::new(input).collect::<Result<Vec<_>, _>>().unwrap();
+//! let names = events.iter().map(|event| match event {
+//!     Event::ParagraphStart { .. } => "{".to_owned(),
+//!     Event::Field { name, value, .. } => format!("{}={:?}", name, value),
+//!     Event::Comment { text, .. } => format!("#{}", text),
+//!     Event::ParagraphEnd => "}".to_owned(),
+//! }).collect::<Vec<_>>();
+//! assert_eq!(names, ["# header", "{", "Package=\"foo\"", "Description=\"short\\nlong\"", "}", "{", "Package=\"bar\"", "}"]);
+//! ```
+
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::fmt::{self, Write};
+use crate::document::{Error, Span, comment_text, unfold};
+use crate::document::error::ErrorInner;
+use crate::ser::error::ErrorInternal;
+
+/// A single item of the parsed input.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Event<'a> {
+    /// A paragraph starts, emitted right before its first field.
+    ParagraphStart {
+        /// The line of the first field.
+        line: usize,
+    },
+    /// A field including all its continuation lines.
+    Field {
+        /// The name as written in the input.
+        name: &'a str,
+        /// The unfolded value, borrowed from the input unless it has multiple lines.
+        value: Cow<'a, str>,
+        /// Position of the whole field including continuation lines.
+        span: Span,
+    },
+    /// A comment line.
+    Comment {
+        /// The text after `#` without the line ending.
+        text: &'a str,
+        /// Position of the comment line.
+        span: Span,
+    },
+    /// The current paragraph ended.
+    ParagraphEnd,
+}
+
+/// Iterator over events of the input.
+///
+/// The iterator stops after returning an error.
+#[derive(Debug, Clone)]
+pub struct Parser<'a> {
+    input: &'a str,
+    // Byte offset and number of the next line
+    offset: usize,
+    line: usize,
+    in_paragraph: bool,
+    pending: VecDeque<Event<'a>>,
+    failed: bool,
+}
+
+impl<'a> Parser<'a> {
+    /// Creates the parser of the input.
+    pub fn new(input: &'a str) -> Self {
+        Parser {
+            input,
+            offset: 0,
+            line: 1,
+            in_paragraph: false,
+            pending: VecDeque::new(),
+            failed: false,
+        }
+    }
+
+    /// Returns the line at `offset` including the line ending.
+    fn line_at(&self, offset: usize) -> Option<&'a str> {
+        let rest = &self.input[offset..];
+        if rest.is_empty() {
+            return None;
+        }
+        let end = rest.find('\n').map_or(rest.len(), |pos| pos + 1);
+        Some(&rest[..end])
+    }
+
+    /// Skips the line which caused the last error so that parsing can continue after it.
+    pub(crate) fn skip_line(&mut self) {
+        if let Some(line) = self.line_at(self.offset) {
+            self.offset += line.len();
+            self.line += 1;
+        }
+        self.failed = false;
+    }
+
+    fn span(&self, start: usize, end: usize, first_line: usize) -> Span {
+        Span::new(start, end, first_line, first_line + self.input[start..end].trim_end_matches('\n').matches('\n').count())
+    }
+
+    /// Consumes the field starting at the current line including its continuation lines and
+    /// interleaved comments.
+    fn field(&mut self, line: &'a str) -> Result<Event<'a>, Error> {
+        let start = self.offset;
+        let first_line = self.line;
+        let colon = line.find(':').ok_or(ErrorInner::MissingColon(first_line))?;
+        let name = &line[..colon];
+        if name.is_empty() {
+            return Err(ErrorInner::EmptyFieldName(first_line).into());
+        }
+
+        let mut end = start + line.len();
+        let mut line_number = first_line + 1;
+        let mut continuations = Vec::new();
+        let mut comments = Vec::new();
+        // Comments are only a part of the field if a continuation line follows them
+        let mut candidate_comments = Vec::new();
+        let mut pos = end;
+        while let Some(next) = self.line_at(pos) {
+            if next.starts_with('#') {
+                candidate_comments.push((pos, line_number, next));
+            } else if next.starts_with(' ') || next.starts_with('\t') {
+                if next.trim().is_empty() {
+                    break;
+                }
+                comments.append(&mut candidate_comments);
+                continuations.push(next);
+                end = pos + next.len();
+            } else {
+                break;
+            }
+            pos += next.len();
+            line_number += 1;
+        }
+
+        self.line = first_line + self.input[start..end].matches('\n').count().max(1);
+        self.offset = end;
+        for (comment_start, comment_line, comment) in comments {
+            let span = Span::new(comment_start, comment_start + comment.len(), comment_line, comment_line);
+            self.pending.push_back(Event::Comment { text: comment_text(comment), span, });
+        }
+
+        let first = &line[(colon + 1)..];
+        let value = if continuations.is_empty() {
+            Cow::Borrowed(first.trim())
+        } else {
+            Cow::Owned(unfold(first, continuations.into_iter()))
+        };
+        Ok(Event::Field { name, value, span: self.span(start, end, first_line), })
+    }
+
+    fn next_event(&mut self) -> Result<Option<Event<'a>>, Error> {
+        if let Some(event) = self.pending.pop_front() {
+            return Ok(Some(event));
+        }
+        while let Some(line) = self.line_at(self.offset) {
+            if line.trim().is_empty() {
+                self.offset += line.len();
+                self.line += 1;
+                if self.in_paragraph {
+                    self.in_paragraph = false;
+                    return Ok(Some(Event::ParagraphEnd));
+                }
+            } else if line.starts_with('#') {
+                let span = Span::new(self.offset, self.offset + line.len(), self.line, self.line);
+                self.offset += line.len();
+                self.line += 1;
+                return Ok(Some(Event::Comment { text: comment_text(line), span, }));
+            } else if line.starts_with(' ') || line.starts_with('\t') {
+                return Err(ErrorInner::OrphanContinuation(self.line).into());
+            } else {
+                let line_number = self.line;
+                let field = self.field(line)?;
+                if self.in_paragraph {
+                    self.pending.push_front(field);
+                } else {
+                    self.in_paragraph = true;
+                    self.pending.push_front(field);
+                    return Ok(Some(Event::ParagraphStart { line: line_number, }));
+                }
+                return Ok(self.pending.pop_front());
+            }
+        }
+        if self.in_paragraph {
+            self.in_paragraph = false;
+            return Ok(Some(Event::ParagraphEnd));
+        }
+        Ok(None)
+    }
+}
+
+impl<'a> Iterator for Parser<'a> {
+    type Item = Result<Event<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let result = self.next_event();
+        if result.is_err() {
+            self.failed = true;
+        }
+        result.transpose()
+    }
+}
+
+/// Writes paragraphs, fields and comments into `fmt::Write`.
+///
+/// Values are folded the same way the serializer does it, including marking empty lines with
+/// ` .` and paragraphs are separated by an empty line.
+/// Calls in an order that can't be represented (e.g. a field outside of a paragraph) return an
+/// error.
+///
+/// # Example
+///
+/// ```
+/// use rfc822_like::events::Emitter;
+///
+/// let mut emitter = Emitter::new(String::new());
+/// emitter.comment(" generated").unwrap();
+/// emitter.start_paragraph().unwrap();
+/// emitter.field("Package", "foo").unwrap();
+/// emitter.field("Description", "short\n\nlong").unwrap();
+/// emitter.end_paragraph().unwrap();
+/// emitter.start_paragraph().unwrap();
+/// emitter.field("Package", &42).unwrap();
+/// emitter.end_paragraph().unwrap();
+/// assert_eq!(emitter.into_inner(), "# generated\nPackage: foo\nDescription: short\n .\n long\n\nPackage: 42\n");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Emitter<W: Write> {
+    output: W,
+    wrap_long_lines: bool,
+    in_paragraph: bool,
+    has_fields: bool,
+    // Set after a paragraph with fields ended, the separator is written before whatever follows
+    pending_separator: bool,
+}
+
+impl<W: Write> Emitter<W> {
+    /// Creates the emitter writing into `output`.
+    pub fn new(output: W) -> Self {
+        Emitter {
+            output,
+            wrap_long_lines: false,
+            in_paragraph: false,
+            has_fields: false,
+            pending_separator: false,
+        }
+    }
+
+    /// Causes lines longer than 80 characters to be wrapped on word boundaries.
+    pub fn wrap_long_lines(mut self, wrap: bool) -> Self {
+        self.wrap_long_lines = wrap;
+        self
+    }
+
+    /// Starts a new paragraph.
+    pub fn start_paragraph(&mut self) -> Result<(), crate::ser::Error> {
+        if self.in_paragraph {
+            return Err(ErrorInternal::InvalidOrder("paragraph started before the previous one ended").into());
+        }
+        self.in_paragraph = true;
+        Ok(())
+    }
+
+    /// Writes a field of the current paragraph.
+    ///
+    /// The value may contain newlines, it's folded into continuation lines.
+    pub fn field<T: fmt::Display + ?Sized>(&mut self, name: &str, value: &T) -> Result<(), crate::ser::Error> {
+        if !self.in_paragraph {
+            return Err(ErrorInternal::InvalidOrder("field written outside of a paragraph").into());
+        }
+        self.write_separator()?;
+        self.has_fields = true;
+        crate::ser::write_text_field(&mut self.output, name, value, self.wrap_long_lines)
+    }
+
+    /// Writes a comment line, `text` is the part following `#`.
+    ///
+    /// Comments may appear both inside and between paragraphs.
+    pub fn comment(&mut self, text: &str) -> Result<(), crate::ser::Error> {
+        if text.contains('\n') {
+            return Err(ErrorInternal::NewlineInComment.into());
+        }
+        self.write_separator()?;
+        writeln!(self.output, "#{}", text).map_err(crate::ser::Error::failed_write)
+    }
+
+    /// Ends the current paragraph.
+    ///
+    /// Paragraphs without fields can't be represented so they produce no output.
+    pub fn end_paragraph(&mut self) -> Result<(), crate::ser::Error> {
+        if !self.in_paragraph {
+            return Err(ErrorInternal::InvalidOrder("no paragraph to end").into());
+        }
+        self.in_paragraph = false;
+        self.pending_separator |= self.has_fields;
+        self.has_fields = false;
+        Ok(())
+    }
+
+    /// Writes the event, allowing to pass the output of [`Parser`] directly.
+    ///
+    /// Spans are ignored.
+    pub fn event(&mut self, event: &Event<'_>) -> Result<(), crate::ser::Error> {
+        match event {
+            Event::ParagraphStart { .. } => self.start_paragraph(),
+            Event::Field { name, value, .. } => self.field(name, &**value),
+            Event::Comment { text, .. } => self.comment(text),
+            Event::ParagraphEnd => self.end_paragraph(),
+        }
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.output
+    }
+
+    fn write_separator(&mut self) -> Result<(), crate::ser::Error> {
+        if self.pending_separator {
+            self.output.write_char('\n').map_err(crate::ser::Error::failed_write)?;
+            self.pending_separator = false;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Emitter, Event, Parser};
+    use crate::document::Document;
+
+    #[test]
+    fn events() {
+        let input = "A: 1\n#c1\n b\n#c2\nB:\n .\n  x\n \t\n\n#c3\nC: 3";
+        let events = Parser::new(input).collect::<Result<Vec<_>, _>>().unwrap();
+        let summary = events.iter().map(|event| match event {
+            Event::ParagraphStart { line, } => format!("start {}", line),
+            Event::Field { name, value, span, } => format!("{}={:?} {} {:?}", name, value, span, &input[span.range()]),
+            Event::Comment { text, span, } => format!("#{} {}", text, span),
+            Event::ParagraphEnd => "end".to_owned(),
+        }).collect::<Vec<_>>();
+        assert_eq!(summary, [
+            "start 1",
+            "A=\"1\\nb\" lines 1-3 \"A: 1\\n#c1\\n b\\n\"",
+            "#c1 line 2",
+            "#c2 line 4",
+            "B=\"\\nx\" lines 5-7 \"B:\\n .\\n  x\\n\"",
+            "end",
+            "#c3 line 10",
+            "start 11",
+            "C=\"3\" line 11 \"C: 3\"",
+            "end",
+        ]);
+
+        let fields = Parser::new(input)
+            .filter_map(|event| match event.unwrap() {
+                Event::Field { value, .. } => Some(value.into_owned()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let document = Document::parse(input).unwrap();
+        let expected = document.iter().flatten().map(|field| field.value().to_owned()).collect::<Vec<_>>();
+        assert_eq!(fields, expected);
+    }
+
+    #[test]
+    fn errors() {
+        let mut parser = Parser::new("A: 1\n\n b\nC: 2\n");
+        assert_eq!(parser.by_ref().filter(Result::is_ok).count(), 3);
+        let mut parser = Parser::new(" b\n");
+        assert_eq!(parser.next().unwrap().unwrap_err().to_string(), "Line 1 is a continuation line but there is no field to continue");
+        assert!(parser.next().is_none());
+        assert_eq!(Parser::new("x\n").next().unwrap().unwrap_err().to_string(), "Line 1 doesn't contain a colon");
+        assert_eq!(Parser::new(":x\n").next().unwrap().unwrap_err().to_string(), "Line 1 contains a field with empty name");
+    }
+
+    #[test]
+    fn emitter() {
+        let input = "# header\nA: 1\n#c1\n b\nB:\n x\n .\n y\n\n#c3\nC: 3\n";
+        let mut emitter = Emitter::new(String::new());
+        for event in Parser::new(input) {
+            emitter.event(&event.unwrap()).unwrap();
+        }
+        assert_eq!(emitter.into_inner(), "# header\nA: 1\n b\n#c1\nB: x\n .\n y\n\n#c3\nC: 3\n");
+
+        let mut emitter = Emitter::new(String::new());
+        emitter.start_paragraph().unwrap();
+        emitter.end_paragraph().unwrap();
+        emitter.start_paragraph().unwrap();
+        assert!(emitter.start_paragraph().is_err());
+        assert!(emitter.comment("a\nb").is_err());
+        assert!(emitter.field("", "x").is_err());
+        emitter.field("A", "x\n").unwrap();
+        emitter.end_paragraph().unwrap();
+        assert!(emitter.end_paragraph().is_err());
+        assert!(emitter.field("B", "y").is_err());
+        emitter.comment(" trailing").unwrap();
+        assert_eq!(emitter.into_inner(), "A: x\n .\n\n# trailing\n");
+    }
+}
@@ -0,0 +1,131 @@
+//! Automatic mapping between struct field names and field names used in files.
+//!
+//! Rust structs use `snake_case` field names while Debian uses `Train-Case` (e.g.
+//! `Installed-Size`) with a few irregular names such as `MD5sum`.
+//! Instead of annotating each field with `#[serde(rename = "...")]` a [`KeyMapping`] can be set
+//! on the [`Serializer`](crate::Serializer) and the [`Deserializer`](crate::Deserializer) to
+//! convert the names at runtime.
+//! The mapping only applies to structs, keys of maps are left intact.
+//!
+//! # Example
+//!
+//! ```
+//! use rfc822_like::key_mapping::KeyMapping;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(serde_derive::Deserialize, serde_derive::Serialize)]
+//! struct Package {
+//!     package: String,
+//!     installed_size: String,
+//!     md5sum: String,
+//! }
+//!
+//! let input = "Package: foo\nInstalled-Size: 42\nMD5sum: 00\n";
+//! let deserializer = rfc822_like::Deserializer::new(input.as_bytes()).key_mapping(KeyMapping::debian());
+//! let package = Package::deserialize(deserializer).unwrap();
+//! assert_eq!(package.installed_size, "42");
+//!
+//! let mut output = String::new();
+//! package.serialize(rfc822_like::Serializer::new(&mut output).key_mapping(KeyMapping::debian())).unwrap();
+//! assert_eq!(output, input);
+//! ```
+
+use std::borrow::Cow;
+
+/// Irregular Debian field names.
+const DEBIAN_OVERRIDES: &[(&str, &str)] = &[
+    ("md5sum", "MD5sum"),
+    ("sha1", "SHA1"),
+    ("sha256", "SHA256"),
+    ("sha512", "SHA512"),
+    ("description_md5", "Description-md5"),
+    ("dm_upload_allowed", "DM-Upload-Allowed"),
+    ("no_support_for_architecture_all", "No-Support-for-Architecture-all"),
+    ("not_automatic", "NotAutomatic"),
+    ("but_automatic_upgrades", "ButAutomaticUpgrades"),
+];
+
+/// Converts struct field names to field names in files and back.
+///
+/// Names are converted between `snake_case` and `Train-Case` unless they are listed in the table
+/// of overrides.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "config", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeyMapping {
+    overrides: Vec<(Cow<'static, str>, Cow<'static, str>)>,
+}
+
+impl KeyMapping {
+    /// Creates the mapping without any overrides.
+    pub fn new() -> Self {
+        KeyMapping::default()
+    }
+
+    /// Creates the mapping with overrides for irregular Debian field names such as `MD5sum`.
+    pub fn debian() -> Self {
+        KeyMapping {
+            overrides: DEBIAN_OVERRIDES
+                .iter()
+                .map(|&(ident, field)| (Cow::Borrowed(ident), Cow::Borrowed(field)))
+                .collect(),
+        }
+    }
+
+    /// Maps the struct field `ident` to the `field` name, replacing the previous override.
+    pub fn with_override<I, F>(mut self, ident: I, field: F) -> Self where I: Into<Cow<'static, str>>, F: Into<Cow<'static, str>> {
+        let ident = ident.into();
+        self.overrides.retain(|(existing, _)| *existing != ident);
+        self.overrides.push((ident, field.into()));
+        self
+    }
+
+    /// Returns the name of the field in file for the struct field `ident`.
+    pub fn to_field<'a>(&'a self, ident: &str) -> Cow<'a, str> {
+        if let Some((_, field)) = self.overrides.iter().find(|(existing, _)| existing == ident) {
+            return Cow::Borrowed(field);
+        }
+
+        let ident = if ident.starts_with("r#") { &ident[2..] } else { ident };
+        let mut result = String::with_capacity(ident.len());
+        for (i, word) in ident.split('_').enumerate() {
+            if i > 0 {
+                result.push('-');
+            }
+            let mut chars = word.chars();
+            if let Some(first) = chars.next() {
+                result.extend(first.to_uppercase());
+                result.push_str(chars.as_str());
+            }
+        }
+        Cow::Owned(result)
+    }
+
+    /// Returns the name of the struct field for the `field` name in file.
+    ///
+    /// The comparison ignores ASCII case since field names in files are case-insensitive.
+    pub fn to_ident<'a>(&'a self, field: &str) -> Cow<'a, str> {
+        match self.overrides.iter().find(|(_, existing)| existing.eq_ignore_ascii_case(field)) {
+            Some((ident, _)) => Cow::Borrowed(ident),
+            None => Cow::Owned(field.to_ascii_lowercase().replace('-', "_")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::KeyMapping;
+
+    #[test]
+    fn conversion() {
+        let mapping = KeyMapping::debian().with_override("md5sum", "MD5Sum").with_override("x_python_version", "X-Python3-Version");
+        assert_eq!(mapping.to_field("installed_size"), "Installed-Size");
+        assert_eq!(mapping.to_field("r#type"), "Type");
+        assert_eq!(mapping.to_field("md5sum"), "MD5Sum");
+        assert_eq!(mapping.to_field("not_automatic"), "NotAutomatic");
+        assert_eq!(mapping.to_field("x_python_version"), "X-Python3-Version");
+        assert_eq!(mapping.to_ident("Installed-Size"), "installed_size");
+        assert_eq!(mapping.to_ident("notautomatic"), "not_automatic");
+        assert_eq!(mapping.to_ident("X-Python3-Version"), "x_python_version");
+        assert_eq!(KeyMapping::new().to_field("sha256"), "Sha256");
+    }
+}
@@ -23,23 +23,107 @@
 //!
 //! Check [`Deserializer`] type for deserialization API reference and examples.
 //! Check [`Serializer`] type for serialization API reference and examples.
+//! Check [`document`] module if you need to work with files without knowing their schema.
+//! Types for common Debian fields (e.g. package relations) are available in the `debian` module
+//! if the `debian` feature is enabled.
+//! Adapters for plain field types usable with `#[serde(with = "...")]` are in the [`with`] module.
+//! Documents can be validated against a declarative schema using the `schema` module if the
+//! `schema` feature is enabled.
+//! The [`lint`] module reports formatting problems such as duplicate fields or trailing whitespace.
+//! The [`events`] module exposes the parser as a pull-based stream of events independent of serde.
+//! The [`split`] module splits input into the raw text of stanzas without parsing them.
+//! The [`clearsigned`] module extracts the payload of OpenPGP clearsigned input such as `InRelease`.
+//! The [`digest`] module abstracts over hash functions so that any crypto library can be used.
+//! [`MultiMap`] keeps repeated fields of a stanza in order when deserializing and serializing.
+//!
+//! Functions working with `std::io` and the file system are behind the default `io` and `fs`
+//! features.
+//! Disabling default features makes the crate suitable for targets like `wasm32-unknown-unknown`
+//! while keeping `from_str`, `to_string` and the [`document`] module available.
 
 #![deny(missing_docs)]
 
+// Must be defined before the modules using it
+macro_rules! unsupported_types {
+    ($(fn $fn_name:ident$(<$($gen:ident),*>)?(self $(, $arg:ident: $arg_type:ty)*) -> Result<$ret:ty> $(where $type:ty: ?Sized + Serialize)?;)*) => {
+        $(
+            fn $fn_name$(<$($gen),*>)?(self $(, $arg: $arg_type)*) -> Result<$ret, Self::Error> $(where $type: ?Sized + serde::Serialize)? {
+                $(
+                    let _ = $arg;
+                )*
+                Err($crate::ser::Error::unsupported_data_type(stringify!($fn_name)))
+            }
+        )*
+    }
+}
+
 pub mod de;
 pub mod ser;
+pub mod document;
+pub mod key_mapping;
+pub mod report;
+pub mod lint;
+pub mod events;
+pub mod split;
+pub mod clearsigned;
+pub mod digest;
+pub mod multimap;
+#[cfg(feature = "schema")]
+pub mod schema;
+pub mod with;
+#[cfg(feature = "test_utils")]
+pub mod test_utils;
+#[cfg(any(feature = "test_utils", feature = "rustcrypto"))]
+mod line_diff;
+#[cfg(test)]
+mod testing;
+#[cfg(feature = "debian")]
+pub mod debian;
 
 pub use de::Deserializer;
 pub use ser::Serializer;
+pub use multimap::MultiMap;
+pub use with::{as_comma_list, as_line_list, as_space_list, as_yes_no};
+#[cfg(all(feature = "debian", feature = "chrono"))]
+pub use with::as_rfc2822_date;
+/// Converts `snake_case` field names to `Train-Case` (e.g. `Installed-Size`).
+///
+/// Fields with explicit `#[serde(rename = "...")]` are left intact.
+/// This macro requires the `macros` feature.
+///
+/// # Example
+///
+/// ```
+/// #[rfc822_like::rename_train_case]
+/// #[derive(serde_derive::Deserialize)]
+/// struct Package {
+///     package: String,
+///     installed_size: String,
+///     #[serde(rename = "MD5sum")]
+///     md5sum: String,
+/// }
+///
+/// let package = rfc822_like::from_str::<Package>("Package: foo\nInstalled-Size: 42\nMD5sum: 00\n").unwrap();
+/// assert_eq!(package.installed_size, "42");
+/// ```
+#[cfg(feature = "macros")]
+pub use rfc822_like_macros::rename_train_case;
 
 use serde::{Serialize, Deserialize};
-use std::{io, fmt};
+use std::fmt;
+#[cfg(feature = "io")]
+use std::io;
+#[cfg(feature = "fs")]
 use std::path::{Path, PathBuf};
+#[cfg(feature = "fs")]
 use de::error::ReadFileError;
 
 /// Deserialize a value from a reader.
 ///
 /// This deserializes `T` using data returned by `reader`.
+///
+/// This function requires the `io` feature.
+#[cfg(feature = "io")]
 pub fn from_reader<T: for<'a> Deserialize<'a>, R: io::BufRead>(reader: R) -> Result<T, de::Error> {
     T::deserialize(Deserializer::new(reader))
 }
@@ -52,6 +136,9 @@ pub fn from_reader<T: for<'a> Deserialize<'a>, R: io::BufRead>(reader: R) -> Res
 ///
 /// Note that instead of [`std::io::Error`] this returns [`ReadFileError`] which carries
 /// information about path so that the error message is more useful.
+///
+/// This function requires the `fs` feature.
+#[cfg(feature = "fs")]
 pub fn from_file<T: for<'a> Deserialize<'a>, P: AsRef<Path> + Into<PathBuf>>(path: P) -> Result<T, ReadFileError> {
     let file = match std::fs::File::open(&path) {
         Ok(file) => file,
@@ -64,13 +151,19 @@ pub fn from_file<T: for<'a> Deserialize<'a>, P: AsRef<Path> + Into<PathBuf>>(pat
 /// Deserializes a value from bytes that are *not* guaranteed to be UTF-8.
 ///
 /// Non-UTF8 data will obviously still fail but you don't have to do the check explicitly.
-pub fn from_bytes<'a, T: Deserialize<'a>>(mut bytes: &'a [u8]) -> Result<T, de::Error> {
-    T::deserialize(Deserializer::new(&mut bytes))
+/// The error reports the line, byte offset and hex dump of the surrounding bytes so that corrupted
+/// input can be located easily.
+/// Fields of type `&str` or `Cow<str>` borrow from `bytes`, see [`Deserializer::from_str`].
+pub fn from_bytes<'a, T: Deserialize<'a>>(bytes: &'a [u8]) -> Result<T, de::Error> {
+    let s = std::str::from_utf8(bytes).map_err(|error| de::Error::invalid_utf8(bytes, error))?;
+    from_str(s)
 }
 
 /// Deserializes a value from a string.
+///
+/// Fields of type `&str` or `Cow<str>` borrow from `s`, see [`Deserializer::from_str`].
 pub fn from_str<'a, T: Deserialize<'a>>(s: &'a str) -> Result<T, de::Error> {
-    from_bytes(s.as_bytes())
+    T::deserialize(Deserializer::from_str(s))
 }
 
 /// Writes the `value` to [`std::fmt::Write`]r.
@@ -83,35 +176,120 @@ pub fn to_fmt_writer<T: Serialize, W: fmt::Write>(writer: W, value: &T) -> Resul
 ///  Writes the `value` to [`std::io::Write`]r.
 ///
 ///  The `Write` trait from `std::io` is more common than `fmt` so a convenience function is
-///  provided that writes to `std::io` instead. This is mainly useful for writing into files. Note
-///  however that this function doesn't perform any buffering so you need to take care of that!
+///  provided that writes to `std::io` instead. This is mainly useful for writing into files.
+///  The serializer produces many small fragments so the output is buffered internally and
+///  flushed before returning, there's no need to wrap files or sockets in `BufWriter`.
+///
+///  This function requires the `io` feature.
+#[cfg(feature = "io")]
 pub fn to_writer<T: Serialize, W: io::Write>(writer: W, value: &T) -> Result<(), ser::Error> {
-    fmt2io::write(writer, |writer| to_fmt_writer(writer, value).map(Ok).or_else(ser::Error::into_fmt))
-        .map_err(ser::error::ErrorInternal::IoWriteFailed)?
+    let mut writer = io::BufWriter::new(writer);
+    fmt2io::write(&mut writer, |writer| to_fmt_writer(writer, value).map(Ok).or_else(ser::Error::into_fmt))
+        .map_err(ser::error::ErrorInternal::IoWriteFailed)??;
+    io::Write::flush(&mut writer).map_err(ser::error::ErrorInternal::IoWriteFailed)?;
+    Ok(())
 }
 
 /// Serializes the `value` into memory.
 ///
-/// This allocates the string and writes the value into it. It may cause multiple reallocations so
-/// it's better to write to writers directly if possible.
+/// This allocates the string and writes the value into it.
+/// When serializing a sequence of known length the space for all records is reserved based on
+/// the average size of the written ones so large documents don't cause many reallocations.
 pub fn to_string<T: Serialize>(value: &T) -> Result<String, ser::Error> {
     let mut result = String::new();
-    to_fmt_writer(&mut result, value)?;
+    value.serialize(Serializer::reserving(&mut result))?;
+    Ok(result)
+}
+
+/// Serializes the `records` into memory using multiple threads.
+///
+/// The output is the same as the output of [`to_string`], see
+/// [`Serializer::serialize_parallel`] for details.
+///
+/// This function requires the `parallel` feature.
+#[cfg(feature = "parallel")]
+pub fn to_string_parallel<T: Serialize + Sync>(records: &[T]) -> Result<String, ser::Error> {
+    let mut result = String::new();
+    Serializer::new(&mut result).serialize_parallel(records)?;
     Ok(result)
 }
 
+/// Serializes the `value` into a byte vector.
+///
+/// This is the same as [`to_string`] converted to bytes.
+pub fn to_vec<T: Serialize>(value: &T) -> Result<Vec<u8>, ser::Error> {
+    to_string(value).map(String::into_bytes)
+}
+
 #[cfg(test)]
 mod tests {
     use quickcheck::{quickcheck, TestResult};
     use std::collections::HashMap;
 
+    #[test]
+    fn to_string_reserves() {
+        let records = (0..100).map(|i| {
+            let mut record = HashMap::new();
+            record.insert("Package", format!("package-{}", i % 10));
+            record
+        }).collect::<Vec<_>>();
+        let output = super::to_string(&records).unwrap();
+        assert!(output.capacity() - output.len() < 30);
+        assert_eq!(super::to_vec(&records).unwrap(), output.into_bytes());
+    }
+
+    #[test]
+    fn to_string_reserves_bounded() {
+        let records = (0..100_000).map(|i| {
+            let mut record = HashMap::new();
+            let package = if i == 0 { "a".repeat(1_000_000) } else { format!("p{}", i % 10) };
+            record.insert("Package", package);
+            record
+        }).collect::<Vec<_>>();
+        let output = super::to_string(&records).unwrap();
+        // The first record must not be extrapolated to all the others
+        assert!(output.capacity() <= output.len() * 5);
+    }
+
+    #[cfg(feature = "io")]
+    #[test]
+    fn to_writer_buffers() {
+        struct CountingWriter {
+            output: Vec<u8>,
+            writes: usize,
+        }
+
+        impl std::io::Write for CountingWriter {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.writes += 1;
+                self.output.extend_from_slice(buf);
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let records = (0..100).map(|i| {
+            let mut record = HashMap::new();
+            record.insert("Package", format!("package-{}", i));
+            record.insert("Description", "a\nmulti-line\n\nvalue".to_owned());
+            record
+        }).collect::<Vec<_>>();
+        let mut writer = CountingWriter { output: Vec::new(), writes: 0, };
+        super::to_writer(&mut writer, &records).unwrap();
+        assert_eq!(writer.output, super::to_string(&records).unwrap().into_bytes());
+        assert!(writer.writes < 10);
+    }
+
     quickcheck! {
         fn reversible_map_string_serialization(map: HashMap<String, String>) -> TestResult {
             for (key, value) in &map {
                 if key.is_empty() || key.contains(&[':', '\n', '\0'] as &[_]) || key.trim() != key || value.trim() != value || value.contains('\0') {
                     return TestResult::discard();
                 }
-                if let Some(_) = value.split('\n').find(|line| line.trim() != *line || *line == ".") {
+                if value.split('\n').any(|line| line.trim() != line || line == ".") {
                     return TestResult::discard();
                 }
             }
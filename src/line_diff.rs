@@ -0,0 +1,52 @@
+//! Line-based comparison of texts shared by `pdiff` and `test_utils`.
+
+/// Returns the positions of matching lines using Myers' algorithm.
+pub(crate) fn common_lines(old: &[&str], new: &[&str]) -> Vec<(usize, usize)> {
+    let n = old.len() as isize;
+    let m = new.len() as isize;
+    let max = (n + m) as usize;
+    let offset = max as isize + 1;
+    let mut v = vec![0isize; 2 * max + 3];
+    // Only the diagonals reachable in each round are stored to keep memory proportional to the
+    // square of the number of differences.
+    let mut trace = Vec::new();
+
+    'rounds: for d in 0..=(max as isize) {
+        trace.push(v[((offset - d) as usize)..=((offset + d) as usize)].to_owned());
+        let mut k = -d;
+        while k <= d {
+            let i = (k + offset) as usize;
+            let mut x = if k == -d || (k != d && v[i - 1] < v[i + 1]) { v[i + 1] } else { v[i - 1] + 1 };
+            let mut y = x - k;
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[i] = x;
+            if x >= n && y >= m {
+                break 'rounds;
+            }
+            k += 2;
+        }
+    }
+
+    let mut common = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let k = x - y;
+        let get = |k: isize| v[(k + d) as usize];
+        let prev_k = if k == -d || (k != d && get(k - 1) < get(k + 1)) { k + 1 } else { k - 1 };
+        let prev_x = if d == 0 { 0 } else { get(prev_k) };
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            x -= 1;
+            y -= 1;
+            common.push((x as usize, y as usize));
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+    common.reverse();
+    common
+}
@@ -0,0 +1,216 @@
+//! Non-fatal checks of the formatting of documents.
+//!
+//! Unlike parsing or schema validation the lints report problems which don't make the
+//! file invalid but are likely mistakes or make the file harder to read:
+//!
+//! * duplicate fields in a paragraph
+//! * non-canonical capitalization of well-known field names (e.g. `package` instead of `Package`)
+//! * trailing whitespace
+//! * lines longer than the limit (80 characters by default)
+//! * misused empty continuation lines - values starting or ending with ` .` and lines containing
+//!   only whitespace, which end the paragraph instead of adding an empty line
+//!
+//! All lints are warnings, only failure to parse the input is reported as an error.
+//! Line-based checks need the original formatting so they skip fields that were modified after
+//! parsing.
+//!
+//! # Example
+//!
+//! ```
+//! use rfc822_like::lint::Linter;
+//!
+//! let input = "package: foo\nDescription: short \n long\n .\n\nPackage: bar\nPackage: baz\n";
+//! let report = Linter::new().lint_str(input);
+//! assert_eq!(report.to_string(), "\
+//! warning: line 1, paragraph 1, field package: non-canonical field name, use Package
+//! warning: line 2, paragraph 1, field Description: trailing whitespace
+//! warning: line 4, paragraph 1, field Description: value ends with an empty line
+//! warning: line 7, paragraph 2, field Package: duplicate field
+//! ");
+//! ```
+
+use std::borrow::Cow;
+use crate::document::{Document, Field, Paragraph, order};
+use crate::report::{Location, Problem, Report};
+
+/// Field names not present in the canonical orders but common in Debian files.
+const EXTRA_KNOWN_FIELDS: &[&str] = &[
+    "Filename",
+    "Size",
+    "MD5sum",
+    "SHA1",
+    "SHA256",
+    "SHA512",
+    "Description-md5",
+    "Tag",
+    "Status",
+    "Origin",
+    "Label",
+    "Suite",
+    "Codename",
+    "Date",
+    "Valid-Until",
+    "Architectures",
+    "Components",
+    "Binary",
+    "Format",
+    "Files",
+    "Checksums-Sha1",
+    "Checksums-Sha256",
+    "Directory",
+    "Conffiles",
+];
+
+/// Configurable set of lints.
+#[derive(Debug, Clone)]
+pub struct Linter {
+    max_line_length: Option<usize>,
+    known_fields: Vec<Cow<'static, str>>,
+}
+
+impl Default for Linter {
+    fn default() -> Self {
+        let known_fields = order::SOURCE_CONTROL.iter()
+            .chain(order::BINARY_CONTROL)
+            .chain(order::DEB_CONTROL)
+            .chain(EXTRA_KNOWN_FIELDS)
+            .map(|name| Cow::Borrowed(*name))
+            .collect();
+
+        Linter {
+            max_line_length: Some(80),
+            known_fields,
+        }
+    }
+}
+
+impl Linter {
+    /// Creates the linter with default settings suitable for Debian files.
+    pub fn new() -> Self {
+        Linter::default()
+    }
+
+    /// Sets the maximum length of lines in characters, `None` disables the check.
+    pub fn with_max_line_length(mut self, max: Option<usize>) -> Self {
+        self.max_line_length = max;
+        self
+    }
+
+    /// Replaces the well-known field names used for checking capitalization.
+    pub fn with_known_fields<I>(mut self, names: I) -> Self where I: IntoIterator, I::Item: Into<Cow<'static, str>> {
+        self.known_fields = names.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Parses and lints the input.
+    ///
+    /// Invalid lines are reported as errors and skipped, see [`Document::parse_tolerant`].
+    pub fn lint_str(&self, input: &str) -> Report {
+        let (document, mut report) = Document::parse_tolerant(input);
+        report.extend(self.lint_document(&document));
+
+        // Lines between paragraphs are not a part of any field.
+        let mut lines = input.lines().enumerate().peekable();
+        let mut previous_empty = true;
+        while let Some((i, line)) = lines.next() {
+            let is_empty = line.trim().is_empty();
+            if is_empty && !line.is_empty() && !previous_empty {
+                let followed_by_text = lines.peek().map_or(false, |(_, next)| !next.trim().is_empty());
+                if followed_by_text {
+                    report.push(Problem::warning("line containing only whitespace ends the paragraph, use ' .' for empty lines in values").at(Location::new().with_line(i + 1)));
+                }
+            }
+            previous_empty = is_empty;
+        }
+        report
+    }
+
+    /// Lints the parsed document.
+    pub fn lint_document(&self, document: &Document) -> Report {
+        let mut report = Report::new();
+        for (index, paragraph) in document.iter().enumerate() {
+            self.lint_paragraph(index, paragraph, &mut report);
+        }
+        report
+    }
+
+    /// Lints a single paragraph, `index` is used in locations.
+    pub fn lint_paragraph(&self, index: usize, paragraph: &Paragraph, report: &mut Report) {
+        for (i, field) in paragraph.iter().enumerate() {
+            let location = |line_offset: usize| {
+                let location = Location::new().with_paragraph(index).with_field(field.name());
+                match field.span() {
+                    Some(span) => location.with_line(span.first_line() + line_offset),
+                    None => location,
+                }
+            };
+
+            if let Some(canonical) = self.known_fields.iter().find(|known| known.eq_ignore_ascii_case(field.name())) {
+                if canonical != field.name() {
+                    report.push(Problem::warning(format!("non-canonical field name, use {}", canonical)).at(location(0)));
+                }
+            }
+            self.lint_lines(field, &location, report);
+            if paragraph.fields()[..i].iter().any(|previous| previous.is_named(field.name())) {
+                report.push(Problem::warning("duplicate field").at(location(0)));
+            }
+        }
+    }
+
+    fn lint_lines<F: Fn(usize) -> Location>(&self, field: &Field, location: &F, report: &mut Report) {
+        let raw = match field.raw() {
+            Some(raw) => raw,
+            None => return,
+        };
+
+        let first_empty = raw.lines().next().map_or(false, |first| first[(field.name().len() + 1)..].trim().is_empty());
+        let mut last_continuation = None;
+        for (i, line) in raw.lines().enumerate() {
+            if line.ends_with(' ') || line.ends_with('\t') {
+                report.push(Problem::warning("trailing whitespace").at(location(i)));
+            }
+            if let Some(max) = self.max_line_length {
+                let length = line.chars().count();
+                if length > max {
+                    report.push(Problem::warning(format!("line is {} characters long, the limit is {}", length, max)).at(location(i)));
+                }
+            }
+            if i > 0 && !line.starts_with('#') {
+                if first_empty && last_continuation.is_none() && line.trim() == "." {
+                    report.push(Problem::warning("value starts with an empty line").at(location(i)));
+                }
+                last_continuation = Some((i, line));
+            }
+        }
+        if let Some((i, line)) = last_continuation {
+            if line.trim() == "." {
+                report.push(Problem::warning("value ends with an empty line").at(location(i)));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Linter;
+
+    #[test]
+    fn lints() {
+        let input = "Files:\n .\n a\nDepends: foo,\n bar\n\nX-Long: aaaaaaaaaa\nX-Long: b\n \nX-Custom: 1\n";
+        let report = Linter::new().with_max_line_length(Some(15)).lint_str(input);
+        let expected = "\
+warning: line 2, paragraph 1, field Files: value starts with an empty line
+warning: line 7, paragraph 2, field X-Long: line is 18 characters long, the limit is 15
+warning: line 8, paragraph 2, field X-Long: duplicate field
+warning: line 9: line containing only whitespace ends the paragraph, use ' .' for empty lines in values
+";
+        assert_eq!(report.to_string(), expected);
+
+        let report = Linter::new().with_known_fields(vec!["X-Custom"]).lint_str("x-custom: 1\nPACKAGE: foo\n");
+        assert_eq!(report.to_string(), "warning: line 1, paragraph 1, field x-custom: non-canonical field name, use X-Custom\n");
+        assert!(Linter::new().lint_str("Package: foo\nDescription: a\n b\n .\n c\n").is_empty());
+        assert!(Linter::new().lint_str("foo\n").has_errors());
+        let report = Linter::new().lint_str("Package: foo\nbar\nPackage: baz\n");
+        assert_eq!(report.to_string(), "error: line 2: line doesn't contain a colon\nwarning: line 3, paragraph 1, field Package: duplicate field\n");
+    }
+}
@@ -0,0 +1,164 @@
+//! Records with repeated field names.
+//!
+//! The format doesn't forbid repeating a field within a stanza and some dialects rely on it.
+//! The deserializer passes repeated fields to the map visitor in order, however maps from the
+//! standard library keep only one of them.
+//! [`MultiMap`] keeps all fields in their original order and serializes them back the same way.
+//!
+//! # Example
+//!
+//! ```
+//! use rfc822_like::MultiMap;
+//!
+//! let input = "Name: foo\nAlias: bar\nAlias: baz\n";
+//! let record = rfc822_like::from_str::<MultiMap<String, String>>(input).unwrap();
+//! assert_eq!(record.get_all("Alias").collect::<Vec<_>>(), ["bar", "baz"]);
+//! assert_eq!(rfc822_like::to_string(&record).unwrap(), input);
+//!
+//! let record = MultiMap::from(vec![("Alias", "bar"), ("Name", "foo"), ("Alias", "baz")]);
+//! assert_eq!(rfc822_like::to_string(&record).unwrap(), "Alias: bar\nName: foo\nAlias: baz\n");
+//! ```
+
+use std::borrow::Borrow;
+use std::fmt;
+use std::marker::PhantomData;
+use serde::ser::SerializeMap;
+
+/// Ordered collection of fields allowing repeated names.
+///
+/// Fields are serialized as a map in the order they are stored, repeated names included.
+/// Any `Vec<(K, V)>` can be converted into it.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct MultiMap<K, V>(pub Vec<(K, V)>);
+
+impl<K, V> MultiMap<K, V> {
+    /// Creates an empty map.
+    pub fn new() -> Self {
+        MultiMap(Vec::new())
+    }
+
+    /// Appends a field, keeping the fields with the same name.
+    pub fn push(&mut self, key: K, value: V) {
+        self.0.push((key, value));
+    }
+
+    /// Returns the value of the first field named `key`.
+    pub fn get<Q: ?Sized + Eq>(&self, key: &Q) -> Option<&V> where K: Borrow<Q> {
+        self.0.iter().find(|(name, _)| name.borrow() == key).map(|(_, value)| value)
+    }
+
+    /// Iterates over the values of all fields named `key` in order.
+    pub fn get_all<'a, Q: ?Sized + Eq>(&'a self, key: &'a Q) -> impl Iterator<Item=&'a V> + 'a where K: Borrow<Q> {
+        self.0.iter().filter(move |(name, _)| name.borrow() == key).map(|(_, value)| value)
+    }
+
+    /// Iterates over all fields in order.
+    pub fn iter(&self) -> impl Iterator<Item=(&K, &V)> {
+        self.0.iter().map(|(key, value)| (key, value))
+    }
+
+    /// Returns the number of fields, repeated ones included.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if there are no fields.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl<K, V> Default for MultiMap<K, V> {
+    fn default() -> Self {
+        MultiMap::new()
+    }
+}
+
+impl<K, V> From<Vec<(K, V)>> for MultiMap<K, V> {
+    fn from(fields: Vec<(K, V)>) -> Self {
+        MultiMap(fields)
+    }
+}
+
+impl<K, V> From<MultiMap<K, V>> for Vec<(K, V)> {
+    fn from(map: MultiMap<K, V>) -> Self {
+        map.0
+    }
+}
+
+impl<K, V> std::iter::FromIterator<(K, V)> for MultiMap<K, V> {
+    fn from_iter<I: IntoIterator<Item=(K, V)>>(iter: I) -> Self {
+        MultiMap(iter.into_iter().collect())
+    }
+}
+
+impl<K, V> Extend<(K, V)> for MultiMap<K, V> {
+    fn extend<I: IntoIterator<Item=(K, V)>>(&mut self, iter: I) {
+        self.0.extend(iter)
+    }
+}
+
+impl<K, V> IntoIterator for MultiMap<K, V> {
+    type Item = (K, V);
+    type IntoIter = std::vec::IntoIter<(K, V)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<K: serde::Serialize, V: serde::Serialize> serde::Serialize for MultiMap<K, V> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (key, value) in &self.0 {
+            map.serialize_entry(key, value)?;
+        }
+        map.end()
+    }
+}
+
+struct MultiMapVisitor<K, V>(PhantomData<(K, V)>);
+
+impl<'de, K: serde::Deserialize<'de>, V: serde::Deserialize<'de>> serde::de::Visitor<'de> for MultiMapVisitor<K, V> {
+    type Value = MultiMap<K, V>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a map")
+    }
+
+    fn visit_map<A: serde::de::MapAccess<'de>>(self, mut access: A) -> Result<Self::Value, A::Error> {
+        let mut fields = Vec::with_capacity(access.size_hint().unwrap_or(0));
+        while let Some(field) = access.next_entry()? {
+            fields.push(field);
+        }
+        Ok(MultiMap(fields))
+    }
+}
+
+impl<'de, K: serde::Deserialize<'de>, V: serde::Deserialize<'de>> serde::Deserialize<'de> for MultiMap<K, V> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(MultiMapVisitor(PhantomData))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MultiMap;
+
+    #[test]
+    fn repeated_fields() {
+        let input = "Package: foo\nTag: a\nTag: b\n\nPackage: bar\nTag: c\nDescription: bar\n Tag: not a field\nTag: d\n";
+        let records = crate::from_str::<Vec<MultiMap<String, String>>>(input).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].get_all("Tag").collect::<Vec<_>>(), ["a", "b"]);
+        assert_eq!(records[1].get("Tag").map(String::as_str), Some("c"));
+        assert_eq!(records[1].len(), 4);
+        assert_eq!(crate::to_string(&records).unwrap(), input);
+
+        let mut record = MultiMap::new();
+        record.push("Tag", "b");
+        record.extend(vec![("Package", "foo"), ("Tag", "a")]);
+        assert_eq!(crate::to_string(&record).unwrap(), "Tag: b\nPackage: foo\nTag: a\n");
+        assert_eq!(Vec::from(record)[2], ("Tag", "a"));
+    }
+}
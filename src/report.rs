@@ -0,0 +1,291 @@
+//! Reports aggregating multiple problems found in the input.
+//!
+//! Deserialization stops at the first error which is what you want when loading data but not
+//! when checking it.
+//! Validation, linting and tolerant parsing instead collect all independent problems into a
+//! [`Report`] so that the user can fix all of them at once.
+
+use std::fmt;
+
+/// How serious a [`Problem`] is.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Severity {
+    /// The input is acceptable but could be improved.
+    Warning,
+    /// The input is invalid.
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Warning => f.write_str("warning"),
+            Severity::Error => f.write_str("error"),
+        }
+    }
+}
+
+/// Place in the input where a [`Problem`] was found.
+///
+/// All parts are optional because some problems don't have a precise location (e.g. a missing
+/// field has no line).
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct Location {
+    line: Option<usize>,
+    paragraph: Option<usize>,
+    field: Option<String>,
+}
+
+impl Location {
+    /// Creates an empty location.
+    pub fn new() -> Self {
+        Location::default()
+    }
+
+    /// Sets the line number (one-based).
+    pub fn with_line(mut self, line: usize) -> Self {
+        self.line = Some(line);
+        self
+    }
+
+    /// Sets the index of the paragraph (zero-based).
+    pub fn with_paragraph(mut self, paragraph: usize) -> Self {
+        self.paragraph = Some(paragraph);
+        self
+    }
+
+    /// Sets the name of the field.
+    pub fn with_field<S: Into<String>>(mut self, field: S) -> Self {
+        self.field = Some(field.into());
+        self
+    }
+
+    /// Returns the line number (one-based) if known.
+    pub fn line(&self) -> Option<usize> {
+        self.line
+    }
+
+    /// Returns the index of the paragraph (zero-based) if known.
+    pub fn paragraph(&self) -> Option<usize> {
+        self.paragraph
+    }
+
+    /// Returns the name of the field if known.
+    pub fn field(&self) -> Option<&str> {
+        self.field.as_ref().map(AsRef::as_ref)
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.line.is_none() && self.paragraph.is_none() && self.field.is_none()
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut separator = "";
+        if let Some(line) = self.line {
+            write!(f, "line {}", line)?;
+            separator = ", ";
+        }
+        if let Some(paragraph) = self.paragraph {
+            write!(f, "{}paragraph {}", separator, paragraph + 1)?;
+            separator = ", ";
+        }
+        if let Some(field) = &self.field {
+            write!(f, "{}field {}", separator, field)?;
+        }
+        Ok(())
+    }
+}
+
+/// A single problem found in the input.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Problem {
+    severity: Severity,
+    location: Location,
+    message: String,
+}
+
+impl Problem {
+    /// Creates a problem with given severity and message and an empty location.
+    pub fn new<S: Into<String>>(severity: Severity, message: S) -> Self {
+        Problem {
+            severity,
+            location: Location::new(),
+            message: message.into(),
+        }
+    }
+
+    /// Creates a problem with `Error` severity.
+    pub fn error<S: Into<String>>(message: S) -> Self {
+        Problem::new(Severity::Error, message)
+    }
+
+    /// Creates a problem with `Warning` severity.
+    pub fn warning<S: Into<String>>(message: S) -> Self {
+        Problem::new(Severity::Warning, message)
+    }
+
+    /// Sets the location of the problem.
+    pub fn at(mut self, location: Location) -> Self {
+        self.location = location;
+        self
+    }
+
+    /// Returns the severity of the problem.
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    /// Returns the location of the problem.
+    pub fn location(&self) -> &Location {
+        &self.location
+    }
+
+    /// Returns the human-readable description of the problem.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.location.is_empty() {
+            write!(f, "{}: {}", self.severity, self.message)
+        } else {
+            write!(f, "{}: {}: {}", self.severity, self.location, self.message)
+        }
+    }
+}
+
+/// Collection of independent problems.
+///
+/// The `Display` implementation writes one problem per line.
+/// The report is an `Error` so it can be returned directly but note that it may also contain
+/// only warnings - use [`Report::into_result`] to treat only errors as failure.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Report {
+    problems: Vec<Problem>,
+}
+
+impl Report {
+    /// Creates an empty report.
+    pub fn new() -> Self {
+        Report::default()
+    }
+
+    /// Adds a problem to the report.
+    pub fn push(&mut self, problem: Problem) {
+        self.problems.push(problem);
+    }
+
+    /// Returns `true` if no problems were reported.
+    pub fn is_empty(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    /// Returns the number of reported problems.
+    pub fn len(&self) -> usize {
+        self.problems.len()
+    }
+
+    /// Returns `true` if at least one problem has `Error` severity.
+    pub fn has_errors(&self) -> bool {
+        self.errors().next().is_some()
+    }
+
+    /// Iterates over all problems in the order they were reported.
+    pub fn iter(&self) -> std::slice::Iter<'_, Problem> {
+        self.problems.iter()
+    }
+
+    /// Iterates over problems with `Error` severity.
+    pub fn errors(&self) -> impl Iterator<Item=&Problem> {
+        self.problems.iter().filter(|problem| problem.severity == Severity::Error)
+    }
+
+    /// Iterates over problems with `Warning` severity.
+    pub fn warnings(&self) -> impl Iterator<Item=&Problem> {
+        self.problems.iter().filter(|problem| problem.severity == Severity::Warning)
+    }
+
+    /// Returns `Err(self)` if the report contains errors, `Ok(self)` otherwise.
+    ///
+    /// The `Ok` variant can still contain warnings.
+    pub fn into_result(self) -> Result<Self, Self> {
+        if self.has_errors() {
+            Err(self)
+        } else {
+            Ok(self)
+        }
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for problem in &self.problems {
+            writeln!(f, "{}", problem)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Report {}
+
+impl Extend<Problem> for Report {
+    fn extend<I: IntoIterator<Item=Problem>>(&mut self, iter: I) {
+        self.problems.extend(iter);
+    }
+}
+
+impl std::iter::FromIterator<Problem> for Report {
+    fn from_iter<I: IntoIterator<Item=Problem>>(iter: I) -> Self {
+        Report {
+            problems: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl IntoIterator for Report {
+    type Item = Problem;
+    type IntoIter = std::vec::IntoIter<Problem>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.problems.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Report {
+    type Item = &'a Problem;
+    type IntoIter = std::slice::Iter<'a, Problem>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.problems.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Report, Problem, Location};
+
+    #[test]
+    fn display() {
+        let mut report = Report::new();
+        report.push(Problem::error("missing field Package").at(Location::new().with_paragraph(1)));
+        report.push(Problem::warning("trailing whitespace").at(Location::new().with_line(3).with_field("Version")));
+        report.push(Problem::warning("empty document"));
+        assert_eq!(report.to_string(), "error: paragraph 2: missing field Package\nwarning: line 3, field Version: trailing whitespace\nwarning: empty document\n");
+    }
+
+    #[test]
+    fn into_result() {
+        let mut report = Report::new();
+        report.push(Problem::warning("foo"));
+        let mut report = report.into_result().unwrap();
+        assert_eq!(report.warnings().count(), 1);
+        report.push(Problem::error("bar"));
+        let report = report.into_result().unwrap_err();
+        assert_eq!(report.errors().count(), 1);
+        assert_eq!(report.len(), 2);
+    }
+}
@@ -0,0 +1,362 @@
+//! Declarative validation of documents.
+//!
+//! A [`Schema`] describes the expected kinds of paragraphs (stanzas) and their fields - whether
+//! they are required, what type their values have, which values are allowed and which pattern
+//! they must match.
+//! Validating a [`Document`] against the schema collects all problems into a [`Report`] with the
+//! locations of the offending paragraphs and fields instead of stopping at the first one.
+//! This works for any RFC822-like dialect, not just the Debian files.
+//!
+//! The stanza kind of each paragraph is determined by the first [`StanzaSchema`] whose
+//! [`Selector`] matches it.
+//! Field names are case-insensitive.
+//! For list types the allowed values and the pattern apply to each item separately.
+//!
+//! This module requires the `schema` feature.
+//!
+//! # Example
+//!
+//! ```
+//! use rfc822_like::document::Document;
+//! use rfc822_like::schema::{Schema, StanzaSchema, FieldSchema, FieldType, Selector};
+//!
+//! let schema = Schema::new()
+//!     .with_stanza(StanzaSchema::new("source")
+//!         .matching(Selector::First)
+//!         .with_field(FieldSchema::required("Source").with_pattern("^[a-z0-9][a-z0-9+.-]+$").unwrap())
+//!         .with_field(FieldSchema::optional("Priority").with_allowed_values(&["required", "important", "standard", "optional", "extra"])))
+//!     .with_stanza(StanzaSchema::new("binary")
+//!         .with_field(FieldSchema::required("Package"))
+//!         .with_field(FieldSchema::required("Architecture").with_type(FieldType::WhitespaceList))
+//!         .with_field(FieldSchema::optional("Essential").with_type(FieldType::YesNo))
+//!         .deny_unknown_fields());
+//!
+//! let document = "Source: foo\nPriority: low\n\nPackage: foo\nArchitecture: any\nEssential: maybe\nX-Foo: bar\n".parse::<Document>().unwrap();
+//! let report = schema.validate(&document);
+//! assert_eq!(report.to_string(), "\
+//! error: line 2, paragraph 1, field Priority: value 'low' is not allowed
+//! error: line 6, paragraph 2, field Essential: expected yes or no, got 'maybe'
+//! error: line 7, paragraph 2, field X-Foo: unknown field in binary stanza
+//! ");
+//! ```
+
+use std::fmt;
+use regex::Regex;
+use crate::document::{Document, Field, Paragraph};
+use crate::report::{Location, Problem, Report};
+
+/// Error returned when a pattern is not a valid regular expression.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error(transparent)]
+pub struct PatternError(#[from] regex::Error);
+
+/// Type of the field value.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum FieldType {
+    /// Any text including multiple lines.
+    Text,
+    /// Text without continuation lines.
+    Line,
+    /// Non-negative decimal integer.
+    Integer,
+    /// `yes` or `no`.
+    YesNo,
+    /// Comma-separated list.
+    CommaList,
+    /// Whitespace-separated list.
+    WhitespaceList,
+}
+
+impl fmt::Display for FieldType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldType::Text => f.write_str("text"),
+            FieldType::Line => f.write_str("a single line"),
+            FieldType::Integer => f.write_str("a non-negative integer"),
+            FieldType::YesNo => f.write_str("yes or no"),
+            FieldType::CommaList => f.write_str("a comma-separated list"),
+            FieldType::WhitespaceList => f.write_str("a whitespace-separated list"),
+        }
+    }
+}
+
+/// Expectations about a single field.
+#[derive(Debug, Clone)]
+pub struct FieldSchema {
+    name: String,
+    required: bool,
+    field_type: FieldType,
+    allowed_values: Option<Vec<String>>,
+    pattern: Option<Regex>,
+}
+
+impl FieldSchema {
+    fn new(name: String, required: bool) -> Self {
+        FieldSchema {
+            name,
+            required,
+            field_type: FieldType::Text,
+            allowed_values: None,
+            pattern: None,
+        }
+    }
+
+    /// Creates a schema of a field that has to be present.
+    pub fn required<N: Into<String>>(name: N) -> Self {
+        FieldSchema::new(name.into(), true)
+    }
+
+    /// Creates a schema of a field that may be missing.
+    pub fn optional<N: Into<String>>(name: N) -> Self {
+        FieldSchema::new(name.into(), false)
+    }
+
+    /// Sets the type of the value, the default is [`FieldType::Text`].
+    pub fn with_type(mut self, field_type: FieldType) -> Self {
+        self.field_type = field_type;
+        self
+    }
+
+    /// Restricts the value (or list items) to the given values.
+    pub fn with_allowed_values<I>(mut self, values: I) -> Self where I: IntoIterator, I::Item: AsRef<str> {
+        self.allowed_values = Some(values.into_iter().map(|value| value.as_ref().to_owned()).collect());
+        self
+    }
+
+    /// Requires the value (or list items) to match the regular expression.
+    ///
+    /// The pattern is not anchored implicitly, use `^` and `$` to match the whole value.
+    pub fn with_pattern(mut self, pattern: &str) -> Result<Self, PatternError> {
+        self.pattern = Some(Regex::new(pattern)?);
+        Ok(self)
+    }
+
+    /// Returns the name of the field.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns `true` if the field is required.
+    pub fn is_required(&self) -> bool {
+        self.required
+    }
+
+    fn validate(&self, field: &Field, location: Location, report: &mut Report) {
+        let value = field.value();
+        let items = match self.field_type {
+            FieldType::Text => vec![value],
+            FieldType::Line => {
+                if value.contains('\n') {
+                    report.push(Problem::error("expected a single line").at(location));
+                    return;
+                }
+                vec![value]
+            },
+            FieldType::Integer => {
+                if value.is_empty() || !value.bytes().all(|byte| byte.is_ascii_digit()) {
+                    report.push(Problem::error(format!("expected {}, got '{}'", self.field_type, value)).at(location));
+                    return;
+                }
+                vec![value]
+            },
+            FieldType::YesNo => {
+                if value != "yes" && value != "no" {
+                    report.push(Problem::error(format!("expected {}, got '{}'", self.field_type, value)).at(location));
+                    return;
+                }
+                vec![value]
+            },
+            FieldType::CommaList => value.split(',').map(str::trim).filter(|item| !item.is_empty()).collect(),
+            FieldType::WhitespaceList => value.split_whitespace().collect(),
+        };
+
+        for item in items {
+            if let Some(allowed) = &self.allowed_values {
+                if !allowed.iter().any(|allowed| allowed == item) {
+                    report.push(Problem::error(format!("value '{}' is not allowed", item)).at(location.clone()));
+                    continue;
+                }
+            }
+            if let Some(pattern) = &self.pattern {
+                if !pattern.is_match(item) {
+                    report.push(Problem::error(format!("value '{}' doesn't match pattern {}", item, pattern)).at(location.clone()));
+                }
+            }
+        }
+    }
+}
+
+/// Decides which paragraphs belong to a stanza kind.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum Selector {
+    /// Matches all paragraphs.
+    Any,
+    /// Matches the first paragraph of the document.
+    First,
+    /// Matches paragraphs containing the field.
+    HasField(String),
+}
+
+impl Selector {
+    fn matches(&self, index: usize, paragraph: &Paragraph) -> bool {
+        match self {
+            Selector::Any => true,
+            Selector::First => index == 0,
+            Selector::HasField(name) => paragraph.get(name).is_some(),
+        }
+    }
+}
+
+/// Expectations about a kind of paragraphs.
+#[derive(Debug, Clone)]
+pub struct StanzaSchema {
+    name: String,
+    selector: Selector,
+    fields: Vec<FieldSchema>,
+    deny_unknown_fields: bool,
+}
+
+impl StanzaSchema {
+    /// Creates a stanza kind with the given name matching all paragraphs.
+    ///
+    /// The name is used in error messages.
+    pub fn new<N: Into<String>>(name: N) -> Self {
+        StanzaSchema {
+            name: name.into(),
+            selector: Selector::Any,
+            fields: Vec::new(),
+            deny_unknown_fields: false,
+        }
+    }
+
+    /// Sets the selector of paragraphs belonging to this kind.
+    pub fn matching(mut self, selector: Selector) -> Self {
+        self.selector = selector;
+        self
+    }
+
+    /// Adds a field to the stanza kind.
+    pub fn with_field(mut self, field: FieldSchema) -> Self {
+        self.fields.push(field);
+        self
+    }
+
+    /// Reports fields not listed in the schema as errors.
+    pub fn deny_unknown_fields(mut self) -> Self {
+        self.deny_unknown_fields = true;
+        self
+    }
+
+    /// Returns the name of the stanza kind.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Validates a single paragraph, `index` is used in locations.
+    pub fn validate_paragraph(&self, index: usize, paragraph: &Paragraph, report: &mut Report) {
+        let mut paragraph_location = Location::new().with_paragraph(index);
+        if let Some(span) = paragraph.span() {
+            paragraph_location = paragraph_location.with_line(span.first_line());
+        }
+
+        for schema in &self.fields {
+            if schema.required && paragraph.get(&schema.name).is_none() {
+                let message = format!("missing required field {} in {} stanza", schema.name, self.name);
+                report.push(Problem::error(message).at(paragraph_location.clone()));
+            }
+        }
+
+        for field in paragraph {
+            let mut location = Location::new().with_paragraph(index).with_field(field.name());
+            if let Some(span) = field.span() {
+                location = location.with_line(span.first_line());
+            }
+            match self.fields.iter().find(|schema| field.is_named(&schema.name)) {
+                Some(schema) => schema.validate(field, location, report),
+                None if self.deny_unknown_fields => {
+                    report.push(Problem::error(format!("unknown field in {} stanza", self.name)).at(location));
+                },
+                None => (),
+            }
+        }
+    }
+}
+
+/// Expected structure of documents.
+#[derive(Debug, Clone, Default)]
+pub struct Schema {
+    stanzas: Vec<StanzaSchema>,
+}
+
+impl Schema {
+    /// Creates an empty schema.
+    ///
+    /// A document can't be valid against an empty schema unless it has no paragraphs.
+    pub fn new() -> Self {
+        Schema::default()
+    }
+
+    /// Adds a stanza kind.
+    ///
+    /// Kinds are tried in the order they were added.
+    pub fn with_stanza(mut self, stanza: StanzaSchema) -> Self {
+        self.stanzas.push(stanza);
+        self
+    }
+
+    /// Returns the stanza kind of the paragraph at `index`.
+    pub fn stanza_of(&self, index: usize, paragraph: &Paragraph) -> Option<&StanzaSchema> {
+        self.stanzas.iter().find(|stanza| stanza.selector.matches(index, paragraph))
+    }
+
+    /// Validates the document returning all problems found.
+    pub fn validate(&self, document: &Document) -> Report {
+        let mut report = Report::new();
+        for (index, paragraph) in document.iter().enumerate() {
+            match self.stanza_of(index, paragraph) {
+                Some(stanza) => stanza.validate_paragraph(index, paragraph, &mut report),
+                None => {
+                    let mut location = Location::new().with_paragraph(index);
+                    if let Some(span) = paragraph.span() {
+                        location = location.with_line(span.first_line());
+                    }
+                    report.push(Problem::error("paragraph doesn't match any stanza kind").at(location));
+                },
+            }
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Schema, StanzaSchema, FieldSchema, FieldType, Selector};
+    use crate::document::Document;
+
+    #[test]
+    fn validate() {
+        let schema = Schema::new()
+            .with_stanza(StanzaSchema::new("header").matching(Selector::HasField("Format".to_owned()))
+                .with_field(FieldSchema::required("Format").with_type(FieldType::Line)))
+            .with_stanza(StanzaSchema::new("files").matching(Selector::HasField("Files".to_owned()))
+                .with_field(FieldSchema::required("Files"))
+                .with_field(FieldSchema::required("License"))
+                .with_field(FieldSchema::optional("Size").with_type(FieldType::Integer))
+                .with_field(FieldSchema::optional("Tags").with_type(FieldType::CommaList).with_pattern("^[a-z]+$").unwrap()));
+
+        let document = "Format: a\n b\n\nFiles: *\nsize: 1x\nTags: foo, Bar,\n\nComment: x\n".parse::<Document>().unwrap();
+        let report = schema.validate(&document);
+        let expected = "\
+error: line 1, paragraph 1, field Format: expected a single line
+error: line 4, paragraph 2: missing required field License in files stanza
+error: line 5, paragraph 2, field size: expected a non-negative integer, got '1x'
+error: line 6, paragraph 2, field Tags: value 'Bar' doesn't match pattern ^[a-z]+$
+error: line 8, paragraph 3: paragraph doesn't match any stanza kind
+";
+        assert_eq!(report.to_string(), expected);
+
+        let document = "Files: *\nLicense: MIT\nSize: 42\nTags: foo\n".parse::<Document>().unwrap();
+        assert!(schema.validate(&document).is_empty());
+        assert!(FieldSchema::required("x").with_pattern("(").is_err());
+    }
+}
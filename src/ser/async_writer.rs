@@ -0,0 +1,118 @@
+//! Serialization into asynchronous writers.
+
+use serde::Serialize;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use super::error::ErrorInternal;
+use super::{Error, Options, Serializer};
+
+/// Serializes the `value` into the asynchronous `writer` and flushes it.
+///
+/// The value is serialized into memory first, use [`RecordSink`] to write large sequences of
+/// records one by one.
+///
+/// This function requires the `tokio` feature.
+pub async fn to_async_writer<T: Serialize, W: AsyncWrite + Unpin>(mut writer: W, value: &T) -> Result<(), Error> {
+    let output = crate::to_string(value)?;
+    writer.write_all(output.as_bytes()).await.map_err(ErrorInternal::IoWriteFailed)?;
+    writer.flush().await.map_err(ErrorInternal::IoWriteFailed)?;
+    Ok(())
+}
+
+/// Writes records into an asynchronous writer as they are produced.
+///
+/// Each record is serialized into a reused buffer and written before the next one is accepted
+/// so generated stanzas can be streamed into e.g. a response body without keeping the whole
+/// document in memory.
+/// The output is the same as the output of serializing all records as a sequence.
+///
+/// This type requires the `tokio` feature.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::BTreeMap;
+/// use rfc822_like::ser::RecordSink;
+/// use tokio::io::AsyncWrite;
+///
+/// async fn write_index<W: AsyncWrite + Unpin>(output: W, packages: &[BTreeMap<String, String>]) -> Result<W, rfc822_like::ser::Error> {
+///     let mut sink = RecordSink::new(output);
+///     for package in packages {
+///         sink.send(package).await?;
+///     }
+///     sink.finish().await
+/// }
+/// ```
+pub struct RecordSink<W> {
+    writer: W,
+    // Only holds the settings, records are serialized into `buffer`
+    settings: Serializer<String>,
+    buffer: String,
+    is_empty: bool,
+}
+
+impl<W: AsyncWrite + Unpin> RecordSink<W> {
+    /// Creates the sink using the default settings of the serializer.
+    pub fn new(writer: W) -> Self {
+        RecordSink::with_options(writer, Options::default())
+    }
+
+    /// Creates the sink serializing the records using `options`.
+    pub fn with_options(writer: W, options: Options) -> Self {
+        RecordSink {
+            writer,
+            settings: Serializer::with_options(String::new(), options),
+            buffer: String::new(),
+            is_empty: true,
+        }
+    }
+
+    /// Serializes the record and writes it.
+    ///
+    /// Records are separated by empty lines.
+    /// Nothing is written if serialization fails so the sink can still be used.
+    pub async fn send<T: Serialize + ?Sized>(&mut self, record: &T) -> Result<(), Error> {
+        self.buffer.clear();
+        if !self.is_empty {
+            self.buffer.push('\n');
+        }
+        record.serialize(self.settings.with_writer(&mut self.buffer))?;
+        self.writer.write_all(self.buffer.as_bytes()).await.map_err(ErrorInternal::IoWriteFailed)?;
+        self.is_empty = false;
+        Ok(())
+    }
+
+    /// Flushes the writer and returns it.
+    pub async fn finish(mut self) -> Result<W, Error> {
+        self.writer.flush().await.map_err(ErrorInternal::IoWriteFailed)?;
+        Ok(self.writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use super::{to_async_writer, RecordSink};
+    use crate::ser::Options;
+
+    fn record(package: &str, tag: &str) -> BTreeMap<&'static str, String> {
+        let mut record = BTreeMap::new();
+        record.insert("Package", package.to_owned());
+        record.insert("Tag", tag.to_owned());
+        record
+    }
+
+    #[tokio::test]
+    async fn stream_records() {
+        let records = vec![record("foo", "a"), record("bar", "b")];
+        let mut output = Vec::new();
+        to_async_writer(&mut output, &records).await.unwrap();
+        assert_eq!(output, crate::to_vec(&records).unwrap());
+
+        let options = Options { exclude_fields: vec!["Tag".to_owned()], ..Options::default() };
+        let mut sink = RecordSink::with_options(Vec::new(), options);
+        sink.send(&records[0]).await.unwrap();
+        assert!(sink.send(&42).await.is_err());
+        sink.send(&records[1]).await.unwrap();
+        assert_eq!(sink.finish().await.unwrap(), b"Package: foo\n\nPackage: bar\n");
+    }
+}
@@ -26,8 +26,15 @@ pub(crate) enum ErrorInternal {
     InvalidKeyChar { key: String, c: char, pos: usize },
     #[error("empty key is not allowed")]
     EmptyKey,
+    #[error("comment contains a newline")]
+    NewlineInComment,
+    #[error("{0}")]
+    InvalidOrder(&'static str),
+    #[error("pre-formatted value of field '{0}' would break the structure of the record")]
+    InvalidPreformatted(String),
     #[error("failed to write")]
     FmtWriteFailed,
+    #[cfg(any(feature = "io", feature = "tokio"))]
     #[error("failed to write")]
     IoWriteFailed(#[from] std::io::Error),
 }
@@ -47,6 +54,7 @@ impl Error {
         ErrorInternal::FmtWriteFailed.into()
     }
 
+    #[cfg(feature = "io")]
     pub(crate) fn into_fmt(self) -> Result<Result<(), Self>, std::fmt::Error> {
         if let ErrorInternal::FmtWriteFailed = self.internal {
             Err(std::fmt::Error)
@@ -10,47 +10,225 @@ use std::fmt::Write;
 use std::fmt;
 use serde::ser;
 use unicode_segmentation::UnicodeSegmentation;
-use std::borrow::Cow;
+use std::sync::Arc;
+use crate::key_mapping::KeyMapping;
 pub use error::Error;
+pub use options::Options;
+pub use preformatted::Preformatted;
+#[cfg(feature = "tokio")]
+pub use async_writer::{to_async_writer, RecordSink};
 
 pub mod error;
+mod options;
+mod preformatted;
+#[cfg(feature = "parallel")]
+mod parallel;
+#[cfg(feature = "tokio")]
+mod async_writer;
+
+/// Spelling of values of `bool` fields, see [`Serializer::bool_style`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "config", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "config", serde(rename_all = "kebab-case"))]
+pub enum BoolStyle {
+    /// `yes` and `no` used by Debian, e.g. `Essential: yes`.
+    YesNo,
+    /// `true` and `false`.
+    TrueFalse,
+}
 
-/// Convenience function serializing into `fmt::Writer`
-pub fn to_fmt_writer<W: Write, T: ser::Serialize>(writer: W, value: T) -> Result<(), Error> {
-    value.serialize(Serializer::new(writer))
+impl BoolStyle {
+    fn as_str(self, value: bool) -> &'static str {
+        match (self, value) {
+            (BoolStyle::YesNo, true) => "yes",
+            (BoolStyle::YesNo, false) => "no",
+            (BoolStyle::TrueFalse, true) => "true",
+            (BoolStyle::TrueFalse, false) => "false",
+        }
+    }
 }
 
-macro_rules! unsupported_types {
-    ($(fn $fn_name:ident$(<$($gen:ident),*>)?(self $(, $arg:ident: $arg_type:ty)*) -> Result<$ret:ty> $(where $type:ty: ?Sized + Serialize)?;)*) => {
+impl Default for BoolStyle {
+    fn default() -> Self {
+        BoolStyle::YesNo
+    }
+}
+
+/// Implements serialization of numbers by writing them using `Display`.
+macro_rules! serialize_numbers {
+    ($($method:ident($type:ty);)*) => {
         $(
-            fn $fn_name$(<$($gen),*>)?(self $(, $arg: $arg_type)*) -> Result<$ret, Self::Error> $(where $type: ?Sized + serde::Serialize)? {
-                $(
-                    let _ = $arg;
-                )*
-                Err(Error::unsupported_data_type(stringify!($fn_name)))
+            fn $method(self, v: $type) -> Result<Self::Ok, Self::Error> {
+                self.collect_str(&v)
             }
         )*
     }
 }
 
+/// Convenience function serializing into `fmt::Writer`
+pub fn to_fmt_writer<W: Write, T: ser::Serialize>(writer: W, value: T) -> Result<(), Error> {
+    value.serialize(Serializer::new(writer))
+}
+
 /// Serializer backed by `fmt::Writer`
 pub struct Serializer<Writer: Write> {
     writer: Writer,
-    wrap_long_lines: bool,
+    // Shared by all serializers created during serialization
+    options: Arc<Options>,
+    reserve: ReserveFn<Writer>,
+}
+
+/// Reserves space in the writer for the given number of remaining records after the given number
+/// of records was written.
+type ReserveFn<W> = fn(&mut W, usize, usize);
+
+fn no_reserve<W>(_: &mut W, _: usize, _: usize) {}
+
+/// Maximum size of the reservation relative to the size of the written records.
+const MAX_RESERVE_FACTOR: usize = 4;
+
+/// Reserves space for remaining records assuming they are as long as the written ones on average.
+///
+/// The reservation is limited to a multiple of the written size so that an unusually large
+/// first record doesn't cause a huge allocation.
+fn reserve_string(output: &mut &mut String, written: usize, remaining: usize) {
+    // Each following record is also preceded by an empty line, rounding up avoids falling back
+    // to doubling of the capacity when the estimate is slightly short
+    let per_record = (output.len() + written) / written;
+    let estimate = per_record.saturating_mul(remaining).min(output.len().saturating_mul(MAX_RESERVE_FACTOR));
+    // The estimate is only refined a logarithmic number of times so exact reservations are cheap
+    output.reserve_exact(estimate);
+}
+
+impl<'a> Serializer<&'a mut String> {
+    /// Constructs the serializer estimating the size of the output to avoid reallocations.
+    ///
+    /// When serializing a sequence of known length the space for all records is reserved after
+    /// the first one is written.
+    pub(crate) fn reserving(output: &'a mut String) -> Self {
+        Serializer {
+            reserve: reserve_string,
+            ..Serializer::new(output)
+        }
+    }
 }
 
 impl<W> Serializer<W> where W: Write {
     /// Constructs the serializer
     pub fn new(writer: W) -> Self {
+        Serializer::with_options(writer, Options::default())
+    }
+
+    /// Constructs the serializer configured using `options`.
+    pub fn with_options(writer: W, options: Options) -> Self {
+        Serializer {
+            writer,
+            options: Arc::new(options),
+            reserve: no_reserve,
+        }
+    }
+
+    /// Returns the settings configured using the builder methods.
+    ///
+    /// This is useful for storing the profile in a configuration file.
+    pub fn to_options(&self) -> Options {
+        (*self.options).clone()
+    }
+
+    /// Constructs a serializer with the same settings writing into `writer`.
+    #[cfg(feature = "tokio")]
+    fn with_writer<V: Write>(&self, writer: V) -> Serializer<V> {
         Serializer {
             writer,
-            wrap_long_lines: false,
+            options: self.options.clone(),
+            reserve: no_reserve,
         }
     }
 
     /// Causes lines longer than 80 characters to be wrapped on word boundaries.
     pub fn wrap_long_lines(mut self, wrap: bool) -> Self {
-        self.wrap_long_lines = wrap;
+        Arc::make_mut(&mut self.options).wrap_long_lines = wrap;
+        self
+    }
+
+    /// Converts names of struct fields using the `mapping`, e.g. `installed_size` to `Installed-Size`.
+    ///
+    /// Keys of maps are not converted.
+    pub fn key_mapping(mut self, mapping: KeyMapping) -> Self {
+        Arc::make_mut(&mut self.options).key_mapping = Some(mapping);
+        self
+    }
+
+    /// Only writes fields with the given names, other fields are skipped.
+    ///
+    /// The names are those written in the output, after applying
+    /// [`key_mapping`](Self::key_mapping), and are matched case-insensitively.
+    /// This applies to fields of both structs and maps and can be combined with
+    /// [`exclude_fields`](Self::exclude_fields).
+    pub fn include_fields<I: IntoIterator>(mut self, names: I) -> Self where I::Item: AsRef<str> {
+        Arc::make_mut(&mut self.options).include_fields = Some(names.into_iter().map(|name| name.as_ref().to_owned()).collect());
+        self
+    }
+
+    /// Skips fields with the given names.
+    ///
+    /// This is useful for producing slim outputs, e.g. an index without `Description` and `Tag`,
+    /// from full records without defining separate types.
+    /// The names are matched the same way as in [`include_fields`](Self::include_fields).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::collections::BTreeMap;
+    /// use rfc822_like::Serializer;
+    /// use serde::Serialize;
+    ///
+    /// let mut record = BTreeMap::new();
+    /// record.insert("Package", "foo");
+    /// record.insert("Description", "The Foo");
+    /// record.insert("Tag", "role::program");
+    ///
+    /// let mut output = String::new();
+    /// record.serialize(Serializer::new(&mut output).exclude_fields(&["description", "tag"])).unwrap();
+    /// assert_eq!(output, "Package: foo\n");
+    /// ```
+    pub fn exclude_fields<I: IntoIterator>(mut self, names: I) -> Self where I::Item: AsRef<str> {
+        Arc::make_mut(&mut self.options).exclude_fields.extend(names.into_iter().map(|name| name.as_ref().to_owned()));
+        self
+    }
+
+    /// Sets the value reported by `is_human_readable()`, `true` by default.
+    ///
+    /// Types such as dates or UUIDs use their textual representation only if this is `true` and
+    /// fields can only hold text, so setting it to `false` is only useful for types that
+    /// explicitly support a different textual format in that case.
+    pub fn human_readable(mut self, human_readable: bool) -> Self {
+        Arc::make_mut(&mut self.options).human_readable = human_readable;
+        self
+    }
+
+    /// Chooses how values of `bool` fields are written, `yes`/`no` by default.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rfc822_like::ser::{BoolStyle, Serializer};
+    /// use serde::Serialize;
+    ///
+    /// #[derive(serde_derive::Serialize)]
+    /// #[serde(rename_all = "PascalCase")]
+    /// struct Package {
+    ///     essential: bool,
+    /// }
+    ///
+    /// assert_eq!(rfc822_like::to_string(&Package { essential: true, }).unwrap(), "Essential: yes\n");
+    ///
+    /// let mut output = String::new();
+    /// Package { essential: false, }.serialize(Serializer::new(&mut output).bool_style(BoolStyle::TrueFalse)).unwrap();
+    /// assert_eq!(output, "Essential: false\n");
+    /// ```
+    pub fn bool_style(mut self, style: BoolStyle) -> Self {
+        Arc::make_mut(&mut self.options).bool_style = style;
         self
     }
 }
@@ -69,7 +247,7 @@ impl<W> serde::Serializer for Serializer<W> where W: Write {
     fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct, Self::Error> {
         Ok(StructSerializer {
             writer: self.writer,
-            wrap_long_lines: self.wrap_long_lines,
+            options: self.options,
         })
     }
 
@@ -80,19 +258,26 @@ impl<W> serde::Serializer for Serializer<W> where W: Write {
     fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
         Ok(MapSerializer {
             writer: self.writer,
-            field_name: None,
-            wrap_long_lines: self.wrap_long_lines,
+            field_name: String::new(),
+            has_field_name: false,
+            options: self.options,
         })
     }
 
-    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
         Ok(SeqSerializer {
             output: self.writer,
-            is_empty: true,
-            wrap_long_lines: self.wrap_long_lines,
+            written: 0,
+            options: self.options,
+            len,
+            reserve: self.reserve,
         })
     }
 
+    fn is_human_readable(&self) -> bool {
+        self.options.human_readable
+    }
+
     unsupported_types! {
         fn serialize_bool(self, v: bool) -> Result<()>;
         fn serialize_i8(self, v: i8) -> Result<()>;
@@ -125,7 +310,7 @@ impl<W> serde::Serializer for Serializer<W> where W: Write {
 
 struct NonSeqSerializer<Writer: Write> {
     writer: Writer,
-    wrap_long_lines: bool,
+    options: Arc<Options>,
 }
 
 impl<W> serde::Serializer for NonSeqSerializer<W> where W: Write {
@@ -142,7 +327,7 @@ impl<W> serde::Serializer for NonSeqSerializer<W> where W: Write {
     fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct, Self::Error> {
         Ok(StructSerializer {
             writer: self.writer,
-            wrap_long_lines: self.wrap_long_lines,
+            options: self.options,
         })
     }
 
@@ -153,11 +338,16 @@ impl<W> serde::Serializer for NonSeqSerializer<W> where W: Write {
     fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
         Ok(MapSerializer {
             writer: self.writer,
-            field_name: None,
-            wrap_long_lines: self.wrap_long_lines,
+            field_name: String::new(),
+            has_field_name: false,
+            options: self.options,
         })
     }
 
+    fn is_human_readable(&self) -> bool {
+        self.options.human_readable
+    }
+
     unsupported_types! {
         fn serialize_bool(self, v: bool) -> Result<()>;
         fn serialize_i8(self, v: i8) -> Result<()>;
@@ -195,8 +385,11 @@ impl<W> serde::Serializer for NonSeqSerializer<W> where W: Write {
 /// `Serializer::SerializeSeq`.
 pub struct SeqSerializer<Writer: Write> {
     output: Writer,
-    wrap_long_lines: bool,
-    is_empty: bool,
+    options: Arc<Options>,
+    // Number of records written so far
+    written: usize,
+    len: Option<usize>,
+    reserve: ReserveFn<Writer>,
 }
 
 impl<W> ser::SerializeSeq for SeqSerializer<W> where W: Write {
@@ -204,11 +397,20 @@ impl<W> ser::SerializeSeq for SeqSerializer<W> where W: Write {
     type Error = Error;
 
     fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error> where T: ser::Serialize + ?Sized {
-        if !self.is_empty {
+        if self.written > 0 {
             writeln!(self.output).map_err(Error::failed_write)?;
         }
-        self.is_empty = false;
-        value.serialize(NonSeqSerializer { writer: &mut self.output, wrap_long_lines: self.wrap_long_lines })
+        value.serialize(NonSeqSerializer {
+            writer: &mut self.output,
+            options: self.options.clone(),
+        })?;
+        self.written += 1;
+        // The written records are the best available estimate of the size of the others, the
+        // estimate is refined each time their number doubles.
+        if let (true, Some(len)) = (self.written.is_power_of_two(), self.len) {
+            (self.reserve)(&mut self.output, self.written, len.saturating_sub(self.written));
+        }
+        Ok(())
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
@@ -219,7 +421,7 @@ impl<W> ser::SerializeSeq for SeqSerializer<W> where W: Write {
 /// Internal serializer for structs
 pub struct StructSerializer<Writer: Write> {
     writer: Writer,
-    wrap_long_lines: bool,
+    options: Arc<Options>,
 }
 
 impl<W: Write> ser::SerializeStruct for StructSerializer<W> {
@@ -227,10 +429,17 @@ impl<W: Write> ser::SerializeStruct for StructSerializer<W> {
     type Error = Error;
 
     fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<Self::Ok, Self::Error> where T: ?Sized + ser::Serialize {
+        let field_name = match &self.options.key_mapping {
+            Some(mapping) => mapping.to_field(key),
+            None => key.into(),
+        };
+        if !is_allowed(&self.options, &field_name) {
+            return Ok(());
+        }
         value.serialize(FieldSerializer {
-            field_name: key.into(),
+            field_name: &field_name,
             output: &mut self.writer,
-            wrap_long_lines: self.wrap_long_lines,
+            options: &self.options,
         })?;
         Ok(())
     }
@@ -244,8 +453,10 @@ impl<W: Write> ser::SerializeStruct for StructSerializer<W> {
 // Can't use non-static lifetime because of lack of GAT
 pub struct MapSerializer<Writer: Write> {
     writer: Writer,
-    field_name: Option<Cow<'static, str>>,
-    wrap_long_lines: bool,
+    // Reused for all keys to avoid allocating for each field
+    field_name: String,
+    has_field_name: bool,
+    options: Arc<Options>,
 }
 
 impl<W: Write> ser::SerializeMap for MapSerializer<W> {
@@ -255,15 +466,22 @@ impl<W: Write> ser::SerializeMap for MapSerializer<W> {
     fn serialize_key<T>(&mut self, value: &T) -> Result<Self::Ok, Self::Error> where T: ?Sized + ser::Serialize {
         value.serialize(KeySerializer {
             key: &mut self.field_name,
+            human_readable: self.options.human_readable,
         })?;
+        self.has_field_name = true;
         Ok(())
     }
 
     fn serialize_value<T>(&mut self, value: &T) -> Result<Self::Ok, Self::Error> where T: ?Sized + ser::Serialize {
+        assert!(self.has_field_name, "serialize_value() called before serialize_key()");
+        self.has_field_name = false;
+        if !is_allowed(&self.options, &self.field_name) {
+            return Ok(());
+        }
         value.serialize(FieldSerializer {
-            field_name: self.field_name.take().expect("serialize_value() called before serialize_key()"),
+            field_name: &self.field_name,
             output: &mut self.writer,
-            wrap_long_lines: self.wrap_long_lines,
+            options: &self.options,
         })?;
         Ok(())
     }
@@ -273,6 +491,12 @@ impl<W: Write> ser::SerializeMap for MapSerializer<W> {
     }
 }
 
+/// Checks the name against `include_fields` and `exclude_fields`.
+fn is_allowed(options: &Options, name: &str) -> bool {
+    let contains = |names: &[String]| names.iter().any(|field| field.eq_ignore_ascii_case(name));
+    options.include_fields.as_ref().map_or(true, |include| contains(include)) && !contains(&options.exclude_fields)
+}
+
 fn check_and_write_key(mut output: impl Write, key: &str) -> Result<(), Error> {
     if key.is_empty() {
         return Err(error::ErrorInternal::EmptyKey.into());
@@ -283,11 +507,13 @@ fn check_and_write_key(mut output: impl Write, key: &str) -> Result<(), Error> {
         return Err(error::ErrorInternal::InvalidKeyChar { key: key.to_owned(), c, pos, }.into());
     }
 
-    write!(output, "{}: ", key).map_err(Error::failed_write)
+    write!(output, "{}:", key).map_err(Error::failed_write)
 }
 
-struct KeySerializer<'a> {
-    key: &'a mut Option<Cow<'static, str>>,
+/// Stores the key into the buffer replacing its previous contents.
+pub(crate) struct KeySerializer<'a> {
+    pub(crate) key: &'a mut String,
+    pub(crate) human_readable: bool,
 }
 
 
@@ -303,10 +529,15 @@ impl<'a> serde::Serializer for KeySerializer<'a> {
     type SerializeStructVariant = ser::Impossible<Self::Ok, Self::Error>;
 
     fn serialize_str(self, value: &str) -> Result<Self::Ok, Self::Error> {
-        *self.key = Some(value.to_owned().into());
+        self.key.clear();
+        self.key.push_str(value);
         Ok(())
     }
 
+    fn is_human_readable(&self) -> bool {
+        self.human_readable
+    }
+
     unsupported_types! {
         fn serialize_bool(self, v: bool) -> Result<()>;
         fn serialize_i8(self, v: i8) -> Result<()>;
@@ -347,24 +578,39 @@ enum FieldWriterState {
     EndedWithNewline,
 }
 
-struct FieldWriter<Writer: Write> {
+pub(crate) struct FieldWriter<Writer: Write> {
     output: Writer,
     wrap_long_lines: bool,
     state: FieldWriterState,
+    // The space separating the value from the key is only written if the first line isn't empty
+    pending_space: bool,
 }
 
 impl<W: Write> FieldWriter<W> {
-    fn new(output: W, wrap_long_lines: bool) -> Self {
+    pub(crate) fn new(output: W, wrap_long_lines: bool) -> Self {
         FieldWriter {
             output,
             wrap_long_lines,
             state: FieldWriterState::FirstLine,
+            pending_space: false,
+        }
+    }
+
+    /// Creates the writer for value following `Key:`.
+    fn after_key(output: W, wrap_long_lines: bool) -> Self {
+        FieldWriter {
+            pending_space: true,
+            ..FieldWriter::new(output, wrap_long_lines)
         }
     }
 
-    fn finish(&mut self) -> fmt::Result {
+    pub(crate) fn finish(&mut self) -> fmt::Result {
+        if self.pending_space {
+            self.output.write_str(" ")?;
+        }
         if let FieldWriterState::EndedWithNewline = self.state {
-            Ok(())
+            // The trailing empty line has to be marked, otherwise it'd end the paragraph
+            self.output.write_str(".\n")
         } else {
             self.output.write_str("\n")
         }
@@ -381,7 +627,13 @@ impl<W: Write> Write for FieldWriter<W> {
         let line = iter.next().expect("split() returned an empty iterator");
         match self.state {
             // We must not wrap the first line
-            FieldWriterState::FirstLine => self.output.write_str(line)?,
+            FieldWriterState::FirstLine => {
+                if self.pending_space && !line.is_empty() {
+                    self.output.write_str(" ")?;
+                    self.pending_space = false;
+                }
+                self.output.write_str(line)?;
+            },
             FieldWriterState::EndedWithNewline if line.is_empty() => self.output.write_str(".")?,
             FieldWriterState::EndedWithNewline | FieldWriterState::Neutral if self.wrap_long_lines => write_wraped(&mut self.output, line)?,
             FieldWriterState::EndedWithNewline | FieldWriterState::Neutral => self.output.write_str(line)?,
@@ -391,6 +643,7 @@ impl<W: Write> Write for FieldWriter<W> {
         let mut iter = iter.peekable();
         while let Some(line) = iter.next() {
             contained_newline = true;
+            self.pending_space = false;
             self.output.write_str("\n ")?;
             if line.is_empty() {
                 // if it's last we don't know what follows
@@ -414,17 +667,56 @@ impl<W: Write> Write for FieldWriter<W> {
     }
 }
 
-struct FieldSerializer<Writer: Write> {
-    field_name: Cow<'static, str>,
+struct FieldSerializer<'a, Writer: Write> {
+    field_name: &'a str,
     output: Writer,
-    wrap_long_lines: bool,
+    options: &'a Options,
+}
+
+/// Writes a single field with the same formatting the serializer would use.
+///
+/// Nothing is written if the value is empty (e.g. `None`).
+pub(crate) fn write_field<W: Write, T: ?Sized + ser::Serialize>(output: W, name: &str, value: &T) -> Result<(), Error> {
+    value.serialize(FieldSerializer {
+        field_name: name,
+        output,
+        options: &Options::default(),
+    })
+}
+
+/// Writes the field with textual value, checking the name and escaping empty lines.
+pub(crate) fn write_text_field<W: Write, T: fmt::Display + ?Sized>(mut output: W, name: &str, value: &T, wrap_long_lines: bool) -> Result<(), Error> {
+    check_and_write_key(&mut output, name)?;
+    let mut writer = FieldWriter::after_key(&mut output, wrap_long_lines);
+    (move || {
+        write!(writer, "{}", value)?;
+        writer.finish()
+    })().map_err(Error::failed_write)
+}
+
+/// Writes the already folded value verbatim if it doesn't break the structure of the record.
+fn write_preformatted_field<W: Write>(mut output: W, name: &str, value: &str) -> Result<(), Error> {
+    if !preformatted::is_valid(value) {
+        return Err(error::ErrorInternal::InvalidPreformatted(name.to_owned()).into());
+    }
+    check_and_write_key(&mut output, name)?;
+    let separator = if value.starts_with('\n') { "" } else { " " };
+    writeln!(output, "{}{}", separator, value).map_err(Error::failed_write)
 }
 
 fn write_wraped<W: Write>(mut out: W, line: &str) -> std::fmt::Result {
+    // Lines starting with whitespace need to go through the loop to skip it. The byte length is
+    // never smaller than the number of graphemes so the line fits.
+    if line.len() < 80 && !line.starts_with(char::is_whitespace) {
+        return out.write_str(line);
+    }
+
+    // Each ASCII byte is one grapheme (lines never contain `\n` so there's no `\r\n`).
+    let is_ascii = line.is_ascii();
     let mut written = 1;
 
     for word in line.split_word_bounds() {
-        let word_len = word.graphemes(true).count();
+        let word_len = if is_ascii { word.len() } else { word.graphemes(true).count() };
         if written + word_len > 80 {
             out.write_str("\n ")?;
             written = 1;
@@ -438,10 +730,10 @@ fn write_wraped<W: Write>(mut out: W, line: &str) -> std::fmt::Result {
     Ok(())
 }
 
-impl<W> serde::Serializer for FieldSerializer<W> where W: Write {
+impl<'a, W> serde::Serializer for FieldSerializer<'a, W> where W: Write {
     type Ok = ();
     type Error = Error;
-    type SerializeSeq = SubSeqSerializer<W>;
+    type SerializeSeq = SubSeqSerializer<'a, W>;
     type SerializeTuple = ser::Impossible<Self::Ok, Self::Error>;
     type SerializeTupleStruct = ser::Impossible<Self::Ok, Self::Error>;
     type SerializeTupleVariant = ser::Impossible<Self::Ok, Self::Error>;
@@ -449,13 +741,8 @@ impl<W> serde::Serializer for FieldSerializer<W> where W: Write {
     type SerializeStruct = ser::Impossible<Self::Ok, Self::Error>;
     type SerializeStructVariant = ser::Impossible<Self::Ok, Self::Error>;
 
-    fn collect_str<T: fmt::Display + ?Sized>(mut self, value: &T) -> Result<Self::Ok, Self::Error> {
-        check_and_write_key(&mut self.output, &self.field_name)?;
-        let mut writer = FieldWriter::new(&mut self.output, self.wrap_long_lines);
-        (move || {
-            write!(writer, "{}", value)?;
-            writer.finish()
-        })().map_err(Error::failed_write)
+    fn collect_str<T: fmt::Display + ?Sized>(self, value: &T) -> Result<Self::Ok, Self::Error> {
+        write_text_field(self.output, self.field_name, value, self.options.wrap_long_lines)
     }
 
     fn serialize_str(self, value: &str) -> Result<Self::Ok, Self::Error> {
@@ -474,29 +761,49 @@ impl<W> serde::Serializer for FieldSerializer<W> where W: Write {
         Ok(SubSeqSerializer {
             output: self.output,
             state: SubSeqSerializerState::Empty { field_name: self.field_name, },
+            human_readable: self.options.human_readable,
         })
     }
 
-    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<(), Self::Error> where T: ?Sized + ser::Serialize {
-        value.serialize(self)
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<(), Self::Error> where T: ?Sized + ser::Serialize {
+        if name == preformatted::TOKEN {
+            let mut raw = String::new();
+            value.serialize(KeySerializer { key: &mut raw, human_readable: self.options.human_readable, })?;
+            write_preformatted_field(self.output, self.field_name, &raw)
+        } else {
+            value.serialize(self)
+        }
     }
 
     fn serialize_unit_variant(self, _name: &'static str, _variant_index: u32, variant: &'static str) -> Result<(), Self::Error> {
         self.serialize_str(variant)
     }
 
+    fn is_human_readable(&self) -> bool {
+        self.options.human_readable
+    }
+
+    fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
+        let value = self.options.bool_style.as_str(v);
+        self.serialize_str(value)
+    }
+
+    serialize_numbers! {
+        serialize_i8(i8);
+        serialize_i16(i16);
+        serialize_i32(i32);
+        serialize_i64(i64);
+        serialize_i128(i128);
+        serialize_u8(u8);
+        serialize_u16(u16);
+        serialize_u32(u32);
+        serialize_u64(u64);
+        serialize_u128(u128);
+        serialize_f32(f32);
+        serialize_f64(f64);
+    }
+
     unsupported_types! {
-        fn serialize_bool(self, v: bool) -> Result<()>;
-        fn serialize_i8(self, v: i8) -> Result<()>;
-        fn serialize_i16(self, v: i16) -> Result<()>;
-        fn serialize_i32(self, v: i32) -> Result<()>;
-        fn serialize_i64(self, v: i64) -> Result<()>;
-        fn serialize_u8(self, v: u8) -> Result<()>;
-        fn serialize_u16(self, v: u16) -> Result<()>;
-        fn serialize_u32(self, v: u32) -> Result<()>;
-        fn serialize_u64(self, v: u64) -> Result<()>;
-        fn serialize_f32(self, v: f32) -> Result<()>;
-        fn serialize_f64(self, v: f64) -> Result<()>;
         fn serialize_char(self, v: char) -> Result<()>;
         fn serialize_bytes(self, v: &[u8]) -> Result<()>;
         fn serialize_unit(self) -> Result<()>; 
@@ -514,17 +821,18 @@ impl<W> serde::Serializer for FieldSerializer<W> where W: Write {
 }
 
 #[derive(Clone)]
-enum SubSeqSerializerState {
-    Empty { field_name: Cow<'static, str>, },
+enum SubSeqSerializerState<'a> {
+    Empty { field_name: &'a str, },
     NonEmpty { indent: usize, },
 }
 
-struct SubSeqSerializer<Writer: Write> {
+struct SubSeqSerializer<'a, Writer: Write> {
     output: Writer,
-    state: SubSeqSerializerState,
+    state: SubSeqSerializerState<'a>,
+    human_readable: bool,
 }
 
-impl<W> ser::SerializeSeq for SubSeqSerializer<W> where W: Write {
+impl<'a, W> ser::SerializeSeq for SubSeqSerializer<'a, W> where W: Write {
     type Ok = ();
     type Error = Error;
 
@@ -547,7 +855,7 @@ impl<W> ser::SerializeSeq for SubSeqSerializer<W> where W: Write {
             }
             Ok(())
         })().map_err(Error::failed_write)?;
-        value.serialize(StringSerializer(&mut self.output))
+        value.serialize(StringSerializer(&mut self.output, self.human_readable))
     }
 
     fn end(mut self) -> Result<Self::Ok, Self::Error> {
@@ -558,7 +866,7 @@ impl<W> ser::SerializeSeq for SubSeqSerializer<W> where W: Write {
     }
 }
 
-struct StringSerializer<Writer: Write>(Writer);
+struct StringSerializer<Writer: Write>(Writer, bool);
 
 impl<W> serde::Serializer for StringSerializer<W> where W: Write {
     type Ok = ();
@@ -587,18 +895,27 @@ impl<W> serde::Serializer for StringSerializer<W> where W: Write {
         self.serialize_str(variant)
     }
 
+    fn is_human_readable(&self) -> bool {
+        self.1
+    }
+
+    serialize_numbers! {
+        serialize_i8(i8);
+        serialize_i16(i16);
+        serialize_i32(i32);
+        serialize_i64(i64);
+        serialize_i128(i128);
+        serialize_u8(u8);
+        serialize_u16(u16);
+        serialize_u32(u32);
+        serialize_u64(u64);
+        serialize_u128(u128);
+        serialize_f32(f32);
+        serialize_f64(f64);
+    }
+
     unsupported_types! {
         fn serialize_bool(self, v: bool) -> Result<()>;
-        fn serialize_i8(self, v: i8) -> Result<()>;
-        fn serialize_i16(self, v: i16) -> Result<()>;
-        fn serialize_i32(self, v: i32) -> Result<()>;
-        fn serialize_i64(self, v: i64) -> Result<()>;
-        fn serialize_u8(self, v: u8) -> Result<()>;
-        fn serialize_u16(self, v: u16) -> Result<()>;
-        fn serialize_u32(self, v: u32) -> Result<()>;
-        fn serialize_u64(self, v: u64) -> Result<()>;
-        fn serialize_f32(self, v: f32) -> Result<()>;
-        fn serialize_f64(self, v: f64) -> Result<()>;
         fn serialize_char(self, v: char) -> Result<()>;
         fn serialize_bytes(self, v: &[u8]) -> Result<()>;
         fn serialize_none(self) -> Result<()>; 
@@ -626,7 +943,7 @@ mod tests {
     // so we need to ignore this nitpicky lint.
     #![allow(clippy::write_with_newline)]
 
-    use super::Serializer;
+    use super::{BoolStyle, Serializer};
     use serde::Serialize;
     use serde::Serializer as SerdeSerializer;
     use std::fmt::Write;
@@ -674,6 +991,34 @@ mod tests {
         assert_eq!(out, "Bar: bar value\nBaz: baz value\n");
     }
 
+    #[test]
+    fn trailing_newline() {
+        #[derive(serde_derive::Serialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct Foo {
+            bar: &'static str,
+            baz: &'static str,
+        }
+
+        let mut out = String::new();
+        Foo { bar: "bar value\n", baz: "baz value" }.serialize(Serializer::new(&mut out)).expect("Failed to serialize");
+        assert_eq!(out, "Bar: bar value\n .\nBaz: baz value\n");
+    }
+
+    #[test]
+    fn leading_newline() {
+        #[derive(serde_derive::Serialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct Foo {
+            bar: &'static str,
+            baz: &'static str,
+        }
+
+        let mut out = String::new();
+        Foo { bar: "\nbar value", baz: "" }.serialize(Serializer::new(&mut out)).expect("Failed to serialize");
+        assert_eq!(out, "Bar:\n bar value\nBaz: \n");
+    }
+
     #[test]
     fn long_begin() {
         #[derive(serde_derive::Serialize)]
@@ -701,6 +1046,46 @@ mod tests {
         assert_eq!(out, "Bar: Begin\n Insanely long string meant for testing, that will be over eighty characters \n long, I believe.\n");
     }
 
+    #[test]
+    fn wrap_fast_paths() {
+        use unicode_segmentation::UnicodeSegmentation;
+
+        // The original implementation counting graphemes of every word.
+        fn reference(line: &str) -> String {
+            let mut out = String::new();
+            let mut written = 1;
+            for word in line.split_word_bounds() {
+                let word_len = word.graphemes(true).count();
+                if written + word_len > 80 {
+                    out.push_str("\n ");
+                    written = 1;
+                }
+                if !(word.trim().is_empty() && written == 1) {
+                    out.push_str(word);
+                    written += word_len;
+                }
+            }
+            out
+        }
+
+        let long = "Insanely long string meant for testing, that will be over eighty characters long, I believe.";
+        let lines = [
+            "short",
+            "  leading whitespace",
+            long,
+            &*long.replace('e', "é"),
+            &*long.replace('e', "e\u{301}"),
+            &*format!("\t{}", long),
+            &*"x".repeat(100),
+            &*"č ".repeat(45),
+        ];
+        for line in &lines {
+            let mut out = String::new();
+            super::write_wraped(&mut out, line).unwrap();
+            assert_eq!(out, reference(line), "{}", line);
+        }
+    }
+
     #[test]
     fn multiline() {
         #[derive(serde_derive::Serialize)]
@@ -975,4 +1360,94 @@ mod tests {
         baz.serialize(Serializer::new(&mut out)).expect("Failed to serialize");
         assert_eq!(out, "Foo: bar\n");
     }
+
+    #[test]
+    fn human_readable() {
+        struct Probe;
+
+        impl Serialize for Probe {
+            fn serialize<S: SerdeSerializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                let text = if serializer.is_human_readable() { "text" } else { "compact" };
+                serializer.serialize_str(text)
+            }
+        }
+
+        #[derive(serde_derive::Serialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct Record {
+            value: Probe,
+            list: Vec<Probe>,
+        }
+
+        let record = Record { value: Probe, list: vec![Probe, Probe], };
+        let mut out = String::new();
+        record.serialize(Serializer::new(&mut out)).unwrap();
+        assert_eq!(out, "Value: text\nList: text,\n      text\n");
+
+        let mut out = String::new();
+        vec![record].serialize(Serializer::new(&mut out).human_readable(false)).unwrap();
+        assert_eq!(out, "Value: compact\nList: compact,\n      compact\n");
+    }
+
+    #[test]
+    fn field_filter() {
+        #[derive(serde_derive::Serialize)]
+        struct Record {
+            package: &'static str,
+            installed_size: &'static str,
+            description: &'static str,
+        }
+
+        let records = vec![Record { package: "foo", installed_size: "42", description: "The Foo", }];
+        let mut out = String::new();
+        let serializer = Serializer::new(&mut out)
+            .key_mapping(crate::key_mapping::KeyMapping::new())
+            .include_fields(&["Package", "installed-size", "Description"])
+            .exclude_fields(&["Description"]);
+        records.serialize(serializer).unwrap();
+        assert_eq!(out, "Package: foo\nInstalled-Size: 42\n");
+    }
+
+    #[test]
+    fn numbers() {
+        #[derive(Debug, PartialEq, serde_derive::Serialize, serde_derive::Deserialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct Record {
+            #[serde(rename = "Installed-Size")]
+            installed_size: u64,
+            offset: i32,
+            ratio: f64,
+            big: u128,
+            sizes: Vec<u16>,
+            priority: Option<i8>,
+        }
+
+        let record = Record { installed_size: 42, offset: -7, ratio: 0.5, big: u128::max_value(), sizes: vec![1, 2], priority: None, };
+        let output = crate::to_string(&record).unwrap();
+        assert_eq!(output, "Installed-Size: 42\nOffset: -7\nRatio: 0.5\nBig: 340282366920938463463374607431768211455\nSizes: 1,\n       2\n");
+        assert_eq!(crate::from_str::<Record>("Installed-Size: 42\nOffset: -7\nRatio: 0.5\nBig: 340282366920938463463374607431768211455\nSizes: 1, 2\nPriority: 3\n").unwrap(), Record { priority: Some(3), ..record });
+
+        let error = crate::from_str::<Record>("Installed-Size: -1\nOffset: 0\nRatio: 1\nBig: 1\nSizes: 1\n").unwrap_err();
+        assert_eq!(error.to_string(), "line 1, paragraph 1, field Installed-Size: invalid value: string \"-1\", expected u64");
+    }
+
+    #[test]
+    fn bools() {
+        #[derive(Debug, PartialEq, serde_derive::Serialize, serde_derive::Deserialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct Record {
+            essential: bool,
+            important: Option<bool>,
+        }
+
+        let record = Record { essential: true, important: Some(false), };
+        let output = crate::to_string(&record).unwrap();
+        assert_eq!(output, "Essential: yes\nImportant: no\n");
+        assert_eq!(crate::from_str::<Record>(&output).unwrap(), record);
+
+        let mut output = String::new();
+        record.serialize(Serializer::new(&mut output).bool_style(BoolStyle::TrueFalse)).unwrap();
+        assert_eq!(output, "Essential: true\nImportant: false\n");
+        assert_eq!(crate::from_str::<Record>(&output).unwrap(), record);
+    }
 }
@@ -0,0 +1,79 @@
+//! Serialization settings that can be stored in configuration files.
+
+use crate::key_mapping::KeyMapping;
+use super::BoolStyle;
+
+/// Settings of the [`Serializer`](super::Serializer).
+///
+/// Each field corresponds to a builder method of the serializer and has the same default.
+/// The serializer stores the options directly and shares them with the serializers of records
+/// and fields.
+/// With the `config` feature the options implement `Serialize` and `Deserialize` so formatting
+/// profiles can be loaded from configuration files of your application, missing fields keep the
+/// default values.
+///
+/// # Example
+///
+/// ```
+/// use rfc822_like::ser::{Options, Serializer};
+/// use serde::Serialize;
+///
+/// let serializer = Serializer::new(String::new()).wrap_long_lines(true).exclude_fields(&["Tag"]);
+/// let options = serializer.to_options();
+/// assert!(options.wrap_long_lines);
+///
+/// let mut output = String::new();
+/// let record = [("Package", "foo"), ("Tag", "role::program")].iter().cloned().collect::<std::collections::BTreeMap<_, _>>();
+/// record.serialize(Serializer::with_options(&mut output, options)).unwrap();
+/// assert_eq!(output, "Package: foo\n");
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "config", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "config", serde(default, rename_all = "kebab-case"))]
+pub struct Options {
+    /// See [`Serializer::wrap_long_lines`](super::Serializer::wrap_long_lines).
+    pub wrap_long_lines: bool,
+    /// See [`Serializer::key_mapping`](super::Serializer::key_mapping).
+    pub key_mapping: Option<KeyMapping>,
+    /// See [`Serializer::include_fields`](super::Serializer::include_fields).
+    pub include_fields: Option<Vec<String>>,
+    /// See [`Serializer::exclude_fields`](super::Serializer::exclude_fields).
+    pub exclude_fields: Vec<String>,
+    /// See [`Serializer::human_readable`](super::Serializer::human_readable).
+    pub human_readable: bool,
+    /// See [`Serializer::bool_style`](super::Serializer::bool_style).
+    pub bool_style: BoolStyle,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            wrap_long_lines: false,
+            key_mapping: None,
+            include_fields: None,
+            exclude_fields: Vec::new(),
+            human_readable: true,
+            bool_style: BoolStyle::YesNo,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "config", feature = "serde_json"))]
+mod tests {
+    use super::Options;
+    use crate::key_mapping::KeyMapping;
+    use crate::ser::BoolStyle;
+
+    #[test]
+    fn load_profile() {
+        let options = serde_json::from_str::<Options>(r#"{"wrap-long-lines": true, "exclude-fields": ["Tag"]}"#).unwrap();
+        assert!(options.wrap_long_lines);
+        assert!(options.human_readable);
+        assert_eq!(options.exclude_fields, ["Tag"]);
+
+        let options = Options { key_mapping: Some(KeyMapping::debian()), bool_style: BoolStyle::TrueFalse, ..options };
+        assert!(serde_json::to_string(&options).unwrap().contains(r#""bool-style":"true-false""#));
+        let json = serde_json::to_string(&options).unwrap();
+        assert_eq!(serde_json::from_str::<Options>(&json).unwrap(), options);
+    }
+}
@@ -0,0 +1,74 @@
+//! Serialization of large record sets using multiple threads.
+
+use std::fmt::Write;
+use rayon::prelude::*;
+use super::{Error, Serializer};
+
+/// Number of chunks per thread, more chunks balance the load better if the records differ in size.
+const CHUNKS_PER_THREAD: usize = 4;
+
+impl<W> Serializer<W> where W: Write {
+    /// Serializes the records using all threads of the current rayon thread pool.
+    ///
+    /// The records are split into chunks serialized into separate buffers in parallel which are
+    /// then written in order.
+    /// The output is the same as the output of serializing the slice using this serializer.
+    /// This pays off for large indexes, especially if long lines are wrapped.
+    ///
+    /// This method requires the `parallel` feature.
+    pub fn serialize_parallel<T: serde::Serialize + Sync>(mut self, records: &[T]) -> Result<(), Error> {
+        let chunk_size = records.len() / (rayon::current_num_threads() * CHUNKS_PER_THREAD) + 1;
+        let options = &self.options;
+        let chunks = records
+            .par_chunks(chunk_size)
+            .map(|chunk| {
+                let mut output = String::new();
+                let serializer = Serializer {
+                    writer: &mut output,
+                    options: options.clone(),
+                    reserve: super::reserve_string,
+                };
+                serde::Serialize::serialize(chunk, serializer)?;
+                Ok(output)
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        for (i, chunk) in chunks.iter().enumerate() {
+            if i > 0 {
+                self.writer.write_char('\n').map_err(Error::failed_write)?;
+            }
+            self.writer.write_str(chunk).map_err(Error::failed_write)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use crate::Serializer;
+
+    #[test]
+    fn same_as_sequential() {
+        let records = (0..1000)
+            .map(|i| {
+                let mut record = BTreeMap::new();
+                record.insert("Package".to_owned(), format!("package-{}", i));
+                record.insert("Description".to_owned(), "word ".repeat(i % 40 + 1).trim_end().to_owned());
+                record
+            })
+            .collect::<Vec<_>>();
+
+        for &wrap in &[false, true] {
+            let mut sequential = String::new();
+            serde::Serialize::serialize(&records, Serializer::new(&mut sequential).wrap_long_lines(wrap)).unwrap();
+            let mut parallel = String::new();
+            Serializer::new(&mut parallel).wrap_long_lines(wrap).serialize_parallel(&records).unwrap();
+            assert_eq!(parallel, sequential);
+        }
+
+        let mut empty = String::new();
+        Serializer::new(&mut empty).serialize_parallel::<BTreeMap<String, String>>(&[]).unwrap();
+        assert_eq!(empty, "");
+    }
+}
@@ -0,0 +1,73 @@
+//! Field values written exactly as given.
+
+/// Name of the newtype struct recognized by the field serializer.
+pub(crate) const TOKEN: &str = "$rfc822_like::Preformatted";
+
+/// Field value that is already folded and is written verbatim.
+///
+/// The value is written after `Name: ` as it is, continuation lines included, without
+/// escaping empty lines or wrapping long lines.
+/// This is useful for copying fields through untouched, see also
+/// [`RawValue`](crate::de::RawValue) which does this automatically.
+///
+/// To keep the structure of the record intact, each line except the first one has to start with
+/// a space or a tab and must not consist only of whitespace.
+/// Serialization fails otherwise.
+///
+/// Serializers other than the one from this crate see a plain string containing the folded value.
+///
+/// # Example
+///
+/// ```
+/// use rfc822_like::ser::Preformatted;
+///
+/// #[derive(serde_derive::Serialize)]
+/// #[serde(rename_all = "PascalCase")]
+/// struct Package<'a> {
+///     package: &'a str,
+///     description: Preformatted<&'a str>,
+/// }
+///
+/// let package = Package {
+///     package: "foo",
+///     description: Preformatted("The Foo\n   It's very foo."),
+/// };
+/// assert_eq!(rfc822_like::to_string(&package).unwrap(), "Package: foo\nDescription: The Foo\n   It's very foo.\n");
+///
+/// let broken = Package {
+///     package: "foo",
+///     description: Preformatted("The Foo\nVersion: 1.0"),
+/// };
+/// assert!(rfc822_like::to_string(&broken).is_err());
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub struct Preformatted<T>(pub T);
+
+impl<T: AsRef<str>> serde::Serialize for Preformatted<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_struct(TOKEN, self.0.as_ref())
+    }
+}
+
+/// Returns `true` if writing the value verbatim keeps the structure of the record.
+pub(crate) fn is_valid(value: &str) -> bool {
+    value
+        .split('\n')
+        .skip(1)
+        .all(|line| (line.starts_with(' ') || line.starts_with('\t')) && !line.trim().is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_valid;
+
+    #[test]
+    fn validity() {
+        assert!(is_valid(""));
+        assert!(is_valid("foo"));
+        assert!(is_valid("foo\n bar\n .\n\tbaz"));
+        assert!(!is_valid("foo\nbar"));
+        assert!(!is_valid("foo\n \n bar"));
+        assert!(!is_valid("foo\n"));
+    }
+}
@@ -0,0 +1,189 @@
+//! Splitting input into the raw text of stanzas without parsing fields.
+//!
+//! Tools that only route, count or grep stanzas don't need to parse them.
+//! The functions in this module only look for the lines separating stanzas so they are much
+//! faster than parsing and the text of interesting stanzas can be parsed later, e.g. using
+//! [`crate::from_str`].
+//!
+//! Stanzas are separated the same way as in [`Document::parse`](crate::document::Document::parse):
+//! by lines consisting only of whitespace.
+//! Each stanza is returned including the newline terminating its last line.
+//! Comment lines are kept in the stanza they're in; blocks consisting only of comments are
+//! skipped since they're not stanzas.
+//!
+//! # Example
+//!
+//! ```
+//! let input = "Package: foo\nVersion: 1.0\n\n# just a comment\n\nPackage: bar\n";
+//! let stanzas = rfc822_like::split::stanzas(input).collect::<Vec<_>>();
+//! assert_eq!(stanzas, ["Package: foo\nVersion: 1.0\n", "Package: bar\n"]);
+//! ```
+
+#[cfg(feature = "io")]
+use std::io;
+
+fn is_blank(line: &str) -> bool {
+    line.trim().is_empty()
+}
+
+fn is_comment(line: &str) -> bool {
+    line.starts_with('#')
+}
+
+/// Returns an iterator over the raw text of stanzas in `input`.
+pub fn stanzas(input: &str) -> Stanzas<'_> {
+    Stanzas {
+        input,
+    }
+}
+
+/// Iterator over the raw text of stanzas returned by [`stanzas`].
+#[derive(Debug, Clone)]
+pub struct Stanzas<'a> {
+    input: &'a str,
+}
+
+impl<'a> Iterator for Stanzas<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.input.is_empty() {
+                return None;
+            }
+            let mut end = 0;
+            let mut has_fields = false;
+            for line in InclusiveLines(self.input) {
+                if is_blank(line) {
+                    if end > 0 {
+                        break;
+                    }
+                    // Blank lines preceding the stanza
+                    self.input = &self.input[line.len()..];
+                    continue;
+                }
+                has_fields |= !is_comment(line);
+                end += line.len();
+            }
+            let stanza = &self.input[..end];
+            self.input = &self.input[end..];
+            if has_fields {
+                return Some(stanza);
+            }
+        }
+    }
+}
+
+/// Iterates over lines including their terminating newlines.
+pub(crate) struct InclusiveLines<'a>(pub(crate) &'a str);
+
+impl<'a> Iterator for InclusiveLines<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.0.is_empty() {
+            return None;
+        }
+        let end = self.0.find('\n').map_or(self.0.len(), |pos| pos + 1);
+        let (line, rest) = self.0.split_at(end);
+        self.0 = rest;
+        Some(line)
+    }
+}
+
+/// Returns an iterator reading the raw text of stanzas from `reader`.
+///
+/// Only one stanza is held in memory at a time.
+/// Invalid UTF-8 is reported as an error of kind `InvalidData`.
+///
+/// This function requires the `io` feature.
+#[cfg(feature = "io")]
+pub fn read_stanzas<R: io::BufRead>(reader: R) -> ReadStanzas<R> {
+    ReadStanzas {
+        reader,
+        line: String::new(),
+        failed: false,
+    }
+}
+
+/// Iterator over the raw text of stanzas returned by [`read_stanzas`].
+///
+/// The iterator stops after returning an error.
+#[cfg(feature = "io")]
+#[derive(Debug)]
+pub struct ReadStanzas<R> {
+    reader: R,
+    line: String,
+    failed: bool,
+}
+
+#[cfg(feature = "io")]
+impl<R: io::BufRead> ReadStanzas<R> {
+    fn read_stanza(&mut self) -> io::Result<Option<String>> {
+        let mut stanza = String::new();
+        let mut has_fields = false;
+        loop {
+            self.line.clear();
+            if self.reader.read_line(&mut self.line)? == 0 {
+                break;
+            }
+            if is_blank(&self.line) {
+                if has_fields {
+                    break;
+                }
+                // Blank lines preceding the stanza and blocks of comments
+                stanza.clear();
+                continue;
+            }
+            has_fields |= !is_comment(&self.line);
+            stanza.push_str(&self.line);
+        }
+        if has_fields {
+            Ok(Some(stanza))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Returns the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+#[cfg(feature = "io")]
+impl<R: io::BufRead> Iterator for ReadStanzas<R> {
+    type Item = io::Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let result = self.read_stanza();
+        self.failed = result.is_err();
+        result.transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::stanzas;
+
+    #[test]
+    fn split() {
+        let input = "\n  \n# header\nPackage: foo\n# inner\nDescription: a\n b\n \t\n#only\n#comments\n\n\nPackage: bar\nVersion: 1.0";
+        let expected = ["# header\nPackage: foo\n# inner\nDescription: a\n b\n", "Package: bar\nVersion: 1.0"];
+        assert_eq!(stanzas(input).collect::<Vec<_>>(), expected);
+        assert_eq!(stanzas("").count(), 0);
+        assert_eq!(stanzas("\n# comment\n").count(), 0);
+
+        #[cfg(feature = "io")]
+        {
+            let read = super::read_stanzas(input.as_bytes()).collect::<Result<Vec<_>, _>>().unwrap();
+            assert_eq!(read, expected);
+            let error = super::read_stanzas(&b"Package: foo\n\nPackage: \xff\n"[..]).collect::<Vec<_>>();
+            assert_eq!(error.len(), 2);
+            assert!(error[1].is_err());
+        }
+    }
+}
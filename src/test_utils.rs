@@ -0,0 +1,316 @@
+//! Helpers for regression-testing format models against a corpus of files.
+//!
+//! A [`Corpus`] is a directory of sample files.
+//! Each file is parsed, serialized back and compared with the original - either byte-for-byte
+//! or after normalization, which ignores formatting differences such as indentation of
+//! continuation lines or comments.
+//! Mismatches are reported with a line diff so that it's easy to see what the model lost.
+//!
+//! This module requires the `test_utils` feature which should be only enabled in
+//! `dev-dependencies`.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use rfc822_like::test_utils::Corpus;
+//!
+//! #[derive(serde_derive::Deserialize, serde_derive::Serialize)]
+//! #[serde(rename_all = "PascalCase")]
+//! struct Record {
+//!     package: String,
+//!     version: String,
+//! }
+//!
+//! // Checks each file in the directory using the `Vec<Record>` model.
+//! Corpus::new("tests/corpus").normalized().assert_roundtrip::<Vec<Record>>();
+//! ```
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use serde::{de::DeserializeOwned, Serialize};
+use crate::document::Document;
+use crate::line_diff::common_lines;
+
+/// How the re-serialized output is compared with the original.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Comparison {
+    /// The output has to be byte-for-byte identical.
+    Exact,
+    /// Both texts are parsed as [`Document`]s and compared ignoring formatting and comments.
+    Normalized,
+}
+
+/// A single file that failed the check.
+#[derive(Debug, Clone)]
+pub struct Failure {
+    path: PathBuf,
+    message: String,
+}
+
+impl Failure {
+    /// Returns the path of the file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the description of the failure, usually a diff.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path.display(), self.message)
+    }
+}
+
+/// All failures found when checking a corpus.
+#[derive(Debug, Clone)]
+pub struct Failures(Vec<Failure>);
+
+impl Failures {
+    /// Iterates over the failures.
+    pub fn iter(&self) -> std::slice::Iter<'_, Failure> {
+        self.0.iter()
+    }
+}
+
+impl fmt::Display for Failures {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} file(s) failed the round trip", self.0.len())?;
+        for failure in &self.0 {
+            writeln!(f, "\n{}", failure)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Failures {}
+
+/// Directory of sample files.
+#[derive(Debug, Clone)]
+pub struct Corpus {
+    dir: PathBuf,
+    comparison: Comparison,
+    extension: Option<String>,
+}
+
+impl Corpus {
+    /// Creates the corpus of all files in `dir` compared exactly.
+    ///
+    /// Subdirectories and hidden files are skipped.
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        Corpus {
+            dir: dir.into(),
+            comparison: Comparison::Exact,
+            extension: None,
+        }
+    }
+
+    /// Compares the output after normalization, see [`Comparison::Normalized`].
+    pub fn normalized(mut self) -> Self {
+        self.comparison = Comparison::Normalized;
+        self
+    }
+
+    /// Only checks files with the given extension.
+    pub fn with_extension<E: Into<String>>(mut self, extension: E) -> Self {
+        self.extension = Some(extension.into());
+        self
+    }
+
+    /// Checks that deserializing each file as `T` and serializing it back produces the same text.
+    pub fn check_roundtrip<T: DeserializeOwned + Serialize>(&self) -> Result<(), Failures> {
+        self.check(|input| {
+            let value = crate::from_str::<T>(input).map_err(|error| format!("failed to deserialize: {}", error))?;
+            crate::to_string(&value).map_err(|error| format!("failed to serialize: {}", error))
+        })
+    }
+
+    /// Checks that parsing each file as [`Document`] and writing it back produces the same text.
+    pub fn check_documents(&self) -> Result<(), Failures> {
+        self.check(|input| {
+            input.parse::<Document>()
+                .map(|document| document.to_string())
+                .map_err(|error| format!("failed to parse: {}", error))
+        })
+    }
+
+    /// Panics with a diff of each mismatching file, see [`check_roundtrip`](Self::check_roundtrip).
+    pub fn assert_roundtrip<T: DeserializeOwned + Serialize>(&self) {
+        if let Err(failures) = self.check_roundtrip::<T>() {
+            panic!("{}", failures);
+        }
+    }
+
+    /// Panics with a diff of each mismatching file, see [`check_documents`](Self::check_documents).
+    pub fn assert_documents(&self) {
+        if let Err(failures) = self.check_documents() {
+            panic!("{}", failures);
+        }
+    }
+
+    /// Checks each file using a custom round trip function.
+    ///
+    /// The function returns the re-serialized text or a description of the failure.
+    pub fn check<F: FnMut(&str) -> Result<String, String>>(&self, mut roundtrip: F) -> Result<(), Failures> {
+        let mut failures = Vec::new();
+        let paths = match self.files() {
+            Ok(paths) => paths,
+            Err(error) => {
+                failures.push(Failure { path: self.dir.clone(), message: format!("failed to list the directory: {}", error), });
+                return Err(Failures(failures));
+            },
+        };
+        for path in paths {
+            let message = match std::fs::read_to_string(&path) {
+                Ok(input) => match roundtrip(&input) {
+                    Ok(output) => compare(&input, &output, self.comparison),
+                    Err(message) => Some(message),
+                },
+                Err(error) => Some(format!("failed to read: {}", error)),
+            };
+            if let Some(message) = message {
+                failures.push(Failure { path, message, });
+            }
+        }
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(Failures(failures))
+        }
+    }
+
+    fn files(&self) -> std::io::Result<Vec<PathBuf>> {
+        let mut paths = Vec::new();
+        for entry in std::fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            if !entry.file_type()?.is_file() || entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            let path = entry.path();
+            if let Some(extension) = &self.extension {
+                if path.extension().map_or(true, |actual| actual != extension.as_str()) {
+                    continue;
+                }
+            }
+            paths.push(path);
+        }
+        paths.sort();
+        Ok(paths)
+    }
+}
+
+/// Returns the description of the difference if the texts don't match.
+fn compare(expected: &str, actual: &str, comparison: Comparison) -> Option<String> {
+    match comparison {
+        Comparison::Exact if expected == actual => None,
+        Comparison::Exact => Some(format!("output differs\n{}", diff(expected, actual))),
+        Comparison::Normalized => {
+            let (expected_document, actual_document) = match (expected.parse::<Document>(), actual.parse::<Document>()) {
+                (Ok(expected), Ok(actual)) => (expected, actual),
+                (Err(error), _) => return Some(format!("failed to parse the input: {}", error)),
+                (_, Err(error)) => return Some(format!("failed to parse the output: {}", error)),
+            };
+            if expected_document == actual_document {
+                return None;
+            }
+            let normalize = |mut document: Document| {
+                for paragraph in document.paragraphs_mut() {
+                    paragraph.discard_formatting();
+                    paragraph.comments_mut().clear();
+                    for field in paragraph.fields_mut() {
+                        field.comments_mut().clear();
+                    }
+                }
+                document.trailing_comments_mut().clear();
+                document.to_string()
+            };
+            Some(format!("normalized output differs\n{}", diff(&normalize(expected_document), &normalize(actual_document))))
+        },
+    }
+}
+
+/// Number of unchanged lines shown around changes.
+const CONTEXT: usize = 2;
+
+/// Renders a line diff of the texts in the unified style.
+///
+/// Lines prefixed with `-` are only in `expected`, lines prefixed with `+` only in `actual`.
+pub fn diff(expected: &str, actual: &str) -> String {
+    let expected = expected.lines().collect::<Vec<_>>();
+    let actual = actual.lines().collect::<Vec<_>>();
+
+    let mut common = common_lines(&expected, &actual);
+    common.push((expected.len(), actual.len()));
+
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    for (common_i, common_j) in common {
+        lines.extend((i..common_i).map(|i| ('-', i, expected[i])));
+        lines.extend(actual[j..common_j].iter().map(|line| ('+', common_i, *line)));
+        if common_i < expected.len() {
+            lines.push((' ', common_i, expected[common_i]));
+        }
+        i = common_i + 1;
+        j = common_j + 1;
+    }
+
+    let mut output = String::new();
+    let mut last_written = None;
+    for (index, (kind, line_number, line)) in lines.iter().enumerate() {
+        let near_change = lines[index.saturating_sub(CONTEXT)..(index + CONTEXT + 1).min(lines.len())]
+            .iter()
+            .any(|(kind, _, _)| *kind != ' ');
+        if !near_change {
+            continue;
+        }
+        if last_written.map_or(true, |last| last + 1 != index) {
+            output.push_str(&format!("@@ line {} @@\n", line_number + 1));
+        }
+        output.push(*kind);
+        output.push_str(line);
+        output.push('\n');
+        last_written = Some(index);
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{diff, Corpus};
+
+    #[derive(serde_derive::Deserialize, serde_derive::Serialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct Record {
+        package: String,
+    }
+
+    #[test]
+    fn corpus() {
+        let dir = crate::testing::temp_dir::TempDir::new("corpus-test");
+        std::fs::write(dir.join("a.control"), "Package: foo\n\nPackage: bar\n").unwrap();
+        std::fs::write(dir.join("b.control"), "Package: foo\nVersion: 1.0\n").unwrap();
+        std::fs::write(dir.join("c.control"), "Package:  baz\n").unwrap();
+        std::fs::write(dir.join("ignored.txt"), "invalid\n").unwrap();
+
+        let corpus = Corpus::new(&*dir).with_extension("control");
+        corpus.assert_documents();
+        let failures = corpus.check_roundtrip::<Vec<Record>>().unwrap_err();
+        let failed = failures.iter().map(|failure| failure.path().file_name().unwrap().to_str().unwrap()).collect::<Vec<_>>();
+        assert_eq!(failed, ["b.control", "c.control"]);
+        assert_eq!(failures.iter().next().unwrap().message(), "output differs\n@@ line 1 @@\n Package: foo\n-Version: 1.0\n");
+        let failures = corpus.normalized().check_roundtrip::<Vec<Record>>().unwrap_err();
+        assert_eq!(failures.iter().count(), 1);
+        assert!(Corpus::new(&*dir).check_documents().is_err());
+    }
+
+    #[test]
+    fn diff_context() {
+        let expected = "a\nb\nc\nd\ne\nf\ng\nh\n";
+        let actual = "a\nB\nc\nd\ne\nf\ng\nh\ni\n";
+        assert_eq!(diff(expected, actual), "@@ line 1 @@\n a\n-b\n+B\n c\n d\n@@ line 7 @@\n g\n h\n+i\n");
+        assert_eq!(diff("a\n", "a\n"), "");
+    }
+}
@@ -0,0 +1,58 @@
+//! Fixtures shared by unit tests.
+
+/// Temporary directories removed even if the test panics.
+#[cfg(any(feature = "test_utils", all(feature = "debian", feature = "fs", any(feature = "chrono", feature = "notify"))))]
+pub(crate) mod temp_dir {
+    use std::path::{Path, PathBuf};
+
+    /// Temporary directory removed when dropped.
+    pub(crate) struct TempDir(PathBuf);
+
+    impl TempDir {
+        /// Creates an empty directory named after the test and the process.
+        pub(crate) fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("rfc822-like-{}-{}", name, std::process::id()));
+            let _ = std::fs::remove_dir_all(&path);
+            std::fs::create_dir_all(&path).unwrap();
+            TempDir(path)
+        }
+    }
+
+    impl std::ops::Deref for TempDir {
+        type Target = Path;
+
+        fn deref(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl AsRef<Path> for TempDir {
+        fn as_ref(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+}
+
+/// Records of `Packages` indexes.
+#[cfg(feature = "debian")]
+pub(crate) mod packages {
+    use crate::debian::packages::Record;
+
+    /// Formats a `Packages` paragraph with the mandatory fields filled in.
+    ///
+    /// `fields` are inserted as-is so each of them has to end with a newline.
+    pub(crate) fn paragraph(package: &str, version: &str, architecture: &str, fields: &str) -> String {
+        format!("Package: {}\nVersion: {}\nMaintainer: Foo <foo@example.org>\nArchitecture: {}\n{}Filename: pool/{}.deb\nSize: 42\n", package, version, architecture, fields, package)
+    }
+
+    /// Parses [`paragraph`] into a record.
+    pub(crate) fn record(package: &str, version: &str, architecture: &str, fields: &str) -> Record {
+        crate::from_str(&paragraph(package, version, architecture, fields)).unwrap()
+    }
+}
@@ -0,0 +1,281 @@
+//! Serde adapters for plain field types.
+//!
+//! These modules allow keeping standard types such as `Vec<T>`, `bool` or `chrono::DateTime`
+//! in structs while choosing the formatting per field using `#[serde(with = "...")]`.
+//! They are re-exported at the crate root so that the paths are short.
+//!
+//! * [`as_comma_list`] - `Vec<T>` written as `a, b, c`
+//! * [`as_space_list`] - `Vec<T>` written as `a b c`
+//! * [`as_line_list`] - `Vec<T>` written one item per line, starting with an empty line
+//! * [`as_yes_no`] - `bool` written as `yes` or `no`
+//! * `as_rfc2822_date` - `chrono::DateTime` written in the RFC 2822 format, requires the
+//!   `debian` and `chrono` features
+//!
+//! Items of lists can be of any type implementing `FromStr` and `Display`.
+//! Combine the list and boolean adapters with `#[serde(default)]` to accept missing fields.
+//!
+//! # Example
+//!
+//! ```
+//! #[derive(serde_derive::Deserialize, serde_derive::Serialize)]
+//! #[serde(rename_all = "PascalCase")]
+//! struct Release {
+//!     #[serde(with = "rfc822_like::as_space_list")]
+//!     architectures: Vec<String>,
+//!     #[serde(rename = "Acquire-By-Hash", with = "rfc822_like::as_yes_no", default)]
+//!     acquire_by_hash: bool,
+//! }
+//!
+//! let input = "Architectures: amd64 arm64\nAcquire-By-Hash: yes\n";
+//! let release = rfc822_like::from_str::<Release>(input).unwrap();
+//! assert_eq!(release.architectures, ["amd64", "arm64"]);
+//! assert!(release.acquire_by_hash);
+//! assert_eq!(rfc822_like::to_string(&release).unwrap(), input);
+//! ```
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::str::FromStr;
+use serde::{Deserializer, Serializer};
+
+/// Displays items separated by `separator`.
+struct Separated<'a, T> {
+    items: &'a [T],
+    separator: &'static str,
+    prefix: &'static str,
+}
+
+impl<'a, T: fmt::Display> fmt::Display for Separated<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, item) in self.items.iter().enumerate() {
+            if i > 0 {
+                f.write_str(self.separator)?;
+            }
+            write!(f, "{}{}", self.prefix, item)?;
+        }
+        Ok(())
+    }
+}
+
+fn serialize_list<T: fmt::Display, S: Serializer>(items: &[T], separator: &'static str, prefix: &'static str, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(&Separated { items, separator, prefix, })
+}
+
+struct ListVisitor<T> {
+    split: fn(&str) -> Vec<&str>,
+    expecting: &'static str,
+    _phantom: PhantomData<T>,
+}
+
+impl<'de, T: FromStr> serde::de::Visitor<'de> for ListVisitor<T> where T::Err: fmt::Display {
+    type Value = Vec<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.expecting)
+    }
+
+    fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        (self.split)(v)
+            .into_iter()
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(|item| item.parse().map_err(E::custom))
+            .collect()
+    }
+}
+
+fn deserialize_list<'de, T: FromStr, D: Deserializer<'de>>(deserializer: D, split: fn(&str) -> Vec<&str>, expecting: &'static str) -> Result<Vec<T>, D::Error> where T::Err: fmt::Display {
+    deserializer.deserialize_str(ListVisitor { split, expecting, _phantom: PhantomData, })
+}
+
+/// Adapter for lists separated by commas such as `Architecture: amd64, arm64`.
+///
+/// Empty items (e.g. caused by a trailing comma) are skipped when deserializing.
+pub mod as_comma_list {
+    use std::fmt;
+    use std::str::FromStr;
+    use serde::{Deserializer, Serializer};
+
+    /// Serializes the items separated by `, `.
+    pub fn serialize<T: fmt::Display, S: Serializer>(items: &[T], serializer: S) -> Result<S::Ok, S::Error> {
+        super::serialize_list(items, ", ", "", serializer)
+    }
+
+    /// Deserializes items separated by commas.
+    pub fn deserialize<'de, T: FromStr, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<T>, D::Error> where T::Err: fmt::Display {
+        super::deserialize_list(deserializer, |s| s.split(',').collect(), "a comma-separated list")
+    }
+}
+
+/// Adapter for lists separated by whitespace such as `Architectures: amd64 arm64`.
+pub mod as_space_list {
+    use std::fmt;
+    use std::str::FromStr;
+    use serde::{Deserializer, Serializer};
+
+    /// Serializes the items separated by a single space.
+    pub fn serialize<T: fmt::Display, S: Serializer>(items: &[T], serializer: S) -> Result<S::Ok, S::Error> {
+        super::serialize_list(items, " ", "", serializer)
+    }
+
+    /// Deserializes items separated by any whitespace including newlines.
+    pub fn deserialize<'de, T: FromStr, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<T>, D::Error> where T::Err: fmt::Display {
+        super::deserialize_list(deserializer, |s| s.split_whitespace().collect(), "a whitespace-separated list")
+    }
+}
+
+/// Adapter for lists with one item per line such as `Files` or `Conffiles`.
+///
+/// The first line (right after the field name) is left empty when serializing and ignored when
+/// deserializing.
+pub mod as_line_list {
+    use std::fmt;
+    use std::str::FromStr;
+    use serde::{Deserializer, Serializer};
+
+    /// Serializes each item on its own line.
+    pub fn serialize<T: fmt::Display, S: Serializer>(items: &[T], serializer: S) -> Result<S::Ok, S::Error> {
+        super::serialize_list(items, "", "\n", serializer)
+    }
+
+    /// Deserializes items from lines skipping empty ones.
+    pub fn deserialize<'de, T: FromStr, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<T>, D::Error> where T::Err: fmt::Display {
+        super::deserialize_list(deserializer, |s| s.lines().collect(), "a list of lines")
+    }
+}
+
+/// Adapter for `bool` written as `yes` or `no`.
+///
+/// The value is case-insensitive when deserializing.
+pub mod as_yes_no {
+    use std::fmt;
+    use serde::{Deserializer, Serializer};
+
+    struct YesNoVisitor;
+
+    impl<'de> serde::de::Visitor<'de> for YesNoVisitor {
+        type Value = bool;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("yes or no")
+        }
+
+        fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+            match v.trim() {
+                value if value.eq_ignore_ascii_case("yes") => Ok(true),
+                value if value.eq_ignore_ascii_case("no") => Ok(false),
+                _ => Err(E::invalid_value(serde::de::Unexpected::Str(v), &self)),
+            }
+        }
+    }
+
+    /// Serializes the value as `yes` or `no`.
+    // serde requires the signature
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn serialize<S: Serializer>(value: &bool, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(if *value { "yes" } else { "no" })
+    }
+
+    /// Deserializes `yes` or `no`.
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+        deserializer.deserialize_str(YesNoVisitor)
+    }
+}
+
+/// Adapter for `chrono::DateTime` written in the RFC 2822 format.
+///
+/// Both `Utc` and `FixedOffset` time zones are supported.
+/// `UTC` is accepted as time zone name when deserializing, see
+/// [`Date`](crate::debian::date::Date) for details.
+///
+/// This module requires the `debian` and `chrono` features.
+#[cfg(all(feature = "debian", feature = "chrono"))]
+pub mod as_rfc2822_date {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use crate::debian::date::Date;
+
+    /// Serializes the date.
+    ///
+    /// `DateTime<Utc>` is written with `UTC` time zone name as in `Release` files.
+    pub fn serialize<T: Into<Date> + Clone, S: Serializer>(date: &T, serializer: S) -> Result<S::Ok, S::Error> {
+        date.clone().into().serialize(serializer)
+    }
+
+    /// Deserializes the date.
+    pub fn deserialize<'de, T: From<Date>, D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {
+        Date::deserialize(deserializer).map(Into::into)
+    }
+
+    /// Adapters for optional dates.
+    ///
+    /// Combine them with `#[serde(default)]` so that missing fields are accepted.
+    pub mod option {
+        use serde::{Deserialize, Deserializer, Serialize, Serializer};
+        use crate::debian::date::Date;
+
+        /// Serializes the date if present.
+        pub fn serialize<T: Into<Date> + Clone, S: Serializer>(date: &Option<T>, serializer: S) -> Result<S::Ok, S::Error> {
+            date.clone().map(Into::into).serialize(serializer)
+        }
+
+        /// Deserializes the date if present.
+        pub fn deserialize<'de, T: From<Date>, D: Deserializer<'de>>(deserializer: D) -> Result<Option<T>, D::Error> {
+            Option::<Date>::deserialize(deserializer).map(|date| date.map(Into::into))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[derive(Debug, Eq, PartialEq, serde_derive::Deserialize, serde_derive::Serialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct Record {
+        #[serde(with = "super::as_comma_list", default, skip_serializing_if = "Vec::is_empty")]
+        depends: Vec<String>,
+        #[serde(with = "super::as_space_list", default)]
+        architectures: Vec<String>,
+        #[serde(with = "super::as_line_list", default)]
+        files: Vec<String>,
+        #[serde(with = "super::as_yes_no", default)]
+        essential: bool,
+        #[serde(with = "super::as_comma_list", default, skip_serializing_if = "Vec::is_empty")]
+        sizes: Vec<u64>,
+    }
+
+    #[test]
+    fn round_trip() {
+        let input = "Depends: foo, bar (>= 1.0)\nArchitectures: amd64 i386\nFiles:\n a 1\n b 2\nEssential: no\nSizes: 1, 2\n";
+        let record = crate::from_str::<Record>(input).unwrap();
+        assert_eq!(record.depends, ["foo", "bar (>= 1.0)"]);
+        assert_eq!(record.files, ["a 1", "b 2"]);
+        assert_eq!(record.sizes, [1, 2]);
+        assert_eq!(crate::to_string(&record).unwrap(), input);
+
+        let record = crate::from_str::<Record>("Depends: foo,\nArchitectures: \n amd64\nFiles:\nEssential: YES\n").unwrap();
+        assert_eq!(record.depends, ["foo"]);
+        assert_eq!(record.architectures, ["amd64"]);
+        assert!(record.essential);
+        assert!(crate::from_str::<Record>("Essential: maybe\n").is_err());
+        assert!(crate::from_str::<Record>("Sizes: 1, x\n").is_err());
+    }
+
+    #[cfg(all(feature = "debian", feature = "chrono"))]
+    #[test]
+    fn date() {
+        use chrono::{DateTime, FixedOffset, Utc};
+
+        #[derive(serde_derive::Deserialize, serde_derive::Serialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct Release {
+            #[serde(with = "super::as_rfc2822_date")]
+            date: DateTime<Utc>,
+            #[serde(rename = "Valid-Until", with = "super::as_rfc2822_date::option", default)]
+            valid_until: Option<DateTime<FixedOffset>>,
+        }
+
+        let input = "Date: Sat, 14 Aug 2021 07:46:50 UTC\nValid-Until: Sat, 21 Aug 2021 09:46:50 +0200\n";
+        let release = crate::from_str::<Release>(input).unwrap();
+        assert_eq!(release.valid_until.unwrap() - release.date.with_timezone(&release.valid_until.unwrap().timezone()), chrono::Duration::days(7));
+        assert_eq!(crate::to_string(&release).unwrap(), input);
+    }
+}